@@ -0,0 +1,133 @@
+//! Deserialisation benchmarks.
+//!
+//! Run with `cargo bench`. To detect regressions, save a baseline on the main branch with
+//! `cargo bench -- --save-baseline main` and compare a branch against it with
+//! `cargo bench -- --baseline main`.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use kle_serial::{Keyboard, KleLayoutIterator, KleLegendsOrProps};
+use serde_json::Value;
+
+const SINGLE_KEY: &str = r#"[["A"]]"#;
+
+const ANSI_104: &str = r##"[
+    {"name": "ANSI 104"},
+    ["Esc", {"x": 1}, "F1", "F2", "F3", "F4", {"x": 0.5}, "F5", "F6", "F7", "F8",
+        {"x": 0.5}, "F9", "F10", "F11", "F12", {"x": 0.25}, "PrtSc", "Scroll Lock",
+        "Pause\nBreak"],
+    [{"y": 0.5}, "~\n`", "!\n1", "@\n2", "#\n3", "$\n4", "%\n5", "^\n6", "&\n7",
+        "*\n8", "(\n9", ")\n0", "_\n-", "+\n=", {"w": 2}, "Backspace", {"x": 0.25},
+        "Insert", "Home", "PgUp", {"x": 0.25}, "Num Lock", "/", "*", "-"],
+    [{"w": 1.5}, "Tab", "Q", "W", "E", "R", "T", "Y", "U", "I", "O", "P", "{\n[",
+        "}\n]", {"w": 1.5}, "|\n\\", {"x": 0.25}, "Delete", "End", "PgDn",
+        {"x": 0.25}, "7\nHome", "8\n↑", "9\nPgUp", {"h": 2}, "+"],
+    [{"w": 1.75}, "Caps Lock", "A", "S", "D", "F", "G", "H", "J", "K", "L", ":\n;",
+        "\"\n'", {"w": 2.25}, "Enter", {"x": 3.5}, "4\n←", "5", "6\n→"],
+    [{"w": 2.25}, "Shift", "Z", "X", "C", "V", "B", "N", "M", "<\n,", ">\n.", "?\n/",
+        {"w": 2.75}, "Shift", {"x": 1.25}, "↑", {"x": 1.25}, "1\nEnd", "2\n↓",
+        "3\nPgDn", {"h": 2}, "Enter"],
+    [{"w": 1.25}, "Ctrl", {"w": 1.25}, "Win", {"w": 1.25}, "Alt", {"w": 6.25}, "",
+        {"w": 1.25}, "Alt", {"w": 1.25}, "Win", {"w": 1.25}, "Menu", {"w": 1.25}, "Ctrl",
+        {"x": 0.25}, "←", "↓", "→", {"x": 0.25, "w": 2}, "0\nIns", ".\nDel"]
+]"##;
+
+// 24 rows of 24 keys, each with its own key and legend colour
+fn large_layout() -> String {
+    let rows: Vec<_> = (0..24)
+        .map(|row| {
+            let keys: Vec<_> = (0..24)
+                .map(|col| {
+                    let (r, g) = (row * 10, col * 10);
+                    format!(r##"{{"c": "#{r:02x}{g:02x}80", "t": "#{g:02x}{r:02x}00"}}, "R{row}\nC{col}""##)
+                })
+                .collect();
+            format!("[{}]", keys.join(", "))
+        })
+        .collect();
+    format!("[{{\"name\": \"large\"}}, {}]", rows.join(",\n"))
+}
+
+// 8 rows of 16 keys, each preceded by a props object setting most of KLE's properties
+fn props_heavy_layout() -> String {
+    let rows: Vec<_> = (0..8)
+        .map(|row| {
+            let keys: Vec<_> = (0..16)
+                .map(|col| {
+                    let props = format!(
+                        r##"{{"x": 0.25, "y": 0.0, "w": 1.25, "h": 1, "x2": 0, "y2": 0, "w2": 1.25,
+                            "h2": 1, "r": {r}, "rx": {col}, "ry": {row}, "c": "#aabbcc",
+                            "t": "#112233\n\n#445566", "a": {a}, "f": 3, "f2": 4,
+                            "fa": [3, 4, 5], "p": "DSA", "sm": "cherry", "sb": "cherry",
+                            "st": "MX1A-11xx", "l": false, "n": false, "g": false, "d": false}}"##,
+                        r = (row * 16 + col) % 30,
+                        a = col % 8,
+                    );
+                    format!("{props}, \"A\\nB\\nC\\nD\\nE\\nF\\nG\\nH\\nI\\nJ\\nK\\nL\"")
+                })
+                .collect();
+            format!("[{}]", keys.join(", "))
+        })
+        .collect();
+    format!("[{}]", rows.join(",\n"))
+}
+
+fn layouts() -> Vec<(&'static str, String)> {
+    vec![
+        ("single_key", SINGLE_KEY.into()),
+        ("ansi_104", ANSI_104.into()),
+        ("large_576", large_layout()),
+        ("props_heavy", props_heavy_layout()),
+    ]
+}
+
+// Extracts the rows of a layout, skipping any metadata object
+fn layout_rows(json: &str) -> Vec<Vec<KleLegendsOrProps>> {
+    let value: Value = serde_json::from_str(json).unwrap();
+    let rows: Vec<_> = (value.as_array().unwrap().iter())
+        .filter(|row| row.is_array())
+        .cloned()
+        .collect();
+    serde_json::from_value(Value::Array(rows)).unwrap()
+}
+
+fn deserialize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("deserialize");
+    for (name, json) in layouts() {
+        let keys = serde_json::from_str::<Keyboard>(&json).unwrap().keys.len();
+        group.throughput(Throughput::Elements(keys as u64));
+        group.bench_function(name, |b| {
+            b.iter(|| serde_json::from_str::<Keyboard>(black_box(&json)).unwrap());
+        });
+    }
+    group.finish();
+}
+
+fn layout_iterator(c: &mut Criterion) {
+    let mut group = c.benchmark_group("layout_iterator");
+    for (name, json) in layouts() {
+        let rows = layout_rows(&json);
+        let keys = KleLayoutIterator::new(rows.clone()).len();
+        group.throughput(Throughput::Elements(keys as u64));
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || rows.clone(),
+                |rows| KleLayoutIterator::new(rows).collect::<Vec<_>>(),
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+fn bounding_box(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bounding_box");
+    for (name, json) in layouts() {
+        let keyboard: Keyboard = serde_json::from_str(&json).unwrap();
+        group.throughput(Throughput::Elements(keyboard.keys.len() as u64));
+        group.bench_function(name, |b| b.iter(|| black_box(&keyboard).bounding_box()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, deserialize, layout_iterator, bounding_box);
+criterion_main!(benches);
@@ -0,0 +1,11 @@
+//! Fuzzes the KLE deserialiser with arbitrary bytes. Errors are expected, but panics are bugs.
+
+#![no_main]
+
+use kle_serial::Keyboard;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<Keyboard<f64>>(data);
+    let _ = serde_json::from_slice::<Keyboard<f32>>(data);
+});
@@ -0,0 +1,148 @@
+//! Fuzzes the KLE deserialiser with structurally valid KLE JSON, focusing coverage on the layout
+//! logic rather than the JSON parser. Layouts are built from the crate's own `KlePropsObject` and
+//! `Metadata` types, so any valid layout must deserialise and round trip through the serialiser
+//! to an equal layout.
+
+#![no_main]
+
+use arbitrary::{Result, Unstructured};
+use kle_serial::{
+    Alignment, Color, FontSize, Keyboard, KleLegendsOrProps, KlePropsObject, Metadata,
+};
+use libfuzzer_sys::fuzz_target;
+use serde_json::Value;
+
+const NUM_LEGENDS: usize = 12;
+
+// Positions and sizes are generated as multiples of 0.25 so they are exactly representable and
+// don't accumulate rounding errors between keys
+fn position(u: &mut Unstructured) -> Result<f64> {
+    Ok(f64::from(u.int_in_range(-64_i8..=64)?) / 4.0)
+}
+
+fn size(u: &mut Unstructured) -> Result<f64> {
+    Ok(f64::from(u.int_in_range(1_i8..=48)?) / 4.0)
+}
+
+fn angle(u: &mut Unstructured) -> Result<f64> {
+    Ok(f64::from(u.int_in_range(-24_i8..=24)?) * 15.0)
+}
+
+// Newlines delimit legends, and KLE treats "\r\n" as a single line break, so neither can appear
+// within a single legend
+fn text(u: &mut Unstructured) -> Result<String> {
+    let text: String = u.arbitrary()?;
+    Ok(text.chars().filter(|c| !matches!(c, '\n' | '\r')).collect())
+}
+
+fn color(u: &mut Unstructured) -> Result<Color> {
+    Ok(Color::new(
+        u.arbitrary()?,
+        u.arbitrary()?,
+        u.arbitrary()?,
+        u8::MAX,
+    ))
+}
+
+fn alignment(u: &mut Unstructured) -> Result<Alignment> {
+    Ok(Alignment::new(u.int_in_range(0..=7)?).unwrap_or_default())
+}
+
+fn font_size(u: &mut Unstructured) -> Result<FontSize> {
+    Ok(FontSize::new(u.int_in_range(0..=9)?).unwrap_or_default())
+}
+
+fn option<T>(
+    u: &mut Unstructured,
+    f: impl FnOnce(&mut Unstructured) -> Result<T>,
+) -> Result<Option<T>> {
+    if u.arbitrary()? {
+        f(u).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+fn vec<T>(
+    u: &mut Unstructured,
+    max_len: usize,
+    mut f: impl FnMut(&mut Unstructured) -> Result<T>,
+) -> Result<Vec<T>> {
+    let len = u.int_in_range(0..=max_len)?;
+    (0..len).map(|_| f(u)).collect()
+}
+
+// Every field is listed so this fails to compile rather than silently ignoring new properties
+fn props(u: &mut Unstructured) -> Result<KlePropsObject<f64>> {
+    Ok(KlePropsObject {
+        x: option(u, position)?,
+        y: option(u, position)?,
+        w: option(u, size)?,
+        h: option(u, size)?,
+        x2: option(u, position)?,
+        y2: option(u, position)?,
+        w2: option(u, size)?,
+        h2: option(u, size)?,
+        r: option(u, angle)?,
+        rx: option(u, position)?,
+        ry: option(u, position)?,
+        l: u.arbitrary()?,
+        n: u.arbitrary()?,
+        d: u.arbitrary()?,
+        g: u.arbitrary()?,
+        sm: option(u, text)?,
+        sb: option(u, text)?,
+        st: option(u, text)?,
+        c: option(u, color)?,
+        t: option(u, |u| vec(u, NUM_LEGENDS, |u| option(u, color)))?,
+        a: option(u, alignment)?,
+        p: option(u, text)?,
+        f: option(u, font_size)?,
+        f2: option(u, font_size)?,
+        fa: option(u, |u| vec(u, NUM_LEGENDS, font_size))?,
+    })
+}
+
+fn item(u: &mut Unstructured) -> Result<KleLegendsOrProps<f64>> {
+    if u.ratio(1, 3)? {
+        Ok(KleLegendsOrProps::Props(Box::new(props(u)?)))
+    } else {
+        Ok(KleLegendsOrProps::Legend(
+            vec(u, NUM_LEGENDS, text)?.join("\n"),
+        ))
+    }
+}
+
+fn metadata(u: &mut Unstructured) -> Result<Metadata> {
+    Ok(Metadata {
+        background_color: color(u)?,
+        name: text(u)?,
+        author: text(u)?,
+        notes: u.arbitrary()?,
+        ..Metadata::default()
+    })
+}
+
+fn layout_json(u: &mut Unstructured) -> Result<String> {
+    let mut items = Vec::new();
+    if u.arbitrary()? {
+        items.push(serde_json::to_value(metadata(u)?).unwrap());
+    }
+    for row in vec(u, 8, |u| vec(u, 16, item))? {
+        items.push(serde_json::to_value(row).unwrap());
+    }
+    Ok(Value::Array(items).to_string())
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(json) = layout_json(&mut Unstructured::new(data)) else {
+        return;
+    };
+
+    let keyboard = serde_json::from_str::<Keyboard<f64>>(&json).unwrap();
+
+    // Serialising must produce a layout which deserialises to the same keys and metadata
+    let json = serde_json::to_string(&keyboard).unwrap();
+    let result = serde_json::from_str::<Keyboard<f64>>(&json).unwrap();
+    assert_eq!(result, keyboard);
+});
@@ -0,0 +1,232 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use num_traits::real::Real;
+
+use crate::{ColorExt, Keyboard};
+
+// WCAG 2.1 AA minimum contrast ratio for normal sized text
+const AA_NORMAL_TEXT_RATIO: f64 = 4.5;
+
+/// An accessibility issue found in a layout's colours by [`Keyboard::accessibility_report`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccessibilityIssue {
+    /// The contrast ratio between a legend and its key is below the required ratio.
+    InsufficientContrast {
+        /// The index of the key.
+        key_index: usize,
+        /// The index of the legend in [`Key::legends`](crate::Key::legends).
+        legend_index: usize,
+        /// The [WCAG 2.1] contrast ratio between the legend and key colours.
+        ///
+        /// [WCAG 2.1]: https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio
+        ratio: f64,
+        /// The minimum contrast ratio that was required.
+        required_ratio: f64,
+    },
+    /// A legend is not visible because its colour is fully transparent.
+    LowVisibility {
+        /// The index of the key.
+        key_index: usize,
+        /// The index of the legend in [`Key::legends`](crate::Key::legends).
+        legend_index: usize,
+    },
+}
+
+impl fmt::Display for AccessibilityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InsufficientContrast {
+                key_index,
+                legend_index,
+                ratio,
+                required_ratio,
+            } => write!(
+                f,
+                "legend {legend_index} of key {key_index} has a contrast ratio of {ratio:.2}:1, \
+                    expected at least {required_ratio:.2}:1"
+            ),
+            Self::LowVisibility {
+                key_index,
+                legend_index,
+            } => write!(f, "legend {legend_index} of key {key_index} is transparent"),
+        }
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Checks the contrast between each legend and its key, returning an [`AccessibilityIssue`]
+    /// for each legend that may be hard to read.
+    ///
+    /// This uses the [WCAG 2.1] AA minimum contrast ratio for normal text of 4.5:1. See
+    /// [`Keyboard::accessibility_report_with_threshold`] to use a different ratio.
+    ///
+    /// ```
+    /// use kle_serial::{AccessibilityIssue, Keyboard};
+    ///
+    /// let keyboard: Keyboard =
+    ///     serde_json::from_str(r##"[[{"c": "#000000", "t": "#222222"}, "A"]]"##).unwrap();
+    /// let report = keyboard.accessibility_report();
+    ///
+    /// assert!(matches!(
+    ///     report[..],
+    ///     [AccessibilityIssue::InsufficientContrast { key_index: 0, legend_index: 0, .. }]
+    /// ));
+    /// ```
+    ///
+    /// [WCAG 2.1]: https://www.w3.org/TR/WCAG21/#contrast-minimum
+    #[must_use]
+    pub fn accessibility_report(&self) -> Vec<AccessibilityIssue> {
+        self.accessibility_report_with_threshold(AA_NORMAL_TEXT_RATIO)
+    }
+
+    /// Checks the contrast between each legend and its key, flagging any legend with a contrast
+    /// ratio below `threshold`.
+    ///
+    /// WCAG 2.1 requires a ratio of at least 4.5 for normal text or 3.0 for large text to meet
+    /// level AA. Issues are returned in key order, then legend order. Decals and legends with no
+    /// text are skipped, and fully transparent legends are reported as
+    /// [`AccessibilityIssue::LowVisibility`] regardless of their contrast ratio.
+    #[must_use]
+    pub fn accessibility_report_with_threshold(&self, threshold: f64) -> Vec<AccessibilityIssue> {
+        let mut issues = Vec::new();
+
+        for (key_index, key) in self.keys.iter().enumerate().filter(|(_, key)| !key.decal) {
+            for (legend_index, legend) in key.legends_iter() {
+                if legend.text.is_empty() {
+                    continue;
+                }
+
+                if legend.color.a == 0 {
+                    issues.push(AccessibilityIssue::LowVisibility {
+                        key_index,
+                        legend_index,
+                    });
+                    continue;
+                }
+
+                let ratio = key.color.contrast_ratio(legend.color);
+                if ratio < threshold {
+                    issues.push(AccessibilityIssue::InsufficientContrast {
+                        key_index,
+                        legend_index,
+                        ratio,
+                        required_ratio: threshold,
+                    });
+                }
+            }
+        }
+
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+
+    use isclose::assert_is_close;
+
+    use crate::{Color, Key, Legend};
+
+    use super::*;
+
+    fn key(color: Color, legend_color: Color) -> Key {
+        Key {
+            color,
+            ..Key::default()
+        }
+        .with_legend_at(0, Legend::new("A").with_color(legend_color))
+    }
+
+    #[test]
+    fn test_accessibility_issue_display() {
+        let issue = AccessibilityIssue::InsufficientContrast {
+            key_index: 1,
+            legend_index: 2,
+            ratio: 1.234,
+            required_ratio: 4.5,
+        };
+        assert_eq!(
+            issue.to_string(),
+            "legend 2 of key 1 has a contrast ratio of 1.23:1, expected at least 4.50:1"
+        );
+
+        let issue = AccessibilityIssue::LowVisibility {
+            key_index: 3,
+            legend_index: 4,
+        };
+        assert_eq!(issue.to_string(), "legend 4 of key 3 is transparent");
+    }
+
+    #[test]
+    fn test_keyboard_accessibility_report() {
+        let black = Color::new(0, 0, 0, 255);
+        let white = Color::new(255, 255, 255, 255);
+        let grey = Color::new(0x77, 0x77, 0x77, 255);
+
+        let keyboard = Keyboard::<f64> {
+            keys: vec![
+                key(black, black),
+                key(white, black),
+                key(white, grey),
+                key(white, Color::new(0, 0, 0, 0)),
+                Key {
+                    decal: true,
+                    ..key(black, black)
+                },
+                Key {
+                    color: black,
+                    ..Key::default()
+                }
+                .with_legend_at(0, Legend::new("").with_color(black)),
+            ],
+            ..Keyboard::default()
+        };
+
+        let report = keyboard.accessibility_report();
+        assert_eq!(report.len(), 3);
+        assert!(matches!(
+            report[0],
+            AccessibilityIssue::InsufficientContrast {
+                key_index: 0,
+                legend_index: 0,
+                ..
+            }
+        ));
+        if let AccessibilityIssue::InsufficientContrast {
+            ratio,
+            required_ratio,
+            ..
+        } = report[0]
+        {
+            assert_is_close!(ratio, 1.0);
+            assert_is_close!(required_ratio, 4.5);
+        }
+        assert!(matches!(
+            report[1],
+            AccessibilityIssue::InsufficientContrast { key_index: 2, .. }
+        ));
+        assert_eq!(
+            report[2],
+            AccessibilityIssue::LowVisibility {
+                key_index: 3,
+                legend_index: 0,
+            }
+        );
+
+        // #777777 on white is approximately 4.48:1, so it passes for large text
+        let report = keyboard.accessibility_report_with_threshold(3.0);
+        assert_eq!(report.len(), 2);
+        assert!(matches!(
+            report[0],
+            AccessibilityIssue::InsufficientContrast { key_index: 0, .. }
+        ));
+
+        assert_is_close!(keyboard.keys[1].legend_contrast(0).unwrap(), 21.0);
+        assert!(Keyboard::<f64>::default().accessibility_report().is_empty());
+    }
+}
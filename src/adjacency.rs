@@ -0,0 +1,249 @@
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use num_traits::real::Real;
+
+use crate::geom::quads_overlap;
+use crate::{Key, Keyboard};
+
+// The default gap threshold used by Keyboard::adjacency_graph, in keyboard units
+#[cfg(feature = "std")]
+const DEFAULT_ADJACENCY_THRESHOLD: f64 = 0.1;
+
+// Returns the shortest distance from point p to the line segment between a and b
+fn point_segment_distance<T: Real>(p: (T, T), a: (T, T), b: (T, T)) -> T {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq > T::zero() {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len_sq)
+            .max(T::zero())
+            .min(T::one())
+    } else {
+        T::zero()
+    };
+    (p.0 - (a.0 + t * dx)).hypot(p.1 - (a.1 + t * dy))
+}
+
+// Returns the shortest distance between the edges of two convex quadrilaterals, or zero if they
+// overlap
+fn quad_distance<T: Real>(a: &[(T, T); 4], b: &[(T, T); 4]) -> T {
+    if quads_overlap(a, b) {
+        return T::zero();
+    }
+
+    let vertex_edge = |p: &[(T, T); 4], q: &[(T, T); 4]| {
+        p.iter()
+            .flat_map(|&v| (0..4).map(move |i| point_segment_distance(v, q[i], q[(i + 1) % 4])))
+            .fold(T::max_value(), T::min)
+    };
+
+    vertex_edge(a, b).min(vertex_edge(b, a))
+}
+
+// Returns the shortest distance between two keys' (possibly rotated) outlines
+fn shape_distance<T: Real>(a: &[[(T, T); 4]; 2], b: &[[(T, T); 4]; 2]) -> T {
+    a.iter()
+        .flat_map(|qa| b.iter().map(move |qb| quad_distance(qa, qb)))
+        .fold(T::max_value(), T::min)
+}
+
+impl<T> Key<T>
+where
+    T: Real,
+{
+    /// Returns an iterator over the keys in `keyboard` whose outlines are closer than `threshold`
+    /// to this key's outline, along with their indices. Rotated keys are compared using their
+    /// rotated outlines. This key itself is excluded if it is part of `keyboard`.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["A", "B", {"x": 1}, "C"]]"#).unwrap();
+    /// let neighbors: Vec<_> = keyboard.keys[1].neighbors_in(&keyboard, 0.1).collect();
+    ///
+    /// assert_eq!(neighbors.len(), 1);
+    /// assert_eq!(neighbors[0].0, 0);
+    /// ```
+    pub fn neighbors_in<'a>(
+        &'a self,
+        keyboard: &'a Keyboard<T>,
+        threshold: T,
+    ) -> impl Iterator<Item = (usize, &'a Key<T>)> {
+        let quads = self.quads();
+        (keyboard.keys.iter().enumerate())
+            .filter(move |&(_, key)| !core::ptr::eq(key, self))
+            .filter(move |&(_, key)| shape_distance(&quads, &key.quads()) < threshold)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Returns the undirected adjacency graph of the layout, mapping each key's index to the
+    /// indices of its neighbours. Two keys are neighbours if their outlines are less than 0.1
+    /// keyboard units apart.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["A", "B"], ["C"]]"#).unwrap();
+    /// let graph = keyboard.adjacency_graph();
+    ///
+    /// assert_eq!(graph[&0], vec![1, 2]);
+    /// assert_eq!(graph[&1], vec![0, 2]);
+    /// assert_eq!(graph[&2], vec![0, 1]);
+    /// ```
+    #[must_use]
+    pub fn adjacency_graph(&self) -> HashMap<usize, Vec<usize>> {
+        let threshold = T::from(DEFAULT_ADJACENCY_THRESHOLD).unwrap_or_else(T::zero);
+        self.adjacency_graph_with_threshold(threshold)
+    }
+
+    /// Returns the undirected adjacency graph of the layout using a custom gap threshold. See
+    /// [`Keyboard::adjacency_graph`] for details.
+    #[must_use]
+    pub fn adjacency_graph_with_threshold(&self, threshold: T) -> HashMap<usize, Vec<usize>> {
+        let quads: Vec<_> = self.keys.iter().map(Key::quads).collect();
+        let mut graph: HashMap<_, Vec<_>> = (0..self.keys.len()).map(|i| (i, vec![])).collect();
+
+        for (i, a) in quads.iter().enumerate() {
+            for (j, b) in quads.iter().enumerate().skip(i + 1) {
+                if shape_distance(a, b) < threshold {
+                    graph.entry(i).or_default().push(j);
+                    graph.entry(j).or_default().push(i);
+                }
+            }
+        }
+
+        for neighbors in graph.values_mut() {
+            neighbors.sort_unstable();
+        }
+
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANSI_60: &str = r##"[
+        ["~", "!", "@", "#", "$", "%", "^", "&", "*", "(", ")", "_", "+", {"w": 2}, "Backspace"],
+        [{"w": 1.5}, "Tab", "Q", "W", "E", "R", "T", "Y", "U", "I", "O", "P", "{", "}",
+            {"w": 1.5}, "|"],
+        [{"w": 1.75}, "Caps Lock", "A", "S", "D", "F", "G", "H", "J", "K", "L", ":", "\"",
+            {"w": 2.25}, "Enter"],
+        [{"w": 2.25}, "Shift", "Z", "X", "C", "V", "B", "N", "M", "<", ">", "?", {"w": 2.75},
+            "Shift"],
+        [{"w": 1.25}, "Ctrl", {"w": 1.25}, "Win", {"w": 1.25}, "Alt", {"w": 6.25}, "",
+            {"w": 1.25}, "Alt", {"w": 1.25}, "Win", {"w": 1.25}, "Menu", {"w": 1.25}, "Ctrl"]
+    ]"##;
+
+    #[test]
+    fn test_key_neighbors_in() {
+        let keyboard: Keyboard = serde_json::from_str(ANSI_60).unwrap();
+
+        // S is surrounded by W, E above, A, D either side, and Z, X below
+        let neighbors: Vec<_> = keyboard.keys[30]
+            .neighbors_in(&keyboard, 0.1)
+            .map(|(_, key)| key.legends[0].as_ref().unwrap().text.as_str())
+            .collect();
+        assert_eq!(neighbors, ["W", "E", "A", "D", "Z", "X"]);
+
+        // A larger threshold includes keys further away
+        assert_eq!(keyboard.keys[0].neighbors_in(&keyboard, 0.1).count(), 2);
+        assert_eq!(keyboard.keys[0].neighbors_in(&keyboard, 1.1).count(), 5);
+
+        // A key which isn't part of the layout
+        let key = Key {
+            x: 15.05,
+            y: -0.5,
+            ..Key::default()
+        };
+        let neighbors: Vec<_> = key.neighbors_in(&keyboard, 0.1).map(|(i, _)| i).collect();
+        assert_eq!(neighbors, [13]);
+    }
+
+    #[test]
+    fn test_key_neighbors_in_rotated() {
+        // The bounding box of the rotated key overlaps the first key, but its outline doesn't
+        // come within 0.3u of it
+        let keyboard = Keyboard {
+            keys: vec![
+                Key::default(),
+                Key {
+                    x: 1.1,
+                    y: 1.1,
+                    rotation: 45.0,
+                    rx: 1.6,
+                    ry: 1.6,
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        };
+        assert_eq!(keyboard.keys[0].neighbors_in(&keyboard, 0.3).count(), 0);
+        assert_eq!(keyboard.keys[0].neighbors_in(&keyboard, 0.4).count(), 1);
+
+        // Keys rotated together remain adjacent
+        let keyboard = Keyboard {
+            keys: vec![
+                Key {
+                    rotation: 30.0,
+                    ..Key::default()
+                },
+                Key {
+                    x: 1.0,
+                    rotation: 30.0,
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        };
+        assert_eq!(keyboard.keys[0].neighbors_in(&keyboard, 0.1).count(), 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_keyboard_adjacency_graph() {
+        let keyboard: Keyboard = serde_json::from_str(ANSI_60).unwrap();
+        let graph = keyboard.adjacency_graph();
+
+        assert_eq!(graph.len(), keyboard.keys.len());
+
+        // The graph is undirected
+        for (&i, neighbors) in &graph {
+            assert!(!neighbors.contains(&i));
+            for j in neighbors {
+                assert!(graph[j].contains(&i));
+            }
+        }
+
+        // Every key has between 2 and 6 neighbours, except the spacebar which spans a whole row
+        let space = keyboard
+            .keys
+            .iter()
+            .position(|key| key.width > 6.0)
+            .unwrap();
+        for (i, neighbors) in &graph {
+            if *i == space {
+                assert_eq!(neighbors.len(), 9);
+            } else {
+                assert!((2..=6).contains(&neighbors.len()), "key {i}: {neighbors:?}");
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_keyboard_adjacency_graph_with_threshold() {
+        let keyboard: Keyboard = serde_json::from_str(r#"[["A", {"x": 0.5}, "B"]]"#).unwrap();
+
+        assert!(keyboard.adjacency_graph()[&0].is_empty());
+        assert!(keyboard.adjacency_graph_with_threshold(0.5)[&0].is_empty());
+        assert_eq!(keyboard.adjacency_graph_with_threshold(0.6)[&0], vec![1]);
+        assert!(Keyboard::<f64>::default().adjacency_graph().is_empty());
+    }
+}
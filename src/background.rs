@@ -0,0 +1,147 @@
+use alloc::string::{String, ToString};
+
+use crate::Background;
+
+impl Background {
+    /// Returns whether the layout uses a custom background, i.e. the background's style is not
+    /// empty.
+    #[inline]
+    #[must_use]
+    pub fn is_custom(&self) -> bool {
+        !self.style.is_empty()
+    }
+
+    /// Returns the URL of the background image set in the background's [CSS style], or [`None`]
+    /// if the style does not set a background image using `url()`.
+    ///
+    /// Both the `background-image` and `background` properties are checked, and the URL may be
+    /// quoted with either single or double quotes, or unquoted.
+    ///
+    /// ```
+    /// use kle_serial::Background;
+    ///
+    /// let background = Background {
+    ///     name: "Carbon fibre 1".into(),
+    ///     style: "background-image: url('/bg/carbonfibre/carbon_texture1879.png');".into(),
+    /// };
+    ///
+    /// assert_eq!(
+    ///     background.css_image_url().as_deref(),
+    ///     Some("/bg/carbonfibre/carbon_texture1879.png")
+    /// );
+    /// ```
+    ///
+    /// [CSS style]: Background::style
+    #[must_use]
+    pub fn css_image_url(&self) -> Option<String> {
+        let style = self.style.as_str();
+        // The start of the current declaration, and the position to continue searching from. URLs
+        // such as data URLs can contain semicolons, so each url() is skipped over as a whole
+        let (mut declaration, mut pos) = (0, 0);
+
+        loop {
+            let rest = &style[pos..];
+            let url = rest.find("url(");
+            match rest.find(';') {
+                Some(semi) if url.map_or(true, |url| semi < url) => {
+                    declaration = pos + semi + 1;
+                    pos = declaration;
+                }
+                _ => {
+                    let start = pos + url? + "url(".len();
+                    let (url, len) = parse_css_url(&style[start..])?;
+
+                    let property = style[declaration..start]
+                        .split_once(':')
+                        .map(|(p, _)| p.trim());
+                    if property.map_or(false, |property| {
+                        property.eq_ignore_ascii_case("background-image")
+                            || property.eq_ignore_ascii_case("background")
+                    }) {
+                        return Some(url.to_string());
+                    }
+                    pos = start + len;
+                }
+            }
+        }
+    }
+}
+
+// Parses the argument of a CSS url() starting just after the opening parenthesis, returning the
+// URL without any quotes and the length up to and including the closing parenthesis
+fn parse_css_url(value: &str) -> Option<(&str, usize)> {
+    let trimmed = value.trim_start();
+    let offset = value.len() - trimmed.len();
+
+    // Quoted URLs can contain parentheses, so find the closing quote first
+    if let Some(quote @ ('\'' | '"')) = trimmed.chars().next() {
+        let end = trimmed[1..].find(quote)? + 1;
+        let paren = end + trimmed[end..].find(')')?;
+        Some((&trimmed[1..end], offset + paren + 1))
+    } else {
+        let paren = trimmed.find(')')?;
+        Some((trimmed[..paren].trim_end(), offset + paren + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn background(style: &str) -> Background {
+        Background {
+            style: style.into(),
+            ..Background::default()
+        }
+    }
+
+    #[test]
+    fn test_background_is_custom() {
+        assert!(!Background::default().is_custom());
+        assert!(background("background-image: url('/bg/wood/wood.png');").is_custom());
+    }
+
+    #[test]
+    fn test_background_css_image_url() {
+        let urls = [
+            (
+                "background-image: url('/bg/carbonfibre/carbon_texture1879.png');",
+                Some("/bg/carbonfibre/carbon_texture1879.png"),
+            ),
+            (
+                r#"background-image: url("/bg/wood/wood.png")"#,
+                Some("/bg/wood/wood.png"),
+            ),
+            (
+                "color: red; BACKGROUND: #fff url( /bg/plain.png ) repeat;",
+                Some("/bg/plain.png"),
+            ),
+            ("background-color: red;", None),
+            ("background-image: none;", None),
+            ("color: url(/bg/plain.png);", None),
+            ("background-image: url('/bg/plain.png'", None),
+            ("", None),
+            // Data URLs contain semicolons, and quoted URLs can contain parentheses
+            (
+                "background-image: url(data:image/png;base64,iVBORw0KGgo=);",
+                Some("data:image/png;base64,iVBORw0KGgo="),
+            ),
+            (
+                r#"background: url("data:image/svg+xml;utf8,<svg>(;)</svg>") no-repeat"#,
+                Some("data:image/svg+xml;utf8,<svg>(;)</svg>"),
+            ),
+            (
+                "mask: url(data:image/png;base64,AAAA); background: url(/bg/plain.png)",
+                Some("/bg/plain.png"),
+            ),
+            (
+                "color: red; background: url('/bg/a.png'), url(data:a;b)",
+                Some("/bg/a.png"),
+            ),
+        ];
+
+        for (style, expected) in urls {
+            assert_eq!(background(style).css_image_url().as_deref(), expected);
+        }
+    }
+}
@@ -0,0 +1,798 @@
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use num_traits::real::Real;
+
+use crate::{
+    utils::{BoundsError, FontSize},
+    Background, Color, Key, Keyboard, Legend, Metadata, Switch, NUM_LEGENDS,
+};
+
+/// An error returned when building an invalid value using one of the builder types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuilderError {
+    /// A legend index was outside the range `0..12`.
+    LegendIndexOutOfRange {
+        /// The invalid index.
+        index: usize,
+    },
+}
+
+impl fmt::Display for BuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::LegendIndexOutOfRange { index } => write!(
+                f,
+                "legend index {index} is out of range, expected 0 <= index < {NUM_LEGENDS}"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BuilderError {}
+
+/// A builder for [`Key`]s, created using [`Key::builder`].
+///
+/// Any fields which are not set will use the same value as [`Key::default`].
+///
+/// ```
+/// use kle_serial::{Key, Legend};
+///
+/// let key: Key = Key::builder()
+///     .position(1.0, 0.0)
+///     .size(2.0, 1.0)
+///     .legend(0, Legend { text: "Backspace".into(), ..Legend::default() })
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(key.width2, 2.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct KeyBuilder<T = f64>
+where
+    T: Real,
+{
+    key: Key<T>,
+    secondary_rect: bool,
+    error: Option<BuilderError>,
+}
+
+impl<T> Key<T>
+where
+    T: Real,
+{
+    /// Creates a new [`KeyBuilder`].
+    #[inline]
+    #[must_use]
+    pub fn builder() -> KeyBuilder<T> {
+        KeyBuilder::new()
+    }
+
+    /// Sets the X and Y position of the key.
+    ///
+    /// ```
+    /// use kle_serial::Key;
+    ///
+    /// let key = Key::<f64>::default()
+    ///     .with_position(1.5, 2.0)
+    ///     .with_size(2.0, 1.0)
+    ///     .with_rotation(15.0, 1.5, 2.0);
+    ///
+    /// assert_eq!((key.x, key.y), (1.5, 2.0));
+    /// assert_eq!((key.width, key.width2), (2.0, 2.0));
+    /// assert_eq!(key.rotation, 15.0);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_position(mut self, x: T, y: T) -> Self {
+        self.x = x;
+        self.y = y;
+        self
+    }
+
+    /// Sets the width and height of the key.
+    ///
+    /// If the key's `width2` or `height2` is equal to its current `width` or `height`
+    /// respectively, it is also set to the new value, so a regular key remains a regular key.
+    #[inline]
+    #[must_use]
+    pub fn with_size(mut self, width: T, height: T) -> Self {
+        if self.width2 == self.width {
+            self.width2 = width;
+        }
+        if self.height2 == self.height {
+            self.height2 = height;
+        }
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Sets the position and size of the stepped or L-shaped part of the key.
+    #[inline]
+    #[must_use]
+    pub fn with_secondary_rect(mut self, x2: T, y2: T, width2: T, height2: T) -> Self {
+        self.x2 = x2;
+        self.y2 = y2;
+        self.width2 = width2;
+        self.height2 = height2;
+        self
+    }
+
+    /// Sets the rotation of the key in degrees and its centre of rotation.
+    #[inline]
+    #[must_use]
+    pub fn with_rotation(mut self, rotation: T, rx: T, ry: T) -> Self {
+        self.rotation = rotation;
+        self.rx = rx;
+        self.ry = ry;
+        self
+    }
+
+    /// Sets the colour of the key.
+    #[inline]
+    #[must_use]
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the keycap profile and row of the key.
+    #[inline]
+    #[must_use]
+    pub fn with_profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = profile.into();
+        self
+    }
+}
+
+impl<T> KeyBuilder<T>
+where
+    T: Real,
+{
+    /// Creates a new [`KeyBuilder`]. This is equivalent to [`Key::builder`].
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            key: Key::default(),
+            secondary_rect: false,
+            error: None,
+        }
+    }
+
+    /// Sets the key's legends. This array uses the same order as [`Key::legends`].
+    #[inline]
+    #[must_use]
+    pub fn legends(mut self, legends: [Option<Legend>; NUM_LEGENDS]) -> Self {
+        self.key.legends = legends;
+        self
+    }
+
+    /// Sets the legend at the given index. The index uses the same order as [`Key::legends`].
+    ///
+    /// An index greater than or equal to 12 will cause [`KeyBuilder::build`] to return an error.
+    #[inline]
+    #[must_use]
+    pub fn legend(mut self, index: usize, legend: Legend) -> Self {
+        if let Some(slot) = self.key.legends.get_mut(index) {
+            *slot = Some(legend);
+        } else {
+            self.error = self
+                .error
+                .or(Some(BuilderError::LegendIndexOutOfRange { index }));
+        }
+        self
+    }
+
+    /// Sets the colour of the key.
+    #[inline]
+    #[must_use]
+    pub fn color(mut self, color: Color) -> Self {
+        self.key.color = color;
+        self
+    }
+
+    /// Sets the X and Y position of the key.
+    #[inline]
+    #[must_use]
+    pub fn position(mut self, x: T, y: T) -> Self {
+        self.key.x = x;
+        self.key.y = y;
+        self
+    }
+
+    /// Sets the width and height of the key.
+    ///
+    /// Unless [`KeyBuilder::secondary_rect`] is also used, the key's `width2` and `height2` will
+    /// also be set to these values, the same as KLE does for a regular key.
+    #[inline]
+    #[must_use]
+    pub fn size(mut self, width: T, height: T) -> Self {
+        self.key.width = width;
+        self.key.height = height;
+        self
+    }
+
+    /// Sets the position and size of the stepped or L-shaped part of the key.
+    #[inline]
+    #[must_use]
+    pub fn secondary_rect(mut self, x2: T, y2: T, width2: T, height2: T) -> Self {
+        self.key.x2 = x2;
+        self.key.y2 = y2;
+        self.key.width2 = width2;
+        self.key.height2 = height2;
+        self.secondary_rect = true;
+        self
+    }
+
+    /// Sets the rotation of the key in degrees and its centre of rotation.
+    #[inline]
+    #[must_use]
+    pub fn rotation(mut self, rotation: T, rx: T, ry: T) -> Self {
+        self.key.rotation = rotation;
+        self.key.rx = rx;
+        self.key.ry = ry;
+        self
+    }
+
+    /// Sets the keycap profile and row of the key.
+    #[inline]
+    #[must_use]
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.key.profile = profile.into();
+        self
+    }
+
+    /// Sets the switch of the key.
+    #[inline]
+    #[must_use]
+    pub fn switch(mut self, switch: Switch) -> Self {
+        self.key.switch = switch;
+        self
+    }
+
+    /// Sets whether the key is ghosted.
+    #[inline]
+    #[must_use]
+    pub fn ghosted(mut self, ghosted: bool) -> Self {
+        self.key.ghosted = ghosted;
+        self
+    }
+
+    /// Sets whether the key is stepped.
+    #[inline]
+    #[must_use]
+    pub fn stepped(mut self, stepped: bool) -> Self {
+        self.key.stepped = stepped;
+        self
+    }
+
+    /// Sets whether the key is a homing key.
+    #[inline]
+    #[must_use]
+    pub fn homing(mut self, homing: bool) -> Self {
+        self.key.homing = homing;
+        self
+    }
+
+    /// Sets whether the key is a decal.
+    #[inline]
+    #[must_use]
+    pub fn decal(mut self, decal: bool) -> Self {
+        self.key.decal = decal;
+        self
+    }
+
+    /// Builds the [`Key`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`BuilderError`] if any invalid values were given to the builder.
+    #[inline]
+    pub fn build(self) -> Result<Key<T>, BuilderError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        let mut key = self.key;
+        if !self.secondary_rect {
+            key.width2 = key.width;
+            key.height2 = key.height;
+        }
+        Ok(key)
+    }
+}
+
+impl<T> Default for KeyBuilder<T>
+where
+    T: Real,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A builder for [`Metadata`], created using [`Metadata::builder`].
+///
+/// Any fields which are not set will use the same value as [`Metadata::default`].
+#[derive(Debug, Clone, Default)]
+pub struct MetadataBuilder {
+    metadata: Metadata,
+}
+
+impl Metadata {
+    /// Creates a new [`MetadataBuilder`].
+    #[inline]
+    #[must_use]
+    pub fn builder() -> MetadataBuilder {
+        MetadataBuilder::new()
+    }
+}
+
+impl MetadataBuilder {
+    /// Creates a new [`MetadataBuilder`]. This is equivalent to [`Metadata::builder`].
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the background colour for the layout.
+    #[inline]
+    #[must_use]
+    pub fn background_color(mut self, background_color: Color) -> Self {
+        self.metadata.background_color = background_color;
+        self
+    }
+
+    /// Sets the background style for the layout.
+    #[inline]
+    #[must_use]
+    pub fn background(mut self, background: Background) -> Self {
+        self.metadata.background = background;
+        self
+    }
+
+    /// Sets the corner radii for the background using CSS [`border-radius`] syntax.
+    ///
+    /// [`border-radius`]: https://developer.mozilla.org/en-US/docs/Web/CSS/border-radius
+    #[inline]
+    #[must_use]
+    pub fn radii(mut self, radii: impl Into<String>) -> Self {
+        self.metadata.radii = radii.into();
+        self
+    }
+
+    /// Sets the name of the layout.
+    #[inline]
+    #[must_use]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.metadata.name = name.into();
+        self
+    }
+
+    /// Sets the author of the layout.
+    #[inline]
+    #[must_use]
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.metadata.author = author.into();
+        self
+    }
+
+    /// Sets the default switch used in the layout.
+    #[inline]
+    #[must_use]
+    pub fn switch(mut self, switch: Switch) -> Self {
+        self.metadata.switch = switch;
+        self
+    }
+
+    /// Sets whether the switch is plate mounted.
+    #[inline]
+    #[must_use]
+    pub fn plate_mount(mut self, plate_mount: bool) -> Self {
+        self.metadata.plate_mount = plate_mount;
+        self
+    }
+
+    /// Sets whether the switch is PCB mounted.
+    #[inline]
+    #[must_use]
+    pub fn pcb_mount(mut self, pcb_mount: bool) -> Self {
+        self.metadata.pcb_mount = pcb_mount;
+        self
+    }
+
+    /// Sets the notes for the layout.
+    #[inline]
+    #[must_use]
+    pub fn notes(mut self, notes: impl Into<String>) -> Self {
+        self.metadata.notes = notes.into();
+        self
+    }
+
+    /// Sets the custom CSS for the layout.
+    #[inline]
+    #[must_use]
+    pub fn css(mut self, css: impl Into<String>) -> Self {
+        self.metadata.css = css.into();
+        self
+    }
+
+    /// Builds the [`Metadata`].
+    #[inline]
+    #[must_use]
+    pub fn build(self) -> Metadata {
+        self.metadata
+    }
+}
+
+/// A builder for [`Keyboard`]s, created using [`Keyboard::builder`].
+///
+/// ```
+/// use kle_serial::{Key, Keyboard, Metadata};
+///
+/// let keyboard: Keyboard = Keyboard::builder()
+///     .metadata(Metadata::builder().name("numpad").build())
+///     .extend_keys((0..3).map(|i| Key::builder().position(f64::from(i), 0.0).build().unwrap()))
+///     .push_key(Key::builder().position(0.0, 1.0).size(3.0, 1.0).build().unwrap())
+///     .build();
+///
+/// assert_eq!(keyboard.metadata.name, "numpad");
+/// assert_eq!(keyboard.keys.len(), 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct KeyboardBuilder<T = f64>
+where
+    T: Real,
+{
+    keyboard: Keyboard<T>,
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Creates a new [`KeyboardBuilder`].
+    #[inline]
+    #[must_use]
+    pub fn builder() -> KeyboardBuilder<T> {
+        KeyboardBuilder::new()
+    }
+}
+
+impl<T> KeyboardBuilder<T>
+where
+    T: Real,
+{
+    /// Creates a new [`KeyboardBuilder`]. This is equivalent to [`Keyboard::builder`].
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            keyboard: Keyboard {
+                metadata: Metadata::default(),
+                keys: Vec::new(),
+            },
+        }
+    }
+
+    /// Sets the layout's metadata.
+    #[inline]
+    #[must_use]
+    pub fn metadata(mut self, metadata: Metadata) -> Self {
+        self.keyboard.metadata = metadata;
+        self
+    }
+
+    /// Adds a key to the layout.
+    #[inline]
+    #[must_use]
+    pub fn push_key(mut self, key: Key<T>) -> Self {
+        self.keyboard.keys.push(key);
+        self
+    }
+
+    /// Adds multiple keys to the layout.
+    #[inline]
+    #[must_use]
+    pub fn extend_keys(mut self, keys: impl IntoIterator<Item = Key<T>>) -> Self {
+        self.keyboard.keys.extend(keys);
+        self
+    }
+
+    /// Builds the [`Keyboard`].
+    #[inline]
+    #[must_use]
+    pub fn build(self) -> Keyboard<T> {
+        self.keyboard
+    }
+}
+
+impl<T> Default for KeyboardBuilder<T>
+where
+    T: Real,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Legend {
+    /// Creates a new [`Legend`] with the given text and the default size and colour.
+    ///
+    /// ```
+    /// use kle_serial::{Color, Legend};
+    ///
+    /// let legend = Legend::new("Esc")
+    ///     .with_size(4)
+    ///     .unwrap()
+    ///     .with_color(Color::new(255, 0, 0, 255));
+    ///
+    /// assert_eq!(legend.text, "Esc");
+    /// assert_eq!(legend.size, 4);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new(text: impl Into<String>) -> Self {
+        Self::empty().with_text(text)
+    }
+
+    /// Creates a new [`Legend`] with empty text and the default size and colour. This is
+    /// equivalent to [`Legend::default`].
+    #[inline]
+    #[must_use]
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Sets the text of the legend.
+    #[inline]
+    #[must_use]
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.text = text.into();
+        self
+    }
+
+    /// Sets the size of the legend.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`BoundsError`] if `size` is not a valid [`FontSize`].
+    #[inline]
+    pub fn with_size(mut self, size: usize) -> Result<Self, BoundsError> {
+        self.size = FontSize::try_from(size)?.into();
+        Ok(self)
+    }
+
+    /// Sets the colour of the legend.
+    #[inline]
+    #[must_use]
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isclose::assert_is_close;
+
+    use super::*;
+
+    #[test]
+    fn test_builder_error_display() {
+        let error = BuilderError::LegendIndexOutOfRange { index: 12 };
+
+        assert_eq!(
+            format!("{error}"),
+            "legend index 12 is out of range, expected 0 <= index < 12"
+        );
+    }
+
+    #[test]
+    fn test_legend_builder() {
+        assert_eq!(Legend::empty(), Legend::default());
+        assert_eq!(
+            Legend::new("A"),
+            Legend {
+                text: "A".into(),
+                ..Legend::default()
+            }
+        );
+
+        let legend = Legend::new("A")
+            .with_text(String::from("B"))
+            .with_size(9)
+            .unwrap()
+            .with_color(Color::new(0, 0, 255, 255));
+        assert_eq!(
+            legend,
+            Legend {
+                text: "B".into(),
+                size: 9,
+                color: Color::new(0, 0, 255, 255),
+            }
+        );
+
+        assert_eq!(
+            Legend::new("A").with_size(10),
+            Err(FontSize::try_from(10).unwrap_err())
+        );
+        assert_eq!(Legend::new("A").with_size(0).unwrap().size, 0);
+    }
+
+    #[test]
+    fn test_key_builder() {
+        let key = Key::<f64>::builder().build().unwrap();
+
+        assert_eq!(key, Key::default());
+
+        let legend = Legend {
+            text: "A".into(),
+            ..Legend::default()
+        };
+        let switch = Switch {
+            mount: "cherry".into(),
+            brand: "cherry".into(),
+            typ: "MX1A-31xx".into(),
+        };
+        let key = Key::<f64>::builder()
+            .legend(4, legend.clone())
+            .color(Color::new(127, 51, 76, 255))
+            .position(1.0, 2.0)
+            .size(1.25, 2.0)
+            .secondary_rect(-0.25, 0.0, 1.5, 1.0)
+            .rotation(15.0, 1.0, 2.0)
+            .profile("DSA")
+            .switch(switch.clone())
+            .ghosted(true)
+            .stepped(true)
+            .homing(true)
+            .decal(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(key.legends[4], Some(legend));
+        assert_eq!(key.color, Color::new(127, 51, 76, 255));
+        assert_is_close!(key.x, 1.0);
+        assert_is_close!(key.y, 2.0);
+        assert_is_close!(key.width, 1.25);
+        assert_is_close!(key.height, 2.0);
+        assert_is_close!(key.x2, -0.25);
+        assert_is_close!(key.y2, 0.0);
+        assert_is_close!(key.width2, 1.5);
+        assert_is_close!(key.height2, 1.0);
+        assert_is_close!(key.rotation, 15.0);
+        assert_is_close!(key.rx, 1.0);
+        assert_is_close!(key.ry, 2.0);
+        assert_eq!(key.profile, "DSA");
+        assert_eq!(key.switch, switch);
+        assert!(key.ghosted);
+        assert!(key.stepped);
+        assert!(key.homing);
+        assert!(key.decal);
+
+        let key = Key::<f32>::builder().size(2.0, 1.0).build().unwrap();
+        assert_is_close!(key.width2, 2.0);
+        assert_is_close!(key.height2, 1.0);
+
+        let legends = std::array::from_fn(|i| {
+            Some(Legend {
+                text: format!("{i}"),
+                ..Legend::default()
+            })
+        });
+        let key = Key::<f64>::builder()
+            .legends(legends.clone())
+            .build()
+            .unwrap();
+        assert_eq!(key.legends, legends);
+
+        let result = Key::<f64>::builder()
+            .legend(12, Legend::default())
+            .legend(13, Legend::default())
+            .build();
+        assert_eq!(
+            result,
+            Err(BuilderError::LegendIndexOutOfRange { index: 12 })
+        );
+    }
+
+    #[test]
+    fn test_key_with_methods() {
+        let key = Key::<f64>::default()
+            .with_position(1.0, 2.0)
+            .with_size(1.25, 2.0)
+            .with_rotation(15.0, 1.0, 2.0)
+            .with_color(Color::new(127, 51, 76, 255))
+            .with_profile("DSA");
+
+        assert_eq!(
+            key,
+            Key::builder()
+                .position(1.0, 2.0)
+                .size(1.25, 2.0)
+                .rotation(15.0, 1.0, 2.0)
+                .color(Color::new(127, 51, 76, 255))
+                .profile("DSA")
+                .build()
+                .unwrap()
+        );
+
+        // The secondary rectangle is only resized where it matches the primary
+        let key = key
+            .with_secondary_rect(-0.25, 0.0, 1.5, 2.0)
+            .with_size(1.5, 3.0);
+        assert_is_close!(key.x2, -0.25);
+        assert_is_close!(key.y2, 0.0);
+        assert_is_close!(key.width, 1.5);
+        assert_is_close!(key.height, 3.0);
+        assert_is_close!(key.width2, 1.5);
+        assert_is_close!(key.height2, 3.0);
+
+        let key = key
+            .with_secondary_rect(0.0, 0.0, 1.0, 1.0)
+            .with_size(2.0, 2.0);
+        assert_is_close!(key.width2, 1.0);
+        assert_is_close!(key.height2, 1.0);
+    }
+
+    #[test]
+    fn test_metadata_builder() {
+        let metadata = Metadata::builder().build();
+
+        assert_eq!(metadata, Metadata::default());
+
+        let background = Background {
+            name: "name".into(),
+            style: "style".into(),
+        };
+        let switch = Switch {
+            mount: "cherry".into(),
+            brand: "cherry".into(),
+            typ: "MX1A-31xx".into(),
+        };
+        let metadata = Metadata::builder()
+            .background_color(Color::new(204, 34, 34, 255))
+            .background(background.clone())
+            .radii("radii")
+            .name("name")
+            .author("author")
+            .switch(switch.clone())
+            .plate_mount(true)
+            .pcb_mount(true)
+            .notes("notes")
+            .css("css")
+            .build();
+
+        assert_eq!(metadata.background_color, Color::new(204, 34, 34, 255));
+        assert_eq!(metadata.background, background);
+        assert_eq!(metadata.radii, "radii");
+        assert_eq!(metadata.name, "name");
+        assert_eq!(metadata.author, "author");
+        assert_eq!(metadata.switch, switch);
+        assert!(metadata.plate_mount);
+        assert!(metadata.pcb_mount);
+        assert_eq!(metadata.notes, "notes");
+        assert_eq!(metadata.css, "css");
+    }
+
+    #[test]
+    fn test_keyboard_builder() {
+        let keyboard = Keyboard::<f64>::builder().build();
+
+        assert_eq!(keyboard, Keyboard::default());
+
+        let keyboard = Keyboard::<f32>::builder()
+            .metadata(Metadata::builder().name("name").build())
+            .push_key(Key::default())
+            .extend_keys(vec![Key::default(); 3])
+            .build();
+
+        assert_eq!(keyboard.metadata.name, "name");
+        assert_eq!(keyboard.keys.len(), 4);
+    }
+}
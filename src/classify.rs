@@ -0,0 +1,287 @@
+use alloc::{vec, vec::Vec};
+use core::cmp::Ordering;
+
+use num_traits::real::Real;
+
+use crate::geom::approx_eq;
+use crate::{Key, Keyboard};
+
+/// The general type of a layout, as returned by [`Keyboard::layout_type`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LayoutType {
+    /// Keys are arranged in a regular grid, such as on a Planck.
+    Ortholinear,
+    /// Each row is offset horizontally from the row above it, as on most standard keyboards.
+    RowStaggered,
+    /// Each column is offset vertically from its neighbours, as on many ergonomic keyboards.
+    ColumnStaggered,
+    /// The layout is split into separate halves, or has clusters rotated in opposite directions.
+    Split,
+    /// The layout could not be classified, for example because it has no keys.
+    Unknown,
+}
+
+fn is_whole<T: Real>(value: T) -> bool {
+    approx_eq(value - value.round(), 0.0)
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    // Only keys in the unrotated part of the layout are used to classify it
+    fn classified_keys(&self) -> impl Iterator<Item = &Key<T>> {
+        (self.keys.iter()).filter(|key| !key.decal && approx_eq(key.rotation, 0.0))
+    }
+
+    /// Returns `true` if the layout's keys are arranged in a regular grid.
+    ///
+    /// This is the case when every key's position and size are whole keyboard units. Rotated keys
+    /// and decals are ignored, and a layout with no other keys is not considered ortholinear.
+    ///
+    /// Like the other layout classification methods, this is a heuristic and may misclassify some
+    /// layouts. See [`Keyboard::layout_type`] for details.
+    #[must_use]
+    pub fn is_ortholinear(&self) -> bool {
+        let mut keys = self.classified_keys().peekable();
+
+        keys.peek().is_some()
+            && keys.all(|key| {
+                [key.x, key.y, key.width, key.height]
+                    .into_iter()
+                    .all(is_whole)
+            })
+    }
+
+    /// Returns `true` if the rows of the layout are offset horizontally from each other.
+    ///
+    /// Keys are grouped into rows by their `y` position. Each row's offset is taken from the `x`
+    /// position of its first 1u wide key, and the layout is row staggered if any two successive
+    /// rows have offsets that differ by a fraction of a unit. Rotated keys and decals are ignored.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard =
+    ///     serde_json::from_str(r#"[["Q", "W"], [{"x": 0.25}, "A", "S"], [{"x": 0.75}, "Z", "X"]]"#)
+    ///         .unwrap();
+    ///
+    /// assert!(keyboard.is_row_staggered());
+    /// assert!(!keyboard.is_ortholinear());
+    /// ```
+    #[must_use]
+    pub fn is_row_staggered(&self) -> bool {
+        let mut keys: Vec<_> = self.classified_keys().collect();
+        keys.sort_by(|a, b| {
+            (a.y.partial_cmp(&b.y).unwrap_or(Ordering::Equal))
+                .then(a.x.partial_cmp(&b.x).unwrap_or(Ordering::Equal))
+        });
+
+        let mut rows: Vec<Vec<&Key<T>>> = Vec::new();
+        for key in keys {
+            match rows.last_mut() {
+                Some(row) if approx_eq(row[0].y - key.y, 0.0) => row.push(key),
+                _ => rows.push(vec![key]),
+            }
+        }
+
+        let offsets: Vec<_> = (rows.iter())
+            .filter_map(|row| row.iter().find(|key| approx_eq(key.width, 1.0)))
+            .map(|key| key.x)
+            .collect();
+
+        offsets.windows(2).any(|w| !is_whole(w[1] - w[0]))
+    }
+
+    /// Returns `true` if the columns of the layout are offset vertically from each other.
+    ///
+    /// This is the case when any key is directly to the right of another key, but offset
+    /// vertically from it by a fraction of a unit. Rotated keys and decals are ignored.
+    #[must_use]
+    pub fn is_column_staggered(&self) -> bool {
+        let keys: Vec<_> = self.classified_keys().collect();
+
+        keys.iter().any(|a| {
+            keys.iter().any(|b| {
+                let dy = b.y - a.y;
+                approx_eq(a.x + a.width - b.x, 0.0) && dy.abs() < T::one() && !is_whole(dy)
+            })
+        })
+    }
+
+    /// Returns `true` if the layout appears to be split into separate halves.
+    ///
+    /// This is the case when the layout contains keys rotated in opposite directions, such as the
+    /// two halves of an Alice style layout, or when there is a horizontal gap of at least 1u that
+    /// no unrotated key spans, with keys on either side of it.
+    #[must_use]
+    pub fn is_split(&self) -> bool {
+        let opposite_rotations = (self.keys.iter()).any(|key| key.rotation > T::zero())
+            && (self.keys.iter()).any(|key| key.rotation < T::zero());
+
+        let mut spans: Vec<_> = (self.classified_keys())
+            .map(|key| (key.x, key.x + key.width))
+            .collect();
+        spans.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        let mut gap = false;
+        let mut end: Option<T> = None;
+        for (start, stop) in spans {
+            gap |= end.map_or(false, |end| start - end >= T::one());
+            end = Some(end.map_or(stop, |end| end.max(stop)));
+        }
+
+        opposite_rotations || gap
+    }
+
+    /// Returns the general type of the layout.
+    ///
+    /// The layout is classified using [`Keyboard::is_split`], [`Keyboard::is_ortholinear`],
+    /// [`Keyboard::is_column_staggered`], and [`Keyboard::is_row_staggered`], in that order. If
+    /// none of these apply, [`LayoutType::Unknown`] is returned.
+    ///
+    /// These are heuristics based only on the position of the keys, so some layouts may be
+    /// misclassified. For example a split keyboard drawn without a gap between the halves will not
+    /// be detected as split, and a small macro pad may be classified as ortholinear.
+    #[must_use]
+    pub fn layout_type(&self) -> LayoutType {
+        if self.is_split() {
+            LayoutType::Split
+        } else if self.is_ortholinear() {
+            LayoutType::Ortholinear
+        } else if self.is_column_staggered() {
+            LayoutType::ColumnStaggered
+        } else if self.is_row_staggered() {
+            LayoutType::RowStaggered
+        } else {
+            LayoutType::Unknown
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(x: f64, y: f64) -> Key {
+        Key {
+            x,
+            y,
+            ..Key::default()
+        }
+    }
+
+    fn grid(x: f64, cols: u8, rows: u8) -> impl Iterator<Item = Key> {
+        (0..rows).flat_map(move |r| (0..cols).map(move |c| key(x + f64::from(c), f64::from(r))))
+    }
+
+    #[test]
+    fn test_keyboard_is_ortholinear() {
+        let mut keyboard: Keyboard = grid(0.0, 12, 4).collect();
+        assert!(keyboard.is_ortholinear());
+        assert!(!keyboard.is_row_staggered());
+        assert!(!keyboard.is_column_staggered());
+        assert!(!keyboard.is_split());
+        assert_eq!(keyboard.layout_type(), LayoutType::Ortholinear);
+
+        // Rotated keys and decals are ignored
+        keyboard.keys.push(Key {
+            rotation: 15.0,
+            ..key(3.3, 4.7)
+        });
+        keyboard.keys.push(Key {
+            decal: true,
+            ..key(0.5, 0.5)
+        });
+        assert!(keyboard.is_ortholinear());
+
+        keyboard.keys[0].width = 1.5;
+        assert!(!keyboard.is_ortholinear());
+
+        assert!(!Keyboard::<f64>::default().is_ortholinear());
+    }
+
+    #[test]
+    fn test_keyboard_is_row_staggered() {
+        let keyboard: Keyboard = serde_json::from_str(
+            r#"[
+                ["", {"x": 1}, "", "", "", "", {"x": 0.5}, "", "", "", "", {"x": 0.5}, "", "", "",
+                    "", {"x": 0.25}, "", "", ""],
+                [{"y": 0.5}, "", "", "", "", "", "", "", "", "", "", "", "", "", {"w": 2}, "",
+                    {"x": 0.25}, "", "", "", {"x": 0.25}, "", "", "", ""],
+                [{"w": 1.5}, "", "", "", "", "", "", "", "", "", "", "", "", "", {"w": 1.5}, "",
+                    {"x": 0.25}, "", "", "", {"x": 0.25}, "", "", "", {"h": 2}, ""],
+                [{"w": 1.75}, "", "", "", "", "", "", "", "", "", "", "", "", {"w": 2.25}, "",
+                    {"x": 3.5}, "", "", ""],
+                [{"w": 2.25}, "", "", "", "", "", "", "", "", "", "", "", {"w": 2.75}, "",
+                    {"x": 1.25}, "", {"x": 1.25}, "", "", "", {"h": 2}, ""],
+                [{"w": 1.25}, "", {"w": 1.25}, "", {"w": 1.25}, "", {"w": 6.25}, "", {"w": 1.25},
+                    "", {"w": 1.25}, "", {"w": 1.25}, "", {"w": 1.25}, "", {"x": 0.25}, "", "", "",
+                    {"x": 0.25, "w": 2}, "", ""]
+            ]"#,
+        )
+        .unwrap();
+
+        assert!(keyboard.is_row_staggered());
+        assert!(!keyboard.is_ortholinear());
+        assert!(!keyboard.is_column_staggered());
+        assert!(!keyboard.is_split());
+        assert_eq!(keyboard.layout_type(), LayoutType::RowStaggered);
+    }
+
+    #[test]
+    fn test_keyboard_is_column_staggered() {
+        let keyboard: Keyboard = [0.5, 0.25, 0.0, 0.125]
+            .into_iter()
+            .zip(0_u8..)
+            .flat_map(|(y, x)| (0_u8..3).map(move |r| key(f64::from(x), y + f64::from(r))))
+            .collect();
+
+        assert!(keyboard.is_column_staggered());
+        assert!(!keyboard.is_row_staggered());
+        assert!(!keyboard.is_ortholinear());
+        assert!(!keyboard.is_split());
+        assert_eq!(keyboard.layout_type(), LayoutType::ColumnStaggered);
+
+        // Fractional row gaps are not a column stagger
+        let keyboard: Keyboard = grid(0.0, 4, 1)
+            .chain(grid(0.0, 4, 2).map(|key| Key {
+                y: key.y + 1.5,
+                ..key
+            }))
+            .collect();
+        assert!(!keyboard.is_column_staggered());
+    }
+
+    #[test]
+    fn test_keyboard_is_split() {
+        let keyboard: Keyboard = grid(0.0, 5, 3).chain(grid(7.0, 5, 3)).collect();
+        assert!(keyboard.is_split());
+        assert_eq!(keyboard.layout_type(), LayoutType::Split);
+
+        let keyboard: Keyboard = grid(0.0, 5, 3).chain(grid(5.5, 5, 3)).collect();
+        assert!(!keyboard.is_split());
+
+        let keyboard: Keyboard = [-10.0, 10.0]
+            .into_iter()
+            .map(|rotation| Key {
+                rotation,
+                ..Key::default()
+            })
+            .collect();
+        assert!(keyboard.is_split());
+        assert_eq!(keyboard.layout_type(), LayoutType::Split);
+    }
+
+    #[test]
+    fn test_keyboard_layout_type_unknown() {
+        assert_eq!(
+            Keyboard::<f64>::default().layout_type(),
+            LayoutType::Unknown
+        );
+
+        // A single row of mixed width keys
+        let keyboard: Keyboard = serde_json::from_str(r#"[[{"w": 1.5}, "", "", ""]]"#).unwrap();
+        assert_eq!(keyboard.layout_type(), LayoutType::Unknown);
+    }
+}
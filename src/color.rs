@@ -0,0 +1,614 @@
+use alloc::{format, string::String, vec::Vec};
+use core::fmt;
+
+use num_traits::real::Real;
+use serde::{Serialize, Serializer};
+
+use crate::{Color, Key, Keyboard};
+
+pub(crate) const BACKGROUND: Color = Color::new(0xEE, 0xEE, 0xEE, 0xFF); // #EEEEEE
+pub(crate) const KEY: Color = Color::new(0xCC, 0xCC, 0xCC, 0xFF); // #CCCCCC
+pub(crate) const LEGEND: Color = Color::new(0x00, 0x00, 0x00, 0xFF); // #000000
+
+/// An error returned when parsing an invalid hex colour string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColorError {
+    input: String,
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid hex colour {:?}", self.input)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseColorError {}
+
+/// Extension methods for [`Color`].
+///
+/// [`Color`] is a type alias of [`rgb::RGBA8`], so these methods are provided by a trait rather than
+/// directly on the type.
+pub trait ColorExt: Sized {
+    /// Formats the colour as a `#rrggbb` hex string, ignoring the alpha channel the same as KLE.
+    #[must_use]
+    fn to_hex_string(&self) -> String;
+
+    /// Formats the colour as a `#rrggbbaa` hex string, including the alpha channel.
+    #[must_use]
+    fn to_hex_string_with_alpha(&self) -> String;
+
+    /// Parses a colour from a hex string.
+    ///
+    /// This accepts the `#rrggbb` and `#rgb` forms used by KLE, as well as `#rrggbbaa` and `#rgba`.
+    /// The leading `#` is optional.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseColorError`] if the string is not a valid hex colour.
+    fn from_hex_str(s: &str) -> Result<Self, ParseColorError>;
+
+    /// Returns the relative luminance of the colour as defined by [WCAG 2.1], ignoring the alpha
+    /// channel. The result is in the range `0.0..=1.0`.
+    ///
+    /// [WCAG 2.1]: https://www.w3.org/TR/WCAG21/#dfn-relative-luminance
+    #[must_use]
+    fn relative_luminance(&self) -> f64;
+
+    /// Returns the contrast ratio between this colour and `other` as defined by [WCAG 2.1]. The
+    /// result is in the range `1.0..=21.0`.
+    ///
+    /// [WCAG 2.1]: https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio
+    #[must_use]
+    fn contrast_ratio(&self, other: Self) -> f64 {
+        let (l1, l2) = (self.relative_luminance(), other.relative_luminance());
+        let (lighter, darker) = if l1 > l2 { (l1, l2) } else { (l2, l1) };
+        (lighter + 0.05) / (darker + 0.05)
+    }
+
+    /// Returns whether the colour is dark, i.e. its relative luminance is less than 0.5.
+    ///
+    /// Use [`ColorExt::relative_luminance`] directly if a different threshold is needed.
+    #[must_use]
+    fn is_dark(&self) -> bool {
+        self.relative_luminance() < 0.5
+    }
+
+    /// Converts the colour to HSL, ignoring the alpha channel.
+    ///
+    /// Returns a tuple of hue in degrees in the range `0.0..360.0`, and saturation and lightness
+    /// in the range `0.0..=1.0`.
+    #[must_use]
+    fn to_hsl(&self) -> (f64, f64, f64);
+
+    /// Creates an opaque colour from HSL values. The hue `h` is in degrees and is wrapped into the
+    /// range `0.0..360.0`, while the saturation `s` and lightness `l` are clamped to `0.0..=1.0`.
+    #[must_use]
+    fn from_hsl(h: f64, s: f64, l: f64) -> Self;
+
+    /// Linearly interpolates between this colour and `other` in sRGB space, including the alpha
+    /// channel. `t` is clamped to `0.0..=1.0`, where `0.0` returns `self` and `1.0` returns `other`.
+    #[must_use]
+    fn blend(&self, other: Self, t: f64) -> Self;
+
+    /// Returns a greyscale version of the colour using the NTSC luminance formula. The alpha
+    /// channel is unchanged.
+    #[must_use]
+    fn to_gray(&self) -> Self;
+}
+
+/// A [`Color`] which is serialised and deserialised as a CSS hex string in the same format as KLE.
+///
+/// Opaque colours are serialised as `#rrggbb`. KLE ignores the alpha channel, so colours which are
+/// not fully opaque are serialised as `#rrggbbaa` to avoid losing information.
+///
+/// ```
+/// use kle_serial::{Color, KleColor};
+///
+/// let color = KleColor(Color::new(255, 0, 0, 255));
+///
+/// assert_eq!(serde_json::to_string(&color).unwrap(), r##""#ff0000""##);
+/// assert_eq!(serde_json::from_str::<KleColor>(r##""#ff0000""##).unwrap(), color);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct KleColor(pub Color);
+
+impl From<Color> for KleColor {
+    fn from(value: Color) -> Self {
+        Self(value)
+    }
+}
+
+impl From<KleColor> for Color {
+    fn from(value: KleColor) -> Self {
+        value.0
+    }
+}
+
+impl Serialize for KleColor {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&color_to_string(self.0))
+    }
+}
+
+pub(crate) fn color_to_string(color: Color) -> String {
+    // KLE ignores alpha, so we only include it if it's not opaque
+    if color.a == u8::MAX {
+        color.to_hex_string()
+    } else {
+        color.to_hex_string_with_alpha()
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Clamped to u8 range
+fn channel_from_f64(c: f64) -> u8 {
+    Real::round(c * 255.0).clamp(0.0, 255.0) as u8
+}
+
+impl ColorExt for Color {
+    fn to_hex_string(&self) -> String {
+        let Self { r, g, b, a: _ } = *self;
+        format!("#{r:02x}{g:02x}{b:02x}")
+    }
+
+    fn to_hex_string_with_alpha(&self) -> String {
+        let Self { r, g, b, a } = *self;
+        format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+    }
+
+    fn from_hex_str(s: &str) -> Result<Self, ParseColorError> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        let error = || ParseColorError { input: s.into() };
+
+        let nibbles = (hex.chars())
+            .map(|c| c.to_digit(16).and_then(|d| u8::try_from(d).ok()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(error)?;
+
+        let channels: Vec<_> = match nibbles.len() {
+            3 | 4 => nibbles.iter().map(|n| n * 0x11).collect(),
+            6 | 8 => nibbles.chunks(2).map(|n| n[0] * 0x10 + n[1]).collect(),
+            _ => return Err(error()),
+        };
+
+        Ok(Self {
+            r: channels[0],
+            g: channels[1],
+            b: channels[2],
+            a: channels.get(3).copied().unwrap_or(u8::MAX),
+        })
+    }
+
+    fn relative_luminance(&self) -> f64 {
+        let linearize = |c: u8| {
+            let c = f64::from(c) / 255.0;
+            if c <= 0.040_45 {
+                c / 12.92
+            } else {
+                Real::powf((c + 0.055) / 1.055, 2.4)
+            }
+        };
+
+        0.2126 * linearize(self.r) + 0.7152 * linearize(self.g) + 0.0722 * linearize(self.b)
+    }
+
+    #[allow(clippy::many_single_char_names)] // Conventional names for colour maths
+    fn to_hsl(&self) -> (f64, f64, f64) {
+        let (r, g, b) = (
+            f64::from(self.r) / 255.0,
+            f64::from(self.g) / 255.0,
+            f64::from(self.b) / 255.0,
+        );
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+
+        let l = (max + min) / 2.0;
+        if delta <= 0.0 {
+            return (0.0, 0.0, l);
+        }
+
+        let s = delta / (1.0 - Real::abs(2.0 * l - 1.0));
+        let h = if max <= r {
+            ((g - b) / delta) % 6.0
+        } else if max <= g {
+            (b - r) / delta + 2.0
+        } else {
+            (r - g) / delta + 4.0
+        } * 60.0;
+        let h = if h < 0.0 { h + 360.0 } else { h };
+
+        (h, s, l)
+    }
+
+    #[allow(clippy::many_single_char_names)]
+    fn from_hsl(h: f64, s: f64, l: f64) -> Self {
+        let h = h % 360.0;
+        let h = if h < 0.0 { h + 360.0 } else { h } / 60.0;
+        let (s, l) = (s.clamp(0.0, 1.0), l.clamp(0.0, 1.0));
+
+        let c = (1.0 - Real::abs(2.0 * l - 1.0)) * s;
+        let x = c * (1.0 - Real::abs(h % 2.0 - 1.0));
+        let m = l - c / 2.0;
+
+        let (r, g, b) = match h {
+            h if h < 1.0 => (c, x, 0.0),
+            h if h < 2.0 => (x, c, 0.0),
+            h if h < 3.0 => (0.0, c, x),
+            h if h < 4.0 => (0.0, x, c),
+            h if h < 5.0 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+
+        Self {
+            r: channel_from_f64(r + m),
+            g: channel_from_f64(g + m),
+            b: channel_from_f64(b + m),
+            a: u8::MAX,
+        }
+    }
+
+    fn blend(&self, other: Self, t: f64) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let lerp = |a: u8, b: u8| {
+            let (a, b) = (f64::from(a) / 255.0, f64::from(b) / 255.0);
+            channel_from_f64(a + (b - a) * t)
+        };
+
+        Self {
+            r: lerp(self.r, other.r),
+            g: lerp(self.g, other.g),
+            b: lerp(self.b, other.b),
+            a: lerp(self.a, other.a),
+        }
+    }
+
+    fn to_gray(&self) -> Self {
+        let gray = channel_from_f64(
+            (0.299 * f64::from(self.r) + 0.587 * f64::from(self.g) + 0.114 * f64::from(self.b))
+                / 255.0,
+        );
+
+        Self {
+            r: gray,
+            g: gray,
+            b: gray,
+            a: self.a,
+        }
+    }
+}
+
+impl<T> Key<T>
+where
+    T: Real,
+{
+    /// Returns the [WCAG 2.1] contrast ratio between the key colour and the colour of the legend at
+    /// `legend_index`, or [`None`] if there is no legend at that index.
+    ///
+    /// [WCAG 2.1]: https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio
+    #[must_use]
+    pub fn legend_contrast(&self, legend_index: usize) -> Option<f64> {
+        let legend = self.legends.get(legend_index)?.as_ref()?;
+        Some(self.color.contrast_ratio(legend.color))
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Returns the distinct key and legend colours used in the layout, in order of first
+    /// occurrence.
+    ///
+    /// ```
+    /// use kle_serial::{Color, Keyboard};
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(
+    ///     r##"[[{"c": "#ffffff", "t": "#ff0000"}, "A", {"t": "#000000"}, "B"]]"##,
+    /// ).unwrap();
+    ///
+    /// assert_eq!(
+    ///     keyboard.color_palette(),
+    ///     [
+    ///         Color::new(0xFF, 0xFF, 0xFF, 0xFF),
+    ///         Color::new(0xFF, 0x00, 0x00, 0xFF),
+    ///         Color::new(0x00, 0x00, 0x00, 0xFF),
+    ///     ],
+    /// );
+    /// ```
+    #[must_use]
+    pub fn color_palette(&self) -> Vec<Color> {
+        dedup_colors(self.keys.iter().flat_map(|key| {
+            core::iter::once(key.color).chain(key.present_legends().map(|legend| legend.color))
+        }))
+    }
+
+    /// Returns the distinct key colours used in the layout, in order of first occurrence.
+    #[must_use]
+    pub fn key_color_palette(&self) -> Vec<Color> {
+        dedup_colors(self.keys.iter().map(|key| key.color))
+    }
+
+    /// Returns the distinct legend colours used in the layout, in order of first occurrence.
+    #[must_use]
+    pub fn legend_color_palette(&self) -> Vec<Color> {
+        dedup_colors(
+            (self.keys.iter())
+                .flat_map(Key::present_legends)
+                .map(|legend| legend.color),
+        )
+    }
+
+    /// Returns whether all keys in the layout share a single colour and all legends share a single
+    /// colour.
+    #[must_use]
+    pub fn is_monochrome(&self) -> bool {
+        self.key_color_palette().len() == 1 && self.legend_color_palette().len() == 1
+    }
+}
+
+// Collects colours into a Vec, keeping only the first occurrence of each
+fn dedup_colors(colors: impl IntoIterator<Item = Color>) -> Vec<Color> {
+    colors.into_iter().fold(Vec::new(), |mut palette, color| {
+        if !palette.contains(&color) {
+            palette.push(color);
+        }
+        palette
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use isclose::assert_is_close;
+
+    use crate::Legend;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_color_error_display() {
+        let error = Color::from_hex_str("red").unwrap_err();
+
+        assert_eq!(format!("{error}"), r#"invalid hex colour "red""#);
+    }
+
+    #[test]
+    fn test_color_to_hex_string() {
+        assert_eq!(
+            Color::new(0xCC, 0x22, 0x22, 0xFF).to_hex_string(),
+            "#cc2222"
+        );
+        assert_eq!(
+            Color::new(0xCC, 0x22, 0x22, 0x80).to_hex_string(),
+            "#cc2222"
+        );
+        assert_eq!(
+            Color::new(0xCC, 0x22, 0x22, 0xFF).to_hex_string_with_alpha(),
+            "#cc2222ff"
+        );
+        assert_eq!(
+            Color::new(0xCC, 0x22, 0x22, 0x80).to_hex_string_with_alpha(),
+            "#cc222280"
+        );
+    }
+
+    #[test]
+    fn test_color_from_hex_str() {
+        let expected = Color::new(0xCC, 0x22, 0x22, 0xFF);
+
+        assert_eq!(Color::from_hex_str("#cc2222"), Ok(expected));
+        assert_eq!(Color::from_hex_str("#CC2222"), Ok(expected));
+        assert_eq!(Color::from_hex_str("cc2222"), Ok(expected));
+        assert_eq!(Color::from_hex_str("#c22"), Ok(expected));
+        assert_eq!(Color::from_hex_str("c22"), Ok(expected));
+        assert_eq!(
+            Color::from_hex_str("#cc222280"),
+            Ok(Color::new(0xCC, 0x22, 0x22, 0x80))
+        );
+
+        assert!(Color::from_hex_str("").is_err());
+        assert!(Color::from_hex_str("#").is_err());
+        assert!(Color::from_hex_str("red").is_err());
+        assert!(Color::from_hex_str("#cc22").is_ok());
+        assert!(Color::from_hex_str("#cc222").is_err());
+        assert!(Color::from_hex_str("#gg2222").is_err());
+        assert!(Color::from_hex_str("rgb(204, 34, 34)").is_err());
+    }
+
+    #[test]
+    fn test_color_relative_luminance() {
+        assert_is_close!(Color::new(0, 0, 0, 255).relative_luminance(), 0.0);
+        assert_is_close!(Color::new(255, 255, 255, 255).relative_luminance(), 1.0);
+        assert_is_close!(Color::new(255, 0, 0, 255).relative_luminance(), 0.2126);
+        assert_is_close!(Color::new(0, 255, 0, 255).relative_luminance(), 0.7152);
+        assert_is_close!(Color::new(0, 0, 255, 255).relative_luminance(), 0.0722);
+    }
+
+    #[test]
+    fn test_color_contrast_ratio() {
+        let black = Color::new(0, 0, 0, 255);
+        let white = Color::new(255, 255, 255, 255);
+
+        assert_is_close!(black.contrast_ratio(white), 21.0);
+        assert_is_close!(white.contrast_ratio(black), 21.0);
+        assert_is_close!(white.contrast_ratio(white), 1.0);
+        assert_is_close!(black.contrast_ratio(black), 1.0);
+    }
+
+    #[test]
+    fn test_color_is_dark() {
+        assert!(Color::new(0, 0, 0, 255).is_dark());
+        assert!(Color::new(0x80, 0x80, 0x80, 255).is_dark());
+        assert!(!KEY.is_dark());
+        assert!(!BACKGROUND.is_dark());
+        assert!(!Color::new(255, 255, 255, 255).is_dark());
+    }
+
+    #[test]
+    fn test_color_to_hsl() {
+        let (h, s, l) = Color::new(255, 0, 0, 255).to_hsl();
+        assert_is_close!(h, 0.0);
+        assert_is_close!(s, 1.0);
+        assert_is_close!(l, 0.5);
+
+        let (h, s, l) = Color::new(0, 0, 255, 255).to_hsl();
+        assert_is_close!(h, 240.0);
+        assert_is_close!(s, 1.0);
+        assert_is_close!(l, 0.5);
+
+        let (h, s, l) = Color::new(0xCC, 0xCC, 0xCC, 255).to_hsl();
+        assert_is_close!(h, 0.0);
+        assert_is_close!(s, 0.0);
+        assert_is_close!(l, 0.8);
+    }
+
+    #[test]
+    fn test_color_from_hsl() {
+        assert_eq!(Color::from_hsl(0.0, 1.0, 0.5), Color::new(255, 0, 0, 255));
+        assert_eq!(Color::from_hsl(120.0, 1.0, 0.5), Color::new(0, 255, 0, 255));
+        assert_eq!(
+            Color::from_hsl(-120.0, 1.0, 0.5),
+            Color::new(0, 0, 255, 255)
+        );
+        assert_eq!(
+            Color::from_hsl(60.0, 0.0, 1.0),
+            Color::new(255, 255, 255, 255)
+        );
+        assert_eq!(Color::from_hsl(60.0, 1.0, 0.0), Color::new(0, 0, 0, 255));
+    }
+
+    #[test]
+    fn test_color_hsl_round_trip() {
+        for hex in ["#ff8000", "#0080ff", "#cc2222", "#eeeeee"] {
+            let color = Color::from_hex_str(hex).unwrap();
+            let (h, s, l) = color.to_hsl();
+            let result = Color::from_hsl(h, s, l);
+
+            assert!(color.r.abs_diff(result.r) <= 1);
+            assert!(color.g.abs_diff(result.g) <= 1);
+            assert!(color.b.abs_diff(result.b) <= 1);
+        }
+    }
+
+    #[test]
+    fn test_color_blend() {
+        let black = Color::new(0, 0, 0, 255);
+        let white = Color::new(255, 255, 255, 0);
+
+        assert_eq!(black.blend(white, 0.0), black);
+        assert_eq!(black.blend(white, 1.0), white);
+        assert_eq!(black.blend(white, 0.5), Color::new(128, 128, 128, 128));
+        assert_eq!(black.blend(white, 2.0), white);
+        assert_eq!(black.blend(white, -1.0), black);
+    }
+
+    #[test]
+    fn test_color_to_gray() {
+        assert_eq!(
+            Color::new(255, 0, 0, 128).to_gray(),
+            Color::new(76, 76, 76, 128)
+        );
+        assert_eq!(
+            Color::new(0xCC, 0xCC, 0xCC, 255).to_gray(),
+            Color::new(0xCC, 0xCC, 0xCC, 255)
+        );
+    }
+
+    #[test]
+    fn test_key_legend_contrast() {
+        let key = Key::<f64> {
+            color: Color::new(255, 255, 255, 255),
+            legends: std::array::from_fn(|i| {
+                (i == 0).then(|| Legend {
+                    color: Color::new(0, 0, 0, 255),
+                    ..Legend::default()
+                })
+            }),
+            ..Key::default()
+        };
+
+        assert_is_close!(key.legend_contrast(0).unwrap(), 21.0);
+        assert!(key.legend_contrast(1).is_none());
+        assert!(key.legend_contrast(12).is_none());
+    }
+
+    #[test]
+    fn test_kle_color_serialize() {
+        let red = KleColor(Color::new(255, 0, 0, 255));
+        assert_eq!(serde_json::to_string(&red).unwrap(), "\"#ff0000\"");
+
+        let translucent = KleColor::from(Color::new(0x12, 0x34, 0x56, 0x78));
+        assert_eq!(
+            serde_json::to_string(&translucent).unwrap(),
+            "\"#12345678\""
+        );
+
+        let colors = [KleColor(KEY), KleColor(LEGEND)];
+        assert_eq!(
+            serde_json::to_string(&colors).unwrap(),
+            r##"["#cccccc","#000000"]"##
+        );
+    }
+
+    #[test]
+    fn test_kle_color_deserialize() {
+        let color: KleColor = serde_json::from_str(r##""#ff0000""##).unwrap();
+        assert_eq!(Color::from(color), Color::new(255, 0, 0, 255));
+
+        let color: KleColor = serde_json::from_str(r##""#1234""##).unwrap();
+        assert_eq!(color.0, Color::new(0x11, 0x22, 0x33, 0x44));
+
+        assert!(serde_json::from_str::<KleColor>(r#""not a colour""#).is_err());
+        assert!(serde_json::from_str::<KleColor>("[255, 0, 0]").is_err());
+
+        let color = KleColor(Color::new(0xAB, 0xCD, 0xEF, 0xFF));
+        let json = serde_json::to_string(&color).unwrap();
+        assert_eq!(serde_json::from_str::<KleColor>(&json).unwrap(), color);
+    }
+
+    fn colorway() -> Keyboard {
+        serde_json::from_str(
+            r##"[
+                [{"c": "#cc0000", "t": "#ffffff"}, "Esc", {"c": "#f0f0f0", "t": "#333333"}, "Q", "W",
+                    {"c": "#555555", "t": "#ffffff"}, "Enter"],
+                [{"c": "#f0f0f0", "t": "#333333"}, "A", "S", {"c": "#555555", "t": "#ffffff"},
+                    "Shift", {"c": "#f0f0f0"}, ""]
+            ]"##,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_keyboard_color_palette() {
+        let red = Color::new(0xCC, 0x00, 0x00, 0xFF);
+        let white = Color::new(0xFF, 0xFF, 0xFF, 0xFF);
+        let light = Color::new(0xF0, 0xF0, 0xF0, 0xFF);
+        let dark = Color::new(0x33, 0x33, 0x33, 0xFF);
+        let mid = Color::new(0x55, 0x55, 0x55, 0xFF);
+
+        let keyboard = colorway();
+        assert_eq!(keyboard.color_palette(), [red, white, light, dark, mid]);
+        assert_eq!(keyboard.key_color_palette(), [red, light, mid]);
+        assert_eq!(keyboard.legend_color_palette(), [white, dark]);
+
+        assert!(Keyboard::<f64>::default().color_palette().is_empty());
+    }
+
+    #[test]
+    fn test_keyboard_is_monochrome() {
+        assert!(!colorway().is_monochrome());
+
+        let keyboard: Keyboard =
+            serde_json::from_str(r##"[[{"c": "#222222", "t": "#eeeeee"}, "A", "B"], ["C"]]"##)
+                .unwrap();
+        assert!(keyboard.is_monochrome());
+
+        // Keys without legends have no legend colours
+        let keyboard: Keyboard = serde_json::from_str(r#"[["", ""]]"#).unwrap();
+        assert!(!keyboard.is_monochrome());
+        assert!(!Keyboard::<f64>::default().is_monochrome());
+    }
+}
@@ -0,0 +1,217 @@
+use alloc::{vec, vec::Vec};
+
+use num_traits::real::Real;
+
+use crate::{Key, UNIT_MM};
+
+/// The shape of a switch cutout in a plate or PCB, as used by [`Key::cutout_polygon`].
+///
+/// All dimensions are in millimetres.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CutoutShape {
+    /// A square cutout with the given side length.
+    Square(f64),
+    /// A rectangular cutout with the given width and height.
+    Rectangle(f64, f64),
+    /// A custom polygon given by its vertices relative to the centre of the key.
+    Custom(Vec<(f64, f64)>),
+}
+
+impl CutoutShape {
+    /// The cutout for Cherry MX and compatible switches.
+    pub const MX: Self = Self::Square(14.0);
+
+    /// The cutout for Alps SKCM/SKCL and compatible switches.
+    pub const ALPS: Self = Self::Rectangle(15.5, 12.8);
+
+    /// Returns the cutout for a switch with the given [`Switch::mount`](crate::Switch::mount).
+    ///
+    /// `"alps"` (case-insensitive) returns [`CutoutShape::ALPS`], and any other mount including
+    /// `"cherry"` returns [`CutoutShape::MX`].
+    #[must_use]
+    pub fn for_mount(mount: &str) -> Self {
+        if mount.trim().eq_ignore_ascii_case("alps") {
+            Self::ALPS
+        } else {
+            Self::MX
+        }
+    }
+
+    /// Returns the vertices of the cutout relative to its centre, in millimetres.
+    ///
+    /// For squares and rectangles these are in clockwise order starting from the top left.
+    #[must_use]
+    pub fn vertices(&self) -> Vec<(f64, f64)> {
+        let (w, h) = match *self {
+            Self::Square(size) => (size / 2.0, size / 2.0),
+            Self::Rectangle(width, height) => (width / 2.0, height / 2.0),
+            Self::Custom(ref vertices) => return vertices.clone(),
+        };
+        vec![(-w, -h), (w, -h), (w, h), (-w, h)]
+    }
+}
+
+impl<T> Key<T>
+where
+    T: Real,
+{
+    /// Returns the switch cutout polygon for the key in millimetres, using the cutout for the
+    /// key's [`Switch::mount`](crate::Switch::mount) as given by [`CutoutShape::for_mount`].
+    ///
+    /// Only the key's own switch is considered. Use [`Key::cutout_polygon`] with the result of
+    /// [`Key::effective_switch_given_metadata`] to take the layout's default switch into account.
+    ///
+    /// ```
+    /// use kle_serial::Key;
+    ///
+    /// let polygon = Key::<f64>::default().pcb_cutout_polygon();
+    ///
+    /// assert_eq!(polygon.len(), 4);
+    /// assert!((polygon[0].0 - 2.525).abs() < 1e-9);
+    /// assert!((polygon[2].1 - 16.525).abs() < 1e-9);
+    /// ```
+    #[must_use]
+    pub fn pcb_cutout_polygon(&self) -> Vec<(T, T)> {
+        self.cutout_polygon(&CutoutShape::for_mount(&self.switch.mount))
+    }
+
+    /// Returns the vertices of the given cutout in millimetres, centred on the centre of the
+    /// key's primary rectangle and rotated with the key.
+    #[must_use]
+    pub fn cutout_polygon(&self, shape: &CutoutShape) -> Vec<(T, T)> {
+        let unit = T::from(UNIT_MM).unwrap_or_else(T::one);
+        let to_units = |mm: f64| T::from(mm / UNIT_MM);
+        let two = T::one() + T::one();
+        let (cx, cy) = (self.x + self.width / two, self.y + self.height / two);
+
+        (shape.vertices().into_iter())
+            .filter_map(|(dx, dy)| Some((to_units(dx)?, to_units(dy)?)))
+            .map(|(dx, dy)| {
+                let (x, y) = self.transform_point(cx + dx, cy + dy);
+                (x * unit, y * unit)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isclose::assert_is_close;
+
+    use crate::Switch;
+
+    use super::*;
+
+    fn assert_polygon_eq(actual: &[(f64, f64)], expected: &[(f64, f64)]) {
+        assert_eq!(actual.len(), expected.len());
+        for (&(ax, ay), &(ex, ey)) in actual.iter().zip(expected) {
+            assert_is_close!(ax, ex);
+            assert_is_close!(ay, ey);
+        }
+    }
+
+    #[test]
+    fn test_cutout_shape_for_mount() {
+        assert_eq!(CutoutShape::for_mount("cherry"), CutoutShape::MX);
+        assert_eq!(CutoutShape::for_mount("alps"), CutoutShape::ALPS);
+        assert_eq!(CutoutShape::for_mount("Alps"), CutoutShape::ALPS);
+        assert_eq!(CutoutShape::for_mount(""), CutoutShape::MX);
+        assert_eq!(CutoutShape::for_mount("topre"), CutoutShape::MX);
+    }
+
+    #[test]
+    fn test_cutout_shape_vertices() {
+        assert_polygon_eq(
+            &CutoutShape::MX.vertices(),
+            &[(-7.0, -7.0), (7.0, -7.0), (7.0, 7.0), (-7.0, 7.0)],
+        );
+        assert_polygon_eq(
+            &CutoutShape::ALPS.vertices(),
+            &[(-7.75, -6.4), (7.75, -6.4), (7.75, 6.4), (-7.75, 6.4)],
+        );
+
+        let custom = vec![(0.0, -5.0), (5.0, 5.0), (-5.0, 5.0)];
+        assert_eq!(CutoutShape::Custom(custom.clone()).vertices(), custom);
+    }
+
+    #[test]
+    fn test_key_pcb_cutout_polygon() {
+        // Standard MX key at the origin is centred at (9.525, 9.525)
+        let key = Key::<f64>::default();
+        assert_polygon_eq(
+            &key.pcb_cutout_polygon(),
+            &[
+                (2.525, 2.525),
+                (16.525, 2.525),
+                (16.525, 16.525),
+                (2.525, 16.525),
+            ],
+        );
+
+        // Alps 2u key
+        let key = Key::<f64> {
+            x: 1.0,
+            width: 2.0,
+            switch: Switch::default().with_mount("alps"),
+            ..Key::default()
+        };
+        assert_polygon_eq(
+            &key.pcb_cutout_polygon(),
+            &[
+                (30.35, 3.125),
+                (45.85, 3.125),
+                (45.85, 15.925),
+                (30.35, 15.925),
+            ],
+        );
+
+        // Key rotated 90° around its centre
+        let key = Key::<f64> {
+            rotation: 90.0,
+            rx: 0.5,
+            ry: 0.5,
+            switch: Switch::default().with_mount("alps"),
+            ..Key::default()
+        };
+        assert_polygon_eq(
+            &key.pcb_cutout_polygon(),
+            &[
+                (15.925, 1.775),
+                (15.925, 17.275),
+                (3.125, 17.275),
+                (3.125, 1.775),
+            ],
+        );
+
+        // Rotating the key around another point moves the cutout with it
+        let key = Key::<f64> {
+            rotation: 180.0,
+            ..Key::default()
+        };
+        assert_polygon_eq(
+            &key.pcb_cutout_polygon(),
+            &[
+                (-2.525, -2.525),
+                (-16.525, -2.525),
+                (-16.525, -16.525),
+                (-2.525, -16.525),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_key_cutout_polygon() {
+        let key = Key::<f32> {
+            x: 2.0,
+            y: 1.0,
+            ..Key::default()
+        };
+        let polygon = key.cutout_polygon(&CutoutShape::Custom(vec![(0.0, -5.0), (5.0, 5.0)]));
+
+        assert_eq!(polygon.len(), 2);
+        assert_is_close!(polygon[0].0, 47.625);
+        assert_is_close!(polygon[0].1, 23.575);
+        assert_is_close!(polygon[1].0, 52.625);
+        assert_is_close!(polygon[1].1, 33.575);
+    }
+}
@@ -1,27 +1,42 @@
-use std::{fmt, marker::PhantomData};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{fmt, marker::PhantomData};
 
-use csscolorparser::Color as CssColor;
 use num_traits::real::Real;
 use serde::{
     de::{Error, SeqAccess, Unexpected, Visitor},
-    Deserialize, Deserializer,
+    ser::SerializeSeq,
+    Deserialize, Deserializer, Serialize, Serializer,
 };
 
 use crate::{
+    color::color_to_string,
     utils::{Alignment, FontSize},
-    Color,
+    Color, KleColor,
 };
 
+#[cfg(feature = "std")]
 fn color_from_str<'de, D>(value: &str) -> Result<Color, D::Error>
 where
     D: Deserializer<'de>,
 {
     csscolorparser::parse(value)
-        .map(|c| CssColor::to_rgba8(&c))
+        .map(|c| csscolorparser::Color::to_rgba8(&c))
         .map(|[r, g, b, a]| Color { r, g, b, a })
         .map_err(|_| D::Error::invalid_value(Unexpected::Str(value), &"a CSS color value"))
 }
 
+// csscolorparser requires std, so we only support hex colours without it
+#[cfg(not(feature = "std"))]
+fn color_from_str<'de, D>(value: &str) -> Result<Color, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use crate::ColorExt;
+
+    Color::from_hex_str(value)
+        .map_err(|_| D::Error::invalid_value(Unexpected::Str(value), &"a hex color value"))
+}
+
 fn de_color<'de, D>(deserializer: D) -> Result<Option<Color>, D::Error>
 where
     D: Deserializer<'de>,
@@ -32,6 +47,15 @@ where
         .transpose()
 }
 
+impl<'de> Deserialize<'de> for KleColor {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        color_from_str::<D>(&String::deserialize(deserializer)?).map(Self)
+    }
+}
+
 // Kle color arrays are just \n delimited strings, so we use this function to turn them into Vecs
 fn de_nl_delimited_colors<'de, D>(deserializer: D) -> Result<Option<Vec<Option<Color>>>, D::Error>
 where
@@ -47,62 +71,169 @@ where
         .transpose()
 }
 
-#[derive(Deserialize, Default, Debug, Clone)]
+// The signature here is dictated by serde's serialize_with
+#[allow(clippy::ref_option, clippy::trivially_copy_pass_by_ref)]
+fn ser_color<S>(color: &Option<Color>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    color.map(color_to_string).serialize(serializer)
+}
+
+// Reverse of de_nl_delimited_colors, with None values output as empty lines
+#[allow(clippy::ref_option)]
+fn ser_nl_delimited_colors<S>(
+    colors: &Option<Vec<Option<Color>>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    colors
+        .as_ref()
+        .map(|colors| {
+            colors
+                .iter()
+                .map(|c| c.map(color_to_string).unwrap_or_default())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .serialize(serializer)
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
 pub(crate) struct KleBackground {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub style: Option<String>,
 }
 
-#[derive(Deserialize, Default, Debug, Clone)]
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
 #[serde(default, rename_all = "camelCase")]
 pub(crate) struct KleMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub author: Option<String>,
-    #[serde(deserialize_with = "de_color")]
+    #[serde(
+        deserialize_with = "de_color",
+        serialize_with = "ser_color",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub backcolor: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub background: Option<KleBackground>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub radii: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub switch_mount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub switch_brand: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub switch_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub css: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub pcb: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub plate: Option<bool>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+/// A JSON object containing properties for the next key(s) in a KLE layout.
+///
+/// Each field corresponds to a property in KLE's JSON format, with [`None`] indicating that the
+/// property is not set.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(default)]
-pub(crate) struct KlePropsObject<T = f64>
+pub struct KlePropsObject<T = f64>
 where
     T: Real,
 {
+    /// Horizontal offset applied to the key's position.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub x: Option<T>,
+    /// Vertical offset applied to the key's position.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub y: Option<T>,
+    /// Width of the key.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub w: Option<T>,
+    /// Height of the key.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub h: Option<T>,
+    /// Horizontal offset of the key's secondary rectangle.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub x2: Option<T>,
+    /// Vertical offset of the key's secondary rectangle.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub y2: Option<T>,
+    /// Width of the key's secondary rectangle.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub w2: Option<T>,
+    /// Height of the key's secondary rectangle.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub h2: Option<T>,
+    /// Rotation of the key in degrees.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub r: Option<T>,
+    /// X coordinate of the centre of rotation.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub rx: Option<T>,
+    /// Y coordinate of the centre of rotation.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub ry: Option<T>,
+    /// Whether the key is stepped.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub l: Option<bool>,
+    /// Whether the key is a homing key.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub n: Option<bool>,
+    /// Whether the key is a decal.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub d: Option<bool>,
+    /// Whether the key is ghosted.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub g: Option<bool>,
+    /// Switch mount.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sm: Option<String>,
+    /// Switch brand.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sb: Option<String>,
+    /// Switch type.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub st: Option<String>,
-    #[serde(deserialize_with = "de_color")]
+    /// Key colour.
+    #[serde(
+        deserialize_with = "de_color",
+        serialize_with = "ser_color",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub c: Option<Color>,
-    #[serde(deserialize_with = "de_nl_delimited_colors")]
+    /// Legend colours, in KLE's legend order. The first colour is also used for any missing colours.
+    #[serde(
+        deserialize_with = "de_nl_delimited_colors",
+        serialize_with = "ser_nl_delimited_colors",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub t: Option<Vec<Option<Color>>>,
+    /// Legend alignment.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub a: Option<Alignment>,
+    /// Keycap profile and row.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub p: Option<String>,
+    /// Font size for all legends.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub f: Option<FontSize>,
+    /// Font size for all legends except the first.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub f2: Option<FontSize>,
+    /// Font sizes in KLE's legend order. A size of 0 uses the size set by `f`.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub fa: Option<Vec<FontSize>>,
 }
 
@@ -142,14 +273,17 @@ where
     }
 }
 
-// Represents either a key or a JSON object containing properties for the next key(s)
-#[derive(Deserialize, Debug, Clone)]
+/// An item in a row of a KLE layout, either a key's legends or a JSON object containing
+/// properties for the next key(s).
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
-pub(crate) enum KleLegendsOrProps<T = f64>
+pub enum KleLegendsOrProps<T = f64>
 where
     T: Real,
 {
+    /// Properties for the next key(s).
     Props(Box<KlePropsObject<T>>),
+    /// A key's legends, separated by newlines in KLE's legend order.
     Legend(String),
 }
 
@@ -223,6 +357,29 @@ where
     }
 }
 
+impl<T> Serialize for KleKeyboard<T>
+where
+    T: Real + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // KLE omits the metadata object entirely if it's empty
+        let meta = (self.meta != KleMetadata::default()).then_some(&self.meta);
+
+        let mut seq =
+            serializer.serialize_seq(Some(self.layout.len() + usize::from(meta.is_some())))?;
+        if let Some(meta) = meta {
+            seq.serialize_element(meta)?;
+        }
+        for row in &self.layout {
+            seq.serialize_element(row)?;
+        }
+        seq.end()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -231,6 +388,7 @@ mod tests {
     use serde_json::{Deserializer, Error};
 
     #[test]
+    #[cfg(feature = "std")]
     fn test_de_color() {
         let colors = [
             ("rebeccapurple", Color::new(102, 51, 153, 255)),
@@ -255,6 +413,28 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(not(feature = "std"))]
+    fn test_de_color() {
+        let colors = [
+            ("#f09", Color::new(255, 0, 153, 255)),
+            ("#ff0099", Color::new(255, 0, 153, 255)),
+            ("f09", Color::new(255, 0, 153, 255)),
+            ("ff0099", Color::new(255, 0, 153, 255)),
+            ("#ff0099cc", Color::new(255, 0, 153, 204)),
+        ];
+
+        for (css, res) in colors {
+            let color = de_color(&mut Deserializer::from_str(&format!(r#""{css}""#)))
+                .unwrap()
+                .unwrap();
+            assert_eq!(color, res);
+        }
+
+        let color = de_color(&mut Deserializer::from_str(r#""rebeccapurple""#));
+        assert_matches!(color, Err(Error { .. }));
+    }
+
     #[test]
     fn test_de_nl_delimited_colors() {
         let colors = de_nl_delimited_colors(&mut Deserializer::from_str(r##""#f00\n\n#ba9""##));
@@ -311,4 +491,74 @@ mod tests {
 
         assert_matches!(serde_json::from_str::<KleKeyboard>("null"), Err(_));
     }
+
+    #[test]
+    fn test_ser_color() {
+        let props = KlePropsObject::<f64> {
+            c: Some(Color::new(255, 0, 153, 255)),
+            ..KlePropsObject::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&props).unwrap(),
+            r##"{"c":"#ff0099"}"##
+        );
+
+        let meta = KleMetadata {
+            backcolor: Some(Color::new(255, 0, 153, 204)),
+            ..KleMetadata::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&meta).unwrap(),
+            r##"{"backcolor":"#ff0099cc"}"##
+        );
+    }
+
+    #[test]
+    fn test_ser_nl_delimited_colors() {
+        let props = KlePropsObject::<f64> {
+            t: Some(vec![
+                Some(Color::new(255, 0, 0, 255)),
+                None,
+                Some(Color::new(187, 170, 153, 255)),
+            ]),
+            ..KlePropsObject::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&props).unwrap(),
+            r##"{"t":"#ff0000\n\n#bbaa99"}"##
+        );
+
+        let props = KlePropsObject::<f64>::default();
+        assert_eq!(serde_json::to_string(&props).unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_serialize_kle_keyboard() {
+        let kle = KleKeyboard::<f64> {
+            meta: KleMetadata::default(),
+            layout: vec![vec![
+                KleLegendsOrProps::Props(Box::new(KlePropsObject {
+                    x: Some(0.5),
+                    ..KlePropsObject::default()
+                })),
+                KleLegendsOrProps::Legend("A".into()),
+            ]],
+        };
+        assert_eq!(serde_json::to_string(&kle).unwrap(), r#"[[{"x":0.5},"A"]]"#);
+
+        let kle = KleKeyboard::<f64> {
+            meta: KleMetadata {
+                name: Some("test".into()),
+                ..KleMetadata::default()
+            },
+            layout: vec![],
+        };
+        assert_eq!(serde_json::to_string(&kle).unwrap(), r#"[{"name":"test"}]"#);
+
+        let kle = KleKeyboard::<f64> {
+            meta: KleMetadata::default(),
+            layout: vec![],
+        };
+        assert_eq!(serde_json::to_string(&kle).unwrap(), "[]");
+    }
 }
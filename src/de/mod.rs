@@ -1,15 +1,14 @@
 mod json;
 
-use std::vec;
+use alloc::{string::String, vec, vec::Vec};
 
 use crate::{
     color,
-    utils::{realign_legends, Alignment, FontSize},
+    utils::{encoded_legend_alignment, realign_legends, unalign_legends, Alignment, FontSize},
     Background, Color, Key, Legend, Metadata, Switch, NUM_LEGENDS,
 };
-use json::{KleBackground, KleLegendsOrProps, KleMetadata, KlePropsObject};
-
-pub(crate) use json::KleKeyboard;
+pub(crate) use json::{KleBackground, KleKeyboard, KleMetadata};
+pub use json::{KleLegendsOrProps, KlePropsObject};
 use num_traits::real::Real;
 
 impl From<KleBackground> for Background {
@@ -42,13 +41,19 @@ impl From<KleMetadata> for Metadata {
             plate_mount: value.plate.unwrap_or(default.plate_mount),
             pcb_mount: value.pcb.unwrap_or(default.pcb_mount),
             notes: value.notes.unwrap_or(default.notes),
+            css: value.css.unwrap_or(default.css),
         }
     }
 }
 
+/// The accumulated key properties while deserialising a KLE layout.
+///
+/// This is exposed by [`KleLayoutIterator::current_props`] for debugging purposes, and its
+/// contents can be inspected using its [`Debug`] implementation. See [`KleLayoutIterator`] for
+/// details on how this is updated.
 #[derive(Debug, Clone)]
 #[allow(clippy::struct_excessive_bools)]
-struct KleProps<T = f64>
+pub struct KleProps<T = f64>
 where
     T: Real,
 {
@@ -86,15 +91,15 @@ impl<T> KleProps<T>
 where
     T: Real,
 {
-    fn update(&mut self, props: KlePropsObject<T>) {
+    pub(crate) fn update(&mut self, props: KlePropsObject<T>) {
         let f = props.f.unwrap_or(self.f);
         let fa = if let Some(fa) = props.fa {
-            std::array::from_fn(|i| match fa.get(i).copied() {
+            core::array::from_fn(|i| match fa.get(i).copied() {
                 Some(fa) if usize::from(fa) > 0 => fa,
                 _ => f,
             })
         } else if let Some(f2) = props.f2 {
-            std::array::from_fn(|i| if i == 0 { f } else { f2 })
+            core::array::from_fn(|i| if i == 0 { f } else { f2 })
         } else if let Some(f) = props.f {
             [f; NUM_LEGENDS]
         } else {
@@ -105,7 +110,7 @@ where
             .and_then(|v| v.first().copied().flatten())
             .unwrap_or(self.t);
         let ta = props.t.map_or(self.ta, |ta| {
-            std::array::from_fn(|i| ta.get(i).copied().flatten().unwrap_or(t))
+            core::array::from_fn(|i| ta.get(i).copied().flatten().unwrap_or(t))
         });
 
         // KLE has some weird rotation behaviour, with rx and ry (if present) resetting x and y
@@ -149,7 +154,7 @@ where
     }
 
     #[inline]
-    fn next_key(&mut self) {
+    pub(crate) fn next_key(&mut self) {
         // Increment x
         self.x = self.x + self.w.max(self.x2 + self.w2);
         // Reset per-key properties
@@ -165,13 +170,13 @@ where
     }
 
     #[inline]
-    fn next_line(&mut self) {
+    pub(crate) fn next_line(&mut self) {
         self.next_key();
         self.x = self.rx; // x resets to rx
         self.y = self.y + T::one();
     }
 
-    fn build_key(&self, legends: &str) -> Key<T> {
+    pub(crate) fn build_key(&self, legends: &str) -> Key<T> {
         let legends =
             legends
                 .lines()
@@ -211,6 +216,149 @@ where
             decal: self.d,
         }
     }
+
+    #[inline]
+    pub(crate) fn is_new_line(&self, key: &Key<T>) -> bool {
+        // KLE only allows r, rx, and ry at the start of a line, so we also need a new line if any
+        // of them change
+        key.y != self.y || key.rotation != self.r || key.rx != self.rx || key.ry != self.ry
+    }
+
+    // The reverse of update and build_key, this creates the props object and legend string which
+    // will result in the given key when used with the current state
+    pub(crate) fn encode_key(&self, key: &Key<T>) -> (KlePropsObject<T>, String) {
+        let alignment = encoded_legend_alignment(&key.legends, self.a);
+        let legends = unalign_legends(key.legends.clone(), alignment);
+        let len = legends
+            .iter()
+            .rposition(Option::is_some)
+            .map_or(0, |i| i + 1);
+        let legends = &legends[..len];
+
+        // x and y are reset to rx and ry if either of them change
+        let (rx, ry) = (key.rx != self.rx, key.ry != self.ry);
+        let (x, y) = if rx || ry {
+            (key.rx, key.ry)
+        } else {
+            (self.x, self.y)
+        };
+
+        let (f, fa) = self.encode_font_sizes(legends);
+
+        let props = KlePropsObject {
+            x: (key.x != x).then(|| key.x - x),
+            y: (key.y != y).then(|| key.y - y),
+            w: (key.width != T::one()).then_some(key.width),
+            h: (key.height != T::one()).then_some(key.height),
+            x2: (key.x2 != T::zero()).then_some(key.x2),
+            y2: (key.y2 != T::zero()).then_some(key.y2),
+            w2: (key.width2 != key.width).then_some(key.width2),
+            h2: (key.height2 != key.height).then_some(key.height2),
+            r: (key.rotation != self.r).then_some(key.rotation),
+            rx: rx.then_some(key.rx),
+            ry: ry.then_some(key.ry),
+            l: key.stepped.then_some(true),
+            n: key.homing.then_some(true),
+            d: key.decal.then_some(true),
+            g: (key.ghosted != self.g).then_some(key.ghosted),
+            sm: (key.switch.mount != self.sm).then(|| key.switch.mount.clone()),
+            sb: (key.switch.brand != self.sb).then(|| key.switch.brand.clone()),
+            st: (key.switch.typ != self.st).then(|| key.switch.typ.clone()),
+            c: (key.color != self.c).then_some(key.color),
+            t: self.encode_colors(legends),
+            a: (alignment != self.a).then_some(alignment),
+            p: (key.profile != self.p).then(|| key.profile.clone()),
+            f,
+            f2: None,
+            fa,
+        };
+
+        let legends = legends
+            .iter()
+            .map(|l| l.as_ref().map_or("", |l| l.text.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        (props, legends)
+    }
+
+    fn encode_font_sizes(
+        &self,
+        legends: &[Option<Legend>],
+    ) -> (Option<FontSize>, Option<Vec<FontSize>>) {
+        let sizes: Vec<_> = (legends.iter().enumerate())
+            .filter_map(|(i, l)| l.as_ref().map(|l| (i, FontSize::saturating_new(l.size))))
+            .collect();
+
+        if sizes.iter().all(|&(i, size)| size == self.fa[i]) {
+            (None, None)
+        } else if sizes.iter().all(|&(_, size)| size == sizes[0].1) {
+            (Some(sizes[0].1), None)
+        } else {
+            // Any 0 values in fa will use f instead, so we also need to set f if any legends
+            // actually have a 0 size
+            let zero = FontSize::saturating_new(0);
+            let f = sizes.iter().any(|&(_, size)| size == zero).then_some(zero);
+            let mut fa = vec![zero; legends.len()];
+            for (i, size) in sizes {
+                fa[i] = size;
+            }
+            (f, Some(fa))
+        }
+    }
+
+    fn encode_colors(&self, legends: &[Option<Legend>]) -> Option<Vec<Option<Color>>> {
+        let colors: Vec<_> = (legends.iter().enumerate())
+            .filter_map(|(i, l)| l.as_ref().map(|l| (i, l.color)))
+            .collect();
+
+        if colors.iter().all(|&(i, color)| color == self.ta[i]) {
+            return None;
+        }
+
+        // The first colour is used as the default for any legends that don't have their own
+        let &(_, default) = colors.first()?;
+        let mut t = vec![None; legends.len()];
+        t[0] = Some(default);
+        for (i, color) in colors {
+            if color != default {
+                t[i] = Some(color);
+            }
+        }
+        t.truncate(t.iter().rposition(Option::is_some).map_or(0, |i| i + 1));
+
+        Some(t)
+    }
+}
+
+impl<T> Key<T>
+where
+    T: Real,
+{
+    /// Returns a [`KlePropsObject`] containing only the properties which need to be set for KLE to
+    /// produce this key when `reference` is the current state.
+    ///
+    /// `reference` should be the state immediately after the previous key, as returned by
+    /// [`KleLayoutIterator::current_props`]. KLE's `x` and `y` are relative, so they are encoded
+    /// as the offset from the reference position, which has already been advanced past the
+    /// previous key. Fields which do not need to change are set to [`None`].
+    ///
+    /// KLE only allows the rotation properties at the start of a row, so if
+    /// [`Key::starts_new_row`] returns `true` a new row must be started (and the reference state
+    /// moved to the start of that row) before using the result.
+    #[must_use]
+    pub fn to_props_delta(&self, reference: &KleProps<T>) -> KlePropsObject<T> {
+        reference.encode_key(self).0
+    }
+
+    /// Returns `true` if this key cannot follow `reference` in the same row of a KLE layout.
+    ///
+    /// This is the case when the key is on a different line or its rotation or rotation centre
+    /// differ from `reference`. See [`Key::to_props_delta`].
+    #[must_use]
+    pub fn starts_new_row(&self, reference: &KleProps<T>) -> bool {
+        reference.is_new_line(self)
+    }
 }
 
 impl<T> Default for KleProps<T>
@@ -248,21 +396,74 @@ where
     }
 }
 
+/// An iterator of [`Key`]s built from the rows of a KLE layout.
+///
+/// This is the iterator used internally by [`KeyIterator`](crate::KeyIterator) and when
+/// deserialising a [`Keyboard`](crate::Keyboard). Most users should deserialise one of those
+/// directly, this is intended for custom processing pipelines which need to drive the
+/// deserialisation of keys manually.
+///
+/// KLE layouts are a list of rows, each containing a mix of legends and property objects. This
+/// walks over these, accumulating properties in a [`KleProps`] until it reaches a legend from which
+/// it builds a [`Key`]. Properties set in a property object apply to the next key, and some (such as
+/// the colour and font sizes) persist until they are changed again.
+///
+/// Between calls to [`next`](Iterator::next), the [`KleProps`] returned by
+/// [`KleLayoutIterator::current_props`] holds the state immediately after the last returned key:
+/// the position has been advanced past the key and per-key properties (such as the width and
+/// height) have been reset. Property objects after the last returned key, including at the start
+/// of the next row, are not applied until the following call to `next`.
+///
+/// ```
+/// use kle_serial::{KleLayoutIterator, KleLegendsOrProps};
+///
+/// let rows: Vec<Vec<KleLegendsOrProps>> =
+///     serde_json::from_str(r#"[[{"w": 2}, "A", "B"]]"#).unwrap();
+/// let mut iter = KleLayoutIterator::new(rows);
+///
+/// assert!(matches!(iter.peek(), Some(KleLegendsOrProps::Props(_))));
+/// assert_eq!(iter.next().unwrap().width, 2.0);
+/// assert!(matches!(iter.peek(), Some(KleLegendsOrProps::Legend(l)) if l == "B"));
+/// ```
 #[derive(Debug, Clone)]
-pub(crate) struct KleLayoutIterator<T = f64>
+pub struct KleLayoutIterator<T = f64>
 where
     T: Real,
 {
     state: KleProps<T>,
     row_iter: vec::IntoIter<Vec<KleLegendsOrProps<T>>>,
     key_iter: vec::IntoIter<KleLegendsOrProps<T>>,
+    // Keys built ahead of time when iterating from the back, since each key depends on the
+    // properties of all keys before it
+    buffer: Option<KeyBuffer<T>>,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct KeyBuffer<T>
+where
+    T: Real,
+{
+    // The raw items remaining when the buffer was created, and the keys built from them along
+    // with the index of the legend each key was built from
+    items: Vec<KleLegendsOrProps<T>>,
+    keys: vec::IntoIter<(usize, Key<T>)>,
+    // The range of items which have not been passed by iterating from either end
+    front: usize,
+    back: usize,
 }
 
 impl<T> KleLayoutIterator<T>
 where
     T: Real,
 {
-    pub(crate) fn new(kle: Vec<Vec<KleLegendsOrProps<T>>>) -> Self {
+    /// Creates a new iterator from the rows of a KLE layout, not including the metadata object.
+    #[must_use]
+    pub fn new(kle: Vec<Vec<KleLegendsOrProps<T>>>) -> Self {
+        let len = (kle.iter().flatten())
+            .filter(|item| matches!(item, KleLegendsOrProps::Legend(_)))
+            .count();
+
         let state = KleProps::default();
         let mut row_iter = kle.into_iter();
         let key_iter = row_iter.next().unwrap_or_default().into_iter();
@@ -270,8 +471,54 @@ where
             state,
             row_iter,
             key_iter,
+            buffer: None,
+            len,
+        }
+    }
+
+    /// Returns a reference to the next legend or property object in the layout without advancing
+    /// the iterator, or [`None`] if there are no more items.
+    ///
+    /// This is analogous to [`Peekable::peek`](core::iter::Peekable::peek), but returns the next
+    /// raw item rather than the next [`Key`]. Items which have been passed by iterating from the
+    /// back using [`next_back`](DoubleEndedIterator::next_back) are not returned.
+    #[must_use]
+    pub fn peek(&self) -> Option<&KleLegendsOrProps<T>> {
+        match self.buffer {
+            Some(ref buffer) => buffer.items[buffer.front..buffer.back].first(),
+            None => self.remaining_items().next(),
         }
     }
+
+    /// Returns the current accumulated properties. See the [`KleLayoutIterator`] documentation for
+    /// details on how these are updated.
+    #[must_use]
+    pub fn current_props(&self) -> &KleProps<T> {
+        &self.state
+    }
+
+    fn remaining_items(&self) -> impl Iterator<Item = &KleLegendsOrProps<T>> {
+        (self.key_iter.as_slice().iter()).chain(self.row_iter.as_slice().iter().flatten())
+    }
+
+    fn build_next(&mut self) -> Option<Key<T>> {
+        let legends = loop {
+            match self.key_iter.next() {
+                Some(KleLegendsOrProps::Props(props)) => self.state.update(*props),
+                Some(KleLegendsOrProps::Legend(str)) => break str,
+                None => {
+                    // Loop rather than taking the first key so empty rows are skipped correctly
+                    self.key_iter = self.row_iter.next()?.into_iter();
+                    self.state.next_line();
+                }
+            }
+        };
+
+        let key = self.state.build_key(&legends);
+        self.state.next_key();
+
+        Some(key)
+    }
 }
 
 impl<T> Iterator for KleLayoutIterator<T>
@@ -281,21 +528,52 @@ where
     type Item = Key<T>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let legends = loop {
-            let key = self.key_iter.next().or_else(|| {
-                self.key_iter = self.row_iter.next()?.into_iter();
-                self.state.next_line();
-                self.key_iter.next()
-            })?;
-
-            match key {
-                KleLegendsOrProps::Props(props) => self.state.update(*props),
-                KleLegendsOrProps::Legend(str) => break str,
+        let key = match self.buffer {
+            Some(ref mut buffer) => {
+                let (index, key) = buffer.keys.next()?;
+                buffer.front = index + 1;
+                key
             }
+            None => self.build_next()?,
         };
+        self.len -= 1;
 
-        let key = self.state.build_key(&legends);
-        self.state.next_key();
+        Some(key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for KleLayoutIterator<T> where T: Real {}
+
+impl<T> DoubleEndedIterator for KleLayoutIterator<T>
+where
+    T: Real,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.buffer.is_none() {
+            // Keep the raw items so peek still works after building the keys
+            let items: Vec<_> = self.remaining_items().cloned().collect();
+            let keys: Vec<_> = core::iter::from_fn(|| self.build_next()).collect();
+            let indices = (items.iter().enumerate())
+                .filter(|(_, item)| matches!(item, KleLegendsOrProps::Legend(_)))
+                .map(|(index, _)| index);
+
+            self.buffer = Some(KeyBuffer {
+                keys: indices.zip(keys).collect::<Vec<_>>().into_iter(),
+                front: 0,
+                back: items.len(),
+                items,
+            });
+        }
+
+        let buffer = self.buffer.as_mut()?;
+        let (_, key) = buffer.keys.next_back()?;
+        // Property objects before a key are passed along with it
+        buffer.back = (buffer.keys.as_slice().last()).map_or(buffer.front, |&(index, _)| index + 1);
+        self.len -= 1;
 
         Some(key)
     }
@@ -303,6 +581,7 @@ where
 
 #[cfg(test)]
 mod tests {
+    use assert_matches::assert_matches;
     use isclose::assert_is_close;
 
     use super::*;
@@ -339,6 +618,7 @@ mod tests {
         assert_eq!(md.plate_mount, Metadata::default().plate_mount);
         assert_eq!(md.pcb_mount, Metadata::default().pcb_mount);
         assert_eq!(md.notes, Metadata::default().notes);
+        assert_eq!(md.css, Metadata::default().css);
 
         let md: Metadata = Metadata::from(KleMetadata {
             author: Some("author".into()),
@@ -370,6 +650,7 @@ mod tests {
         assert!(md.plate_mount);
         assert!(md.pcb_mount);
         assert_eq!(md.notes, "notes");
+        assert_eq!(md.css, "css");
     }
 
     #[test]
@@ -619,6 +900,130 @@ mod tests {
         assert_eq!(key.profile, "DSA");
     }
 
+    #[test]
+    fn test_kle_props_is_new_line() {
+        let props = <KleProps>::default();
+
+        assert!(!props.is_new_line(&Key::default()));
+        assert!(props.is_new_line(&Key {
+            y: 1.0,
+            ..Key::default()
+        }));
+        assert!(props.is_new_line(&Key {
+            rotation: 15.0,
+            ..Key::default()
+        }));
+        assert!(props.is_new_line(&Key {
+            rx: 1.0,
+            ..Key::default()
+        }));
+        assert!(props.is_new_line(&Key {
+            ry: 1.0,
+            ..Key::default()
+        }));
+    }
+
+    #[test]
+    fn test_kle_props_encode_key() {
+        let mut props = <KleProps>::default();
+        let (props_obj, legends) = props.encode_key(&Key::default());
+        assert_eq!(props_obj, KlePropsObject::default());
+        assert_eq!(legends, "");
+
+        let legend = |text: &str, size, color| {
+            Some(Legend {
+                text: text.into(),
+                size,
+                color,
+            })
+        };
+        let key = Key {
+            legends: [
+                legend("A", 4, Color::new(255, 0, 0, 255)),
+                None,
+                legend("B", 3, Color::new(0, 0, 255, 255)),
+                None,
+                None,
+                None,
+                legend("C", 5, Color::new(255, 0, 0, 255)),
+                None,
+                None,
+                None,
+                legend("D", 4, Color::new(0, 255, 0, 255)),
+                None,
+            ],
+            color: Color::new(127, 51, 76, 255),
+            x: 1.25,
+            y: 0.5,
+            width: 1.25,
+            height: 2.0,
+            x2: -0.25,
+            y2: 0.0,
+            width2: 1.5,
+            height2: 1.0,
+            rotation: 15.0,
+            rx: 0.5,
+            ry: 0.25,
+            profile: "DSA".into(),
+            switch: Switch {
+                mount: "cherry".into(),
+                brand: "cherry".into(),
+                typ: "MX1A-31xx".into(),
+            },
+            ghosted: true,
+            stepped: true,
+            homing: true,
+            decal: true,
+        };
+
+        let (props_obj, legends) = props.encode_key(&key);
+        assert_eq!(legends, "A\nC\nB\n\nD");
+        assert_is_close!(props_obj.x.unwrap(), 0.75);
+        assert_is_close!(props_obj.y.unwrap(), 0.25);
+        assert_eq!(
+            props_obj
+                .fa
+                .as_ref()
+                .map(|fa| fa.iter().copied().map(usize::from).collect::<Vec<_>>()),
+            Some(vec![4, 5, 3, 0, 4])
+        );
+        assert_eq!(
+            props_obj.t,
+            Some(vec![
+                Some(Color::new(255, 0, 0, 255)),
+                None,
+                Some(Color::new(0, 0, 255, 255)),
+                None,
+                Some(Color::new(0, 255, 0, 255)),
+            ])
+        );
+
+        props.update(props_obj);
+        assert_eq!(props.build_key(&legends), key);
+
+        // Nothing changes, so we shouldn't need any props for the same key again
+        props.next_key();
+        let key = Key { x: props.x, ..key };
+        let (props_obj, legends) = props.encode_key(&key);
+        assert_eq!(
+            props_obj,
+            KlePropsObject {
+                w: Some(1.25),
+                h: Some(2.0),
+                x2: Some(-0.25),
+                w2: Some(1.5),
+                h2: Some(1.0),
+                l: Some(true),
+                n: Some(true),
+                d: Some(true),
+                ..KlePropsObject::default()
+            }
+        );
+
+        props.update(props_obj);
+        assert_eq!(props.build_key(&legends), key);
+    }
+
     #[test]
     fn test_kle_layout_iterator() {
         let kle: KleKeyboard = serde_json::from_str(
@@ -655,4 +1060,164 @@ mod tests {
         assert_is_close!(keys[2].x, 1.5);
         assert_is_close!(keys[3].x, 0.0);
     }
+
+    #[test]
+    fn test_kle_layout_iterator_ordered_float() {
+        use ordered_float::OrderedFloat;
+
+        let props = KleProps::<OrderedFloat<f64>>::default();
+        assert_eq!(props.w, OrderedFloat(1.0));
+
+        let layout = vec![
+            vec![
+                KleLegendsOrProps::Legend("A".into()),
+                KleLegendsOrProps::Props(Box::new(KlePropsObject {
+                    x: Some(OrderedFloat(0.5)),
+                    w: Some(OrderedFloat(1.5)),
+                    ..KlePropsObject::default()
+                })),
+                KleLegendsOrProps::Legend("B".into()),
+            ],
+            vec![KleLegendsOrProps::Legend("C".into())],
+        ];
+        let keys: Vec<_> = KleLayoutIterator::new(layout).collect();
+
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys[1].x, OrderedFloat(1.5));
+        assert_eq!(keys[1].width, OrderedFloat(1.5));
+        assert_eq!(keys[2].y, OrderedFloat(1.0));
+
+        let mut sorted = keys.clone();
+        sorted.sort_by_key(|key| core::cmp::Reverse((key.y, key.x)));
+        assert_eq!(sorted[0], keys[2]);
+        assert_eq!(sorted[2], keys[0]);
+    }
+
+    #[test]
+    fn test_key_to_props_delta() {
+        let mut reference = KleProps::<f64>::default();
+        reference.update(KlePropsObject {
+            c: Some(Color::new(255, 0, 0, 255)),
+            w: Some(2.0),
+            ..KlePropsObject::default()
+        });
+        reference.next_key();
+
+        let key = Key {
+            x: 2.5,
+            color: Color::new(255, 0, 0, 255),
+            width: 1.5,
+            width2: 1.5,
+            ..Key::default()
+        };
+        assert!(!key.starts_new_row(&reference));
+        assert_eq!(
+            key.to_props_delta(&reference),
+            KlePropsObject {
+                x: Some(0.5),
+                w: Some(1.5),
+                ..KlePropsObject::default()
+            }
+        );
+
+        let key = Key {
+            x: 2.0,
+            color: Color::new(255, 0, 0, 255),
+            ..Key::default()
+        };
+        assert_eq!(key.to_props_delta(&reference), KlePropsObject::default());
+
+        let key = Key {
+            rotation: 15.0,
+            ..Key::default()
+        };
+        assert!(key.starts_new_row(&reference));
+        let delta = key.to_props_delta(&reference);
+        assert_eq!(delta.r, Some(15.0));
+        assert_eq!(delta.c, Some(Color::new(204, 204, 204, 255)));
+        assert_eq!(delta.a, None);
+
+        // The alignment only changes if the current one cannot display the legends
+        let mut key = Key {
+            x: 2.0,
+            color: Color::new(255, 0, 0, 255),
+            ..Key::default()
+        };
+        key.legends[4] = Some(Legend::default());
+        assert_eq!(key.to_props_delta(&reference).a, None);
+        key.legends[9] = Some(Legend::default());
+        assert_eq!(
+            key.to_props_delta(&reference).a,
+            Some(Alignment::new(3).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_kle_layout_iterator_peek() {
+        let kle: KleKeyboard<f64> = serde_json::from_str(
+            r##"[
+                [
+                    {"c": "#ff0000", "w": 2},
+                    "A",
+                    "B"
+                ],
+                [],
+                [
+                    "C"
+                ]
+            ]"##,
+        )
+        .unwrap();
+        let mut iterator = KleLayoutIterator::new(kle.layout);
+
+        assert_matches!(iterator.peek(), Some(KleLegendsOrProps::Props(props)) if props.w == Some(2.0));
+        assert_is_close!(iterator.current_props().w, 1.0);
+
+        iterator.next().unwrap();
+        assert_matches!(iterator.peek(), Some(KleLegendsOrProps::Legend(legend)) if legend == "B");
+        assert_is_close!(iterator.current_props().x, 2.0);
+        assert_is_close!(iterator.current_props().w, 1.0);
+        assert_eq!(iterator.current_props().c, Color::new(255, 0, 0, 255));
+
+        // Skips the empty row
+        iterator.next().unwrap();
+        assert_matches!(iterator.peek(), Some(KleLegendsOrProps::Legend(legend)) if legend == "C");
+        assert_is_close!(iterator.current_props().y, 0.0);
+
+        iterator.next().unwrap();
+        assert!(iterator.peek().is_none());
+        assert_is_close!(iterator.current_props().y, 2.0);
+
+        // Peeking still works after iterating from the back
+        let kle: KleKeyboard<f64> =
+            serde_json::from_str(r#"[["A", {"w": 2}, "B"], ["C", {"x": 1}]]"#).unwrap();
+        let mut iterator = KleLayoutIterator::new(kle.layout);
+
+        assert_eq!(
+            iterator.next_back().unwrap().legends[0]
+                .as_ref()
+                .unwrap()
+                .text,
+            "C"
+        );
+        assert_matches!(iterator.peek(), Some(KleLegendsOrProps::Legend(legend)) if legend == "A");
+
+        iterator.next().unwrap();
+        assert_matches!(iterator.peek(), Some(KleLegendsOrProps::Props(props)) if props.w == Some(2.0));
+
+        assert_is_close!(iterator.next_back().unwrap().width, 2.0);
+        assert!(iterator.peek().is_none());
+        assert!(iterator.next().is_none());
+
+        // Property objects before a key taken from the back are passed along with it
+        let kle: KleKeyboard<f64> =
+            serde_json::from_str(r#"[["A", "B", {"x": 1}], ["C"]]"#).unwrap();
+        let mut iterator = KleLayoutIterator::new(kle.layout);
+        iterator.next().unwrap();
+        assert_matches!(iterator.peek(), Some(KleLegendsOrProps::Legend(legend)) if legend == "B");
+        assert_is_close!(iterator.next_back().unwrap().y, 1.0);
+        assert_matches!(iterator.peek(), Some(KleLegendsOrProps::Legend(legend)) if legend == "B");
+        iterator.next().unwrap();
+        assert!(iterator.peek().is_none());
+    }
 }
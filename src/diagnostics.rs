@@ -0,0 +1,313 @@
+use alloc::{string::String, vec::Vec};
+use core::{convert::Infallible, fmt};
+
+use num_traits::real::Real;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::strict::{
+    to_usize, walk_layout, LayoutVisitor, MAX_ALIGNMENT, MAX_FONT_SIZE, METADATA_FIELDS,
+    MIN_FONT_SIZE,
+};
+use crate::Keyboard;
+
+/// A non-fatal issue found when deserialising a layout with
+/// [`Keyboard::from_str_with_diagnostics`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// A font size (`f`, `f2`, or `fa`) was outside the range used by KLE and was clamped.
+    FontSizeClamped {
+        /// The font size in the input.
+        original: usize,
+        /// The font size after clamping.
+        clamped: usize,
+    },
+    /// An alignment (`a`) was outside the range `0..=7` and was clamped.
+    AlignmentClamped {
+        /// The alignment in the input.
+        original: usize,
+        /// The alignment after clamping.
+        clamped: usize,
+    },
+    /// A rotation property (`r`, `rx`, or `ry`) appeared somewhere other than the start of a row.
+    RotationMidRow,
+    /// The metadata object contained a field that is not recognised by KLE.
+    UnknownField(String),
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FontSizeClamped { original, clamped } => {
+                write!(f, "font size {original} clamped to {clamped}")
+            }
+            Self::AlignmentClamped { original, clamped } => {
+                write!(f, "alignment {original} clamped to {clamped}")
+            }
+            Self::RotationMidRow => write!(f, "rotation property not at start of row"),
+            Self::UnknownField(field) => write!(f, "unknown metadata field {field:?}"),
+        }
+    }
+}
+
+/// A receiver for non-fatal issues found by [`Keyboard::from_str_with_diagnostics`].
+pub trait ParseDiagnostics {
+    /// Called for each issue found while deserialising a layout.
+    ///
+    /// `row` is the index of the row, not including the metadata object, and `col` is the index of
+    /// the properties object within that row's JSON array. Both are 0 for issues in the metadata.
+    fn warn(&mut self, kind: DiagnosticKind, row: usize, col: usize);
+}
+
+/// A [`ParseDiagnostics`] implementation which collects all issues for later inspection.
+///
+/// ```
+/// use kle_serial::{CollectingDiagnostics, DiagnosticKind, Keyboard};
+///
+/// let mut diagnostics = CollectingDiagnostics::default();
+/// let keyboard: Keyboard =
+///     Keyboard::from_str_with_diagnostics(r#"[[{"a": 9}, "A"]]"#, &mut diagnostics).unwrap();
+///
+/// assert_eq!(
+///     diagnostics.warnings(),
+///     [(DiagnosticKind::AlignmentClamped { original: 9, clamped: 7 }, 0, 0)]
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CollectingDiagnostics {
+    warnings: Vec<(DiagnosticKind, usize, usize)>,
+}
+
+impl CollectingDiagnostics {
+    /// Returns the collected issues along with their row and column, in the order they were
+    /// found.
+    #[must_use]
+    pub fn warnings(&self) -> &[(DiagnosticKind, usize, usize)] {
+        &self.warnings
+    }
+
+    /// Consumes the collector and returns the collected issues. See
+    /// [`CollectingDiagnostics::warnings`].
+    #[must_use]
+    pub fn into_warnings(self) -> Vec<(DiagnosticKind, usize, usize)> {
+        self.warnings
+    }
+}
+
+impl ParseDiagnostics for CollectingDiagnostics {
+    fn warn(&mut self, kind: DiagnosticKind, row: usize, col: usize) {
+        self.warnings.push((kind, row, col));
+    }
+}
+
+fn clamp_value(value: &mut Value, min: usize, max: usize) -> Option<(usize, usize)> {
+    let original = to_usize(value)?;
+    let clamped = original.clamp(min, max);
+    (clamped != original).then(|| {
+        *value = clamped.into();
+        (original, clamped)
+    })
+}
+
+fn diagnose_props(
+    props: &mut Map<String, Value>,
+    row: usize,
+    col: usize,
+    diagnostics: &mut impl ParseDiagnostics,
+) {
+    if col != 0 && ["r", "rx", "ry"].iter().any(|&k| props.contains_key(k)) {
+        diagnostics.warn(DiagnosticKind::RotationMidRow, row, col);
+    }
+
+    let mut font_sizes = Vec::new();
+    for key in ["f", "f2"] {
+        if let Some(size) = props.get_mut(key) {
+            font_sizes.extend(clamp_value(size, MIN_FONT_SIZE, MAX_FONT_SIZE));
+        }
+    }
+    // KLE uses 0 in the font size array for legends using the default size
+    if let Some(sizes) = props.get_mut("fa").and_then(Value::as_array_mut) {
+        for size in sizes {
+            font_sizes.extend(clamp_value(size, 0, MAX_FONT_SIZE));
+        }
+    }
+    for (original, clamped) in font_sizes {
+        diagnostics.warn(
+            DiagnosticKind::FontSizeClamped { original, clamped },
+            row,
+            col,
+        );
+    }
+
+    if let Some((original, clamped)) =
+        (props.get_mut("a")).and_then(|a| clamp_value(a, 0, MAX_ALIGNMENT))
+    {
+        diagnostics.warn(
+            DiagnosticKind::AlignmentClamped { original, clamped },
+            row,
+            col,
+        );
+    }
+}
+
+// Reports issues to the wrapped ParseDiagnostics, clamping out of range values as it goes
+struct DiagnosticsVisitor<'a, D>(&'a mut D);
+
+impl<D> LayoutVisitor for DiagnosticsVisitor<'_, D>
+where
+    D: ParseDiagnostics,
+{
+    type Error = Infallible;
+
+    fn visit_metadata(&mut self, metadata: &mut Map<String, Value>) -> Result<(), Infallible> {
+        for field in metadata.keys() {
+            if !METADATA_FIELDS.contains(&field.as_str()) {
+                self.0
+                    .warn(DiagnosticKind::UnknownField(field.clone()), 0, 0);
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_props(
+        &mut self,
+        props: &mut Map<String, Value>,
+        row: usize,
+        col: usize,
+    ) -> Result<(), Infallible> {
+        diagnose_props(props, row, col, self.0);
+        Ok(())
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real + DeserializeOwned,
+{
+    /// Deserialises a [`Keyboard`] from a string containing KLE JSON data, reporting any
+    /// out-of-spec but recoverable content to `diagnostics`.
+    ///
+    /// Unlike [`Keyboard::from_str_strict`], this accepts the same layouts as KLE does. Font sizes
+    /// and alignments outside of the ranges used by KLE are clamped rather than rejected, and
+    /// rotation properties not at the start of a row and unknown metadata fields are reported but
+    /// otherwise handled the same as the regular deserialiser.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`serde_json::Error`] if the input is not a valid KLE layout.
+    pub fn from_str_with_diagnostics(
+        s: &str,
+        diagnostics: &mut impl ParseDiagnostics,
+    ) -> Result<Self, serde_json::Error> {
+        let mut value: Value = serde_json::from_str(s)?;
+        match walk_layout(&mut value, &mut DiagnosticsVisitor(diagnostics)) {
+            Ok(()) => serde_json::from_value(value),
+            Err(never) => match never {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_diagnostic_kind_display() {
+        let kind = DiagnosticKind::FontSizeClamped {
+            original: 0,
+            clamped: 1,
+        };
+        assert_eq!(format!("{kind}"), "font size 0 clamped to 1");
+
+        let kind = DiagnosticKind::AlignmentClamped {
+            original: 8,
+            clamped: 7,
+        };
+        assert_eq!(format!("{kind}"), "alignment 8 clamped to 7");
+
+        let kind = DiagnosticKind::RotationMidRow;
+        assert_eq!(format!("{kind}"), "rotation property not at start of row");
+
+        let kind = DiagnosticKind::UnknownField("foo".into());
+        assert_eq!(format!("{kind}"), r#"unknown metadata field "foo""#);
+    }
+
+    #[test]
+    fn test_keyboard_from_str_with_diagnostics() {
+        let mut diagnostics = CollectingDiagnostics::default();
+        let keyboard = Keyboard::<f64>::from_str_with_diagnostics(
+            r#"[
+                {"name": "test", "foo": "bar"},
+                [{"f": 0, "fa": [0, 12]}, "A", {"r": 15}, "B"],
+                ["C", {"a": 9, "f2": 10}, "D"]
+            ]"#,
+            &mut diagnostics,
+        )
+        .unwrap();
+
+        assert_eq!(keyboard.metadata.name, "test");
+        assert_eq!(keyboard.keys.len(), 4);
+        assert_eq!(keyboard.keys[0].legends[0].as_ref().unwrap().size, 1);
+
+        assert_eq!(
+            diagnostics.into_warnings(),
+            vec![
+                (DiagnosticKind::UnknownField("foo".into()), 0, 0),
+                (
+                    DiagnosticKind::FontSizeClamped {
+                        original: 0,
+                        clamped: 1
+                    },
+                    0,
+                    0
+                ),
+                (
+                    DiagnosticKind::FontSizeClamped {
+                        original: 12,
+                        clamped: 9
+                    },
+                    0,
+                    0
+                ),
+                (DiagnosticKind::RotationMidRow, 0, 2),
+                (
+                    DiagnosticKind::FontSizeClamped {
+                        original: 10,
+                        clamped: 9
+                    },
+                    1,
+                    1
+                ),
+                (
+                    DiagnosticKind::AlignmentClamped {
+                        original: 9,
+                        clamped: 7
+                    },
+                    1,
+                    1
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keyboard_from_str_with_diagnostics_valid() {
+        let mut diagnostics = CollectingDiagnostics::default();
+        let keyboard = Keyboard::<f64>::from_str_with_diagnostics(
+            r#"[[{"r": 15, "rx": 1, "a": 7, "f": 9, "fa": [0, 2]}, "A"]]"#,
+            &mut diagnostics,
+        )
+        .unwrap();
+
+        assert_eq!(keyboard.keys.len(), 1);
+        assert!(diagnostics.warnings().is_empty());
+
+        let result = Keyboard::<f64>::from_str_with_diagnostics("{", &mut diagnostics);
+        assert!(result.is_err());
+        let result = Keyboard::<f64>::from_str_with_diagnostics("null", &mut diagnostics);
+        assert!(result.is_err());
+        assert!(diagnostics.warnings().is_empty());
+    }
+}
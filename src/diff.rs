@@ -0,0 +1,217 @@
+use alloc::{format, string::String, vec::Vec};
+
+use num_traits::{real::Real, NumCast};
+
+use crate::{Key, Keyboard};
+
+// Default tolerance used when matching keys by position and size
+const DEFAULT_TOLERANCE: f64 = 1e-6;
+
+/// The differences between two [`Keyboard`]s, as returned by [`Keyboard::diff`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyboardDiff<T = f64>
+where
+    T: Real,
+{
+    /// Keys which are only present in the new layout.
+    pub added: Vec<Key<T>>,
+    /// Keys which are only present in the old layout.
+    pub removed: Vec<Key<T>>,
+    /// Keys which are present in both layouts but have changed, as `(old, new)` pairs.
+    pub modified: Vec<(Key<T>, Key<T>)>,
+    /// Whether the layout's metadata has changed.
+    pub metadata_changed: bool,
+}
+
+impl<T> KeyboardDiff<T>
+where
+    T: Real,
+{
+    /// Returns whether there are no differences between the layouts.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && !self.metadata_changed
+    }
+
+    /// Returns a short human readable summary of the differences, for example
+    /// `"1 key added, 2 keys modified, metadata changed"`.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let keys = |count: usize, action: &str| {
+            let plural = if count == 1 { "" } else { "s" };
+            (count > 0).then(|| format!("{count} key{plural} {action}"))
+        };
+
+        let parts: Vec<_> = [
+            keys(self.added.len(), "added"),
+            keys(self.removed.len(), "removed"),
+            keys(self.modified.len(), "modified"),
+            self.metadata_changed.then(|| "metadata changed".into()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if parts.is_empty() {
+            "no changes".into()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Compares this layout to `other`, treating `self` as the old layout and `other` as the new.
+    ///
+    /// KLE does not have stable key identifiers, so keys are matched by their position and size.
+    /// Matching keys which differ in any other field are reported as modified. This uses a small
+    /// tolerance to allow for floating point error; use [`Keyboard::diff_with_tolerance`] to
+    /// specify a different tolerance.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> KeyboardDiff<T> {
+        let tolerance = NumCast::from(DEFAULT_TOLERANCE).unwrap_or_else(T::epsilon);
+        self.diff_with_tolerance(other, tolerance)
+    }
+
+    /// Compares this layout to `other`, matching keys whose position and size differ by at most
+    /// `tolerance` keyboard units.
+    ///
+    /// See [`Keyboard::diff`] for more details.
+    #[must_use]
+    pub fn diff_with_tolerance(&self, other: &Self, tolerance: T) -> KeyboardDiff<T> {
+        let matches = |a: &Key<T>, b: &Key<T>| {
+            [
+                (a.x, b.x),
+                (a.y, b.y),
+                (a.width, b.width),
+                (a.height, b.height),
+            ]
+            .iter()
+            .all(|&(a, b)| (a - b).abs() <= tolerance)
+        };
+
+        let mut unmatched: Vec<_> = other.keys.iter().map(Some).collect();
+        let mut removed = Vec::new();
+        let mut modified = Vec::new();
+
+        for old in &self.keys {
+            let new = unmatched
+                .iter_mut()
+                .find(|new| new.map_or(false, |new| matches(old, new)))
+                .and_then(Option::take);
+
+            match new {
+                Some(new) if new != old => modified.push((old.clone(), new.clone())),
+                Some(_) => {}
+                None => removed.push(old.clone()),
+            }
+        }
+
+        KeyboardDiff {
+            added: unmatched.into_iter().flatten().cloned().collect(),
+            removed,
+            modified,
+            metadata_changed: self.metadata != other.metadata,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{Color, Legend};
+
+    use super::*;
+
+    fn keyboard() -> Keyboard {
+        Keyboard {
+            keys: vec![
+                Key::default(),
+                Key {
+                    x: 1.0,
+                    legends: core::array::from_fn(|i| (i == 0).then(Legend::default)),
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        }
+    }
+
+    #[test]
+    fn test_keyboard_diff_unchanged() {
+        let diff = keyboard().diff(&keyboard());
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.summary(), "no changes");
+    }
+
+    #[test]
+    fn test_keyboard_diff_added() {
+        let mut new = keyboard();
+        new.keys.push(Key {
+            x: 2.0,
+            ..Key::default()
+        });
+        let diff = keyboard().diff(&new);
+
+        assert!(!diff.is_empty());
+        assert_eq!(diff.added, vec![new.keys[2].clone()]);
+        assert!(diff.removed.is_empty());
+        assert!(diff.modified.is_empty());
+        assert_eq!(diff.summary(), "1 key added");
+    }
+
+    #[test]
+    fn test_keyboard_diff_removed() {
+        let mut new = keyboard();
+        new.keys.remove(0);
+        let diff = keyboard().diff(&new);
+
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.removed, vec![Key::default()]);
+        assert!(diff.modified.is_empty());
+        assert_eq!(diff.summary(), "1 key removed");
+    }
+
+    #[test]
+    fn test_keyboard_diff_modified() {
+        let mut new = keyboard();
+        new.keys[1].legends[0].as_mut().unwrap().color = Color::new(0xFF, 0, 0, 0xFF);
+        new.keys[0].x = 1e-7;
+        let diff = keyboard().diff(&new);
+
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.modified.len(), 2);
+        assert_eq!(
+            diff.modified[1],
+            (keyboard().keys[1].clone(), new.keys[1].clone())
+        );
+        assert_eq!(diff.summary(), "2 keys modified");
+
+        let diff = keyboard().diff_with_tolerance(&new, 0.0);
+
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.summary(), "1 key added, 1 key removed, 1 key modified");
+    }
+
+    #[test]
+    fn test_keyboard_diff_metadata() {
+        let mut new = keyboard();
+        new.metadata.name = "test".into();
+        let diff = keyboard().diff(&new);
+
+        assert!(!diff.is_empty());
+        assert!(diff.metadata_changed);
+        assert_eq!(diff.summary(), "metadata changed");
+    }
+}
@@ -0,0 +1,238 @@
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt::{self, Display};
+
+use num_traits::real::Real;
+
+use crate::{ColorExt, Key, Keyboard, Metadata};
+
+// Number of characters per key unit when rendering ASCII art
+const ASCII_UNIT_WIDTH: f64 = 4.0;
+
+// Maximum number of characters of each legend shown when displaying a key
+const MAX_LEGEND_CHARS: usize = 3;
+
+// Maximum number of lines and characters per line rendered as ASCII art
+const MAX_ASCII_ROWS: i64 = 256;
+const MAX_ASCII_COLS: i64 = 1024;
+
+impl<T> Display for Key<T>
+where
+    T: Real + Display,
+{
+    /// Formats the key on a single line showing its position, size, first two legends, and colour,
+    /// for example `Key @ (1.00, 2.00) 1.00×1.00 [A/a] #cccccc`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let legends: Vec<String> = (self.legends.iter().flatten())
+            .take(2)
+            .map(|legend| legend.text.chars().take(MAX_LEGEND_CHARS).collect())
+            .collect();
+
+        write!(
+            f,
+            "Key @ ({:.2}, {:.2}) {:.2}×{:.2} [{}] {}",
+            self.x,
+            self.y,
+            self.width,
+            self.height,
+            legends.join("/"),
+            self.color.to_hex_string()
+        )
+    }
+}
+
+impl Display for Metadata {
+    /// Formats the metadata as `name (author)`, omitting the author if it is empty.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.author.is_empty() {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{} ({})", self.name, self.author)
+        }
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Renders a rough ASCII art representation of the layout.
+    ///
+    /// Each key is drawn on the row nearest its `y` position as `[` and `]` surrounding the first
+    /// character of its first legend, with each key unit being 4 characters wide. Rotation and the
+    /// secondary rectangle of stepped and L-shaped keys are ignored.
+    ///
+    /// The output is limited to 256 lines of 1024 characters measured from the top left key, and
+    /// anything beyond that is cut off.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["Q", "W"], [{"w": 2}, "Shift"]]"#).unwrap();
+    ///
+    /// assert_eq!(keyboard.display_ascii(), "[Q ][W ]\n[S     ]");
+    /// ```
+    #[must_use]
+    pub fn display_ascii(&self) -> String {
+        // Out of range values saturate, and are then clipped to the rendered area below
+        #[allow(clippy::cast_possible_truncation)]
+        let cells: Vec<_> = (self.keys.iter())
+            .map(|key| {
+                let round = |v: T, scale: f64| (v.to_f64().unwrap_or(0.0) * scale).round() as i64;
+                let (row, col) = (round(key.y, 1.0), round(key.x, ASCII_UNIT_WIDTH));
+                let width = round(key.x + key.width, ASCII_UNIT_WIDTH).saturating_sub(col);
+                let ch = (key.legends.iter().flatten())
+                    .find_map(|legend| legend.text.chars().next())
+                    .unwrap_or(' ');
+                (row, col, width, ch)
+            })
+            .collect();
+
+        let (row_min, col_min) = (cells.iter()).fold(
+            (i64::MAX, i64::MAX),
+            |(row_min, col_min), &(row, col, ..)| (row_min.min(row), col_min.min(col)),
+        );
+        let cells: Vec<_> = (cells.into_iter())
+            .filter_map(|(row, col, width, ch)| {
+                let (row, col) = (row.saturating_sub(row_min), col.saturating_sub(col_min));
+                (row < MAX_ASCII_ROWS && col < MAX_ASCII_COLS)
+                    .then(|| (row, col, width.clamp(1, MAX_ASCII_COLS - col), ch))
+            })
+            .collect();
+        let row_count = cells.iter().map(|&(row, ..)| row + 1).max().unwrap_or(0);
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // All values are >= 0
+        let mut lines = vec![Vec::new(); row_count as usize];
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        for (row, col, width, ch) in cells {
+            let line = &mut lines[row as usize];
+            let (start, width) = (col as usize, width as usize);
+            if line.len() < start + width {
+                line.resize(start + width, ' ');
+            }
+
+            if width >= 3 {
+                line[start] = '[';
+                line[start + 1] = ch;
+                line[start + width - 1] = ']';
+            } else {
+                line[start] = ch;
+            }
+        }
+
+        (lines.into_iter())
+            .map(|line| line.into_iter().collect::<String>().trim_end().into())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use crate::{Color, Legend};
+
+    use super::*;
+
+    #[test]
+    fn test_key_display() {
+        let legend = |text: &str| {
+            Some(Legend {
+                text: text.into(),
+                ..Legend::default()
+            })
+        };
+        let key = Key::<f64> {
+            x: 1.0,
+            y: 2.0,
+            width: 1.25,
+            legends: [
+                legend("Shift"),
+                None,
+                None,
+                None,
+                None,
+                None,
+                legend("a"),
+                None,
+                None,
+                legend("b"),
+                None,
+                None,
+            ],
+            color: Color::new(0xCC, 0x22, 0x22, 0xFF),
+            ..Key::default()
+        };
+
+        assert_eq!(
+            format!("{key}"),
+            "Key @ (1.00, 2.00) 1.25×1.00 [Shi/a] #cc2222"
+        );
+        assert_eq!(
+            format!("{}", Key::<f32>::default()),
+            "Key @ (0.00, 0.00) 1.00×1.00 [] #cccccc"
+        );
+    }
+
+    #[test]
+    fn test_metadata_display() {
+        let metadata = Metadata {
+            name: "test".into(),
+            ..Metadata::default()
+        };
+        assert_eq!(format!("{metadata}"), "test");
+
+        let metadata = Metadata {
+            author: "author".into(),
+            ..metadata
+        };
+        assert_eq!(format!("{metadata}"), "test (author)");
+    }
+
+    #[test]
+    fn test_keyboard_display_ascii() {
+        let key = |x: f64, y: f64, width: f64, text: &str| Key {
+            x,
+            y,
+            width,
+            legends: core::array::from_fn(|i| {
+                (i == 0).then(|| Legend {
+                    text: text.into(),
+                    ..Legend::default()
+                })
+            }),
+            ..Key::default()
+        };
+        let keyboard = Keyboard {
+            keys: vec![
+                key(-1.0, 0.0, 1.0, "Esc"),
+                key(0.5, 0.0, 1.0, "F1"),
+                key(0.0, 1.0, 1.5, "Tab"),
+                key(0.0, 3.0, 1.0, ""),
+            ],
+            ..Keyboard::default()
+        };
+
+        assert_eq!(
+            keyboard.display_ascii(),
+            "[E ]  [F ]\n    [T   ]\n\n    [  ]"
+        );
+        assert_eq!(Keyboard::<f64>::default().display_ascii(), "");
+    }
+
+    #[test]
+    fn test_keyboard_display_ascii_clipped() {
+        let keyboard: Keyboard =
+            serde_json::from_str(r#"[["A", {"w": 1e9}, "B"], [{"y": 1e9}, "C"]]"#).unwrap();
+        let ascii = keyboard.display_ascii();
+
+        assert_eq!(ascii.lines().count(), 1);
+        assert_eq!(ascii.chars().count(), 1024);
+        assert!(ascii.starts_with("[A ][B  "));
+        assert!(ascii.ends_with("  ]"));
+
+        let keyboard: Keyboard = serde_json::from_str(r#"[["A"], [{"x": -1e300}, "B"]]"#).unwrap();
+
+        assert_eq!(keyboard.display_ascii(), "\nB");
+    }
+}
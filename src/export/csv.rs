@@ -0,0 +1,397 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{Display, Write};
+use core::iter;
+
+use num_traits::real::Real;
+
+use crate::{ColorExt, Keyboard, NUM_LEGENDS, UNIT_MM};
+
+const HEADER: [&str; 14] = [
+    "row",
+    "col_in_row",
+    "x",
+    "y",
+    "width",
+    "height",
+    "rotation",
+    "rx",
+    "ry",
+    "color",
+    "profile",
+    "switch_mount",
+    "switch_brand",
+    "switch_type",
+];
+
+const POSITIONS_HEADER: [&str; 8] = [
+    "index",
+    "x_mm",
+    "y_mm",
+    "width_mm",
+    "height_mm",
+    "rotation_deg",
+    "rx_mm",
+    "ry_mm",
+];
+
+impl<T> Keyboard<T>
+where
+    T: Real + Display,
+{
+    /// Exports the layout's keys as comma separated values.
+    ///
+    /// The first line is a header containing the column names
+    /// `row,col_in_row,x,y,width,height,rotation,rx,ry,color,profile,switch_mount,switch_brand,switch_type,legend_0,...,legend_11`,
+    /// followed by one line per key. Keys are grouped into rows as described in
+    /// [`Keyboard::rows`]. Positions and sizes are in keyboard units and colours are formatted as
+    /// `#rrggbb`.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["Esc", "F1"]]"#).unwrap();
+    /// let csv = keyboard.to_csv();
+    ///
+    /// assert_eq!(csv.lines().count(), 3);
+    /// assert!(csv.lines().nth(1).unwrap().starts_with("0,0,0,0,1,1,0,0,0,#cccccc,,,,,Esc,"));
+    /// ```
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        self.to_csv_with_options(',', true)
+    }
+
+    /// Exports the layout's keys as delimited values using the given separator.
+    ///
+    /// The format is the same as [`Keyboard::to_csv`], but fields are separated by `sep`, so
+    /// `'\t'` can be used to produce tab separated values. If `include_empty_legends` is `false`,
+    /// legend columns which are empty for every key are omitted. Fields containing the separator,
+    /// quotes, or newlines are quoted.
+    #[must_use]
+    pub fn to_csv_with_options(&self, sep: char, include_empty_legends: bool) -> String {
+        let legend_columns: Vec<_> = (0..NUM_LEGENDS)
+            .filter(|&i| {
+                include_empty_legends
+                    || (self.keys.iter()).any(|key| {
+                        key.legends[i]
+                            .as_ref()
+                            .map_or(false, |legend| !legend.text.is_empty())
+                    })
+            })
+            .collect();
+
+        let mut csv = String::new();
+
+        let header = (HEADER.iter().map(|&name| String::from(name)))
+            .chain(legend_columns.iter().map(|i| format!("legend_{i}")));
+        write_line(&mut csv, header, sep);
+
+        for (row, keys) in self.rows().enumerate() {
+            for (col, key) in keys.into_iter().enumerate() {
+                let mut fields = Vec::with_capacity(HEADER.len() + legend_columns.len());
+                fields.extend([row, col].map(|v| v.to_string()));
+                fields.extend(
+                    [
+                        key.x,
+                        key.y,
+                        key.width,
+                        key.height,
+                        key.rotation,
+                        key.rx,
+                        key.ry,
+                    ]
+                    .map(|v| v.to_string()),
+                );
+                fields.push(key.color.to_hex_string());
+                fields.push(key.profile.clone());
+                fields.push(key.switch.mount.clone());
+                fields.push(key.switch.brand.clone());
+                fields.push(key.switch.typ.clone());
+                fields.extend(legend_columns.iter().map(|&i| {
+                    key.legends[i]
+                        .as_ref()
+                        .map(|legend| legend.text.clone())
+                        .unwrap_or_default()
+                }));
+
+                write_line(&mut csv, fields, sep);
+            }
+        }
+
+        csv
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real + Into<f64>,
+{
+    /// Exports the position of each key in millimetres as comma separated values, for use with
+    /// PCB footprint placement scripts.
+    ///
+    /// The first line is a header containing the column names
+    /// `index,x_mm,y_mm,width_mm,height_mm,rotation_deg,rx_mm,ry_mm`, followed by one line per key
+    /// in the same order as [`Keyboard::keys`]. The position is the top left corner of the key
+    /// before rotation. All values are written to 4 decimal places.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["Esc", {"w": 2}, "F1"]]"#).unwrap();
+    /// let csv = keyboard.to_positions_csv();
+    ///
+    /// assert_eq!(
+    ///     csv.lines().nth(2),
+    ///     Some("1,19.0500,0.0000,38.1000,19.0500,0.0000,0.0000,0.0000"),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn to_positions_csv(&self) -> String {
+        self.to_positions_csv_with_options((0.0, 0.0), false)
+    }
+
+    /// Exports the position of each key in millimetres relative to the given origin.
+    ///
+    /// The format is the same as [`Keyboard::to_positions_csv`], but `origin` (in millimetres) is
+    /// subtracted from all positions. If `invert_y` is `true` the Y axis points upwards as is
+    /// common in PCB tools, so Y positions are negated and rotations are reversed to keep keys
+    /// rotating in the same visual direction.
+    #[must_use]
+    pub fn to_positions_csv_with_options(&self, origin: (f64, f64), invert_y: bool) -> String {
+        let (x0, y0) = origin;
+        let to_x = |x: T| x.into() * UNIT_MM - x0;
+        let to_y = |y: T| {
+            let y = y.into() * UNIT_MM;
+            if invert_y {
+                y0 - y
+            } else {
+                y - y0
+            }
+        };
+
+        let mut csv = String::new();
+        write_line(&mut csv, POSITIONS_HEADER.map(String::from), ',');
+
+        for (index, key) in self.keys.iter().enumerate() {
+            let rotation = key.rotation.into();
+            let rotation = if invert_y { 0.0 - rotation } else { rotation };
+            let values = [
+                to_x(key.x),
+                to_y(key.y),
+                key.width.into() * UNIT_MM,
+                key.height.into() * UNIT_MM,
+                rotation,
+                to_x(key.rx),
+                to_y(key.ry),
+            ];
+
+            let fields = iter::once(index.to_string()).chain(values.map(|v| format!("{v:.4}")));
+            write_line(&mut csv, fields, ',');
+        }
+
+        csv
+    }
+}
+
+fn write_line(csv: &mut String, fields: impl IntoIterator<Item = String>, sep: char) {
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            csv.push(sep);
+        }
+        if field.contains(&[sep, '"', '\n', '\r'][..]) {
+            // Writing to a String never fails
+            let _ = write!(csv, "\"{}\"", field.replace('"', "\"\""));
+        } else {
+            csv.push_str(&field);
+        }
+    }
+    csv.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use isclose::assert_is_close;
+
+    use crate::{Key, Legend};
+
+    use super::*;
+
+    fn keyboard() -> Keyboard<f64> {
+        let legend = |text: &str| {
+            Some(Legend {
+                text: text.into(),
+                ..Legend::default()
+            })
+        };
+
+        Keyboard {
+            keys: vec![
+                Key {
+                    legends: [
+                        legend("A"),
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                        legend("a, \"b\""),
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                    ],
+                    ..Key::default()
+                },
+                Key {
+                    x: 1.25,
+                    width: 1.75,
+                    ..Key::default()
+                },
+                Key {
+                    x: 0.5,
+                    y: 1.0,
+                    height: 2.0,
+                    rotation: -12.5,
+                    rx: 0.3,
+                    ry: 1.1,
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        }
+    }
+
+    #[test]
+    fn test_keyboard_to_csv() {
+        let keyboard = keyboard();
+        let csv = keyboard.to_csv();
+        let mut lines = csv.lines();
+
+        let header = lines.next().unwrap();
+        assert!(header.starts_with("row,col_in_row,x,y,width,height,rotation,rx,ry,color,"));
+        assert!(header.ends_with(",legend_10,legend_11"));
+        assert_eq!(header.split(',').count(), HEADER.len() + NUM_LEGENDS);
+
+        let lines: Vec<_> = lines.collect();
+        assert_eq!(lines.len(), keyboard.keys.len());
+        assert_eq!(
+            lines[0],
+            "0,0,0,0,1,1,0,0,0,#cccccc,,,,,A,,,,,,\"a, \"\"b\"\"\",,,,,"
+        );
+        assert!(lines[1].starts_with("0,1,1.25,0,1.75,1,"));
+        assert!(lines[2].starts_with("1,0,0.5,1,1,2,-12.5,0.3,1.1,"));
+
+        // Check the numeric fields would round trip
+        for (line, key) in lines.iter().zip(&keyboard.keys) {
+            let values: Vec<f64> = (line.split(',').skip(2).take(7))
+                .map(|v| v.parse().unwrap())
+                .collect();
+            let expected = [
+                key.x,
+                key.y,
+                key.width,
+                key.height,
+                key.rotation,
+                key.rx,
+                key.ry,
+            ];
+
+            assert_eq!(values, expected);
+        }
+    }
+
+    #[test]
+    fn test_keyboard_to_csv_with_options() {
+        let csv = keyboard().to_csv_with_options('\t', false);
+        let mut lines = csv.lines();
+
+        let header = lines.next().unwrap();
+        assert!(header.ends_with("\tswitch_type\tlegend_0\tlegend_6"));
+        assert_eq!(header.split('\t').count(), HEADER.len() + 2);
+
+        let line = lines.next().unwrap();
+        assert_eq!(
+            line,
+            "0\t0\t0\t0\t1\t1\t0\t0\t0\t#cccccc\t\t\t\t\tA\t\"a, \"\"b\"\"\""
+        );
+        assert!(lines.all(|line| line.split('\t').count() == HEADER.len() + 2));
+
+        let csv = Keyboard::<f32>::default().to_csv_with_options(',', false);
+        assert_eq!(csv, "row,col_in_row,x,y,width,height,rotation,rx,ry,color,profile,switch_mount,switch_brand,switch_type\n");
+    }
+
+    #[test]
+    fn test_keyboard_to_positions_csv() {
+        let keyboard = keyboard();
+        let csv = keyboard.to_positions_csv();
+        let mut lines = csv.lines();
+
+        assert_eq!(
+            lines.next(),
+            Some("index,x_mm,y_mm,width_mm,height_mm,rotation_deg,rx_mm,ry_mm")
+        );
+
+        let rows: Vec<Vec<f64>> = lines
+            .map(|line| line.split(',').map(|v| v.parse().unwrap()).collect())
+            .collect();
+        assert_eq!(rows.len(), keyboard.keys.len());
+
+        // 1u key at the origin
+        assert_eq!(rows[0], [0.0, 0.0, 0.0, 19.05, 19.05, 0.0, 0.0, 0.0]);
+
+        assert_is_close!(rows[1][1], 23.8125);
+        assert_is_close!(rows[1][3], 33.3375);
+
+        assert_is_close!(rows[2][2], 19.05);
+        assert_is_close!(rows[2][4], 38.1);
+        assert_is_close!(rows[2][5], -12.5);
+        assert_is_close!(rows[2][6], 5.715);
+        assert_is_close!(rows[2][7], 20.955);
+
+        let keyboard = Keyboard {
+            keys: vec![Key {
+                width: 2.0,
+                ..Key::default()
+            }],
+            ..Keyboard::default()
+        };
+        let csv = keyboard.to_positions_csv();
+        let width: f64 = csv
+            .lines()
+            .nth(1)
+            .unwrap()
+            .split(',')
+            .nth(3)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_is_close!(width, 38.1);
+    }
+
+    #[test]
+    fn test_keyboard_to_positions_csv_with_options() {
+        let csv = keyboard().to_positions_csv_with_options((10.0, 20.0), true);
+        let lines: Vec<_> = csv.lines().skip(1).collect();
+
+        assert_eq!(
+            lines[0],
+            "0,-10.0000,20.0000,19.0500,19.0500,0.0000,-10.0000,20.0000"
+        );
+        assert_eq!(
+            lines[2],
+            "2,-0.4750,0.9500,19.0500,38.1000,12.5000,-4.2850,-0.9550"
+        );
+
+        let csv = Keyboard::<f32>::default().to_positions_csv_with_options((1.0, 1.0), true);
+        assert_eq!(
+            csv,
+            "index,x_mm,y_mm,width_mm,height_mm,rotation_deg,rx_mm,ry_mm\n"
+        );
+    }
+}
@@ -0,0 +1,10 @@
+mod csv;
+#[cfg(feature = "json")]
+mod qmk;
+mod svg;
+mod zmk;
+
+#[cfg(feature = "json")]
+pub use qmk::QmkOptions;
+pub use svg::SvgOptions;
+pub use zmk::ZmkOptions;
@@ -0,0 +1,261 @@
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+
+use num_traits::real::Real;
+use serde::Serialize;
+
+use crate::Keyboard;
+
+/// Options used to control QMK `info.json` generation with
+/// [`Keyboard::to_qmk_info_json_with_options`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QmkOptions {
+    /// The name of the layout macro, for example `LAYOUT` or `LAYOUT_ansi`.
+    pub layout_name: String,
+    /// Whether to include a `label` for each key using the key's first legend.
+    pub include_labels: bool,
+}
+
+impl Default for QmkOptions {
+    fn default() -> Self {
+        Self {
+            layout_name: "LAYOUT".into(),
+            include_labels: true,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct QmkInfo<'a> {
+    layouts: BTreeMap<&'a str, QmkLayout<'a>>,
+}
+
+#[derive(Serialize)]
+struct QmkLayout<'a> {
+    layout: Vec<QmkKey<'a>>,
+}
+
+#[derive(Serialize)]
+struct QmkKey<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<&'a str>,
+    matrix: [usize; 2],
+    x: f64,
+    y: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    w: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    h: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    r: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rx: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ry: Option<f64>,
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real + Into<f64>,
+{
+    /// Exports the layout as the `layouts` section of a QMK `info.json` file using the default
+    /// [`QmkOptions`].
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["Esc", "F1"]]"#).unwrap();
+    /// let json = keyboard.to_qmk_info_json();
+    ///
+    /// assert!(json.contains(r#""LAYOUT""#));
+    /// assert!(json.contains(r#""label": "Esc""#));
+    /// ```
+    #[must_use]
+    pub fn to_qmk_info_json(&self) -> String {
+        self.to_qmk_info_json_with_options(&QmkOptions::default())
+    }
+
+    /// Exports the layout as the `layouts` section of a QMK `info.json` file using the given
+    /// [`QmkOptions`].
+    ///
+    /// The result is a JSON object with a single `layouts` field containing one layout. Keys are
+    /// listed in the order they appear in [`Keyboard::keys`], and each key's matrix position is
+    /// its row and column as described in [`Keyboard::rows`]. The `w` and `h` fields are omitted
+    /// for 1u dimensions, and the `r`, `rx`, and `ry` fields are only included for rotated keys.
+    /// Stepped and L-shaped parts of keys are not represented.
+    #[must_use]
+    pub fn to_qmk_info_json_with_options(&self, options: &QmkOptions) -> String {
+        let non_unit = |value: T| (value != T::one()).then(|| value.into());
+
+        let layout = (self.keys.iter().zip(self.matrix_positions()))
+            .map(|(key, (row, col))| {
+                let rotated = !key.rotation.is_zero();
+                QmkKey {
+                    label: (key.legends.iter().flatten())
+                        .next()
+                        .filter(|_| options.include_labels)
+                        .map(|legend| legend.text.as_str()),
+                    matrix: [row, col],
+                    x: key.x.into(),
+                    y: key.y.into(),
+                    w: non_unit(key.width),
+                    h: non_unit(key.height),
+                    r: rotated.then(|| key.rotation.into()),
+                    rx: rotated.then(|| key.rx.into()),
+                    ry: rotated.then(|| key.ry.into()),
+                }
+            })
+            .collect();
+
+        let info = QmkInfo {
+            layouts: BTreeMap::from([(options.layout_name.as_str(), QmkLayout { layout })]),
+        };
+
+        // Serialising to a String never fails since all map keys are strings
+        serde_json::to_string_pretty(&info).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use serde_json::Value;
+
+    use crate::{Key, Legend};
+
+    use super::*;
+
+    fn keyboard() -> Keyboard<f64> {
+        let legend = |text: &str| {
+            Some(Legend {
+                text: text.into(),
+                ..Legend::default()
+            })
+        };
+
+        Keyboard {
+            keys: vec![
+                Key {
+                    legends: [
+                        None,
+                        None,
+                        None,
+                        None,
+                        legend("A"),
+                        legend("B"),
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                    ],
+                    ..Key::default()
+                },
+                Key {
+                    x: 1.0,
+                    width: 1.5,
+                    ..Key::default()
+                },
+                Key {
+                    y: 1.0,
+                    height: 2.0,
+                    legends: [
+                        legend("C"),
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                    ],
+                    ..Key::default()
+                },
+                Key {
+                    x: 2.5,
+                    y: 1.0,
+                    rotation: 15.0,
+                    rx: 2.5,
+                    ry: 1.0,
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        }
+    }
+
+    #[test]
+    fn test_qmk_options_default() {
+        let options = QmkOptions::default();
+
+        assert_eq!(options.layout_name, "LAYOUT");
+        assert!(options.include_labels);
+    }
+
+    #[test]
+    fn test_keyboard_to_qmk_info_json() {
+        let keyboard = keyboard();
+        let json: Value = serde_json::from_str(&keyboard.to_qmk_info_json()).unwrap();
+        let layout = json["layouts"]["LAYOUT"]["layout"].as_array().unwrap();
+
+        assert_eq!(layout.len(), keyboard.keys.len());
+        for (qmk, key) in layout.iter().zip(&keyboard.keys) {
+            assert_eq!(qmk["x"].as_f64(), Some(key.x));
+            assert_eq!(qmk["y"].as_f64(), Some(key.y));
+        }
+
+        let matrix: Vec<_> = layout.iter().map(|key| key["matrix"].clone()).collect();
+        assert_eq!(
+            matrix,
+            [
+                serde_json::json!([0, 0]),
+                serde_json::json!([0, 1]),
+                serde_json::json!([1, 0]),
+                serde_json::json!([1, 1]),
+            ]
+        );
+
+        assert_eq!(layout[0]["label"].as_str(), Some("A"));
+        assert!(layout[1].get("label").is_none());
+        assert_eq!(layout[2]["label"].as_str(), Some("C"));
+
+        assert!(layout[0].get("w").is_none());
+        assert_eq!(layout[1]["w"].as_f64(), Some(1.5));
+        assert!(layout[1].get("h").is_none());
+        assert_eq!(layout[2]["h"].as_f64(), Some(2.0));
+
+        assert!(layout[2].get("r").is_none());
+        assert_eq!(layout[3]["r"].as_f64(), Some(15.0));
+        assert_eq!(layout[3]["rx"].as_f64(), Some(2.5));
+        assert_eq!(layout[3]["ry"].as_f64(), Some(1.0));
+    }
+
+    #[test]
+    fn test_keyboard_to_qmk_info_json_with_options() {
+        let options = QmkOptions {
+            layout_name: "LAYOUT_ansi".into(),
+            include_labels: false,
+        };
+        let json = keyboard().to_qmk_info_json_with_options(&options);
+        let json: Value = serde_json::from_str(&json).unwrap();
+
+        assert!(json["layouts"].get("LAYOUT").is_none());
+        let layout = json["layouts"]["LAYOUT_ansi"]["layout"].as_array().unwrap();
+        assert_eq!(layout.len(), 4);
+        assert!(layout.iter().all(|key| key.get("label").is_none()));
+
+        let empty = Keyboard::<f64>::default().to_qmk_info_json();
+        let empty: Value = serde_json::from_str(&empty).unwrap();
+        assert_eq!(
+            empty["layouts"]["LAYOUT"]["layout"]
+                .as_array()
+                .map(Vec::len),
+            Some(0)
+        );
+    }
+}
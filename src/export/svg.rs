@@ -0,0 +1,337 @@
+use alloc::{format, string::String};
+use core::fmt::Write;
+
+use num_traits::real::Real;
+
+use crate::{ColorExt, Key, Keyboard, Legend, Rect};
+
+// KLE's own rendering uses 54px per key unit. These are all relative to that size
+const KLE_UNIT_PX: f64 = 54.0;
+const TOP_FACE_INSET: (f64, f64, f64) = (6.0, 3.0, 9.0); // Sides, top, bottom
+const LEGEND_PADDING: f64 = 3.0;
+
+/// Options used to control SVG rendering with [`Keyboard::to_svg_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SvgOptions {
+    /// The size of a single key unit in pixels.
+    pub unit_size_px: f64,
+    /// The corner radius of keys in pixels. This is not applied to stepped and L-shaped keys.
+    pub corner_radius_px: f64,
+    /// Whether ghosted keys are rendered. If enabled, ghosted keys are drawn semi-transparent.
+    pub show_ghosted: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            unit_size_px: KLE_UNIT_PX,
+            corner_radius_px: 5.0,
+            show_ghosted: true,
+        }
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real + Into<f64>,
+{
+    /// Renders the layout as an SVG 1.1 image using the default [`SvgOptions`].
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["Esc", "F1"]]"#).unwrap();
+    /// let svg = keyboard.to_svg();
+    ///
+    /// assert!(svg.starts_with("<svg"));
+    /// assert!(svg.contains(r#"viewBox="0 0 108 54""#));
+    /// ```
+    #[must_use]
+    pub fn to_svg(&self) -> String {
+        self.to_svg_with_options(&SvgOptions::default())
+    }
+
+    /// Renders the layout as an SVG 1.1 image using the given [`SvgOptions`].
+    ///
+    /// The image's `viewBox` is derived from [`Keyboard::bounding_box`]. Each key is drawn as a
+    /// `<rect>`, or a `<path>` for stepped and L-shaped keys, with its legends positioned the same
+    /// as in KLE. Decals are drawn without a key outline.
+    #[must_use]
+    pub fn to_svg_with_options(&self, options: &SvgOptions) -> String {
+        let scale = options.unit_size_px;
+        let bbox = self.bounding_box().map_or(
+            Rect {
+                x: 0.0,
+                y: 0.0,
+                width: 0.0,
+                height: 0.0,
+            },
+            |rect| Rect {
+                x: rect.x.into() * scale,
+                y: rect.y.into() * scale,
+                width: rect.width.into() * scale,
+                height: rect.height.into() * scale,
+            },
+        );
+        let (x, y, width, height) = (num(bbox.x), num(bbox.y), num(bbox.width), num(bbox.height));
+
+        let mut svg = String::new();
+
+        // Writing to a String never fails, so the results are ignored
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" viewBox="{x} {y} {width} {height}" width="{width}" height="{height}">"#
+        );
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{x}" y="{y}" width="{width}" height="{height}" fill="{}"/>"#,
+            self.metadata.background_color.to_hex_string()
+        );
+
+        for key in self
+            .keys
+            .iter()
+            .filter(|key| options.show_ghosted || !key.ghosted)
+        {
+            write_key(&mut svg, key, options);
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+fn write_key<T>(svg: &mut String, key: &Key<T>, options: &SvgOptions)
+where
+    T: Real + Into<f64>,
+{
+    let scale = options.unit_size_px;
+    let px = |v: T| v.into() * scale;
+
+    let mut attrs = String::new();
+    if key.rotation.into() != 0.0 {
+        let _ = write!(
+            attrs,
+            r#" transform="rotate({} {} {})""#,
+            num(key.rotation.into()),
+            num(px(key.rx)),
+            num(px(key.ry))
+        );
+    }
+    if key.ghosted {
+        attrs.push_str(r#" opacity="0.5""#);
+    }
+    let _ = writeln!(svg, "<g{attrs}>");
+
+    if !key.decal {
+        let fill = key.color.to_hex_string();
+        if key.is_regular() {
+            let radius = num(options.corner_radius_px);
+            let _ = writeln!(
+                svg,
+                r##"<rect x="{}" y="{}" width="{}" height="{}" rx="{radius}" ry="{radius}" fill="{fill}" stroke="#000000"/>"##,
+                num(px(key.x)),
+                num(px(key.y)),
+                num(px(key.width)),
+                num(px(key.height)),
+            );
+        } else {
+            let outline = key.primary_rect().union_outline(&key.secondary_rect());
+            let path = outline
+                .iter()
+                .enumerate()
+                .map(|(i, &(x, y))| {
+                    let cmd = if i == 0 { 'M' } else { 'L' };
+                    format!("{cmd}{} {}", num(px(x)), num(px(y)))
+                })
+                .collect::<alloc::vec::Vec<_>>()
+                .join(" ");
+            let _ = writeln!(
+                svg,
+                r##"<path d="{path} Z" fill="{fill}" stroke="#000000"/>"##
+            );
+        }
+    }
+
+    for (index, legend) in key.legends.iter().enumerate() {
+        if let Some(legend) = legend {
+            write_legend(svg, key, index, legend, options);
+        }
+    }
+
+    svg.push_str("</g>\n");
+}
+
+fn write_legend<T>(
+    svg: &mut String,
+    key: &Key<T>,
+    index: usize,
+    legend: &Legend,
+    options: &SvgOptions,
+) where
+    T: Real + Into<f64>,
+{
+    let scale = options.unit_size_px;
+    let rel = scale / KLE_UNIT_PX;
+    let (x, y) = (key.x.into() * scale, key.y.into() * scale);
+    let (width, height) = (key.width.into() * scale, key.height.into() * scale);
+
+    let (side, top, bottom) = TOP_FACE_INSET;
+    let (side, top, bottom) = (side * rel, top * rel, bottom * rel);
+    let padding = LEGEND_PADDING * rel;
+
+    // Legends are indexed left to right, top to bottom, with the front legends in the last row
+    let (text_x, anchor) = match index % 3 {
+        0 => (x + side + padding, "start"),
+        1 => (x + width / 2.0, "middle"),
+        _ => (x + width - side - padding, "end"),
+    };
+    let (text_y, baseline) = match index / 3 {
+        0 => (y + top + padding, "hanging"),
+        1 => (y + (top + height - bottom) / 2.0, "central"),
+        2 => (y + height - bottom - padding, "alphabetic"),
+        _ => (y + height - bottom / 2.0, "central"),
+    };
+
+    // KLE renders font sizes as 6px + 2px per size unit, with sizes clamped to 1..=9
+    #[allow(clippy::cast_precision_loss)] // size is at most 9
+    let font_size = (6.0 + 2.0 * legend.size.clamp(1, 9) as f64) * rel;
+
+    let _ = writeln!(
+        svg,
+        r#"<text x="{}" y="{}" font-size="{}" fill="{}" text-anchor="{anchor}" dominant-baseline="{baseline}">{}</text>"#,
+        num(text_x),
+        num(text_y),
+        num(font_size),
+        legend.color.to_hex_string(),
+        escape(&legend.text),
+    );
+}
+
+// Formats a number with at most 3 decimal places and no trailing zeros
+fn num(value: f64) -> String {
+    let mut result = format!("{value:.3}");
+    while result.ends_with('0') {
+        result.pop();
+    }
+    if result.ends_with('.') {
+        result.pop();
+    }
+    if result == "-0" {
+        result.remove(0);
+    }
+    result
+}
+
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_keyboard_to_svg() {
+        let keyboard = Keyboard::<f64> {
+            keys: vec![
+                Key::default(),
+                Key {
+                    x: 1.0,
+                    legends: core::array::from_fn(|i| {
+                        (i == 0).then(|| Legend {
+                            text: "<&>".into(),
+                            ..Legend::default()
+                        })
+                    }),
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        };
+        let svg = keyboard.to_svg();
+
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" version="1.1""#));
+        assert!(svg.contains(r#"viewBox="0 0 108 54""#));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<rect ").count(), 3);
+        assert_eq!(svg.matches("<text ").count(), 1);
+        assert!(svg.contains(">&lt;&amp;&gt;</text>"));
+        assert!(svg.contains(r##"fill="#cccccc""##));
+        assert!(svg.contains(r##"fill="#eeeeee""##));
+    }
+
+    #[test]
+    fn test_keyboard_to_svg_with_options() {
+        let keyboard = Keyboard::<f32> {
+            keys: vec![
+                // ISO enter
+                Key {
+                    x: 0.25,
+                    width: 1.25,
+                    height: 2.0,
+                    x2: -0.25,
+                    width2: 1.5,
+                    height2: 1.0,
+                    ..Key::default()
+                },
+                Key {
+                    x: 2.0,
+                    rotation: 15.0,
+                    rx: 2.0,
+                    ghosted: true,
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        };
+        let options = SvgOptions {
+            unit_size_px: 10.0,
+            corner_radius_px: 1.0,
+            show_ghosted: true,
+        };
+        let svg = keyboard.to_svg_with_options(&options);
+
+        assert!(svg.contains(r#"<path d="M0 0 L15 0 L15 20 L2.5 20 L2.5 10 L0 10 Z""#));
+        assert!(svg.contains(r#"<g transform="rotate(15 20 0)" opacity="0.5">"#));
+        assert!(svg.contains(r#"rx="1""#));
+
+        let options = SvgOptions {
+            show_ghosted: false,
+            ..options
+        };
+        let svg = keyboard.to_svg_with_options(&options);
+
+        assert!(!svg.contains("rotate"));
+        assert_eq!(svg.matches("<g").count(), 1);
+    }
+
+    #[test]
+    fn test_keyboard_to_svg_empty() {
+        let svg = Keyboard::<f64>::default().to_svg();
+
+        assert!(svg.contains(r#"viewBox="0 0 0 0""#));
+    }
+
+    #[test]
+    fn test_num() {
+        assert_eq!(num(1.0), "1");
+        assert_eq!(num(0.25), "0.25");
+        assert_eq!(num(0.1 + 0.2), "0.3");
+        assert_eq!(num(-0.0001), "0");
+        assert_eq!(num(-1.5), "-1.5");
+    }
+}
@@ -0,0 +1,176 @@
+use alloc::string::String;
+use core::fmt::Write;
+
+use num_traits::real::Real;
+
+use crate::Keyboard;
+
+// Binding used for keys with no corresponding keycode
+const NO_BINDING: &str = "&none";
+
+/// Options used to control ZMK keymap generation with [`Keyboard::to_zmk_keymap_with_options`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZmkOptions {
+    /// The `compatible` string of the keymap node.
+    pub compatible: String,
+    /// The name of the keymap node.
+    pub node_name: String,
+}
+
+impl Default for ZmkOptions {
+    fn default() -> Self {
+        Self {
+            compatible: "zmk,keymap".into(),
+            node_name: "keymap".into(),
+        }
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Generates a ZMK `.keymap` devicetree stub with a single layer using the default
+    /// [`ZmkOptions`].
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["Esc", "F1"]]"#).unwrap();
+    /// let keymap = keyboard.to_zmk_keymap("default_layer", &["&kp ESC", "&kp F1"]);
+    ///
+    /// assert!(keymap.contains("compatible = \"zmk,keymap\";"));
+    /// assert!(keymap.contains("/* RC(0,0) */ &kp ESC /* RC(0,1) */ &kp F1"));
+    /// ```
+    #[must_use]
+    pub fn to_zmk_keymap(&self, layer_name: &str, keycodes: &[&str]) -> String {
+        self.to_zmk_keymap_with_options(layer_name, keycodes, &ZmkOptions::default())
+    }
+
+    /// Generates a ZMK `.keymap` devicetree stub with a single layer using the given
+    /// [`ZmkOptions`].
+    ///
+    /// The layer node is named `layer_name` and its `bindings` contain one entry from `keycodes`
+    /// per key in the order they appear in [`Keyboard::keys`]. Each entry should be a complete ZMK
+    /// binding such as `&kp A`. Keys with no corresponding entry are bound to `&none`, and any
+    /// extra entries are ignored.
+    ///
+    /// Each binding is preceded by an `RC(row,col)` comment giving the key's position as described
+    /// in [`Keyboard::rows`], and a new line is started whenever the row changes.
+    #[must_use]
+    pub fn to_zmk_keymap_with_options(
+        &self,
+        layer_name: &str,
+        keycodes: &[&str],
+        options: &ZmkOptions,
+    ) -> String {
+        let mut keymap = String::new();
+
+        // Writing to a String never fails, so the results are ignored
+        let _ = writeln!(keymap, "/ {{");
+        let _ = writeln!(keymap, "    {} {{", options.node_name);
+        let _ = writeln!(keymap, "        compatible = \"{}\";", options.compatible);
+        let _ = writeln!(keymap);
+        let _ = writeln!(keymap, "        {layer_name} {{");
+        let _ = write!(keymap, "            bindings = <");
+
+        let mut prev_row = None;
+        for (i, (row, col)) in self.matrix_positions().into_iter().enumerate() {
+            if prev_row != Some(row) {
+                keymap.push_str("\n               ");
+                prev_row = Some(row);
+            }
+
+            let binding = keycodes.get(i).copied().unwrap_or(NO_BINDING);
+            let _ = write!(keymap, " /* RC({row},{col}) */ {binding}");
+        }
+
+        let _ = writeln!(keymap);
+        let _ = writeln!(keymap, "            >;");
+        let _ = writeln!(keymap, "        }};");
+        let _ = writeln!(keymap, "    }};");
+        let _ = writeln!(keymap, "}};");
+
+        keymap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::Key;
+
+    use super::*;
+
+    fn keyboard() -> Keyboard<f64> {
+        Keyboard {
+            keys: vec![
+                Key::default(),
+                Key {
+                    x: 1.0,
+                    ..Key::default()
+                },
+                Key {
+                    y: 1.0,
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        }
+    }
+
+    #[test]
+    fn test_zmk_options_default() {
+        let options = ZmkOptions::default();
+
+        assert_eq!(options.compatible, "zmk,keymap");
+        assert_eq!(options.node_name, "keymap");
+    }
+
+    #[test]
+    fn test_keyboard_to_zmk_keymap() {
+        let keyboard = keyboard();
+        let keycodes = ["&kp Q", "&kp W", "&kp A"];
+        let keymap = keyboard.to_zmk_keymap("default_layer", &keycodes);
+
+        assert_eq!(keycodes.len(), keyboard.keys.len());
+        assert!(keymap.contains("bindings = <"));
+        assert_eq!(keymap.matches("/* RC(").count(), keyboard.keys.len());
+
+        let expected = "\
+/ {
+    keymap {
+        compatible = \"zmk,keymap\";
+
+        default_layer {
+            bindings = <
+                /* RC(0,0) */ &kp Q /* RC(0,1) */ &kp W
+                /* RC(1,0) */ &kp A
+            >;
+        };
+    };
+};
+";
+        assert_eq!(keymap, expected);
+    }
+
+    #[test]
+    fn test_keyboard_to_zmk_keymap_with_options() {
+        let options = ZmkOptions {
+            compatible: "vendor,keymap".into(),
+            node_name: "my_keymap".into(),
+        };
+        let keymap = keyboard().to_zmk_keymap_with_options("base", &["&kp Q"], &options);
+
+        assert!(keymap.contains("    my_keymap {\n"));
+        assert!(keymap.contains("compatible = \"vendor,keymap\";"));
+        assert!(keymap.contains("        base {\n"));
+        assert!(keymap.contains("/* RC(0,1) */ &none"));
+        assert!(keymap.contains("/* RC(1,0) */ &none"));
+
+        let keymap = Keyboard::<f64>::default().to_zmk_keymap("base", &["&kp Q"]);
+        assert!(keymap.contains("bindings = <\n            >;"));
+        assert!(!keymap.contains("&kp Q"));
+    }
+}
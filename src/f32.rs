@@ -3,15 +3,63 @@
 /// Type alias of [`crate::Color`]
 pub type Color = crate::Color;
 
+/// Type alias of [`crate::ParseColorError`]
+pub type ParseColorError = crate::ParseColorError;
+
+/// Type alias of [`crate::KleColor`]
+pub type KleColor = crate::KleColor;
+
+/// Type alias of [`crate::KleLayoutIterator<f32>`]
+pub type KleLayoutIterator = crate::KleLayoutIterator<f32>;
+
+/// Type alias of [`crate::KleLegendsOrProps<f32>`]
+pub type KleLegendsOrProps = crate::KleLegendsOrProps<f32>;
+
+/// Type alias of [`crate::KleProps<f32>`]
+pub type KleProps = crate::KleProps<f32>;
+
+/// Type alias of [`crate::KlePropsObject<f32>`]
+pub type KlePropsObject = crate::KlePropsObject<f32>;
+
 /// Type alias of [`crate::Legend`]
 pub type Legend = crate::Legend;
 
+/// Type alias of [`crate::FontSize`]
+pub type FontSize = crate::FontSize;
+
+/// Type alias of [`crate::Alignment`]
+pub type Alignment = crate::Alignment;
+
+/// Type alias of [`crate::BoundsError`]
+pub type BoundsError = crate::BoundsError;
+
+/// Type alias of [`crate::LegendRow`]
+pub type LegendRow = crate::LegendRow;
+
+/// Type alias of [`crate::LegendColumn`]
+pub type LegendColumn = crate::LegendColumn;
+
+/// Type alias of [`crate::LegendHAlign`]
+pub type LegendHAlign = crate::LegendHAlign;
+
+/// Type alias of [`crate::LegendVAlign`]
+pub type LegendVAlign = crate::LegendVAlign;
+
 /// Type alias of [`crate::Switch`]
 pub type Switch = crate::Switch;
 
+/// Type alias of [`crate::Profile`]
+pub type Profile = crate::Profile;
+
 /// Type alias of [`crate::Key<f32>`]
 pub type Key = crate::Key<f32>;
 
+/// Type alias of [`crate::Rect<f32>`]
+pub type Rect = crate::Rect<f32>;
+
+/// Type alias of [`crate::ClipMode`]
+pub type ClipMode = crate::ClipMode;
+
 /// Type alias of [`crate::Background`]
 pub type Background = crate::Background;
 
@@ -23,3 +71,112 @@ pub type Keyboard = crate::Keyboard<f32>;
 
 /// Type alias of [`crate::KeyIterator<f32>`]
 pub type KeyIterator = crate::KeyIterator<f32>;
+
+/// Type alias of [`crate::LayoutDeserializer<f32>`]
+pub type LayoutDeserializer = crate::LayoutDeserializer<f32>;
+
+/// Type alias of [`crate::KeyLocation`]
+#[cfg(feature = "json")]
+pub type KeyLocation = crate::KeyLocation;
+
+/// Type alias of [`crate::ParseError`]
+#[cfg(feature = "json")]
+pub type ParseError = crate::ParseError;
+
+/// Type alias of [`crate::FileError`]
+#[cfg(all(feature = "json", feature = "std"))]
+pub type FileError = crate::FileError;
+
+/// Type alias of [`crate::StrictError`]
+#[cfg(feature = "json")]
+pub type StrictError = crate::StrictError;
+
+/// Type alias of [`crate::DiagnosticKind`]
+#[cfg(feature = "json")]
+pub type DiagnosticKind = crate::DiagnosticKind;
+
+/// Type alias of [`crate::CollectingDiagnostics`]
+#[cfg(feature = "json")]
+pub type CollectingDiagnostics = crate::CollectingDiagnostics;
+
+/// Type alias of [`crate::StreamingKeyIterator<R, f32>`]
+#[cfg(all(feature = "json", feature = "std"))]
+pub type StreamingKeyIterator<R> = crate::StreamingKeyIterator<R, f32>;
+
+/// Type alias of [`crate::KdTree<f32>`]
+#[cfg(feature = "kdtree")]
+pub type KdTree = crate::KdTree<f32>;
+
+/// Type alias of [`crate::KeyBuilder<f32>`]
+pub type KeyBuilder = crate::KeyBuilder<f32>;
+
+/// Type alias of [`crate::MetadataBuilder`]
+pub type MetadataBuilder = crate::MetadataBuilder;
+
+/// Type alias of [`crate::KeyboardBuilder<f32>`]
+pub type KeyboardBuilder = crate::KeyboardBuilder<f32>;
+
+/// Type alias of [`crate::BuilderError`]
+pub type BuilderError = crate::BuilderError;
+
+/// Type alias of [`crate::QmkOptions`]
+#[cfg(feature = "json")]
+pub type QmkOptions = crate::QmkOptions;
+
+/// Type alias of [`crate::SvgOptions`]
+pub type SvgOptions = crate::SvgOptions;
+
+/// Type alias of [`crate::ZmkOptions`]
+pub type ZmkOptions = crate::ZmkOptions;
+
+/// Type alias of [`crate::KeyboardDiff<f32>`]
+pub type KeyboardDiff = crate::KeyboardDiff<f32>;
+
+/// Type alias of [`crate::KeyGrid<f32>`]
+pub type KeyGrid = crate::KeyGrid<f32>;
+
+/// Type alias of [`crate::KeyGridIter<'a, f32>`]
+pub type KeyGridIter<'a> = crate::KeyGridIter<'a, f32>;
+
+/// Type alias of [`crate::MatrixCollisionError`]
+pub type MatrixCollisionError = crate::MatrixCollisionError;
+
+/// Type alias of [`crate::ValidationWarning`]
+pub type ValidationWarning = crate::ValidationWarning;
+
+/// Type alias of [`crate::AccessibilityIssue`]
+pub type AccessibilityIssue = crate::AccessibilityIssue;
+
+/// Type alias of [`crate::LayoutType`]
+pub type LayoutType = crate::LayoutType;
+
+/// Type alias of [`crate::KeyType`]
+pub type KeyType = crate::KeyType;
+
+/// Type alias of [`crate::CutoutShape`]
+pub type CutoutShape = crate::CutoutShape;
+
+/// Type alias of [`crate::ParseWarning`]
+#[cfg(feature = "json")]
+pub type ParseWarning = crate::ParseWarning;
+
+/// Type alias of [`crate::KeyParseWarning`]
+#[cfg(feature = "json")]
+pub type KeyParseWarning = crate::KeyParseWarning;
+
+/// Type alias of [`crate::KeyboardStatistics<f32>`]
+#[cfg(feature = "std")]
+pub type KeyboardStatistics = crate::KeyboardStatistics<f32>;
+
+/// Type alias of [`crate::FixedKeyboard<N, f32>`]
+#[cfg(feature = "no_alloc")]
+pub type FixedKeyboard<const N: usize> = crate::FixedKeyboard<N, f32>;
+
+/// Type alias of [`crate::FixedKeyboardDeserializer<'a, N, f32>`]
+#[cfg(feature = "no_alloc")]
+pub type FixedKeyboardDeserializer<'a, const N: usize> =
+    crate::FixedKeyboardDeserializer<'a, N, f32>;
+
+/// Type alias of [`crate::FixedCapacityError`]
+#[cfg(feature = "no_alloc")]
+pub type FixedCapacityError = crate::FixedCapacityError;
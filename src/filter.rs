@@ -0,0 +1,169 @@
+use num_traits::real::Real;
+
+use crate::{Color, Key, Keyboard};
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Returns an iterator over the keys with the given keycap profile, along with their index in
+    /// [`Keyboard::keys`]. The profile is compared case-insensitively.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard =
+    ///     serde_json::from_str(r#"[["A", {"p": "DSA"}, "B", {"p": "dsa"}, "C"]]"#).unwrap();
+    /// let indices: Vec<_> = keyboard.keys_with_profile("DSA").map(|(i, _)| i).collect();
+    ///
+    /// assert_eq!(indices, [1, 2]);
+    /// ```
+    pub fn keys_with_profile<'a>(
+        &'a self,
+        profile: &'a str,
+    ) -> impl Iterator<Item = (usize, &'a Key<T>)> {
+        self.enumerate_keys_where(move |key| key.profile.eq_ignore_ascii_case(profile))
+    }
+
+    /// Returns an iterator over the keys with the given switch brand, along with their index in
+    /// [`Keyboard::keys`]. The brand is compared case-insensitively.
+    ///
+    /// Note this only considers the key's own switch, not the layout's global switch. See
+    /// [`Keyboard::keys_with_switch`] to match on other switch fields.
+    pub fn keys_with_switch_brand<'a>(
+        &'a self,
+        brand: &'a str,
+    ) -> impl Iterator<Item = (usize, &'a Key<T>)> {
+        self.enumerate_keys_where(move |key| key.switch.brand.eq_ignore_ascii_case(brand))
+    }
+
+    /// Returns an iterator over the keys with the given colour, along with their index in
+    /// [`Keyboard::keys`].
+    pub fn keys_with_color(&self, color: Color) -> impl Iterator<Item = (usize, &Key<T>)> {
+        self.enumerate_keys_where(move |key| key.color == color)
+    }
+
+    /// Returns an iterator over the homing keys, along with their index in [`Keyboard::keys`].
+    pub fn homing_keys(&self) -> impl Iterator<Item = (usize, &Key<T>)> {
+        self.enumerate_keys_where(|key| key.homing)
+    }
+
+    /// Returns an iterator over the stepped keys, along with their index in [`Keyboard::keys`].
+    pub fn stepped_keys(&self) -> impl Iterator<Item = (usize, &Key<T>)> {
+        self.enumerate_keys_where(|key| key.stepped)
+    }
+
+    /// Returns an iterator over the ghosted keys, along with their index in [`Keyboard::keys`].
+    pub fn ghosted_keys(&self) -> impl Iterator<Item = (usize, &Key<T>)> {
+        self.enumerate_keys_where(|key| key.ghosted)
+    }
+
+    /// Returns an iterator over the decals, along with their index in [`Keyboard::keys`].
+    pub fn decal_keys(&self) -> impl Iterator<Item = (usize, &Key<T>)> {
+        self.enumerate_keys_where(|key| key.decal)
+    }
+
+    fn enumerate_keys_where<F>(&self, f: F) -> impl Iterator<Item = (usize, &Key<T>)>
+    where
+        F: Fn(&Key<T>) -> bool,
+    {
+        (self.keys.iter().enumerate()).filter(move |&(_, key)| f(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::*;
+    use crate::Switch;
+
+    fn keyboard() -> Keyboard<f64> {
+        let red = Color::new(255, 0, 0, 255);
+
+        Keyboard {
+            keys: vec![
+                Key {
+                    profile: "DSA".into(),
+                    color: red,
+                    homing: true,
+                    ..Key::default()
+                },
+                Key {
+                    profile: "dsa".into(),
+                    switch: Switch {
+                        brand: "Cherry".into(),
+                        ..Switch::default()
+                    },
+                    stepped: true,
+                    ..Key::default()
+                },
+                Key {
+                    profile: "SA R1".into(),
+                    switch: Switch {
+                        brand: "cherry".into(),
+                        ..Switch::default()
+                    },
+                    color: red,
+                    ghosted: true,
+                    ..Key::default()
+                },
+                Key {
+                    decal: true,
+                    homing: true,
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        }
+    }
+
+    fn indices<'a>(keys: impl Iterator<Item = (usize, &'a Key<f64>)>) -> Vec<usize> {
+        keys.map(|(i, _)| i).collect()
+    }
+
+    #[test]
+    fn test_keyboard_keys_with_profile() {
+        let keyboard = keyboard();
+
+        assert_eq!(indices(keyboard.keys_with_profile("DSA")), [0, 1]);
+        assert_eq!(indices(keyboard.keys_with_profile("sa r1")), [2]);
+        assert_eq!(indices(keyboard.keys_with_profile("")), [3]);
+        assert!(indices(keyboard.keys_with_profile("OEM")).is_empty());
+    }
+
+    #[test]
+    fn test_keyboard_keys_with_switch_brand() {
+        let keyboard = keyboard();
+
+        assert_eq!(indices(keyboard.keys_with_switch_brand("CHERRY")), [1, 2]);
+        assert!(indices(keyboard.keys_with_switch_brand("gateron")).is_empty());
+    }
+
+    #[test]
+    fn test_keyboard_keys_with_color() {
+        let keyboard = keyboard();
+
+        let (i, key) = keyboard
+            .keys_with_color(Color::new(255, 0, 0, 255))
+            .nth(1)
+            .unwrap();
+        assert_eq!(i, 2);
+        assert!(core::ptr::eq(key, &keyboard.keys[2]));
+        assert_eq!(
+            indices(keyboard.keys_with_color(Key::<f64>::default().color)),
+            [1, 3]
+        );
+    }
+
+    #[test]
+    fn test_keyboard_flag_filters() {
+        let keyboard = keyboard();
+
+        assert_eq!(indices(keyboard.homing_keys()), [0, 3]);
+        assert_eq!(indices(keyboard.stepped_keys()), [1]);
+        assert_eq!(indices(keyboard.ghosted_keys()), [2]);
+        assert_eq!(indices(keyboard.decal_keys()), [3]);
+        assert!(indices(Keyboard::<f64>::default().homing_keys()).is_empty());
+    }
+}
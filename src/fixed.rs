@@ -0,0 +1,546 @@
+use core::{fmt, ops::Deref};
+
+use num_traits::real::Real;
+use serde::de::{
+    value::MapAccessDeserializer, DeserializeSeed, Error, MapAccess, SeqAccess, Unexpected, Visitor,
+};
+use serde::{Deserialize, Deserializer};
+
+use crate::de::{KleLegendsOrProps, KleMetadata, KleProps};
+use crate::{Key, Keyboard, Metadata};
+
+/// An error returned when a layout has more keys than a [`FixedKeyboard`] can hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedCapacityError {
+    /// The maximum number of keys the [`FixedKeyboard`] can hold.
+    pub capacity: usize,
+    /// The number of keys in the layout.
+    pub len: usize,
+}
+
+impl fmt::Display for FixedCapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "layout has {} keys, but the capacity is {}",
+            self.len, self.capacity
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FixedCapacityError {}
+
+/// A keyboard layout which stores up to `N` keys in a fixed-size array rather than a [`Vec`].
+///
+/// This has a fixed memory footprint, which is useful on embedded targets where the maximum size
+/// of a layout is known ahead of time. Note that the keys themselves still allocate for their
+/// legends, profile, and switch, so an allocator is still required.
+///
+/// A `FixedKeyboard` dereferences to a slice of its keys. Deserialising a `FixedKeyboard` builds
+/// each key directly into the array as the layout is read, see [`FixedKeyboardDeserializer`].
+///
+/// ```
+/// use kle_serial::FixedKeyboard;
+///
+/// let keyboard: FixedKeyboard<4> = serde_json::from_str(r#"[["A", "B", "C"]]"#).unwrap();
+/// assert_eq!(keyboard.len(), 3);
+///
+/// let result = serde_json::from_str::<FixedKeyboard<2>>(r#"[["A", "B", "C"]]"#);
+/// assert!(result.is_err());
+/// ```
+///
+/// [`Vec`]: alloc::vec::Vec
+#[derive(Clone)]
+pub struct FixedKeyboard<const N: usize, T = f64>
+where
+    T: Real,
+{
+    /// Keyboard layout's metadata.
+    pub metadata: Metadata,
+    keys: [Key<T>; N],
+    len: usize,
+}
+
+impl<const N: usize, T> FixedKeyboard<N, T>
+where
+    T: Real,
+{
+    /// Creates a new layout with no keys and the default metadata.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            metadata: Metadata::default(),
+            keys: core::array::from_fn(|_| Key::default()),
+            len: 0,
+        }
+    }
+
+    /// Returns the maximum number of keys the layout can hold.
+    #[inline]
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Appends a key to the layout.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`FixedCapacityError`] if the layout is already full, in which case the key is
+    /// not added.
+    pub fn try_push(&mut self, key: Key<T>) -> Result<(), FixedCapacityError> {
+        let slot = self.keys.get_mut(self.len).ok_or(FixedCapacityError {
+            capacity: N,
+            len: self.len + 1,
+        })?;
+        *slot = key;
+        self.len += 1;
+        Ok(())
+    }
+}
+
+impl<const N: usize, T> Default for FixedKeyboard<N, T>
+where
+    T: Real,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, T> fmt::Debug for FixedKeyboard<N, T>
+where
+    T: Real + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FixedKeyboard")
+            .field("metadata", &self.metadata)
+            .field("keys", &self.as_ref())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<const N: usize, T> PartialEq for FixedKeyboard<N, T>
+where
+    T: Real,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.metadata == other.metadata && self.as_ref() == other.as_ref()
+    }
+}
+
+impl<const N: usize, T> Deref for FixedKeyboard<N, T>
+where
+    T: Real,
+{
+    type Target = [Key<T>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.keys[..self.len]
+    }
+}
+
+impl<const N: usize, T> AsRef<[Key<T>]> for FixedKeyboard<N, T>
+where
+    T: Real,
+{
+    fn as_ref(&self) -> &[Key<T>] {
+        self
+    }
+}
+
+impl<const N: usize, T> TryFrom<Keyboard<T>> for FixedKeyboard<N, T>
+where
+    T: Real,
+{
+    type Error = FixedCapacityError;
+
+    fn try_from(keyboard: Keyboard<T>) -> Result<Self, Self::Error> {
+        // Check the length up front so we don't move any keys for a layout that doesn't fit
+        if keyboard.keys.len() > N {
+            return Err(FixedCapacityError {
+                capacity: N,
+                len: keyboard.keys.len(),
+            });
+        }
+
+        let mut result = Self {
+            metadata: keyboard.metadata,
+            ..Self::new()
+        };
+        for key in keyboard.keys {
+            result.try_push(key)?;
+        }
+        Ok(result)
+    }
+}
+
+impl<const N: usize, T> From<FixedKeyboard<N, T>> for Keyboard<T>
+where
+    T: Real,
+{
+    fn from(keyboard: FixedKeyboard<N, T>) -> Self {
+        let len = keyboard.len;
+        Self {
+            metadata: keyboard.metadata,
+            keys: keyboard.keys.into_iter().take(len).collect(),
+        }
+    }
+}
+
+impl<'de, const N: usize, T> Deserialize<'de> for FixedKeyboard<N, T>
+where
+    T: Real + Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut result = Self::new();
+        FixedKeyboardDeserializer::new(&mut result).deserialize(deserializer)?;
+        Ok(result)
+    }
+}
+
+/// Deserialises a KLE layout directly into an existing [`FixedKeyboard`].
+///
+/// Unlike deserialising a [`Keyboard`], the layout's rows are not collected before building the
+/// keys. Each key is built and written into the [`FixedKeyboard`]'s array as soon as its legends
+/// are read, so the only storage used for keys is the array itself.
+///
+/// This implements [`DeserializeSeed`], so can be used with any self-describing [`Deserializer`].
+/// Any existing keys and metadata in the [`FixedKeyboard`] are replaced. If the layout has more
+/// keys than the [`FixedKeyboard`] can hold, the rest of the layout is still read to count its
+/// keys and an error containing a [`FixedCapacityError`] is returned.
+///
+/// ```
+/// use kle_serial::{FixedKeyboard, FixedKeyboardDeserializer};
+/// use serde::de::DeserializeSeed;
+///
+/// let mut keyboard = FixedKeyboard::<4>::new();
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"[["A", "B"], ["C"]]"#);
+/// FixedKeyboardDeserializer::new(&mut keyboard).deserialize(&mut deserializer).unwrap();
+/// assert_eq!(keyboard.len(), 3);
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"[["A", "B", "C", "D", "E"]]"#);
+/// let result = FixedKeyboardDeserializer::new(&mut keyboard).deserialize(&mut deserializer);
+/// assert!(result.is_err());
+/// ```
+///
+/// If an error is returned, the contents of the [`FixedKeyboard`] are unspecified.
+#[derive(Debug)]
+pub struct FixedKeyboardDeserializer<'a, const N: usize, T = f64>
+where
+    T: Real,
+{
+    keyboard: &'a mut FixedKeyboard<N, T>,
+}
+
+impl<'a, const N: usize, T> FixedKeyboardDeserializer<'a, N, T>
+where
+    T: Real,
+{
+    /// Creates a new deserialiser which writes into the given [`FixedKeyboard`].
+    #[must_use]
+    pub fn new(keyboard: &'a mut FixedKeyboard<N, T>) -> Self {
+        Self { keyboard }
+    }
+}
+
+impl<'de, const N: usize, T> DeserializeSeed<'de> for FixedKeyboardDeserializer<'_, N, T>
+where
+    T: Real + Deserialize<'de>,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.keyboard.metadata = Metadata::default();
+        self.keyboard.len = 0;
+
+        deserializer.deserialize_seq(FixedLayoutVisitor {
+            keyboard: self.keyboard,
+            state: KleProps::default(),
+            len: 0,
+            rows: 0,
+        })
+    }
+}
+
+// Holds the state while reading a layout. `len` counts every key in the layout, including any
+// which did not fit in the keyboard, and `rows` counts the rows read so far
+struct FixedLayoutVisitor<'a, const N: usize, T>
+where
+    T: Real,
+{
+    keyboard: &'a mut FixedKeyboard<N, T>,
+    state: KleProps<T>,
+    len: usize,
+    rows: usize,
+}
+
+impl<'de, const N: usize, T> Visitor<'de> for FixedLayoutVisitor<'_, N, T>
+where
+    T: Real + Deserialize<'de>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence")
+    }
+
+    fn visit_seq<A>(mut self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        // The first item is either the metadata object or the first row
+        if seq
+            .next_element_seed(FixedRowSeed {
+                visitor: &mut self,
+                first: true,
+            })?
+            .is_none()
+        {
+            return Ok(());
+        }
+        while (seq.next_element_seed(FixedRowSeed {
+            visitor: &mut self,
+            first: false,
+        })?)
+        .is_some()
+        {}
+
+        if self.len > N {
+            return Err(A::Error::custom(FixedCapacityError {
+                capacity: N,
+                len: self.len,
+            }));
+        }
+        Ok(())
+    }
+}
+
+// Reads a single row of the layout, or the metadata object if `first` is set
+struct FixedRowSeed<'a, 'b, const N: usize, T>
+where
+    T: Real,
+{
+    visitor: &'b mut FixedLayoutVisitor<'a, N, T>,
+    first: bool,
+}
+
+impl<'de, const N: usize, T> DeserializeSeed<'de> for FixedRowSeed<'_, '_, N, T>
+where
+    T: Real + Deserialize<'de>,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if self.first {
+            deserializer.deserialize_any(self)
+        } else {
+            deserializer.deserialize_seq(self)
+        }
+    }
+}
+
+impl<'de, const N: usize, T> Visitor<'de> for FixedRowSeed<'_, '_, N, T>
+where
+    T: Real + Deserialize<'de>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if self.first {
+            formatter.write_str("a sequence or map")
+        } else {
+            formatter.write_str("a sequence")
+        }
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let FixedLayoutVisitor {
+            keyboard,
+            state,
+            len,
+            rows,
+        } = self.visitor;
+
+        // The first row starts at the origin, every other row starts on a new line
+        if *rows > 0 {
+            state.next_line();
+        }
+        *rows += 1;
+
+        while let Some(item) = seq.next_element()? {
+            match item {
+                KleLegendsOrProps::Props(props) => state.update(*props),
+                KleLegendsOrProps::Legend(legends) => {
+                    // Keep counting keys once the keyboard is full, but don't build them
+                    if *len < N {
+                        keyboard
+                            .try_push(state.build_key(&legends))
+                            .map_err(A::Error::custom)?;
+                    }
+                    state.next_key();
+                    *len += 1;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        if !self.first {
+            return Err(A::Error::invalid_type(Unexpected::Map, &self));
+        }
+
+        let meta = KleMetadata::deserialize(MapAccessDeserializer::new(map))?;
+        self.visitor.keyboard.metadata = meta.into();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isclose::assert_is_close;
+
+    use super::*;
+
+    const LAYOUT: &str = r#"[
+        {"name": "test"},
+        ["1", "2", "3", "4", "5"],
+        ["Q", "W", "E", "R", "T"]
+    ]"#;
+
+    #[test]
+    fn test_fixed_capacity_error_display() {
+        let error = FixedCapacityError {
+            capacity: 5,
+            len: 10,
+        };
+        assert_eq!(
+            format!("{error}"),
+            "layout has 10 keys, but the capacity is 5"
+        );
+    }
+
+    #[test]
+    fn test_fixed_keyboard_deserialize() {
+        let keyboard: FixedKeyboard<10> = serde_json::from_str(LAYOUT).unwrap();
+        assert_eq!(keyboard.metadata.name, "test");
+        assert_eq!(keyboard.len(), 10);
+        assert_eq!(keyboard.capacity(), 10);
+        assert_is_close!(keyboard[9].x, 4.0);
+        assert_is_close!(keyboard[9].y, 1.0);
+
+        let keyboard: FixedKeyboard<16, f32> = serde_json::from_str(LAYOUT).unwrap();
+        assert_eq!(keyboard.as_ref().len(), 10);
+        assert_eq!(
+            Keyboard::from(keyboard),
+            serde_json::from_str::<Keyboard<f32>>(LAYOUT).unwrap()
+        );
+
+        let result = serde_json::from_str::<FixedKeyboard<5, f64>>(LAYOUT);
+        let error = result.unwrap_err();
+        assert!(format!("{error}").contains("layout has 10 keys, but the capacity is 5"));
+    }
+
+    #[test]
+    fn test_fixed_keyboard_deserializer() {
+        let layout = r##"[
+            [{"c": "#ff0000", "w": 1.5}, "Tab", "Q"],
+            [],
+            [{"r": 15, "rx": 1, "y": -0.5}, "A", {"a": 7}, "S"]
+        ]"##;
+        let mut keyboard =
+            FixedKeyboard::<10, f64>::try_from(serde_json::from_str::<Keyboard>(LAYOUT).unwrap())
+                .unwrap();
+
+        let mut deserializer = serde_json::Deserializer::from_str(layout);
+        FixedKeyboardDeserializer::new(&mut keyboard)
+            .deserialize(&mut deserializer)
+            .unwrap();
+        assert_eq!(keyboard.metadata, Metadata::default());
+        assert_eq!(
+            Keyboard::from(keyboard.clone()),
+            serde_json::from_str::<Keyboard>(layout).unwrap()
+        );
+
+        let mut deserializer = serde_json::Deserializer::from_str("[]");
+        FixedKeyboardDeserializer::new(&mut keyboard)
+            .deserialize(&mut deserializer)
+            .unwrap();
+        assert!(keyboard.is_empty());
+
+        let mut deserializer = serde_json::Deserializer::from_str(r#"[["A"], {"name": "test"}]"#);
+        let result = FixedKeyboardDeserializer::new(&mut keyboard).deserialize(&mut deserializer);
+        assert!(result.is_err());
+
+        let mut deserializer = serde_json::Deserializer::from_str(LAYOUT);
+        let error = FixedKeyboardDeserializer::new(&mut FixedKeyboard::<5, f64>::new())
+            .deserialize(&mut deserializer)
+            .unwrap_err();
+        assert!(format!("{error}").contains("layout has 10 keys, but the capacity is 5"));
+    }
+
+    #[test]
+    fn test_fixed_keyboard_try_push() {
+        let mut keyboard = FixedKeyboard::<2, f64>::default();
+        assert!(keyboard.is_empty());
+
+        keyboard.try_push(Key::default()).unwrap();
+        keyboard
+            .try_push(Key {
+                x: 1.0,
+                ..Key::default()
+            })
+            .unwrap();
+        assert_eq!(keyboard.len(), 2);
+
+        let error = keyboard.try_push(Key::default()).unwrap_err();
+        assert_eq!(
+            error,
+            FixedCapacityError {
+                capacity: 2,
+                len: 3
+            }
+        );
+        assert_eq!(keyboard.len(), 2);
+        assert_is_close!(keyboard[1].x, 1.0);
+    }
+
+    #[test]
+    fn test_fixed_keyboard_try_from() {
+        let keyboard: Keyboard = serde_json::from_str(LAYOUT).unwrap();
+
+        let fixed = FixedKeyboard::<10>::try_from(keyboard.clone()).unwrap();
+        assert_eq!(&*fixed, keyboard.keys.as_slice());
+        assert_eq!(fixed.metadata, keyboard.metadata);
+        assert_eq!(Keyboard::from(fixed.clone()), keyboard);
+        assert_eq!(fixed, FixedKeyboard::try_from(keyboard.clone()).unwrap());
+
+        let error = FixedKeyboard::<5>::try_from(keyboard).unwrap_err();
+        assert_eq!(
+            error,
+            FixedCapacityError {
+                capacity: 5,
+                len: 10
+            }
+        );
+    }
+}
@@ -0,0 +1,1635 @@
+use alloc::{vec, vec::Vec};
+use core::cmp::Ordering;
+
+use num_traits::{real::Real, NumCast};
+
+use crate::{Key, Keyboard, Rect, UNIT_IN, UNIT_MM};
+
+// Tolerance used when comparing key dimensions against known shapes and when checking overlaps
+const SHAPE_EPSILON: f64 = 1e-6;
+
+pub(crate) fn shape_epsilon<T: Real>() -> T {
+    NumCast::from(SHAPE_EPSILON).unwrap_or_else(T::epsilon)
+}
+
+pub(crate) fn approx_eq<T: Real>(a: T, b: f64) -> bool {
+    NumCast::from(b).map_or(false, |b: T| (a - b).abs() <= shape_epsilon())
+}
+
+// Tests whether two convex quadrilaterals overlap using the separating axis theorem. Shapes which
+// only touch along an edge are not considered to overlap
+pub(crate) fn quads_overlap<T: Real>(a: &[(T, T); 4], b: &[(T, T); 4]) -> bool {
+    let project = |quad: &[(T, T); 4], (ax, ay): (T, T)| {
+        quad.iter()
+            .fold((T::max_value(), T::min_value()), |(min, max), &(x, y)| {
+                let proj = x * ax + y * ay;
+                (min.min(proj), max.max(proj))
+            })
+    };
+
+    let eps = shape_epsilon();
+    let edges = |quad: &[(T, T); 4]| {
+        let quad = *quad;
+        (0..4).map(move |i| {
+            let ((x0, y0), (x1, y1)) = (quad[i], quad[(i + 1) % 4]);
+            (x1 - x0, y1 - y0)
+        })
+    };
+
+    edges(a)
+        .chain(edges(b))
+        .map(|(dx, dy)| (-dy, dx)) // The axis is the normal of the edge
+        .filter(|&(ax, ay)| ax != T::zero() || ay != T::zero())
+        .all(|axis| {
+            let len = axis.0.hypot(axis.1);
+            let axis = (axis.0 / len, axis.1 / len);
+            let ((a_min, a_max), (b_min, b_max)) = (project(a, axis), project(b, axis));
+            a_max > b_min + eps && b_max > a_min + eps
+        })
+}
+
+/// Controls which keys are kept by [`Keyboard::clip_to_rect_with_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ClipMode {
+    /// Keep keys which lie entirely within the clip rectangle.
+    Contains,
+    /// Keep keys which lie at least partially within the clip rectangle.
+    #[default]
+    Intersects,
+}
+
+impl<T> Rect<T>
+where
+    T: Real,
+{
+    fn from_points(points: impl IntoIterator<Item = (T, T)>) -> Option<Self> {
+        let ((x_min, y_min), (x_max, y_max)) =
+            points.into_iter().fold(None, |bounds, (x, y)| {
+                Some(match bounds {
+                    None => ((x, y), (x, y)),
+                    Some(((x_min, y_min), (x_max, y_max))) => {
+                        ((x.min(x_min), y.min(y_min)), (x.max(x_max), y.max(y_max)))
+                    }
+                })
+            })?;
+
+        Some(Self {
+            x: x_min,
+            y: y_min,
+            width: x_max - x_min,
+            height: y_max - y_min,
+        })
+    }
+
+    fn shrink(&self, margin: T) -> Self {
+        let two = T::one() + T::one();
+        Self {
+            x: self.x + margin,
+            y: self.y + margin,
+            width: self.width - two * margin,
+            height: self.height - two * margin,
+        }
+    }
+
+    fn contains(&self, (x, y): (T, T)) -> bool {
+        x >= self.x && x <= self.x + self.width && y >= self.y && y <= self.y + self.height
+    }
+
+    fn corners(&self) -> [(T, T); 4] {
+        let (x0, y0) = (self.x, self.y);
+        let (x1, y1) = (self.x + self.width, self.y + self.height);
+        [(x0, y0), (x1, y0), (x1, y1), (x0, y1)]
+    }
+
+    fn contains_rect(&self, other: &Self) -> bool {
+        let (x0, y0) = (other.x, other.y);
+        let (x1, y1) = (other.x + other.width, other.y + other.height);
+        self.contains((x0, y0)) && self.contains((x1, y1))
+    }
+
+    /// Returns the outline of the union of two rectangles in clockwise order starting from the top
+    /// left corner.
+    pub(crate) fn union_outline(&self, other: &Self) -> Vec<(T, T)> {
+        if self.contains_rect(other) {
+            return self.corners().to_vec();
+        } else if other.contains_rect(self) {
+            return other.corners().to_vec();
+        }
+
+        let sorted = |mut values: Vec<T>| {
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+            values.dedup();
+            values
+        };
+        let xs = sorted(vec![
+            self.x,
+            self.x + self.width,
+            other.x,
+            other.x + other.width,
+        ]);
+        let ys = sorted(vec![
+            self.y,
+            self.y + self.height,
+            other.y,
+            other.y + other.height,
+        ]);
+
+        // Split the area into a grid of cells and check which ones are covered by either rectangle
+        let two = T::one() + T::one();
+        let filled = |i: usize, j: usize| {
+            i + 1 < xs.len()
+                && j + 1 < ys.len()
+                && [self, other].iter().any(|rect| {
+                    rect.contains(((xs[i] + xs[i + 1]) / two, (ys[j] + ys[j + 1]) / two))
+                })
+        };
+
+        // Find the clockwise edges of all filled cells which border an empty cell
+        let mut edges = Vec::new();
+        for j in 0..ys.len() {
+            for i in 0..xs.len() {
+                if !filled(i, j) {
+                    continue;
+                }
+                if j == 0 || !filled(i, j - 1) {
+                    edges.push(((i, j), (i + 1, j)));
+                }
+                if !filled(i + 1, j) {
+                    edges.push(((i + 1, j), (i + 1, j + 1)));
+                }
+                if !filled(i, j + 1) {
+                    edges.push(((i + 1, j + 1), (i, j + 1)));
+                }
+                if i == 0 || !filled(i - 1, j) {
+                    edges.push(((i, j + 1), (i, j)));
+                }
+            }
+        }
+
+        // Chain the edges together into a single loop. The first edge is the top edge of the top
+        // left cell, so we start at the top left corner
+        let start = match edges.first() {
+            Some(&(start, _)) => start,
+            None => return self.corners().to_vec(),
+        };
+        let mut vertices = vec![start];
+        let mut current = start;
+        while let Some(&(_, end)) = edges.iter().find(|&&(from, _)| from == current) {
+            if end == start || vertices.len() > edges.len() {
+                break;
+            }
+            vertices.push(end);
+            current = end;
+        }
+
+        // The rectangles are disjoint or only touch at a corner, so there is no single outline
+        if vertices.len() != edges.len() {
+            return self.corners().into_iter().chain(other.corners()).collect();
+        }
+
+        // Remove any vertices in the middle of a straight edge
+        let len = vertices.len();
+        (0..len)
+            .filter(|&k| {
+                let (prev, curr, next) = (
+                    vertices[(k + len - 1) % len],
+                    vertices[k],
+                    vertices[(k + 1) % len],
+                );
+                !((prev.0 == curr.0 && curr.0 == next.0) || (prev.1 == curr.1 && curr.1 == next.1))
+            })
+            .map(|k| (xs[vertices[k].0], ys[vertices[k].1]))
+            .collect()
+    }
+}
+
+impl<T> Key<T>
+where
+    T: Real,
+{
+    pub(crate) fn primary_rect(&self) -> Rect<T> {
+        Rect {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    pub(crate) fn secondary_rect(&self) -> Rect<T> {
+        Rect {
+            x: self.x + self.x2,
+            y: self.y + self.y2,
+            width: self.width2,
+            height: self.height2,
+        }
+    }
+
+    fn unrotated_rect(&self) -> Rect<T> {
+        let primary = self.primary_rect();
+        let points = primary
+            .corners()
+            .into_iter()
+            .chain(self.secondary_rect().corners());
+
+        Rect::from_points(points).unwrap_or(primary) // We always have 8 points so this never fails
+    }
+
+    /// Returns the 2×2 matrix for the key's rotation.
+    ///
+    /// Positive rotation is clockwise since KLE's Y axis points downwards. Multiplying a point's
+    /// offset from the centre of rotation `(rx, ry)` by this matrix gives the offset of the rotated
+    /// point; [`Key::transform_point`] does this for you.
+    #[must_use]
+    pub fn rotation_matrix(&self) -> [[T; 2]; 2] {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        [[cos, -sin], [sin, cos]]
+    }
+
+    /// Applies the key's rotation around `(rx, ry)` to the point `(px, py)`, converting it from
+    /// the key's unrotated coordinates to layout coordinates.
+    #[must_use]
+    pub fn transform_point(&self, px: T, py: T) -> (T, T) {
+        let [[a, b], [c, d]] = self.rotation_matrix();
+        let (dx, dy) = (px - self.rx, py - self.ry);
+        (self.rx + a * dx + b * dy, self.ry + c * dx + d * dy)
+    }
+
+    /// Applies the inverse of the key's rotation around `(rx, ry)` to the point `(px, py)`,
+    /// converting it from layout coordinates to the key's unrotated coordinates.
+    ///
+    /// This is the inverse of [`Key::transform_point`].
+    #[must_use]
+    pub fn inverse_transform_point(&self, px: T, py: T) -> (T, T) {
+        // The inverse of a rotation matrix is its transpose
+        let [[a, b], [c, d]] = self.rotation_matrix();
+        let (dx, dy) = (px - self.rx, py - self.ry);
+        (self.rx + a * dx + c * dy, self.ry + b * dx + d * dy)
+    }
+
+    /// Returns the X position of the true left edge of the key.
+    ///
+    /// [`Key::x`] is the left edge of the key's primary rectangle, which is not the left edge of
+    /// the key when [`Key::x2`] is negative, such as for an ISO enter. This does not take into
+    /// account the key's rotation; use [`Key::bounding_box`] for that.
+    #[must_use]
+    pub fn true_x(&self) -> T {
+        self.unrotated_rect().x
+    }
+
+    /// Returns the Y position of the true top edge of the key.
+    ///
+    /// [`Key::y`] is the top edge of the key's primary rectangle, which is not the top edge of the
+    /// key when [`Key::y2`] is negative. This does not take into account the key's rotation; use
+    /// [`Key::bounding_box`] for that.
+    #[must_use]
+    pub fn true_y(&self) -> T {
+        self.unrotated_rect().y
+    }
+
+    /// Returns the position of the true top left corner of the key.
+    ///
+    /// Equivalent to `(key.true_x(), key.true_y())`.
+    #[must_use]
+    pub fn true_origin(&self) -> (T, T) {
+        let rect = self.unrotated_rect();
+        (rect.x, rect.y)
+    }
+
+    /// Returns the total width of the key including both its primary and secondary rectangles.
+    ///
+    /// This does not take into account the key's rotation; use [`Key::bounding_box`] for that.
+    #[must_use]
+    pub fn true_width(&self) -> T {
+        self.unrotated_rect().width
+    }
+
+    /// Returns the total height of the key including both its primary and secondary rectangles.
+    ///
+    /// This does not take into account the key's rotation; use [`Key::bounding_box`] for that.
+    #[must_use]
+    pub fn true_height(&self) -> T {
+        self.unrotated_rect().height
+    }
+
+    /// Returns whether the key is a regular rectangular key.
+    ///
+    /// This is the case when the key's secondary rectangle is identical to its primary rectangle,
+    /// i.e. `x2 = 0`, `y2 = 0`, `w2 = w`, and `h2 = h` in KLE, and the key is not stepped.
+    #[must_use]
+    pub fn is_regular(&self) -> bool {
+        approx_eq(self.x2, 0.0)
+            && approx_eq(self.y2, 0.0)
+            && approx_eq(self.width2 - self.width, 0.0)
+            && approx_eq(self.height2 - self.height, 0.0)
+            && !self.stepped
+    }
+
+    /// Returns whether the key is shaped like an ISO enter.
+    ///
+    /// This is a heuristic which checks for the values KLE uses for its ISO enter preset:
+    /// `w = 1.25`, `h = 2`, `x2 = -0.25`, `y2 = 0`, `w2 = 1.5`, and `h2 = 1`.
+    #[must_use]
+    pub fn is_iso_enter(&self) -> bool {
+        approx_eq(self.width, 1.25)
+            && approx_eq(self.height, 2.0)
+            && approx_eq(self.x2, -0.25)
+            && approx_eq(self.y2, 0.0)
+            && approx_eq(self.width2, 1.5)
+            && approx_eq(self.height2, 1.0)
+    }
+
+    /// Returns whether the key is shaped like a stepped caps lock.
+    ///
+    /// This is a heuristic which checks for a stepped key (`l = true` in KLE) with `w = 1.75`, as
+    /// used by KLE's stepped caps lock preset. KLE's preset additionally uses `w2 = 1.25`, but this
+    /// is not checked.
+    #[must_use]
+    pub fn is_stepped_caps_lock(&self) -> bool {
+        self.stepped && approx_eq(self.width, 1.75)
+    }
+
+    /// Returns the area of the key's primary rectangle in square keyboard units.
+    #[must_use]
+    pub fn area(&self) -> T {
+        self.width * self.height
+    }
+
+    /// Returns the area of the key's secondary rectangle in square keyboard units.
+    ///
+    /// For regular keys the secondary rectangle is the same as the primary rectangle, so this is
+    /// the same as [`Key::area`].
+    #[must_use]
+    pub fn secondary_area(&self) -> T {
+        self.width2 * self.height2
+    }
+
+    /// Returns the visual area of the key in square keyboard units.
+    ///
+    /// This is the area of the union of the key's primary and secondary rectangles, so for an ISO
+    /// enter this is the area of the L-shaped key.
+    #[must_use]
+    pub fn visual_area(&self) -> T {
+        let (primary, secondary) = (self.primary_rect(), self.secondary_rect());
+
+        let overlap = |a0: T, a1: T, b0: T, b1: T| (a1.min(b1) - a0.max(b0)).max(T::zero());
+        let intersection = overlap(
+            primary.x,
+            primary.x + primary.width,
+            secondary.x,
+            secondary.x + secondary.width,
+        ) * overlap(
+            primary.y,
+            primary.y + primary.height,
+            secondary.y,
+            secondary.y + secondary.height,
+        );
+
+        self.area() + self.secondary_area() - intersection
+    }
+
+    /// Returns the four corners of the key after applying its rotation.
+    ///
+    /// The corners are returned in the order top left, top right, bottom right, bottom left
+    /// relative to the unrotated key. This does not include the secondary rectangle used by stepped
+    /// and L-shaped keys; use [`Key::all_corners`] to include it.
+    #[must_use]
+    pub fn corners(&self) -> [(T, T); 4] {
+        self.primary_rect()
+            .corners()
+            .map(|(x, y)| self.transform_point(x, y))
+    }
+
+    /// Returns the centre of the key's primary rectangle after applying its rotation.
+    ///
+    /// This does not take into account the secondary rectangle used by stepped and L-shaped keys.
+    #[must_use]
+    pub fn center_point(&self) -> (T, T) {
+        let two = T::one() + T::one();
+        let center = (self.x + self.width / two, self.y + self.height / two);
+        self.transform_point(center.0, center.1)
+    }
+
+    /// Returns the vertices of the outline of the key after applying its rotation.
+    ///
+    /// For stepped and L-shaped keys this is the outline of the union of the key's primary and
+    /// secondary rectangles, which has up to 8 vertices. The vertices are returned in clockwise order
+    /// starting from the top left corner of the unrotated key.
+    #[must_use]
+    pub fn all_corners(&self) -> Vec<(T, T)> {
+        self.primary_rect()
+            .union_outline(&self.secondary_rect())
+            .into_iter()
+            .map(|(x, y)| self.transform_point(x, y))
+            .collect()
+    }
+
+    /// Returns the smallest axis-aligned rectangle enclosing the key.
+    ///
+    /// This takes into account both the key's rotation and the secondary rectangle used by stepped
+    /// and L-shaped keys.
+    #[must_use]
+    pub fn bounding_box(&self) -> Rect<T> {
+        let primary = self.primary_rect();
+        let points = primary
+            .corners()
+            .into_iter()
+            .chain(self.secondary_rect().corners())
+            .map(|(x, y)| self.transform_point(x, y));
+
+        Rect::from_points(points).unwrap_or(primary) // We always have 8 points so this never fails
+    }
+
+    /// Returns whether the point `(x, y)` lies within the key.
+    ///
+    /// This takes into account both the key's rotation and the secondary rectangle used by stepped
+    /// and L-shaped keys. Points lying exactly on the edge of the key are considered to be inside.
+    #[must_use]
+    pub fn contains_point(&self, x: T, y: T) -> bool {
+        self.contains_point_with_margin(x, y, T::zero())
+    }
+
+    /// Returns whether the point `(x, y)` lies within the key, after shrinking the key by `margin`
+    /// on all sides.
+    ///
+    /// A negative `margin` can be used to grow the key instead, for example to give touch targets
+    /// a larger area.
+    #[must_use]
+    pub fn contains_point_with_margin(&self, x: T, y: T, margin: T) -> bool {
+        // Rotate the point into the key's unrotated coordinate system
+        let point = self.inverse_transform_point(x, y);
+
+        [self.primary_rect(), self.secondary_rect()]
+            .iter()
+            .any(|rect| rect.shrink(margin).contains(point))
+    }
+
+    pub(crate) fn quads(&self) -> [[(T, T); 4]; 2] {
+        [self.primary_rect(), self.secondary_rect()]
+            .map(|rect| rect.corners().map(|(x, y)| self.transform_point(x, y)))
+    }
+
+    /// Returns whether the key overlaps with the axis-aligned rectangle with top left corner
+    /// `(x, y)` and size `w` × `h`.
+    ///
+    /// This takes into account both the key's rotation and the secondary rectangle used by stepped
+    /// and L-shaped keys. Keys which only touch the rectangle along an edge are not considered to
+    /// intersect it.
+    #[must_use]
+    pub fn intersects_rect(&self, x: T, y: T, w: T, h: T) -> bool {
+        let rect = Rect {
+            x,
+            y,
+            width: w,
+            height: h,
+        }
+        .corners();
+
+        self.quads().iter().any(|quad| quads_overlap(quad, &rect))
+    }
+
+    fn within_rect(&self, rect: &Rect<T>) -> bool {
+        let rect = rect.shrink(-shape_epsilon::<T>());
+        self.quads()
+            .iter()
+            .flatten()
+            .all(|&point| rect.contains(point))
+    }
+
+    /// Returns whether the key overlaps with `other`.
+    ///
+    /// This takes into account both keys' rotation and the secondary rectangle used by stepped and
+    /// L-shaped keys. Keys which only touch along an edge are not considered to overlap.
+    #[must_use]
+    pub fn overlaps_with(&self, other: &Self) -> bool {
+        let (quads, other_quads) = (self.quads(), other.quads());
+
+        quads
+            .iter()
+            .any(|quad| other_quads.iter().any(|other| quads_overlap(quad, other)))
+    }
+}
+
+impl<T> Key<T>
+where
+    T: Real + Into<f64>,
+{
+    /// Returns the X position of the key in millimetres.
+    #[must_use]
+    pub fn physical_x_mm(&self) -> f64 {
+        self.x.into() * UNIT_MM
+    }
+
+    /// Returns the Y position of the key in millimetres.
+    #[must_use]
+    pub fn physical_y_mm(&self) -> f64 {
+        self.y.into() * UNIT_MM
+    }
+
+    /// Returns the width of the key in millimetres.
+    #[must_use]
+    pub fn physical_width_mm(&self) -> f64 {
+        self.width.into() * UNIT_MM
+    }
+
+    /// Returns the height of the key in millimetres.
+    #[must_use]
+    pub fn physical_height_mm(&self) -> f64 {
+        self.height.into() * UNIT_MM
+    }
+
+    /// Returns the X and Y position of the key in millimetres.
+    #[must_use]
+    pub fn physical_position_mm(&self) -> (f64, f64) {
+        (self.physical_x_mm(), self.physical_y_mm())
+    }
+
+    /// Returns the width and height of the key in millimetres.
+    #[must_use]
+    pub fn physical_size_mm(&self) -> (f64, f64) {
+        (self.physical_width_mm(), self.physical_height_mm())
+    }
+
+    /// Returns the position and size of the key in millimetres.
+    ///
+    /// This ignores the key's rotation and any stepped or L-shaped part of the key. Use
+    /// [`Key::bounding_box`] to find the full extents of the key.
+    #[must_use]
+    pub fn physical_rect_mm(&self) -> Rect<f64> {
+        Rect {
+            x: self.physical_x_mm(),
+            y: self.physical_y_mm(),
+            width: self.physical_width_mm(),
+            height: self.physical_height_mm(),
+        }
+    }
+
+    /// Returns the X position of the key in inches.
+    #[must_use]
+    pub fn physical_x_in(&self) -> f64 {
+        self.x.into() * UNIT_IN
+    }
+
+    /// Returns the Y position of the key in inches.
+    #[must_use]
+    pub fn physical_y_in(&self) -> f64 {
+        self.y.into() * UNIT_IN
+    }
+
+    /// Returns the width of the key in inches.
+    #[must_use]
+    pub fn physical_width_in(&self) -> f64 {
+        self.width.into() * UNIT_IN
+    }
+
+    /// Returns the height of the key in inches.
+    #[must_use]
+    pub fn physical_height_in(&self) -> f64 {
+        self.height.into() * UNIT_IN
+    }
+
+    /// Returns the X and Y position of the key in inches.
+    #[must_use]
+    pub fn physical_position_in(&self) -> (f64, f64) {
+        (self.physical_x_in(), self.physical_y_in())
+    }
+
+    /// Returns the width and height of the key in inches.
+    #[must_use]
+    pub fn physical_size_in(&self) -> (f64, f64) {
+        (self.physical_width_in(), self.physical_height_in())
+    }
+
+    /// Returns the position and size of the key in inches.
+    ///
+    /// This ignores the key's rotation and any stepped or L-shaped part of the key. Use
+    /// [`Key::bounding_box`] to find the full extents of the key.
+    #[must_use]
+    pub fn physical_rect_in(&self) -> Rect<f64> {
+        Rect {
+            x: self.physical_x_in(),
+            y: self.physical_y_in(),
+            width: self.physical_width_in(),
+            height: self.physical_height_in(),
+        }
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Returns the smallest axis-aligned rectangle enclosing all keys in the layout, or [`None`] if
+    /// the layout has no keys.
+    #[must_use]
+    pub fn bounding_box(&self) -> Option<Rect<T>> {
+        let points = self
+            .keys
+            .iter()
+            .flat_map(|key| key.bounding_box().corners());
+
+        Rect::from_points(points)
+    }
+
+    /// Returns the total visual area of all keys in the layout in square keyboard units, excluding
+    /// decals. See [`Key::visual_area`] for details.
+    #[must_use]
+    pub fn total_key_area(&self) -> T {
+        (self.keys.iter())
+            .filter(|key| !key.decal)
+            .fold(T::zero(), |total, key| total + key.visual_area())
+    }
+
+    /// Returns the index of and a reference to the key whose [centre](Key::center_point) is
+    /// closest to the point `(x, y)`, or [`None`] if the layout has no keys.
+    ///
+    /// If multiple keys are equally close, the first one in [`Keyboard::keys`] is returned. This
+    /// performs a linear scan over all keys.
+    #[must_use]
+    pub fn nearest_key_to(&self, x: T, y: T) -> Option<(usize, &Key<T>)> {
+        (self.keys.iter().enumerate())
+            .map(|(i, key)| {
+                let (cx, cy) = key.center_point();
+                let (dx, dy) = (cx - x, cy - y);
+                (dx * dx + dy * dy, i, key)
+            })
+            .min_by(|(a, ..), (b, ..)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+            .map(|(_, i, key)| (i, key))
+    }
+
+    /// Returns the index of and a reference to the key containing the point `(x, y)`, or [`None`]
+    /// if no key contains it.
+    ///
+    /// If multiple keys overlap at the point, the last one in [`Keyboard::keys`] is returned since
+    /// it is drawn on top. This is intended for hit testing, for example in layout editors, and
+    /// performs a linear scan over all keys using [`Key::contains_point`].
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["A", {"x": 0.5}, "B"]]"#).unwrap();
+    ///
+    /// assert_eq!(keyboard.find_key_at_position(1.75, 0.5).map(|(i, _)| i), Some(1));
+    /// assert!(keyboard.find_key_at_position(1.25, 0.5).is_none());
+    /// ```
+    #[must_use]
+    pub fn find_key_at_position(&self, x: T, y: T) -> Option<(usize, &Key<T>)> {
+        (self.keys.iter().enumerate())
+            .rev()
+            .find(|(_, key)| key.contains_point(x, y))
+    }
+
+    /// Returns a copy of the layout containing only the keys which intersect the rectangle with top
+    /// left corner `(x, y)` and size `width` × `height`.
+    ///
+    /// This is equivalent to [`Keyboard::clip_to_rect_with_mode`] with [`ClipMode::Intersects`].
+    #[must_use]
+    pub fn clip_to_rect(&self, x: T, y: T, width: T, height: T) -> Self {
+        self.clip_to_rect_with_mode(x, y, width, height, ClipMode::Intersects)
+    }
+
+    /// Returns a copy of the layout containing only the keys which lie within the rectangle with
+    /// top left corner `(x, y)` and size `width` × `height`, according to the given [`ClipMode`].
+    ///
+    /// This takes into account both the keys' rotation and the secondary rectangle used by stepped
+    /// and L-shaped keys. See [`Key::intersects_rect`] for details. The layout's metadata is
+    /// preserved.
+    ///
+    /// ```
+    /// use kle_serial::{ClipMode, Keyboard};
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["A", "B", "C"]]"#).unwrap();
+    ///
+    /// let clipped = keyboard.clip_to_rect_with_mode(0.5, 0.0, 2.0, 1.0, ClipMode::Intersects);
+    /// assert_eq!(clipped.keys.len(), 3);
+    ///
+    /// let clipped = keyboard.clip_to_rect_with_mode(0.5, 0.0, 2.0, 1.0, ClipMode::Contains);
+    /// assert_eq!(clipped.keys.len(), 1);
+    /// ```
+    #[must_use]
+    pub fn clip_to_rect_with_mode(&self, x: T, y: T, width: T, height: T, mode: ClipMode) -> Self {
+        let rect = Rect {
+            x,
+            y,
+            width,
+            height,
+        };
+
+        Self {
+            metadata: self.metadata.clone(),
+            keys: (self.keys.iter())
+                .filter(|key| match mode {
+                    ClipMode::Contains => key.within_rect(&rect),
+                    ClipMode::Intersects => key.intersects_rect(x, y, width, height),
+                })
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Returns the indices of all pairs of keys in the layout which overlap.
+    ///
+    /// Each pair `(i, j)` is returned once with `i < j`. See [`Key::overlaps_with`] for details on
+    /// how overlaps are detected.
+    #[must_use]
+    pub fn find_overlapping_keys(&self) -> Vec<(usize, usize)> {
+        (self.keys.iter().enumerate())
+            .flat_map(|(i, key)| {
+                (self.keys.iter().enumerate().skip(i + 1))
+                    .filter(move |(_, other)| key.overlaps_with(other))
+                    .map(move |(j, _)| (i, j))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isclose::assert_is_close;
+
+    use super::*;
+
+    #[test]
+    fn test_key_bounding_box() {
+        let bbox = Key::<f64>::default().bounding_box();
+
+        assert_is_close!(bbox.x, 0.0);
+        assert_is_close!(bbox.y, 0.0);
+        assert_is_close!(bbox.width, 1.0);
+        assert_is_close!(bbox.height, 1.0);
+
+        // ISO enter
+        let bbox = Key::<f64> {
+            x: 0.25,
+            width: 1.25,
+            height: 2.0,
+            x2: -0.25,
+            width2: 1.5,
+            height2: 1.0,
+            ..Key::default()
+        }
+        .bounding_box();
+
+        assert_is_close!(bbox.x, 0.0);
+        assert_is_close!(bbox.y, 0.0);
+        assert_is_close!(bbox.width, 1.5);
+        assert_is_close!(bbox.height, 2.0);
+
+        let bbox = Key::<f64> {
+            x: 2.0,
+            y: 2.0,
+            width: 2.0,
+            width2: 2.0,
+            rotation: 90.0,
+            rx: 2.0,
+            ry: 2.0,
+            ..Key::default()
+        }
+        .bounding_box();
+
+        assert_is_close!(bbox.x, 1.0);
+        assert_is_close!(bbox.y, 2.0);
+        assert_is_close!(bbox.width, 1.0);
+        assert_is_close!(bbox.height, 2.0);
+
+        let bbox = Key::<f64> {
+            rotation: 45.0,
+            ..Key::default()
+        }
+        .bounding_box();
+
+        assert_is_close!(bbox.x, -std::f64::consts::FRAC_1_SQRT_2);
+        assert_is_close!(bbox.y, 0.0);
+        assert_is_close!(bbox.width, std::f64::consts::SQRT_2);
+        assert_is_close!(bbox.height, std::f64::consts::SQRT_2);
+    }
+
+    #[test]
+    fn test_key_true_rect() {
+        let key = Key::<f64> {
+            x: 1.0,
+            y: 2.0,
+            ..Key::default()
+        };
+
+        assert_is_close!(key.true_x(), 1.0);
+        assert_is_close!(key.true_y(), 2.0);
+        assert_is_close!(key.true_width(), 1.0);
+        assert_is_close!(key.true_height(), 1.0);
+
+        // ISO enter
+        let key = Key::<f64> {
+            x: 13.75,
+            y: 1.0,
+            width: 1.25,
+            height: 2.0,
+            x2: -0.25,
+            width2: 1.5,
+            height2: 1.0,
+            rotation: 30.0,
+            ..Key::default()
+        };
+        let (x, y) = key.true_origin();
+
+        assert_is_close!(x, 13.5);
+        assert_is_close!(y, 1.0);
+        assert_is_close!(key.true_x(), 13.5);
+        assert_is_close!(key.true_y(), 1.0);
+        assert_is_close!(key.true_width(), 1.5);
+        assert_is_close!(key.true_height(), 2.0);
+
+        // Big-ass enter
+        let key = Key::<f64> {
+            x: 13.0,
+            y: 1.0,
+            width: 1.5,
+            height: 2.0,
+            x2: -0.75,
+            y2: 1.0,
+            width2: 2.25,
+            height2: 1.0,
+            ..Key::default()
+        };
+
+        assert_is_close!(key.true_x(), 12.25);
+        assert_is_close!(key.true_y(), 1.0);
+        assert_is_close!(key.true_width(), 2.25);
+        assert_is_close!(key.true_height(), 2.0);
+    }
+
+    #[test]
+    fn test_key_shapes() {
+        let regular = Key::<f64> {
+            width: 2.0,
+            width2: 2.0,
+            ..Key::default()
+        };
+        let iso_enter = Key::<f64> {
+            width: 1.25,
+            height: 2.0,
+            x2: -0.25,
+            width2: 1.5,
+            height2: 1.0,
+            ..Key::default()
+        };
+        let stepped_caps = Key::<f64> {
+            width: 1.75,
+            width2: 1.25,
+            stepped: true,
+            ..Key::default()
+        };
+
+        assert!(Key::<f64>::default().is_regular());
+        assert!(regular.is_regular());
+        assert!(!iso_enter.is_regular());
+        assert!(!stepped_caps.is_regular());
+
+        assert!(!regular.is_iso_enter());
+        assert!(iso_enter.is_iso_enter());
+        assert!(!stepped_caps.is_iso_enter());
+
+        assert!(!regular.is_stepped_caps_lock());
+        assert!(!iso_enter.is_stepped_caps_lock());
+        assert!(stepped_caps.is_stepped_caps_lock());
+
+        let iso_enter = Key::<f32> {
+            width: 1.25 + 1e-7,
+            height: 2.0,
+            x2: -0.25,
+            width2: 1.5,
+            height2: 1.0,
+            ..Key::default()
+        };
+        assert!(iso_enter.is_iso_enter());
+    }
+
+    #[test]
+    fn test_key_corners() {
+        let key = Key::<f64> {
+            x: 1.0,
+            y: 2.0,
+            width: 2.0,
+            width2: 2.0,
+            ..Key::default()
+        };
+        let expected = [(1.0, 2.0), (3.0, 2.0), (3.0, 3.0), (1.0, 3.0)];
+
+        for (corner, exp) in key.corners().into_iter().zip(expected) {
+            assert_is_close!(corner.0, exp.0);
+            assert_is_close!(corner.1, exp.1);
+        }
+
+        let key = Key::<f64> {
+            rotation: 90.0,
+            rx: 1.0,
+            ry: 2.0,
+            ..key
+        };
+        let expected = [(1.0, 2.0), (1.0, 4.0), (0.0, 4.0), (0.0, 2.0)];
+
+        for (corner, exp) in key.corners().into_iter().zip(expected) {
+            assert_is_close!(corner.0, exp.0);
+            assert_is_close!(corner.1, exp.1);
+        }
+
+        let bbox = key.bounding_box();
+        assert_is_close!(bbox.width, 1.0);
+        assert_is_close!(bbox.height, 2.0);
+    }
+
+    #[test]
+    fn test_key_all_corners() {
+        let key = Key::<f64>::default();
+        let corners = key.all_corners();
+        let expected = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+
+        assert_eq!(corners.len(), expected.len());
+        for (corner, exp) in corners.into_iter().zip(expected) {
+            assert_is_close!(corner.0, exp.0);
+            assert_is_close!(corner.1, exp.1);
+        }
+
+        // ISO enter
+        let key = Key::<f64> {
+            x: 0.25,
+            width: 1.25,
+            height: 2.0,
+            x2: -0.25,
+            width2: 1.5,
+            height2: 1.0,
+            ..Key::default()
+        };
+        let corners = key.all_corners();
+        let expected = [
+            (0.0, 0.0),
+            (1.5, 0.0),
+            (1.5, 2.0),
+            (0.25, 2.0),
+            (0.25, 1.0),
+            (0.0, 1.0),
+        ];
+
+        assert_eq!(corners.len(), expected.len());
+        for (corner, exp) in corners.into_iter().zip(expected) {
+            assert_is_close!(corner.0, exp.0);
+            assert_is_close!(corner.1, exp.1);
+        }
+
+        // Big-ass enter
+        let key = Key::<f64> {
+            x: 0.75,
+            width: 1.5,
+            height: 2.0,
+            x2: -0.75,
+            y2: 1.0,
+            width2: 2.25,
+            height2: 1.0,
+            ..Key::default()
+        };
+        let corners = key.all_corners();
+        let expected = [
+            (0.75, 0.0),
+            (2.25, 0.0),
+            (2.25, 2.0),
+            (0.0, 2.0),
+            (0.0, 1.0),
+            (0.75, 1.0),
+        ];
+
+        assert_eq!(corners.len(), expected.len());
+        for (corner, exp) in corners.into_iter().zip(expected) {
+            assert_is_close!(corner.0, exp.0);
+            assert_is_close!(corner.1, exp.1);
+        }
+
+        // Stepped caps lock only uses the primary rectangle
+        let key = Key::<f64> {
+            width: 1.75,
+            width2: 1.25,
+            stepped: true,
+            rotation: 90.0,
+            ..Key::default()
+        };
+        let corners = key.all_corners();
+        let expected = [(0.0, 0.0), (0.0, 1.75), (-1.0, 1.75), (-1.0, 0.0)];
+
+        assert_eq!(corners.len(), expected.len());
+        for (corner, exp) in corners.into_iter().zip(expected) {
+            assert_is_close!(corner.0, exp.0);
+            assert_is_close!(corner.1, exp.1);
+        }
+    }
+
+    #[test]
+    fn test_key_contains_point() {
+        let key = Key::<f64>::default();
+
+        assert!(key.contains_point(0.5, 0.5));
+        assert!(key.contains_point(0.0, 0.0));
+        assert!(key.contains_point(1.0, 1.0));
+        assert!(!key.contains_point(1.5, 0.5));
+        assert!(!key.contains_point(0.5, -0.5));
+
+        // ISO enter
+        let key = Key::<f64> {
+            x: 0.25,
+            width: 1.25,
+            height: 2.0,
+            x2: -0.25,
+            width2: 1.5,
+            height2: 1.0,
+            ..Key::default()
+        };
+
+        assert!(key.contains_point(0.1, 0.5));
+        assert!(key.contains_point(1.0, 1.5));
+        assert!(!key.contains_point(0.1, 1.5));
+        assert!(!key.contains_point(1.6, 0.5));
+
+        // Stepped caps lock
+        let key = Key::<f64> {
+            width: 1.75,
+            width2: 1.25,
+            stepped: true,
+            ..Key::default()
+        };
+
+        assert!(key.contains_point(0.5, 0.5));
+        assert!(key.contains_point(1.5, 0.5));
+        assert!(!key.contains_point(2.0, 0.5));
+
+        let key = Key::<f64> {
+            x: 2.0,
+            y: 2.0,
+            width: 2.0,
+            width2: 2.0,
+            rotation: 90.0,
+            rx: 2.0,
+            ry: 2.0,
+            ..Key::default()
+        };
+
+        assert!(key.contains_point(1.5, 3.5));
+        assert!(!key.contains_point(3.5, 2.5));
+        assert!(!key.contains_point(2.5, 1.5));
+
+        let key = Key::<f64> {
+            rotation: 45.0,
+            ..Key::default()
+        };
+
+        assert!(key.contains_point(0.0, 1.0));
+        assert!(!key.contains_point(0.9, 0.1));
+    }
+
+    #[test]
+    fn test_key_contains_point_with_margin() {
+        let key = Key::<f64>::default();
+
+        assert!(key.contains_point_with_margin(0.5, 0.5, 0.25));
+        assert!(!key.contains_point_with_margin(0.1, 0.5, 0.25));
+        assert!(key.contains_point_with_margin(1.1, 0.5, -0.25));
+
+        let key = Key::<f64> {
+            x: 0.25,
+            width: 1.25,
+            height: 2.0,
+            x2: -0.25,
+            width2: 1.5,
+            height2: 1.0,
+            rotation: 90.0,
+            rx: 1.0,
+            ry: 1.0,
+            ..Key::default()
+        };
+
+        assert!(key.contains_point_with_margin(1.5, 0.1, 0.05));
+        assert!(!key.contains_point_with_margin(1.5, 0.1, 0.15));
+    }
+
+    #[test]
+    fn test_key_physical_mm() {
+        let key = Key::<f64>::default();
+
+        assert_is_close!(key.physical_x_mm(), 0.0);
+        assert_is_close!(key.physical_y_mm(), 0.0);
+        assert_is_close!(key.physical_width_mm(), 19.05);
+        assert_is_close!(key.physical_height_mm(), 19.05);
+
+        let key = Key::<f32> {
+            x: 1.5,
+            y: 2.0,
+            width: 2.25,
+            ..Key::default()
+        };
+        let (x, y) = key.physical_position_mm();
+        let (width, height) = key.physical_size_mm();
+        let rect = key.physical_rect_mm();
+
+        assert_is_close!(x, 28.575);
+        assert_is_close!(y, 38.1);
+        assert_is_close!(width, 42.8625);
+        assert_is_close!(height, 19.05);
+        assert_is_close!(rect.x, x);
+        assert_is_close!(rect.y, y);
+        assert_is_close!(rect.width, width);
+        assert_is_close!(rect.height, height);
+    }
+
+    #[test]
+    fn test_key_physical_in() {
+        let key = Key::<f64>::default();
+
+        assert_is_close!(key.physical_x_in(), 0.0);
+        assert_is_close!(key.physical_y_in(), 0.0);
+        assert_is_close!(key.physical_width_in(), 0.75);
+        assert_is_close!(key.physical_height_in(), 0.75);
+
+        let key = Key::<f32> {
+            x: 1.5,
+            y: 2.0,
+            width: 2.25,
+            ..Key::default()
+        };
+        let (x, y) = key.physical_position_in();
+        let (width, height) = key.physical_size_in();
+        let rect = key.physical_rect_in();
+
+        assert_is_close!(x, 1.125);
+        assert_is_close!(y, 1.5);
+        assert_is_close!(width, 1.6875);
+        assert_is_close!(height, 0.75);
+        assert_is_close!(rect.x, x);
+        assert_is_close!(rect.y, y);
+        assert_is_close!(rect.width, width);
+        assert_is_close!(rect.height, height);
+    }
+
+    #[test]
+    fn test_key_overlaps_with() {
+        let key = Key::<f64>::default();
+        let adjacent = Key {
+            x: 1.0,
+            ..Key::default()
+        };
+        let overlapping = Key {
+            x: 0.5,
+            y: 0.5,
+            ..Key::default()
+        };
+
+        assert!(key.overlaps_with(&key));
+        assert!(!key.overlaps_with(&adjacent));
+        assert!(key.overlaps_with(&overlapping));
+        assert!(overlapping.overlaps_with(&key));
+
+        // ISO enter, with the keys to its left on both rows and a left shift on the row below
+        let iso_enter = Key::<f64> {
+            x: 13.75,
+            y: 1.0,
+            width: 1.25,
+            height: 2.0,
+            x2: -0.25,
+            width2: 1.5,
+            height2: 1.0,
+            ..Key::default()
+        };
+        let bracket = Key {
+            x: 12.5,
+            y: 1.0,
+            ..Key::default()
+        };
+        let hash = Key {
+            x: 12.75,
+            y: 2.0,
+            ..Key::default()
+        };
+        let shift = Key {
+            x: 12.75,
+            y: 3.0,
+            width: 2.25,
+            width2: 2.25,
+            ..Key::default()
+        };
+
+        assert!(!iso_enter.overlaps_with(&bracket));
+        assert!(!iso_enter.overlaps_with(&hash));
+        assert!(!iso_enter.overlaps_with(&shift));
+
+        let hash = Key { x: 12.8, ..hash };
+        assert!(iso_enter.overlaps_with(&hash));
+        assert!(hash.overlaps_with(&iso_enter));
+
+        // Keys rotated around their centres
+        let rotated = |x| Key::<f64> {
+            x,
+            rotation: 45.0,
+            rx: x + 0.5,
+            ry: 0.5,
+            ..Key::default()
+        };
+
+        assert!(rotated(0.0).overlaps_with(&rotated(1.2)));
+        assert!(!rotated(0.0).overlaps_with(&rotated(1.5)));
+
+        let unrotated = |x| Key {
+            rotation: 0.0,
+            ..rotated(x)
+        };
+        assert!(!unrotated(0.0).overlaps_with(&unrotated(1.2)));
+    }
+
+    #[test]
+    fn test_key_rotation_matrix() {
+        let key = Key::<f64> {
+            rotation: 90.0,
+            ..Key::default()
+        };
+        let expected = [[0.0, -1.0], [1.0, 0.0]];
+
+        for (row, exp) in key.rotation_matrix().into_iter().zip(expected) {
+            assert_is_close!(row[0], exp[0]);
+            assert_is_close!(row[1], exp[1]);
+        }
+
+        let matrix = Key::<f64>::default().rotation_matrix();
+        assert_eq!(matrix, [[1.0, -0.0], [0.0, 1.0]]);
+    }
+
+    #[test]
+    fn test_key_transform_point() {
+        let key = Key::<f64> {
+            rotation: 90.0,
+            rx: 1.0,
+            ry: 2.0,
+            ..Key::default()
+        };
+
+        let (x, y) = key.transform_point(2.0, 2.0);
+        assert_is_close!(x, 1.0);
+        assert_is_close!(y, 3.0);
+
+        let (x, y) = key.inverse_transform_point(1.0, 3.0);
+        assert_is_close!(x, 2.0);
+        assert_is_close!(y, 2.0);
+
+        let (x, y) = key.transform_point(1.0, 2.0);
+        assert_is_close!(x, 1.0);
+        assert_is_close!(y, 2.0);
+    }
+
+    #[test]
+    fn test_key_transform_point_round_trip() {
+        let points = [(0.0, 0.0), (1.5, -2.25), (-3.7, 4.1), (12.3, 0.8)];
+
+        for rotation in [-135.0, -30.0, 0.0, 15.0, 90.0, 200.0] {
+            let key = Key::<f64> {
+                rotation,
+                rx: 2.5,
+                ry: -1.0,
+                ..Key::default()
+            };
+
+            for (px, py) in points {
+                let (x, y) = key.inverse_transform_point(px, py);
+                let (x, y) = key.transform_point(x, y);
+                assert_is_close!(x, px);
+                assert_is_close!(y, py);
+
+                let (x, y) = key.transform_point(px, py);
+                let (x, y) = key.inverse_transform_point(x, y);
+                assert_is_close!(x, px);
+                assert_is_close!(y, py);
+            }
+        }
+    }
+
+    #[test]
+    fn test_key_center_point() {
+        let key = Key::<f64> {
+            x: 1.0,
+            y: 2.0,
+            width: 2.0,
+            ..Key::default()
+        };
+        let (x, y) = key.center_point();
+        assert_is_close!(x, 2.0);
+        assert_is_close!(y, 2.5);
+
+        let key = Key {
+            rotation: 90.0,
+            rx: 1.0,
+            ry: 2.0,
+            ..key
+        };
+        let (x, y) = key.center_point();
+        assert_is_close!(x, 0.5);
+        assert_is_close!(y, 3.0);
+    }
+
+    #[test]
+    fn test_keyboard_nearest_key_to() {
+        let keyboard = Keyboard::<f64> {
+            keys: vec![
+                Key::default(),
+                Key {
+                    x: 1.0,
+                    ..Key::default()
+                },
+                Key {
+                    x: 3.0,
+                    width: 2.0,
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        };
+
+        // Exactly on a key
+        assert_eq!(keyboard.nearest_key_to(0.5, 0.5).map(|(i, _)| i), Some(0));
+        assert_eq!(keyboard.nearest_key_to(1.9, 0.1).map(|(i, _)| i), Some(1));
+        // Between keys
+        assert_eq!(keyboard.nearest_key_to(2.6, 0.5).map(|(i, _)| i), Some(1));
+        assert_eq!(keyboard.nearest_key_to(2.8, 0.5).map(|(i, _)| i), Some(2));
+        // Equidistant
+        assert_eq!(keyboard.nearest_key_to(1.0, 5.0).map(|(i, _)| i), Some(0));
+
+        let (_, key) = keyboard.nearest_key_to(10.0, -10.0).unwrap();
+        assert!(core::ptr::eq(key, &keyboard.keys[2]));
+
+        assert!(Keyboard::<f64>::default()
+            .nearest_key_to(0.0, 0.0)
+            .is_none());
+    }
+
+    #[test]
+    fn test_keyboard_find_key_at_position() {
+        let keyboard = Keyboard::<f64> {
+            keys: vec![
+                Key::default(),
+                Key {
+                    x: 1.25,
+                    width: 2.0,
+                    width2: 2.0,
+                    ..Key::default()
+                },
+                Key {
+                    x: 1.5,
+                    width: 0.5,
+                    height: 0.5,
+                    width2: 0.5,
+                    height2: 0.5,
+                    decal: true,
+                    ..Key::default()
+                },
+                Key {
+                    y: 2.0,
+                    rotation: 45.0,
+                    rx: 0.0,
+                    ry: 2.0,
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        };
+        let index = |x, y| keyboard.find_key_at_position(x, y).map(|(i, _)| i);
+
+        assert_eq!(index(0.5, 0.5), Some(0));
+        assert_eq!(index(3.0, 0.9), Some(1));
+        // Overlapping decal drawn on top
+        assert_eq!(index(1.75, 0.25), Some(2));
+        assert_eq!(index(1.75, 0.75), Some(1));
+        // Gap between keys
+        assert_eq!(index(1.1, 0.5), None);
+        // Rotated key
+        assert_eq!(index(0.0, 2.5), Some(3));
+        assert_eq!(index(0.9, 2.1), None);
+
+        let (_, key) = keyboard.find_key_at_position(1.6, 0.1).unwrap();
+        assert!(core::ptr::eq(key, &keyboard.keys[2]));
+
+        assert!(Keyboard::<f64>::default()
+            .find_key_at_position(0.0, 0.0)
+            .is_none());
+    }
+
+    #[test]
+    fn test_keyboard_find_key_at_position_secondary_rect() {
+        // ISO enter, the top part being wider than the bottom part
+        let keyboard = Keyboard::<f64> {
+            keys: vec![Key {
+                x: 4.0,
+                width: 1.25,
+                height: 2.0,
+                x2: -0.25,
+                width2: 1.5,
+                height2: 1.0,
+                ..Key::default()
+            }],
+            ..Keyboard::default()
+        };
+        let index = |x, y| keyboard.find_key_at_position(x, y).map(|(i, _)| i);
+
+        assert_eq!(index(4.5, 1.5), Some(0));
+        // Only inside the secondary rectangle
+        assert_eq!(index(3.85, 0.5), Some(0));
+        // Left of the bottom part, below the secondary rectangle
+        assert_eq!(index(3.85, 1.5), None);
+    }
+
+    #[test]
+    fn test_keyboard_find_overlapping_keys() {
+        let keyboard = Keyboard::<f64> {
+            keys: vec![
+                Key::default(),
+                Key {
+                    x: 1.0,
+                    ..Key::default()
+                },
+                Key {
+                    x: 1.5,
+                    ..Key::default()
+                },
+                Key {
+                    x: 0.5,
+                    y: 0.5,
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        };
+
+        assert_eq!(
+            keyboard.find_overlapping_keys(),
+            vec![(0, 3), (1, 2), (1, 3)]
+        );
+        assert!(Keyboard::<f64>::default()
+            .find_overlapping_keys()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_key_area() {
+        let key = Key::<f64> {
+            width: 2.0,
+            width2: 2.0,
+            ..Key::default()
+        };
+
+        assert_is_close!(key.area(), 2.0);
+        assert_is_close!(key.secondary_area(), 2.0);
+        assert_is_close!(key.visual_area(), 2.0);
+
+        // ISO enter
+        let key = Key::<f64> {
+            x: 0.25,
+            width: 1.25,
+            height: 2.0,
+            x2: -0.25,
+            width2: 1.5,
+            height2: 1.0,
+            ..Key::default()
+        };
+
+        assert_is_close!(key.area(), 2.5);
+        assert_is_close!(key.secondary_area(), 1.5);
+        assert_is_close!(key.visual_area(), 2.75);
+
+        // Stepped caps lock
+        let key = Key::<f64> {
+            width: 1.75,
+            width2: 1.25,
+            stepped: true,
+            ..Key::default()
+        };
+
+        assert_is_close!(key.visual_area(), 1.75);
+    }
+
+    #[test]
+    fn test_keyboard_total_key_area() {
+        // Standard 104 key ANSI layout
+        let keyboard: Keyboard = serde_json::from_str(
+            r#"[
+                ["", {"x": 1}, "", "", "", "", {"x": 0.5}, "", "", "", "", {"x": 0.5}, "", "", "",
+                    "", {"x": 0.25}, "", "", ""],
+                [{"y": 0.5}, "", "", "", "", "", "", "", "", "", "", "", "", "", {"w": 2}, "",
+                    {"x": 0.25}, "", "", "", {"x": 0.25}, "", "", "", ""],
+                [{"w": 1.5}, "", "", "", "", "", "", "", "", "", "", "", "", "", {"w": 1.5}, "",
+                    {"x": 0.25}, "", "", "", {"x": 0.25}, "", "", "", {"h": 2}, ""],
+                [{"w": 1.75}, "", "", "", "", "", "", "", "", "", "", "", "", {"w": 2.25}, "",
+                    {"x": 3.5}, "", "", ""],
+                [{"w": 2.25}, "", "", "", "", "", "", "", "", "", "", "", {"w": 2.75}, "",
+                    {"x": 1.25}, "", {"x": 1.25}, "", "", "", {"h": 2}, ""],
+                [{"w": 1.25}, "", {"w": 1.25}, "", {"w": 1.25}, "", {"w": 6.25}, "", {"w": 1.25},
+                    "", {"w": 1.25}, "", {"w": 1.25}, "", {"w": 1.25}, "", {"x": 0.25}, "", "", "",
+                    {"x": 0.25, "w": 2}, "", ""]
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(keyboard.keys.len(), 104);
+
+        let expected: f64 = keyboard.keys.iter().map(|key| key.width * key.height).sum();
+        assert_is_close!(expected, 121.0);
+        assert_is_close!(keyboard.total_key_area(), expected);
+
+        let mut keyboard = keyboard;
+        keyboard.keys[0].decal = true;
+        assert_is_close!(keyboard.total_key_area(), expected - 1.0);
+    }
+
+    #[test]
+    fn test_key_intersects_rect() {
+        let key = Key::<f64> {
+            x: 1.0,
+            y: 1.0,
+            ..Key::default()
+        };
+
+        assert!(key.intersects_rect(0.0, 0.0, 1.5, 1.5));
+        assert!(key.intersects_rect(0.0, 0.0, 3.0, 3.0));
+        assert!(key.intersects_rect(1.25, 1.25, 0.5, 0.5));
+        assert!(!key.intersects_rect(0.0, 0.0, 1.0, 1.0)); // Only touching
+        assert!(!key.intersects_rect(2.5, 0.0, 1.0, 3.0));
+
+        // Rotated 45 degrees around its centre, so the corners of the key miss the rectangle's
+        // corner even though the unrotated key would overlap
+        let key = Key {
+            rotation: 45.0,
+            rx: 1.5,
+            ry: 1.5,
+            ..key
+        };
+        assert!(!key.intersects_rect(0.0, 0.0, 1.1, 1.1));
+        assert!(key.intersects_rect(0.0, 1.4, 1.0, 0.2));
+    }
+
+    #[test]
+    fn test_keyboard_clip_to_rect() {
+        // Standard 104 key ANSI layout
+        let keyboard: Keyboard = serde_json::from_str(
+            r#"[
+                {"name": "ANSI 104"},
+                ["", {"x": 1}, "", "", "", "", {"x": 0.5}, "", "", "", "", {"x": 0.5}, "", "", "",
+                    "", {"x": 0.25}, "", "", ""],
+                [{"y": 0.5}, "", "", "", "", "", "", "", "", "", "", "", "", "", {"w": 2}, "",
+                    {"x": 0.25}, "", "", "", {"x": 0.25}, "", "", "", ""],
+                [{"w": 1.5}, "", "", "", "", "", "", "", "", "", "", "", "", "", {"w": 1.5}, "",
+                    {"x": 0.25}, "", "", "", {"x": 0.25}, "", "", "", {"h": 2}, ""],
+                [{"w": 1.75}, "", "", "", "", "", "", "", "", "", "", "", "", {"w": 2.25}, "",
+                    {"x": 3.5}, "", "", ""],
+                [{"w": 2.25}, "", "", "", "", "", "", "", "", "", "", "", {"w": 2.75}, "",
+                    {"x": 1.25}, "", {"x": 1.25}, "", "", "", {"h": 2}, ""],
+                [{"w": 1.25}, "", {"w": 1.25}, "", {"w": 1.25}, "", {"w": 6.25}, "", {"w": 1.25},
+                    "", {"w": 1.25}, "", {"w": 1.25}, "", {"w": 1.25}, "", {"x": 0.25}, "", "", "",
+                    {"x": 0.25, "w": 2}, "", ""]
+            ]"#,
+        )
+        .unwrap();
+
+        // 4x4 region at the top of the numpad. This contains 14 keys entirely, and also partially
+        // contains the numpad enter
+        let clipped = keyboard.clip_to_rect_with_mode(18.5, 1.5, 4.0, 4.0, ClipMode::Contains);
+        assert_eq!(clipped.keys.len(), 14);
+        assert_eq!(clipped.metadata, keyboard.metadata);
+
+        let clipped = keyboard.clip_to_rect(18.5, 1.5, 4.0, 4.0);
+        assert_eq!(clipped.keys.len(), 15);
+        assert_eq!(clipped.metadata.name, "ANSI 104");
+
+        let clipped = keyboard.clip_to_rect(100.0, 100.0, 4.0, 4.0);
+        assert!(clipped.keys.is_empty());
+    }
+
+    #[test]
+    fn test_keyboard_bounding_box() {
+        let keyboard = Keyboard::<f64>::default();
+
+        assert!(keyboard.bounding_box().is_none());
+
+        let keyboard = Keyboard::<f64> {
+            keys: vec![
+                Key {
+                    x: 1.0,
+                    y: 0.5,
+                    ..Key::default()
+                },
+                Key {
+                    x: 3.0,
+                    y: 2.0,
+                    width: 2.0,
+                    width2: 2.0,
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        };
+        let bbox = keyboard.bounding_box().unwrap();
+
+        assert_is_close!(bbox.x, 1.0);
+        assert_is_close!(bbox.y, 0.5);
+        assert_is_close!(bbox.width, 4.0);
+        assert_is_close!(bbox.height, 2.5);
+    }
+}
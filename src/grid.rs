@@ -0,0 +1,621 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::{cmp::Ordering, fmt, iter, slice};
+
+use num_traits::real::Real;
+
+use crate::geom::shape_epsilon;
+use crate::{Key, Keyboard, Legend};
+
+/// A 2D grid of keys indexed by row and column, intended for matrix style layouts such as
+/// ortholinear keyboards and numpads.
+///
+/// Rows and columns are found by grouping keys with similar `y` and `x` positions respectively.
+/// Positions in the grid without a key return [`None`], so non-rectangular layouts are supported.
+///
+/// ```
+/// use kle_serial::{KeyGrid, Keyboard};
+///
+/// let keyboard: Keyboard = serde_json::from_str(r#"[["7", "8", "9"], ["4", "5"]]"#).unwrap();
+/// let grid = KeyGrid::from_keyboard(&keyboard, 0.1);
+///
+/// assert_eq!((grid.rows(), grid.cols()), (2, 3));
+/// assert!(grid.get(1, 1).is_some());
+/// assert!(grid.get(1, 2).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyGrid<T = f64>
+where
+    T: Real,
+{
+    // Sorted in row-major order
+    keys: Vec<((usize, usize), Key<T>)>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> KeyGrid<T>
+where
+    T: Real,
+{
+    /// Creates a new [`KeyGrid`] from the keys in `keyboard`.
+    ///
+    /// Keys whose `y` values are within `tolerance` of the first key in a row are grouped into the
+    /// same row, and likewise for `x` values and columns. If multiple keys fall into the same grid
+    /// position, only the first key in [`Keyboard::keys`] is kept.
+    #[must_use]
+    pub fn from_keyboard(keyboard: &Keyboard<T>, tolerance: T) -> Self {
+        let row_positions = group_positions(keyboard.keys.iter().map(|key| key.y), tolerance);
+        let col_positions = group_positions(keyboard.keys.iter().map(|key| key.x), tolerance);
+
+        let mut keys: Vec<_> = (keyboard.keys.iter())
+            .filter_map(|key| {
+                let row = find_group(&row_positions, key.y, tolerance)?;
+                let col = find_group(&col_positions, key.x, tolerance)?;
+                Some(((row, col), key.clone()))
+            })
+            .collect();
+
+        // Stable sort to ensure the first key is kept when deduplicating
+        keys.sort_by_key(|&(pos, _)| pos);
+        keys.dedup_by_key(|&mut (pos, _)| pos);
+
+        Self {
+            keys,
+            rows: row_positions.len(),
+            cols: col_positions.len(),
+        }
+    }
+
+    /// Returns the key at the given row and column, or [`None`] if there is no key at that
+    /// position.
+    #[must_use]
+    pub fn get(&self, row: usize, col: usize) -> Option<&Key<T>> {
+        self.keys
+            .binary_search_by_key(&(row, col), |&(pos, _)| pos)
+            .ok()
+            .map(|i| &self.keys[i].1)
+    }
+
+    /// Returns the number of rows in the grid.
+    #[inline]
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns in the grid.
+    #[inline]
+    #[must_use]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns an iterator over the keys in the grid and their `(row, column)` positions in
+    /// row-major order.
+    pub fn iter(&self) -> KeyGridIter<'_, T> {
+        self.into_iter()
+    }
+}
+
+/// An iterator over the keys in a [`KeyGrid`] and their `(row, column)` positions.
+pub type KeyGridIter<'a, T> = iter::Map<
+    slice::Iter<'a, ((usize, usize), Key<T>)>,
+    fn(&'a ((usize, usize), Key<T>)) -> ((usize, usize), &'a Key<T>),
+>;
+
+impl<'a, T> IntoIterator for &'a KeyGrid<T>
+where
+    T: Real,
+{
+    type Item = ((usize, usize), &'a Key<T>);
+    type IntoIter = KeyGridIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.keys.iter().map(|(pos, key)| (*pos, key))
+    }
+}
+
+/// An error returned by [`Keyboard::approximate_key_matrix`] when two keys snap to the same
+/// position in the matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatrixCollisionError {
+    /// The row and column of the position both keys snap to.
+    pub position: (usize, usize),
+    /// The index of the first key at the position.
+    pub first: usize,
+    /// The index of the second key at the position.
+    pub second: usize,
+}
+
+impl fmt::Display for MatrixCollisionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (row, col) = self.position;
+        write!(
+            f,
+            "keys {} and {} both snap to row {row}, column {col}",
+            self.first, self.second
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MatrixCollisionError {}
+
+impl<T> Key<T>
+where
+    T: Real,
+{
+    /// Returns the key's row found by rounding its `y` position to the nearest whole unit.
+    ///
+    /// This uses the key's unrotated position, and keys with negative positions are placed in row
+    /// 0.
+    #[must_use]
+    pub fn approximate_row(&self) -> usize {
+        self.y.round().to_usize().unwrap_or_default()
+    }
+
+    /// Returns the key's column found by rounding its `x` position to the nearest whole unit.
+    ///
+    /// This uses the key's unrotated position, and keys with negative positions are placed in
+    /// column 0.
+    #[must_use]
+    pub fn approximate_column(&self) -> usize {
+        self.x.round().to_usize().unwrap_or_default()
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Returns a sparse matrix mapping each `(row, column)` position to the index of the key at
+    /// that position, using each key's [`Key::approximate_row`] and [`Key::approximate_column`].
+    ///
+    /// Positions without a key are absent from the map, so keys placed far from the origin do not
+    /// cause a large allocation.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["Q", "W"], [{"x": 0.25}, "A"]]"#).unwrap();
+    /// let matrix = keyboard.approximate_key_matrix().unwrap();
+    ///
+    /// assert_eq!(matrix.get(&(0, 1)), Some(&1));
+    /// assert_eq!(matrix.get(&(1, 0)), Some(&2));
+    /// assert_eq!(matrix.get(&(1, 1)), None);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MatrixCollisionError`] if two keys snap to the same position.
+    pub fn approximate_key_matrix(
+        &self,
+    ) -> Result<BTreeMap<(usize, usize), usize>, MatrixCollisionError> {
+        let mut matrix = BTreeMap::new();
+
+        for (index, key) in self.keys.iter().enumerate() {
+            let position = (key.approximate_row(), key.approximate_column());
+            if let Some(&first) = matrix.get(&position) {
+                return Err(MatrixCollisionError {
+                    position,
+                    first,
+                    second: index,
+                });
+            }
+            matrix.insert(position, index);
+        }
+
+        Ok(matrix)
+    }
+
+    /// Returns the legend at `legend_index` on the key at the given row and column, as found by
+    /// [`Key::approximate_row`] and [`Key::approximate_column`].
+    ///
+    /// Returns [`None`] if no key occupies the position or if the legend slot is empty. If
+    /// multiple keys snap to the same position, the first is used.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["Q", "W"], [{"x": 0.25}, "A"]]"#).unwrap();
+    ///
+    /// assert_eq!(keyboard.legend_at_position(1, 0, 0).unwrap().text, "A");
+    /// assert!(keyboard.legend_at_position(1, 1, 0).is_none());
+    /// ```
+    #[must_use]
+    pub fn legend_at_position(
+        &self,
+        row: usize,
+        col: usize,
+        legend_index: usize,
+    ) -> Option<&Legend> {
+        self.key_at_position(row, col)?
+            .legends
+            .get(legend_index)?
+            .as_ref()
+    }
+
+    /// Sets the legend at `legend_index` on the key at the given row and column, as found by
+    /// [`Key::approximate_row`] and [`Key::approximate_column`].
+    ///
+    /// Returns `false` without changing the layout if no key occupies the position or if
+    /// `legend_index` is out of range. If multiple keys snap to the same position, the first is
+    /// used.
+    pub fn set_legend_at_position(
+        &mut self,
+        row: usize,
+        col: usize,
+        legend_index: usize,
+        legend: Legend,
+    ) -> bool {
+        let slot = (self.keys.iter_mut())
+            .find(|key| (key.approximate_row(), key.approximate_column()) == (row, col))
+            .and_then(|key| key.legends.get_mut(legend_index));
+
+        if let Some(slot) = slot {
+            *slot = Some(legend);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn key_at_position(&self, row: usize, col: usize) -> Option<&Key<T>> {
+        (self.keys.iter())
+            .find(|key| (key.approximate_row(), key.approximate_column()) == (row, col))
+    }
+
+    /// Returns copies of the layout's keys grouped into rows, in the same way as
+    /// [`Keyboard::rows`].
+    ///
+    /// Rows are returned in ascending Y order and keys within each row are sorted by their `x`
+    /// position. Like [`Key::approximate_row`], this uses each key's unrotated position, so keys
+    /// in rotated clusters are grouped using their `y` field.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["Q", "W"], [{"x": 0.25}, "A"]]"#).unwrap();
+    /// let rows = keyboard.split_into_rows();
+    ///
+    /// assert_eq!(rows.len(), 2);
+    /// assert_eq!(rows[1][0].x, 0.25);
+    /// ```
+    #[must_use]
+    pub fn split_into_rows(&self) -> Vec<Vec<Key<T>>> {
+        self.rows()
+            .map(|row| {
+                let mut row: Vec<_> = row.into_iter().cloned().collect();
+                row.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(Ordering::Equal));
+                row
+            })
+            .collect()
+    }
+
+    /// Returns the Y position of each row returned by [`Keyboard::split_into_rows`] and
+    /// [`Keyboard::rows`], in ascending order. These are the keys' `y` positions rounded to the
+    /// nearest whole unit.
+    #[must_use]
+    pub fn row_y_positions(&self) -> Vec<T> {
+        self.row_positions()
+    }
+}
+
+// Groups values which are within tolerance of the first value in each group, returning the first
+// value of each group in ascending order. The tolerance is widened slightly so values exactly
+// tolerance apart are grouped despite rounding errors, e.g. 1.1 - 1.0 > 0.1
+fn group_positions<T: Real>(values: impl Iterator<Item = T>, tolerance: T) -> Vec<T> {
+    let mut values: Vec<_> = values.collect();
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let tolerance = tolerance + shape_epsilon();
+
+    values.into_iter().fold(Vec::new(), |mut groups, value| {
+        if groups
+            .last()
+            .map_or(true, |&start| value - start > tolerance)
+        {
+            groups.push(value);
+        }
+        groups
+    })
+}
+
+fn find_group<T: Real>(groups: &[T], value: T, tolerance: T) -> Option<usize> {
+    let tolerance = tolerance + shape_epsilon();
+    groups
+        .iter()
+        .rposition(|&start| value >= start && value - start <= tolerance)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn key(x: f64, y: f64) -> Key {
+        Key {
+            x,
+            y,
+            ..Key::default()
+        }
+    }
+
+    #[test]
+    fn test_key_grid() {
+        // 3x3 numpad-like grid with a missing key, a slightly offset key and a duplicate
+        let keyboard = Keyboard {
+            keys: vec![
+                key(0.0, 0.0),
+                key(1.0, 0.0),
+                key(2.0, 0.0),
+                key(0.0, 1.0),
+                key(2.05, 1.0),
+                key(0.0, 2.0),
+                key(1.0, 2.0),
+                key(2.0, 2.0),
+                key(2.0, 2.01),
+            ],
+            ..Keyboard::default()
+        };
+        let grid = KeyGrid::from_keyboard(&keyboard, 0.1);
+
+        assert_eq!(grid.rows(), 3);
+        assert_eq!(grid.cols(), 3);
+        assert_eq!(grid.get(0, 0), Some(&keyboard.keys[0]));
+        assert_eq!(grid.get(1, 1), None);
+        assert_eq!(grid.get(1, 2), Some(&keyboard.keys[4]));
+        assert_eq!(grid.get(2, 2), Some(&keyboard.keys[7]));
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.get(0, 3), None);
+
+        let positions: Vec<_> = grid.iter().map(|(pos, _)| pos).collect();
+        assert_eq!(
+            positions,
+            vec![
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 0),
+                (1, 2),
+                (2, 0),
+                (2, 1),
+                (2, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_key_grid_empty() {
+        let grid = KeyGrid::from_keyboard(&Keyboard::<f32>::default(), 0.1);
+
+        assert_eq!(grid.rows(), 0);
+        assert_eq!(grid.cols(), 0);
+        assert!(grid.get(0, 0).is_none());
+        assert_eq!(grid.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_matrix_collision_error_display() {
+        let error = MatrixCollisionError {
+            position: (1, 2),
+            first: 3,
+            second: 4,
+        };
+
+        assert_eq!(
+            format!("{error}"),
+            "keys 3 and 4 both snap to row 1, column 2"
+        );
+    }
+
+    #[test]
+    fn test_key_approximate_row_column() {
+        let key = key(2.4, 0.6);
+        assert_eq!(key.approximate_row(), 1);
+        assert_eq!(key.approximate_column(), 2);
+
+        let key = Key {
+            rotation: 45.0,
+            rx: 5.0,
+            ry: 5.0,
+            ..key.clone()
+        };
+        assert_eq!(key.approximate_row(), 1);
+        assert_eq!(key.approximate_column(), 2);
+
+        let key = self::key(-1.0, -0.2);
+        assert_eq!(key.approximate_row(), 0);
+        assert_eq!(key.approximate_column(), 0);
+    }
+
+    #[test]
+    fn test_keyboard_approximate_key_matrix() {
+        // Row-staggered QWERTY alpha block with Tab, Caps Lock, and Shift
+        let keyboard: Keyboard = serde_json::from_str(
+            r#"[
+                ["`", "1", "2", "3", "4"],
+                [{"w": 1.5}, "Tab", "Q", "W", "E"],
+                [{"w": 1.75}, "Caps Lock", "A", "S", "D"],
+                [{"w": 2.25}, "Shift", "Z", "X", "C"]
+            ]"#,
+        )
+        .unwrap();
+        let matrix = keyboard.approximate_key_matrix().unwrap();
+
+        assert_eq!(matrix.len(), 17);
+
+        // First key in each row
+        for (row, first) in [0, 5, 9, 13].into_iter().enumerate() {
+            assert_eq!(matrix.get(&(row, 0)), Some(&first));
+        }
+        // Q, A, and Z are all staggered to column 2
+        assert_eq!(matrix.get(&(1, 2)), Some(&6));
+        assert_eq!(matrix.get(&(2, 2)), Some(&10));
+        assert_eq!(matrix.get(&(3, 2)), Some(&14));
+        assert_eq!(matrix.get(&(1, 1)), None);
+
+        let empty = Keyboard::<f64>::default().approximate_key_matrix().unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_keyboard_approximate_key_matrix_sparse() {
+        let keyboard: Keyboard = serde_json::from_str(r#"[["A", {"x": 1e9}, "B"]]"#).unwrap();
+        let matrix = keyboard.approximate_key_matrix().unwrap();
+
+        assert_eq!(matrix.len(), 2);
+        assert_eq!(matrix.get(&(0, 0)), Some(&0));
+        assert_eq!(matrix.get(&(0, 1_000_000_001)), Some(&1));
+    }
+
+    #[test]
+    fn test_keyboard_approximate_key_matrix_collision() {
+        let keyboard = Keyboard {
+            keys: vec![key(0.0, 0.0), key(1.0, 0.0), key(1.4, 0.2)],
+            ..Keyboard::default()
+        };
+
+        assert_eq!(
+            keyboard.approximate_key_matrix(),
+            Err(MatrixCollisionError {
+                position: (0, 1),
+                first: 1,
+                second: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn test_keyboard_legend_at_position() {
+        let keyboard: Keyboard = serde_json::from_str(
+            r#"[
+                ["1", "2", "3"],
+                ["4", "5\nfive", "6"],
+                ["7", "", "9"]
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(keyboard.legend_at_position(1, 1, 0).unwrap().text, "5");
+        assert_eq!(keyboard.legend_at_position(1, 1, 6).unwrap().text, "five");
+        assert_eq!(keyboard.legend_at_position(0, 2, 0).unwrap().text, "3");
+        assert_eq!(keyboard.legend_at_position(2, 0, 0).unwrap().text, "7");
+
+        // Empty legend slots, empty keys, and positions without a key
+        assert!(keyboard.legend_at_position(1, 1, 1).is_none());
+        assert!(keyboard.legend_at_position(2, 1, 0).is_none());
+        assert!(keyboard.legend_at_position(3, 0, 0).is_none());
+        assert!(keyboard.legend_at_position(0, 3, 0).is_none());
+        assert!(keyboard.legend_at_position(0, 0, 12).is_none());
+    }
+
+    #[test]
+    fn test_keyboard_set_legend_at_position() {
+        let mut keyboard = Keyboard {
+            keys: (0..3)
+                .flat_map(|row| (0..3).map(move |col| key(f64::from(col), f64::from(row))))
+                .collect(),
+            ..Keyboard::default()
+        };
+
+        assert!(keyboard.set_legend_at_position(1, 1, 0, Legend::new("X")));
+        assert_eq!(keyboard.legend_at_position(1, 1, 0).unwrap().text, "X");
+        assert_eq!(keyboard.keys[4].legends[0].as_ref().unwrap().text, "X");
+
+        assert!(keyboard.set_legend_at_position(1, 1, 0, Legend::new("Y")));
+        assert_eq!(keyboard.legend_at_position(1, 1, 0).unwrap().text, "Y");
+
+        let before = keyboard.clone();
+        assert!(!keyboard.set_legend_at_position(3, 0, 0, Legend::new("Z")));
+        assert!(!keyboard.set_legend_at_position(0, 0, 12, Legend::new("Z")));
+        assert_eq!(keyboard, before);
+    }
+
+    #[test]
+    fn test_keyboard_split_into_rows() {
+        // Ortholinear
+        let keyboard = Keyboard {
+            keys: vec![key(1.0, 1.0), key(0.0, 1.0), key(1.0, 0.0), key(0.0, 0.0)],
+            ..Keyboard::default()
+        };
+        let rows = keyboard.split_into_rows();
+        let positions: Vec<Vec<_>> = (rows.iter())
+            .map(|row| row.iter().map(|key| (key.x, key.y)).collect())
+            .collect();
+
+        assert_eq!(
+            positions,
+            [[(0.0, 0.0), (1.0, 0.0)], [(0.0, 1.0), (1.0, 1.0)]]
+        );
+        assert_eq!(keyboard.row_y_positions(), [0.0, 1.0]);
+
+        // Staggered with a gap between rows
+        let keyboard = Keyboard {
+            keys: vec![
+                key(0.0, 0.0),
+                key(1.5, 1.5),
+                key(0.25, 1.6),
+                key(0.5, 2.4),
+                key(1.0, -0.2),
+            ],
+            ..Keyboard::default()
+        };
+        let rows = keyboard.split_into_rows();
+        let xs: Vec<Vec<_>> = (rows.iter())
+            .map(|row| row.iter().map(|key| key.x).collect())
+            .collect();
+
+        assert_eq!(xs, [vec![0.0, 1.0], vec![0.25, 0.5, 1.5]]);
+        assert_eq!(keyboard.row_y_positions(), [0.0, 2.0]);
+
+        // Rotated cluster
+        let rotated = |x, y| Key {
+            rotation: 30.0,
+            rx: 5.0,
+            ry: 0.0,
+            ..key(x, y)
+        };
+        let keyboard = Keyboard {
+            keys: vec![
+                key(0.0, 0.0),
+                rotated(5.0, 0.0),
+                rotated(5.0, 1.0),
+                key(0.0, 1.0),
+            ],
+            ..Keyboard::default()
+        };
+        let rows = keyboard.split_into_rows();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0][1], keyboard.keys[1]);
+        assert_eq!(rows[1][0], keyboard.keys[3]);
+        assert_eq!(rows[1][1], keyboard.keys[2]);
+
+        // Agrees with Keyboard::rows
+        let keyboard = Keyboard {
+            keys: vec![key(0.0, 0.1 + 0.2), key(1.0, 0.3), key(0.0, 0.6)],
+            ..Keyboard::default()
+        };
+        let rows = keyboard.split_into_rows();
+
+        assert_eq!(rows.len(), keyboard.row_count());
+        for (row, keys) in rows.iter().zip(keyboard.rows()) {
+            assert_eq!(row.len(), keys.len());
+        }
+
+        assert!(Keyboard::<f64>::default().split_into_rows().is_empty());
+        assert!(Keyboard::<f64>::default().row_y_positions().is_empty());
+    }
+
+    #[test]
+    fn test_group_positions() {
+        let groups = group_positions([0.0, 1.0, 0.05, 1.1, 3.0, 2.0].into_iter(), 0.1);
+        assert_eq!(groups, vec![0.0, 1.0, 2.0, 3.0]);
+
+        assert_eq!(find_group(&groups, 0.05, 0.1), Some(0));
+        assert_eq!(find_group(&groups, 1.1, 0.1), Some(1));
+        assert_eq!(find_group(&groups, 1.5, 0.1), None);
+        assert_eq!(find_group(&groups, -0.5, 0.1), None);
+    }
+}
@@ -0,0 +1,247 @@
+use core::hash::Hash;
+use std::collections::{HashMap, HashSet};
+
+use num_traits::real::Real;
+
+use crate::{Color, Key, Keyboard};
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Returns the set of distinct non-empty keycap profiles used in the layout.
+    #[must_use]
+    pub fn unique_profiles(&self) -> HashSet<String> {
+        (self.keys.iter())
+            .filter(|key| !key.profile.is_empty())
+            .map(|key| key.profile.clone())
+            .collect()
+    }
+
+    /// Groups the layout's keys by their keycap profile, mapping each profile to the indices of its
+    /// keys in [`Keyboard::keys`]. Keys with no profile are grouped under an empty string.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["A", {"p": "DSA"}, "B", "C"]]"#).unwrap();
+    /// let groups = keyboard.group_by_profile();
+    ///
+    /// assert_eq!(groups[""], [0]);
+    /// assert_eq!(groups["DSA"], [1, 2]);
+    /// ```
+    #[must_use]
+    pub fn group_by_profile(&self) -> HashMap<String, Vec<usize>> {
+        self.group_by(|key| key.profile.clone())
+    }
+
+    /// Returns the set of distinct non-empty switch types used in the layout.
+    #[must_use]
+    pub fn unique_switch_types(&self) -> HashSet<String> {
+        (self.keys.iter())
+            .filter(|key| !key.switch.typ.is_empty())
+            .map(|key| key.switch.typ.clone())
+            .collect()
+    }
+
+    /// Groups the layout's keys by their switch type. Keys with no switch type are grouped under
+    /// an empty string.
+    #[must_use]
+    pub fn group_by_switch_type(&self) -> HashMap<String, Vec<&Key<T>>> {
+        self.keys.iter().fold(HashMap::new(), |mut groups, key| {
+            groups.entry(key.switch.typ.clone()).or_default().push(key);
+            groups
+        })
+    }
+
+    /// Groups the layout's keys by their switch brand, mapping each brand to the indices of its
+    /// keys in [`Keyboard::keys`]. Keys with no switch brand are grouped under an empty string.
+    #[must_use]
+    pub fn group_by_switch_brand(&self) -> HashMap<String, Vec<usize>> {
+        self.group_by(|key| key.switch.brand.clone())
+    }
+
+    /// Returns the set of distinct key colours used in the layout.
+    #[must_use]
+    pub fn unique_key_colors(&self) -> HashSet<Color> {
+        self.keys.iter().map(|key| key.color).collect()
+    }
+
+    /// Returns the set of distinct legend colours used in the layout.
+    #[must_use]
+    pub fn unique_legend_colors(&self) -> HashSet<Color> {
+        (self.keys.iter())
+            .flat_map(Key::present_legends)
+            .map(|legend| legend.color)
+            .collect()
+    }
+
+    /// Groups the layout's keys by their colour, mapping each colour to the indices of its keys in
+    /// [`Keyboard::keys`].
+    ///
+    /// ```
+    /// use kle_serial::{Color, Keyboard};
+    ///
+    /// let keyboard: Keyboard =
+    ///     serde_json::from_str(r##"[["A", {"c": "#ff0000"}, "B", {"c": "#cccccc"}, "C"]]"##).unwrap();
+    /// let groups = keyboard.group_by_color();
+    ///
+    /// assert_eq!(groups[&Color::new(204, 204, 204, 255)], [0, 2]);
+    /// assert_eq!(groups[&Color::new(255, 0, 0, 255)], [1]);
+    /// ```
+    #[must_use]
+    pub fn group_by_color(&self) -> HashMap<Color, Vec<usize>> {
+        self.group_by(|key| key.color)
+    }
+
+    /// Groups the layout's legends by their colour, mapping each colour to a list of
+    /// `(key_index, legend_index)` pairs. These are the indices of the key in [`Keyboard::keys`]
+    /// and of the legend in [`Key::legends`] respectively.
+    #[must_use]
+    pub fn group_by_legend_color(&self) -> HashMap<Color, Vec<(usize, usize)>> {
+        let legends = (self.keys.iter().enumerate()).flat_map(|(key_index, key)| {
+            (key.legends_iter())
+                .map(move |(legend_index, legend)| (legend.color, (key_index, legend_index)))
+        });
+
+        legends.fold(HashMap::new(), |mut groups, (color, indices)| {
+            groups.entry(color).or_insert_with(Vec::new).push(indices);
+            groups
+        })
+    }
+
+    fn group_by<K>(&self, f: impl Fn(&Key<T>) -> K) -> HashMap<K, Vec<usize>>
+    where
+        K: Eq + Hash,
+    {
+        (self.keys.iter().enumerate()).fold(HashMap::new(), |mut groups, (i, key)| {
+            groups.entry(f(key)).or_insert_with(Vec::new).push(i);
+            groups
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Legend, Switch};
+
+    use super::*;
+
+    fn keyboard() -> Keyboard<f64> {
+        let key = |profile: &str, typ: &str, color: Color| Key {
+            profile: profile.into(),
+            switch: Switch {
+                typ: typ.into(),
+                ..Switch::default()
+            },
+            color,
+            ..Key::default()
+        };
+        let red = Color::new(255, 0, 0, 255);
+        let blue = Color::new(0, 0, 255, 255);
+
+        let mut keys = vec![
+            key("DSA", "MX1A-11xx", red),
+            key("DSA", "MX1A-L1xx", blue),
+            key("SA R1", "MX1A-11xx", red),
+            key("", "", blue),
+        ];
+        keys[0].legends[0] = Some(Legend {
+            color: red,
+            ..Legend::default()
+        });
+        keys[1].legends[4] = Some(Legend::default());
+        keys[2].legends[8] = Some(Legend::default());
+
+        Keyboard {
+            keys,
+            ..Keyboard::default()
+        }
+    }
+
+    #[test]
+    fn test_keyboard_unique_profiles() {
+        let profiles = keyboard().unique_profiles();
+
+        assert_eq!(profiles, HashSet::from(["DSA".into(), "SA R1".into()]));
+        assert!(Keyboard::<f64>::default().unique_profiles().is_empty());
+    }
+
+    #[test]
+    fn test_keyboard_group_by_profile() {
+        let keyboard = keyboard();
+        let groups = keyboard.group_by_profile();
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups["DSA"], [0, 1]);
+        assert_eq!(groups["SA R1"], [2]);
+        assert_eq!(groups[""], [3]);
+    }
+
+    #[test]
+    fn test_keyboard_unique_switch_types() {
+        let types = keyboard().unique_switch_types();
+
+        assert_eq!(
+            types,
+            HashSet::from(["MX1A-11xx".into(), "MX1A-L1xx".into()])
+        );
+    }
+
+    #[test]
+    fn test_keyboard_group_by_switch_type() {
+        let keyboard = keyboard();
+        let groups = keyboard.group_by_switch_type();
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups["MX1A-11xx"].len(), 2);
+        assert!(std::ptr::eq(groups["MX1A-11xx"][0], &keyboard.keys[0]));
+        assert!(std::ptr::eq(groups["MX1A-11xx"][1], &keyboard.keys[2]));
+        assert!(std::ptr::eq(groups["MX1A-L1xx"][0], &keyboard.keys[1]));
+        assert!(std::ptr::eq(groups[""][0], &keyboard.keys[3]));
+    }
+
+    #[test]
+    fn test_keyboard_group_by_switch_brand() {
+        let mut keyboard = keyboard();
+        keyboard.keys[1].switch.brand = "cherry".into();
+        let groups = keyboard.group_by_switch_brand();
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups["cherry"], [1]);
+        assert_eq!(groups[""], [0, 2, 3]);
+    }
+
+    #[test]
+    fn test_keyboard_group_by_color() {
+        let groups = keyboard().group_by_color();
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[&Color::new(255, 0, 0, 255)], [0, 2]);
+        assert_eq!(groups[&Color::new(0, 0, 255, 255)], [1, 3]);
+        assert!(Keyboard::<f64>::default().group_by_color().is_empty());
+    }
+
+    #[test]
+    fn test_keyboard_group_by_legend_color() {
+        let groups = keyboard().group_by_legend_color();
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[&Color::new(255, 0, 0, 255)], [(0, 0)]);
+        assert_eq!(groups[&Legend::default().color], [(1, 4), (2, 8)]);
+    }
+
+    #[test]
+    fn test_keyboard_unique_colors() {
+        let keyboard = keyboard();
+
+        assert_eq!(
+            keyboard.unique_key_colors(),
+            HashSet::from([Color::new(255, 0, 0, 255), Color::new(0, 0, 255, 255)])
+        );
+        assert_eq!(
+            keyboard.unique_legend_colors(),
+            HashSet::from([Color::new(255, 0, 0, 255), Legend::default().color])
+        );
+    }
+}
@@ -0,0 +1,912 @@
+use alloc::string::String;
+use core::fmt;
+use core::str::FromStr;
+
+use num_traits::real::Real;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::de::{KleKeyboard, KleLayoutIterator, KleLegendsOrProps, KleMetadata, KlePropsObject};
+use crate::ser::normalize_layout;
+use crate::{Keyboard, Metadata};
+
+/// The location of a key within a KLE layout.
+///
+/// Both indices are zero-based. The row index does not include the metadata object, and the key
+/// index counts every item in the row, including property objects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyLocation {
+    /// The index of the row.
+    pub row: usize,
+    /// The index of the item within the row.
+    pub column_in_row: usize,
+}
+
+impl fmt::Display for KeyLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "row {}, key {}", self.row, self.column_in_row)
+    }
+}
+
+/// An error returned when deserialising a [`Keyboard`] using [`Keyboard::from_str`] or
+/// [`Keyboard::from_reader`].
+///
+/// This wraps a [`serde_json::Error`] along with the location of the key that caused it, if the
+/// error could be attributed to a single key.
+#[derive(Debug)]
+pub struct ParseError {
+    error: serde_json::Error,
+    location: Option<KeyLocation>,
+}
+
+impl ParseError {
+    /// Returns the location of the key that caused the error, if known.
+    #[inline]
+    #[must_use]
+    pub fn location(&self) -> Option<KeyLocation> {
+        self.location
+    }
+
+    /// Returns the underlying [`serde_json::Error`].
+    #[inline]
+    #[must_use]
+    pub fn json_error(&self) -> &serde_json::Error {
+        &self.error
+    }
+
+    /// Converts this into the underlying [`serde_json::Error`].
+    #[inline]
+    #[must_use]
+    pub fn into_json_error(self) -> serde_json::Error {
+        self.error
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some(location) => write!(f, "error at {location}: {}", self.error),
+            None => write!(f, "{}", self.error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<serde_json::Error> for ParseError {
+    fn from(error: serde_json::Error) -> Self {
+        Self {
+            error,
+            location: None,
+        }
+    }
+}
+
+/// An error returned when loading a [`Keyboard`] from a file using [`Keyboard::from_path`].
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum FileError {
+    /// The file could not be opened or read.
+    Io(std::io::Error),
+    /// The file's contents are not a valid KLE layout.
+    Parse(ParseError),
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "error reading file: {error}"),
+            Self::Parse(error) => write!(f, "error parsing file: {error}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Parse(error) => Some(error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for FileError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<ParseError> for FileError {
+    fn from(error: ParseError) -> Self {
+        // Read errors are reported by serde_json, so convert them back into I/O errors
+        if error.error.is_io() {
+            Self::Io(error.error.into())
+        } else {
+            Self::Parse(error)
+        }
+    }
+}
+
+// Finds the first key in the layout which fails to deserialise along with its error
+fn locate_error<T>(value: &Value) -> Option<(KeyLocation, serde_json::Error)>
+where
+    T: Real + DeserializeOwned,
+{
+    let items = value.as_array()?;
+    let skip = usize::from(items.first().map_or(false, Value::is_object));
+
+    (items.iter().skip(skip).enumerate())
+        .filter_map(|(row, keys)| Some((row, keys.as_array()?)))
+        .flat_map(|(row, keys)| {
+            keys.iter()
+                .enumerate()
+                .map(move |(column_in_row, key)| (KeyLocation { row, column_in_row }, key))
+        })
+        .find_map(|(location, key)| {
+            // Deserialise props directly to get a more specific error than the untagged enum gives
+            let result = if key.is_object() {
+                KlePropsObject::<T>::deserialize(key).map(drop)
+            } else {
+                KleLegendsOrProps::<T>::deserialize(key).map(drop)
+            };
+            result.err().map(|error| (location, error))
+        })
+}
+
+// Rewrites any whole floating point numbers as integers, recursing into arrays and objects
+fn compact_numbers(value: &mut Value) {
+    match value {
+        Value::Number(number) => {
+            // Only convert numbers which can be represented exactly, larger values are left as is
+            const MAX_EXACT: f64 = 9_007_199_254_740_992.0; // 2^53
+            if let Some(float) = number.as_f64().filter(|_| number.is_f64()) {
+                if float.fract() == 0.0 && float.abs() <= MAX_EXACT {
+                    #[allow(clippy::cast_possible_truncation)] // We check the range above
+                    let int = float as i64;
+                    *number = int.into();
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(compact_numbers),
+        Value::Object(map) => map.values_mut().for_each(compact_numbers),
+        Value::Null | Value::Bool(_) | Value::String(_) => {}
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real + DeserializeOwned,
+{
+    /// Deserialises a [`Keyboard`] from a reader containing KLE JSON data.
+    ///
+    /// This works the same as [`serde_json::from_reader`], but any error will include the location
+    /// of the key that caused it where possible.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseError`] if reading fails or the data is not a valid KLE layout.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R>(mut reader: R) -> Result<Self, ParseError>
+    where
+        R: std::io::Read,
+    {
+        // Read everything up front so the data can be parsed again to locate any error
+        let mut bytes = alloc::vec::Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(serde_json::Error::io)?;
+        Self::from_slice(&bytes)
+    }
+
+    /// Loads a [`Keyboard`] from a KLE JSON file.
+    ///
+    /// ```no_run
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard = Keyboard::<f64>::from_path("layout.json").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`FileError::Io`] if the file cannot be opened or read, or a [`FileError::Parse`]
+    /// if it does not contain a valid KLE layout.
+    #[cfg(feature = "std")]
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<Self, FileError> {
+        let file = std::fs::File::open(path)?;
+        Ok(Self::from_reader(std::io::BufReader::new(file))?)
+    }
+
+    /// Deserialises a [`Keyboard`] from a [`serde_json::Value`] containing a KLE layout. This is
+    /// the inverse of [`Keyboard::into_kle_value`].
+    ///
+    /// This is equivalent to [`serde_json::from_value`] or [`Keyboard::try_from`], but can be
+    /// called directly on the [`f32::Keyboard`](crate::f32::Keyboard) and
+    /// [`f64::Keyboard`](crate::f64::Keyboard) aliases without naming any trait bounds.
+    ///
+    /// ```
+    /// use kle_serial::f64::Keyboard;
+    /// use serde_json::json;
+    ///
+    /// let keyboard = Keyboard::from_kle_value(json!([["A", "B"]])).unwrap();
+    ///
+    /// assert_eq!(keyboard.keys.len(), 2);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`serde_json::Error`] if the value is not a valid KLE layout.
+    pub fn from_kle_value(value: Value) -> Result<Self, serde_json::Error> {
+        serde_json::from_value(value)
+    }
+
+    fn from_slice(bytes: &[u8]) -> Result<Self, ParseError> {
+        serde_json::from_slice(bytes).map_err(|error| {
+            // Only build a Value to locate the error once we know deserialising has failed
+            match serde_json::from_slice::<Value>(bytes) {
+                Ok(value) => Self::locate_error(error, &value),
+                Err(_) => ParseError::from(error),
+            }
+        })
+    }
+
+    fn locate_error(error: serde_json::Error, value: &Value) -> ParseError {
+        match locate_error::<T>(value) {
+            Some((location, error)) => ParseError {
+                error,
+                location: Some(location),
+            },
+            None => ParseError::from(error),
+        }
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real + Serialize,
+{
+    /// Serialises the layout into a compact KLE JSON string.
+    ///
+    /// Like the [`Serialize`] implementation, only properties which differ from what KLE would
+    /// otherwise infer from the previous keys are emitted, so per-key properties matching their
+    /// defaults are omitted and persistent properties such as colours and font sizes are only
+    /// written when they change. In addition, whole numbers are written without a fractional part
+    /// (`2` rather than `2.0`) as KLE itself does, and no whitespace is added. The result can be
+    /// deserialised back into an equal [`Keyboard`].
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = r##"[[{"c": "#ff0000", "w": 2}, "A", {"c": "#ff0000"}, "B"]]"##
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     keyboard.to_compact_json().unwrap(),
+    ///     r##"[[{"c":"#ff0000","w":2},"A","B"]]"##
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`serde_json::Error`] if the layout cannot be serialised, for example if a
+    /// coordinate type's [`Serialize`] implementation fails.
+    pub fn to_compact_json(&self) -> Result<String, serde_json::Error> {
+        let mut value = serde_json::to_value(self)?;
+        compact_numbers(&mut value);
+        serde_json::to_string(&value)
+    }
+
+    /// Serialises the layout into a [`serde_json::Value`] in KLE's JSON format.
+    ///
+    /// This is useful for embedding a layout in a larger JSON document without serialising it to
+    /// a string first. The result is always a [`Value::Array`].
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    /// use serde_json::json;
+    ///
+    /// let keyboard: Keyboard = r#"[["A", "B"]]"#.parse().unwrap();
+    /// let config = json!({ "layout": keyboard.to_kle_value() });
+    ///
+    /// assert_eq!(config, json!({ "layout": [["A", "B"]] }));
+    /// ```
+    #[must_use]
+    pub fn to_kle_value(&self) -> Value {
+        // Serialising a Keyboard never fails
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    /// Consumes the layout and serialises it into a [`serde_json::Value`] in KLE's JSON format.
+    /// See [`Keyboard::to_kle_value`].
+    #[must_use]
+    pub fn into_kle_value(self) -> Value {
+        self.to_kle_value()
+    }
+
+    /// Returns a copy of the layout rebuilt from the fewest KLE properties needed to describe it.
+    ///
+    /// This encodes the layout in KLE's format, applies the same normalisation as
+    /// [`normalize_kle_json`], and decodes it again. The normalisation is lossless, so the result
+    /// is always equal to the original layout. A [`Keyboard`] only stores the resolved properties
+    /// of each key, so any redundant properties in the file it was read from are already gone; use
+    /// [`normalize_kle_json`] to shrink a KLE JSON layout directly.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let bloated = r##"[[{"a": 4}, {"c": "#cccccc"}, "A", {"x": 0}, "B"]]"##;
+    /// let keyboard: Keyboard = bloated.parse().unwrap();
+    /// let normalized = keyboard.normalize_props();
+    ///
+    /// assert_eq!(normalized, keyboard);
+    /// assert_eq!(normalized.to_compact_json().unwrap(), r#"[["A","B"]]"#);
+    /// ```
+    #[must_use]
+    pub fn normalize_props(&self) -> Self {
+        let KleKeyboard { layout, .. } = KleKeyboard::from(self);
+
+        Self {
+            metadata: self.metadata.clone(),
+            keys: KleLayoutIterator::new(normalize_layout(layout)).collect(),
+        }
+    }
+}
+
+impl<T> FromStr for Keyboard<T>
+where
+    T: Real + DeserializeOwned,
+{
+    type Err = ParseError;
+
+    /// Deserialises a [`Keyboard`] from a string containing KLE JSON data.
+    ///
+    /// This works the same as [`serde_json::from_str`], but any error will include the location of
+    /// the key that caused it where possible.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_slice(s.as_bytes())
+    }
+}
+
+impl<T> TryFrom<Value> for Keyboard<T>
+where
+    T: Real + DeserializeOwned,
+{
+    type Error = serde_json::Error;
+
+    /// Deserialises a [`Keyboard`] from a [`serde_json::Value`].
+    ///
+    /// This is equivalent to using [`serde_json::from_value`].
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        serde_json::from_value(value)
+    }
+}
+
+impl<'a, T> TryFrom<&'a Value> for Keyboard<T>
+where
+    T: Real + Deserialize<'a>,
+{
+    type Error = serde_json::Error;
+
+    /// Deserialises a [`Keyboard`] from a reference to a [`serde_json::Value`].
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        Self::deserialize(value)
+    }
+}
+
+impl<T> From<Keyboard<T>> for Value
+where
+    T: Real + Serialize,
+{
+    /// Serialises a [`Keyboard`] into a [`serde_json::Value`] in KLE's JSON format.
+    ///
+    /// This is equivalent to [`Keyboard::to_kle_value`].
+    fn from(value: Keyboard<T>) -> Self {
+        value.to_kle_value()
+    }
+}
+
+impl Metadata {
+    /// Serialises the metadata into the JSON object used as the first element of a KLE layout.
+    ///
+    /// Keys use KLE's names (such as `backcolor` and `switchMount`) and fields which match their
+    /// default values are omitted.
+    ///
+    /// ```
+    /// use kle_serial::Metadata;
+    ///
+    /// let metadata = Metadata {
+    ///     name: "example".into(),
+    ///     ..Metadata::default()
+    /// };
+    /// let map = metadata.to_kle_map();
+    ///
+    /// assert_eq!(map.len(), 1);
+    /// assert_eq!(map["name"], "example");
+    /// ```
+    #[must_use]
+    pub fn to_kle_map(&self) -> Map<String, Value> {
+        // Serialising Metadata never fails and always produces an object
+        match serde_json::to_value(self) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        }
+    }
+
+    /// Deserialises metadata from a JSON object in the format used as the first element of a KLE
+    /// layout. This is the inverse of [`Metadata::to_kle_map`].
+    ///
+    /// Missing fields are set to their default values and unknown fields are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`serde_json::Error`] if any field has an invalid value.
+    pub fn from_kle_map(map: &Map<String, Value>) -> Result<Self, serde_json::Error> {
+        serde_json::from_value::<KleMetadata>(Value::Object(map.clone())).map(Self::from)
+    }
+}
+
+/// Rewrites a KLE JSON layout using the fewest properties needed to describe the same keys.
+///
+/// KLE files edited repeatedly tend to accumulate redundant property objects, such as objects
+/// which only set a default value, several consecutive objects before a single key, or `x` offsets
+/// which only undo KLE's reset to `rx` at the start of each row. This drops any properties which
+/// match KLE's current state, merges consecutive property objects into one, and writes the result
+/// in the same compact format as [`Keyboard::to_compact_json`]. Rows are kept as they are.
+///
+/// The normalisation is lossless, so the result deserialises to a [`Keyboard`] equal to the one
+/// the original layout deserialises to. There is no need to normalise a layout before serialising
+/// a [`Keyboard`], as [`Keyboard`] only stores the resolved properties of each key and only
+/// properties which change are written when serialising.
+///
+/// ```
+/// use kle_serial::{normalize_kle_json, Keyboard};
+///
+/// let bloated = r##"[{"name": "example"}, [{"a": 4}, {"c": "#cccccc"}, "A", {"x": 0}, "B"]]"##;
+/// let normalized = normalize_kle_json(bloated).unwrap();
+///
+/// assert_eq!(normalized, r#"[{"name":"example"},["A","B"]]"#);
+/// assert_eq!(
+///     normalized.parse::<Keyboard>().unwrap(),
+///     bloated.parse::<Keyboard>().unwrap()
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Returns a [`serde_json::Error`] if the input is not a valid KLE layout.
+pub fn normalize_kle_json(json: &str) -> Result<String, serde_json::Error> {
+    let KleKeyboard { meta, layout } = serde_json::from_str::<KleKeyboard<f64>>(json)?;
+    let mut value = serde_json::to_value(KleKeyboard {
+        meta,
+        layout: normalize_layout(layout),
+    })?;
+    compact_numbers(&mut value);
+    serde_json::to_string(&value)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{boxed::Box, vec::Vec};
+
+    use isclose::assert_is_close;
+    use serde_json::json;
+
+    use crate::de::KleProps;
+    use crate::{Alignment, Color, FontSize, Switch};
+
+    use super::*;
+
+    const LAYOUT: &str = r#"[{"name": "test"}, ["A", "B"], ["C"]]"#;
+
+    // Standard 104 key ANSI layout
+    const ANSI_LAYOUT: &str = r##"[
+        {"name": "ANSI 104"},
+        ["Esc", {"x": 1}, "F1", "F2", "F3", "F4", {"x": 0.5}, "F5", "F6", "F7", "F8",
+            {"x": 0.5}, "F9", "F10", "F11", "F12", {"x": 0.25}, "PrtSc", "Scroll Lock",
+            "Pause\nBreak"],
+        [{"y": 0.5}, "~\n`", "!\n1", "@\n2", "#\n3", "$\n4", "%\n5", "^\n6", "&\n7",
+            "*\n8", "(\n9", ")\n0", "_\n-", "+\n=", {"w": 2}, "Backspace", {"x": 0.25},
+            "Insert", "Home", "PgUp", {"x": 0.25}, "Num Lock", "/", "*", "-"],
+        [{"w": 1.5}, "Tab", "Q", "W", "E", "R", "T", "Y", "U", "I", "O", "P", "{\n[",
+            "}\n]", {"w": 1.5}, "|\n\\", {"x": 0.25}, "Delete", "End", "PgDn",
+            {"x": 0.25}, "7\nHome", "8\n↑", "9\nPgUp", {"h": 2}, "+"],
+        [{"w": 1.75}, "Caps Lock", "A", "S", "D", "F", "G", "H", "J", "K", "L", ":\n;",
+            "\"\n'", {"w": 2.25}, "Enter", {"x": 3.5}, "4\n←", "5", "6\n→"],
+        [{"w": 2.25}, "Shift", "Z", "X", "C", "V", "B", "N", "M", "<\n,", ">\n.", "?\n/",
+            {"w": 2.75}, "Shift", {"x": 1.25}, "↑", {"x": 1.25}, "1\nEnd", "2\n↓",
+            "3\nPgDn", {"h": 2}, "Enter"],
+        [{"w": 1.25}, "Ctrl", {"w": 1.25}, "Win", {"w": 1.25}, "Alt", {"w": 6.25}, "",
+            {"w": 1.25}, "Alt", {"w": 1.25}, "Win", {"w": 1.25}, "Menu", {"w": 1.25}, "Ctrl",
+            {"x": 0.25}, "←", "↓", "→", {"x": 0.25, "w": 2}, "0\nIns", ".\nDel"]
+    ]"##;
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_keyboard_from_reader() {
+        struct FailingReader;
+
+        impl std::io::Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::Other.into())
+            }
+        }
+
+        let keyboard = Keyboard::<f64>::from_reader(LAYOUT.as_bytes()).unwrap();
+
+        assert_eq!(keyboard.metadata.name, "test");
+        assert_eq!(keyboard.keys.len(), 3);
+
+        let result = Keyboard::<f32>::from_reader(&b"{"[..]);
+
+        assert!(result.is_err());
+
+        let error = Keyboard::<f64>::from_reader(&br#"[["A", {"w": "x"}]]"#[..]).unwrap_err();
+
+        assert_eq!(
+            error.location(),
+            Some(KeyLocation {
+                row: 0,
+                column_in_row: 1
+            })
+        );
+
+        let error = Keyboard::<f64>::from_reader(FailingReader).unwrap_err();
+
+        assert!(error.json_error().is_io());
+        assert!(matches!(FileError::from(error), FileError::Io(_)));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_keyboard_from_path() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("kle-serial-test-{}.json", std::process::id()));
+
+        std::fs::write(&path, LAYOUT).unwrap();
+        let keyboard = Keyboard::<f64>::from_path(&path);
+        let keyboard_f32 = crate::f32::Keyboard::from_path(&path);
+
+        std::fs::write(&path, "[[").unwrap();
+        let malformed = Keyboard::<f64>::from_path(&path);
+
+        std::fs::remove_file(&path).unwrap();
+        let missing = Keyboard::<f64>::from_path(&path);
+
+        assert_eq!(keyboard.unwrap().keys.len(), 3);
+        assert_eq!(keyboard_f32.unwrap().metadata.name, "test");
+        assert!(matches!(malformed, Err(FileError::Parse(_))));
+        assert!(matches!(missing, Err(FileError::Io(_))));
+        assert!(format!("{}", missing.unwrap_err()).starts_with("error reading file: "));
+    }
+
+    #[test]
+    fn test_keyboard_from_str() {
+        let keyboard = Keyboard::<f32>::from_str(LAYOUT).unwrap();
+
+        assert_eq!(keyboard.metadata.name, "test");
+        assert_eq!(keyboard.keys.len(), 3);
+
+        let keyboard: Keyboard = LAYOUT.parse().unwrap();
+
+        assert_eq!(keyboard.keys.len(), 3);
+
+        let result = Keyboard::<f64>::from_str("null");
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_key_location_display() {
+        let location = KeyLocation {
+            row: 3,
+            column_in_row: 2,
+        };
+
+        assert_eq!(format!("{location}"), "row 3, key 2");
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = Keyboard::<f64>::from_str(
+            r#"[{"name": "test"}, ["A"], ["B", {"c": "notacolor"}, "C"]]"#,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.location(),
+            Some(KeyLocation {
+                row: 1,
+                column_in_row: 1
+            })
+        );
+        assert!(format!("{error}").starts_with("error at row 1, key 1: "));
+        assert!(format!("{error}").contains("notacolor"));
+
+        let error = Keyboard::<f64>::from_str(r#"[["A", {"f": 12}]]"#).unwrap_err();
+
+        assert_eq!(
+            error.location(),
+            Some(KeyLocation {
+                row: 0,
+                column_in_row: 1
+            })
+        );
+
+        let error = Keyboard::<f64>::from_str(r#"[["A", 5]]"#).unwrap_err();
+
+        assert_eq!(
+            error.location(),
+            Some(KeyLocation {
+                row: 0,
+                column_in_row: 1
+            })
+        );
+
+        let error = Keyboard::<f64>::from_str(r#"[{"name": 5}, ["A"]]"#).unwrap_err();
+
+        assert_eq!(error.location(), None);
+        assert!(!format!("{error}").starts_with("error at"));
+
+        let error = Keyboard::<f64>::from_str("[").unwrap_err();
+
+        assert_eq!(error.location(), None);
+        assert!(error.json_error().is_eof());
+        assert!(error.into_json_error().is_eof());
+    }
+
+    #[test]
+    fn test_keyboard_try_from_value() {
+        let value = json!([{"name": "test"}, ["A", "B"], [{"w": 2}, "C"]]);
+
+        let keyboard = Keyboard::<f64>::try_from(&value).unwrap();
+
+        assert_eq!(keyboard.metadata.name, "test");
+        assert_eq!(keyboard.keys.len(), 3);
+
+        let keyboard = Keyboard::<f32>::try_from(value).unwrap();
+
+        assert_eq!(keyboard.metadata.name, "test");
+        assert_eq!(keyboard.keys.len(), 3);
+
+        let result = Keyboard::<f64>::try_from(json!({"name": "test"}));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_keyboard_to_kle_value() {
+        for layout in [LAYOUT, ANSI_LAYOUT] {
+            let keyboard: Keyboard = layout.parse().unwrap();
+            let value = keyboard.to_kle_value();
+
+            assert!(value.is_array());
+            assert_eq!(serde_json::from_value::<Keyboard>(value).unwrap(), keyboard);
+        }
+
+        let keyboard: Keyboard = LAYOUT.parse().unwrap();
+        assert_eq!(
+            keyboard.to_kle_value(),
+            json!([{"name": "test"}, ["A", "B"], ["C"]])
+        );
+    }
+
+    #[test]
+    fn test_keyboard_from_kle_value() {
+        let value = json!([{"name": "test"}, ["A", "B"], [{"w": 2}, "C"]]);
+
+        let keyboard = crate::f64::Keyboard::from_kle_value(value.clone()).unwrap();
+        assert_eq!(keyboard.metadata.name, "test");
+        assert_eq!(keyboard.keys.len(), 3);
+        assert_is_close!(keyboard.keys[2].width, 2.0);
+
+        let keyboard = crate::f32::Keyboard::from_kle_value(value).unwrap();
+        assert_eq!(keyboard.keys.len(), 3);
+        assert_is_close!(keyboard.keys[2].width, 2.0);
+
+        assert!(Keyboard::<f64>::from_kle_value(json!({"name": "test"})).is_err());
+        assert!(Keyboard::<f64>::from_kle_value(json!([[{"w": "x"}, "A"]])).is_err());
+    }
+
+    #[test]
+    fn test_keyboard_into_kle_value() {
+        for layout in [LAYOUT, ANSI_LAYOUT] {
+            let keyboard: Keyboard = layout.parse().unwrap();
+            let value = keyboard.clone().into_kle_value();
+
+            assert_eq!(value, keyboard.to_kle_value());
+            assert_eq!(Keyboard::from_kle_value(value).unwrap(), keyboard);
+        }
+
+        let keyboard: crate::f32::Keyboard = LAYOUT.parse().unwrap();
+        let round_trip = crate::f32::Keyboard::from_kle_value(keyboard.clone().into_kle_value());
+        assert_eq!(round_trip.unwrap(), keyboard);
+    }
+
+    #[test]
+    fn test_value_from_keyboard() {
+        let keyboard: Keyboard = LAYOUT.parse().unwrap();
+        let value = Value::from(keyboard);
+
+        assert_eq!(value, json!([{"name": "test"}, ["A", "B"], ["C"]]));
+    }
+
+    #[test]
+    fn test_metadata_to_kle_map() {
+        assert!(Metadata::default().to_kle_map().is_empty());
+
+        let metadata = Metadata {
+            background_color: Color::new(0x22, 0x22, 0x22, 0xFF),
+            name: "test".into(),
+            author: "me".into(),
+            switch: Switch {
+                mount: "alps".into(),
+                brand: "alps".into(),
+                typ: "SKCM Blue".into(),
+            },
+            pcb_mount: true,
+            ..Metadata::default()
+        };
+        let map = metadata.to_kle_map();
+
+        assert_eq!(
+            Value::Object(map),
+            json!({
+                "backcolor": "#222222",
+                "name": "test",
+                "author": "me",
+                "switchMount": "alps",
+                "switchBrand": "alps",
+                "switchType": "SKCM Blue",
+                "pcb": true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_metadata_from_kle_map() {
+        let value = json!({
+            "backcolor": "#222222",
+            "name": "test",
+            "switchMount": "alps",
+            "plate": true,
+            "unknown": 42,
+        });
+        let metadata = Metadata::from_kle_map(value.as_object().unwrap()).unwrap();
+
+        assert_eq!(
+            metadata.background_color,
+            Color::new(0x22, 0x22, 0x22, 0xFF)
+        );
+        assert_eq!(metadata.name, "test");
+        assert_eq!(metadata.switch.mount, "alps");
+        assert!(metadata.plate_mount);
+        assert_eq!(metadata.author, Metadata::default().author);
+
+        let round_trip = Metadata::from_kle_map(&metadata.to_kle_map()).unwrap();
+        assert_eq!(round_trip, metadata);
+
+        assert_eq!(
+            Metadata::from_kle_map(&Map::new()).unwrap(),
+            Metadata::default()
+        );
+
+        let value = json!({"name": 42});
+        assert!(Metadata::from_kle_map(value.as_object().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_keyboard_to_compact_json() {
+        let keyboard: Keyboard = ANSI_LAYOUT.parse().unwrap();
+        assert_eq!(keyboard.keys.len(), 104);
+
+        let compact = keyboard.to_compact_json().unwrap();
+        assert!(!compact.contains(": "));
+        assert!(!compact.contains(".0,") && !compact.contains(".0}"));
+        assert!(compact.len() < serde_json::to_string(&keyboard).unwrap().len());
+        assert!(!compact.contains('\n'));
+        assert_eq!(compact.parse::<Keyboard>().unwrap(), keyboard);
+
+        // A naive serialisation which emits the size, colour, alignment, and font size of every
+        // key rather than only the properties that changed
+        let mut state = KleProps::default();
+        let mut layout = Vec::new();
+        let mut row = Vec::new();
+        for key in &keyboard.keys {
+            if !row.is_empty() && key.starts_new_row(&state) {
+                layout.push(core::mem::take(&mut row));
+                state.next_line();
+            }
+            let legends = (key.legends.iter())
+                .map(|l| l.as_ref().map_or("", |l| l.text.as_str()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let props = KlePropsObject {
+                w: Some(key.width),
+                h: Some(key.height),
+                c: Some(key.color),
+                a: Some(Alignment::new(0).unwrap()),
+                f: Some(FontSize::default()),
+                ..key.to_props_delta(&state)
+            };
+            state.update(props.clone());
+            row.push(KleLegendsOrProps::Props(Box::new(props)));
+            row.push(KleLegendsOrProps::Legend(legends));
+            state.next_key();
+        }
+        layout.push(row);
+        let naive = serde_json::to_string(&layout).unwrap();
+
+        // The compact form should be at least 30% smaller
+        assert!(compact.len() * 10 < naive.len() * 7);
+    }
+
+    #[test]
+    fn test_compact_numbers() {
+        let mut value = json!([{"w": 2.0, "x": -0.5, "y": -3.0, "h": 1e300, "n": 7}, ["1.0"]]);
+        compact_numbers(&mut value);
+
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"[{"h":1e+300,"n":7,"w":2,"x":-0.5,"y":-3},["1.0"]]"#
+        );
+    }
+
+    #[test]
+    fn test_keyboard_normalize_props() {
+        let bloated = r##"[
+            {"name": "test"},
+            [{"a": 4, "rx": 0, "x": 0}, "Esc", {"c": "#cccccc"}, {"a": 4, "x": 0}, "F1"],
+            [{"rx": 3, "ry": 2, "x": -3}, "A", {"c": "#cccccc", "t": "#000000"}, "S"]
+        ]"##;
+        let keyboard: Keyboard = bloated.parse().unwrap();
+        let normalized = keyboard.normalize_props();
+
+        assert_eq!(normalized, keyboard);
+        assert_eq!(
+            normalized.to_compact_json().unwrap(),
+            normalize_kle_json(bloated).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_normalize_kle_json() {
+        // Redundant properties left behind by editing in KLE: defaults set explicitly, several
+        // props objects before one key, and x offsets undoing the reset to rx = 0
+        let bloated = r##"[
+            {"name": "test"},
+            [{"a": 4, "rx": 0, "x": 0}, "Esc", {"c": "#cccccc"}, {"a": 4, "x": 0}, "F1",
+                {"f": 3}, {"t": "#000000"}, "F2"],
+            [{"r": 0, "rx": 0, "ry": 1, "y": 0}, "Q", {"x": 0}, "W", {"a": 4}, "E"],
+            [{"rx": 3, "ry": 2, "x": -3}, "A", {"c": "#cccccc", "t": "#000000"}, "S", "D"],
+            [{"x": -3}, "Z", {"f": 3}, "X", "C"]
+        ]"##;
+        let normalized = normalize_kle_json(bloated).unwrap();
+
+        assert_eq!(
+            normalized,
+            r#"[{"name":"test"},["Esc","F1","F2"],[{"ry":1},"Q","W","E"],[{"rx":3,"ry":2,"x":-3},"A","S","D"],[{"x":-3},"Z","X","C"]]"#
+        );
+        assert_eq!(
+            normalized.parse::<Keyboard>().unwrap(),
+            bloated.parse::<Keyboard>().unwrap()
+        );
+
+        // The result should be less than half the size of the original
+        let bloated_len = serde_json::to_string(&serde_json::from_str::<Value>(bloated).unwrap())
+            .unwrap()
+            .len();
+        assert!(normalized.len() * 2 < bloated_len);
+
+        assert!(normalize_kle_json("{").is_err());
+        assert!(normalize_kle_json(r#"[["A", {"w": "x"}]]"#).is_err());
+    }
+}
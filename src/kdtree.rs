@@ -0,0 +1,200 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use num_traits::real::Real;
+
+use crate::Keyboard;
+
+/// A 2D k-d tree of key centres, used for repeated nearest key queries.
+///
+/// This is created using [`Keyboard::kd_tree`] and stores the [centre](crate::Key::center_point)
+/// and index of each key in the layout. It does not borrow the layout, so it will not reflect any
+/// later changes to it.
+///
+/// ```
+/// use kle_serial::Keyboard;
+///
+/// let keyboard: Keyboard = serde_json::from_str(r#"[["Q", "W", "E"], ["A", "S", "D"]]"#).unwrap();
+/// let tree = keyboard.kd_tree();
+///
+/// assert_eq!(tree.nearest(1.4, 1.6), Some(4));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct KdTree<T = f64>
+where
+    T: Real,
+{
+    // Stored so that each subtree occupies a contiguous slice with its root at the median
+    nodes: Vec<(T, T, usize)>,
+}
+
+impl<T> KdTree<T>
+where
+    T: Real,
+{
+    fn new(mut nodes: Vec<(T, T, usize)>) -> Self {
+        build(&mut nodes, 0);
+        Self { nodes }
+    }
+
+    /// Returns the number of keys in the tree.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the tree contains no keys.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the index in [`Keyboard::keys`] of the key whose centre is closest to the point
+    /// `(x, y)`, or [`None`] if the tree is empty.
+    ///
+    /// If multiple keys are equally close, any one of them may be returned.
+    #[must_use]
+    pub fn nearest(&self, x: T, y: T) -> Option<usize> {
+        let mut best = None;
+        nearest(&self.nodes, 0, (x, y), &mut best);
+        best.map(|(_, index)| index)
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Builds a [`KdTree`] of the keys in the layout for repeated nearest key queries.
+    ///
+    /// For one-off queries [`Keyboard::nearest_key_to`] is usually faster, since building the tree
+    /// requires sorting the keys.
+    #[must_use]
+    pub fn kd_tree(&self) -> KdTree<T> {
+        KdTree::new(
+            (self.keys.iter().enumerate())
+                .map(|(i, key)| {
+                    let (x, y) = key.center_point();
+                    (x, y, i)
+                })
+                .collect(),
+        )
+    }
+}
+
+fn axis_value<T: Real>(node: &(T, T, usize), depth: usize) -> T {
+    if depth % 2 == 0 {
+        node.0
+    } else {
+        node.1
+    }
+}
+
+fn build<T: Real>(nodes: &mut [(T, T, usize)], depth: usize) {
+    if nodes.len() <= 1 {
+        return;
+    }
+
+    nodes.sort_by(|a, b| {
+        (axis_value(a, depth).partial_cmp(&axis_value(b, depth))).unwrap_or(Ordering::Equal)
+    });
+
+    let mid = nodes.len() / 2;
+    let (left, right) = nodes.split_at_mut(mid);
+    build(left, depth + 1);
+    build(&mut right[1..], depth + 1);
+}
+
+fn nearest<T: Real>(
+    nodes: &[(T, T, usize)],
+    depth: usize,
+    (x, y): (T, T),
+    best: &mut Option<(T, usize)>,
+) {
+    if nodes.is_empty() {
+        return;
+    }
+
+    let mid = nodes.len() / 2;
+    let node = &nodes[mid];
+
+    let (dx, dy) = (node.0 - x, node.1 - y);
+    let dist = dx * dx + dy * dy;
+    if best.map_or(true, |(best_dist, _)| dist < best_dist) {
+        *best = Some((dist, node.2));
+    }
+
+    let diff = axis_value(&(x, y, 0), depth) - axis_value(node, depth);
+    let (near, far) = if diff < T::zero() {
+        (&nodes[..mid], &nodes[mid + 1..])
+    } else {
+        (&nodes[mid + 1..], &nodes[..mid])
+    };
+
+    nearest(near, depth + 1, (x, y), best);
+    // Only search the far side if it could contain a closer point
+    if best.map_or(true, |(best_dist, _)| diff * diff < best_dist) {
+        nearest(far, depth + 1, (x, y), best);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::Key;
+
+    use super::*;
+
+    fn keyboard() -> Keyboard<f64> {
+        // A 5x4 grid with every other row staggered by half a unit
+        (0..20)
+            .map(|i| {
+                let (row, col) = (i / 5, i % 5);
+                Key {
+                    x: f64::from(col) + f64::from(row % 2) * 0.5,
+                    y: f64::from(row),
+                    ..Key::default()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_kd_tree_nearest() {
+        let keyboard = keyboard();
+        let tree = keyboard.kd_tree();
+
+        assert_eq!(tree.len(), 20);
+        assert!(!tree.is_empty());
+
+        // Exactly on a key
+        assert_eq!(tree.nearest(0.5, 0.5), Some(0));
+        assert_eq!(tree.nearest(3.2, 1.8), Some(7));
+
+        // Compare against a linear scan for a grid of points, avoiding points equidistant from
+        // multiple keys
+        for i in 0..60 {
+            for j in 0..40 {
+                let (x, y) = (f64::from(i) * 0.1 - 0.53, f64::from(j) * 0.1 - 0.47);
+                let expected = keyboard.nearest_key_to(x, y).map(|(i, _)| i);
+                assert_eq!(tree.nearest(x, y), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_kd_tree_empty() {
+        let tree = Keyboard::<f64>::default().kd_tree();
+
+        assert_eq!(tree.len(), 0);
+        assert!(tree.is_empty());
+        assert_eq!(tree.nearest(0.0, 0.0), None);
+
+        let keyboard = Keyboard::<f64> {
+            keys: vec![Key::default()],
+            ..Keyboard::default()
+        };
+        assert_eq!(keyboard.kd_tree().nearest(100.0, 100.0), Some(0));
+    }
+}
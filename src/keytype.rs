@@ -0,0 +1,417 @@
+use core::iter;
+
+use num_traits::real::Real;
+
+use crate::Key;
+
+/// The guessed function of a key, as returned by [`Key::guess_key_type`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyType {
+    /// A letter key.
+    Alpha,
+    /// A key on the number row, including the symbol keys either side of the digits.
+    Numrow,
+    /// A modifier key such as Shift, Ctrl, or Caps Lock.
+    Modifier,
+    /// A function key in the range F1 to F24.
+    FunctionKey,
+    /// A spacebar.
+    Spacebar,
+    /// A navigation key such as Home, Page Up, or an arrow key.
+    Navigation,
+    /// A key on the numpad.
+    Numpad,
+    /// The key's function could not be determined.
+    Unknown,
+}
+
+const NUMROW_SYMBOLS: &[char] = &[
+    '`', '~', '!', '@', '#', '$', '%', '^', '&', '*', '(', ')', '-', '_', '=', '+', '§', '±', '°',
+    '²', '£', '€', '¤',
+];
+
+const MODIFIERS: &[&str] = &[
+    // US English
+    "Shift",
+    "LShift",
+    "RShift",
+    "Ctrl",
+    "Control",
+    "LCtrl",
+    "RCtrl",
+    "Alt",
+    "AltGr",
+    "Alt Gr",
+    "Option",
+    "Opt",
+    "Meta",
+    "Super",
+    "Win",
+    "Windows",
+    "Cmd",
+    "Command",
+    "GUI",
+    "Fn",
+    "Caps Lock",
+    "CapsLock",
+    "Caps",
+    "Tab",
+    "Menu",
+    // German
+    "Strg",
+    "Umschalt",
+    "Feststell",
+    // French
+    "Maj",
+    "Verr Maj",
+    // Spanish
+    "Mayús",
+    "Bloq Mayús",
+    // Symbols
+    "⇧",
+    "⌃",
+    "⌥",
+    "⌘",
+    "⇪",
+    "⇥",
+    "↹",
+];
+
+const NAVIGATION: &[&str] = &[
+    // US English
+    "Home",
+    "End",
+    "PgUp",
+    "PgDn",
+    "Page Up",
+    "Page Down",
+    "PageUp",
+    "PageDown",
+    "Insert",
+    "Ins",
+    "Delete",
+    "Del",
+    "Up",
+    "Down",
+    "Left",
+    "Right",
+    // German
+    "Pos1",
+    "Ende",
+    "Bild↑",
+    "Bild↓",
+    "Bild auf",
+    "Bild ab",
+    "Einfg",
+    "Entf",
+    // French
+    "Début",
+    "Fin",
+    "Pg Préc",
+    "Pg Suiv",
+    "Inser",
+    "Suppr",
+    // Spanish
+    "Inicio",
+    "Re Pág",
+    "Av Pág",
+    "Supr",
+    // Symbols
+    "←",
+    "→",
+    "↑",
+    "↓",
+];
+
+const NUMPAD: &[&str] = &[
+    "Num Lock", "NumLock", "Num", "Verr Num", "Bloq Num", "Clear",
+];
+
+const SPACEBAR: &[&str] = &[
+    "Space",
+    "Spacebar",
+    "Space Bar",
+    "Leertaste",
+    "Espace",
+    "Espacio",
+];
+
+// The minimum width of an unlabelled key to be considered a spacebar
+const SPACEBAR_MIN_WIDTH: f64 = 4.0;
+
+// Compares two legends case-insensitively, treating any run of whitespace as a single space
+fn legend_eq(a: &str, b: &str) -> bool {
+    fn normalize(s: &str) -> impl Iterator<Item = char> + '_ {
+        s.split_whitespace()
+            .flat_map(|word| iter::once(' ').chain(word.chars().flat_map(char::to_lowercase)))
+    }
+    normalize(a).eq(normalize(b))
+}
+
+fn legend_in(text: &str, names: &[&str]) -> bool {
+    names.iter().any(|name| legend_eq(text, name))
+}
+
+fn single_char(text: &str) -> Option<char> {
+    let mut chars = text.trim().chars();
+    chars.next().filter(|_| chars.next().is_none())
+}
+
+impl<T> Key<T>
+where
+    T: Real,
+{
+    /// Returns `true` if the key's primary legend is a single ASCII letter.
+    #[must_use]
+    pub fn is_alpha(&self) -> bool {
+        self.first_text_legend()
+            .and_then(single_char)
+            .map_or(false, |c| c.is_ascii_alphabetic())
+    }
+
+    /// Returns `true` if the key's primary legend is a digit or a symbol commonly found on the
+    /// number row, or if any of its legends is a single digit.
+    ///
+    /// The second check handles layouts such as QWERTZ where the primary legend of the 2 key is
+    /// `"`, which would otherwise be ambiguous with the US quote key.
+    #[must_use]
+    pub fn is_numrow(&self) -> bool {
+        let is_numrow_char = |c: char| c.is_ascii_digit() || NUMROW_SYMBOLS.contains(&c);
+        self.first_text_legend()
+            .and_then(single_char)
+            .map_or(false, is_numrow_char)
+            || (self.present_legends())
+                .filter_map(|legend| single_char(&legend.text))
+                .any(|c| c.is_ascii_digit())
+    }
+
+    /// Returns `true` if the key's primary legend is a well-known modifier such as `Shift`, `Ctrl`,
+    /// `Alt`, `Meta`, `Caps Lock`, or `Tab`. Common German, French, and Spanish names and macOS
+    /// modifier symbols are also recognised.
+    #[must_use]
+    pub fn is_modifier(&self) -> bool {
+        self.first_text_legend()
+            .map_or(false, |text| legend_in(text, MODIFIERS))
+    }
+
+    /// Returns `true` if the key's primary legend is one of `F1` to `F24`.
+    #[must_use]
+    pub fn is_function_key(&self) -> bool {
+        self.first_text_legend()
+            .map(str::trim)
+            .and_then(|text| text.strip_prefix(|c| c == 'F' || c == 'f'))
+            .filter(|num| !num.starts_with('0') && num.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|num| num.parse::<u8>().ok())
+            .map_or(false, |num| (1..=24).contains(&num))
+    }
+
+    /// Guesses the function of the key based on its legends.
+    ///
+    /// Unlabelled keys at least 4 units wide are assumed to be spacebars, and digit or decimal
+    /// point keys which also have a navigation legend (such as `7` and `Home`) are assumed to be on
+    /// the numpad.
+    ///
+    /// ```
+    /// use kle_serial::{Key, KeyType, Keyboard};
+    ///
+    /// let keyboard: Keyboard =
+    ///     serde_json::from_str(r#"[["Q", "!\n1", "F5", "Ctrl", "7\nHome", {"w": 6.25}, ""]]"#)
+    ///         .unwrap();
+    /// let types: Vec<_> = keyboard.keys.iter().map(Key::guess_key_type).collect();
+    ///
+    /// assert_eq!(
+    ///     types,
+    ///     [
+    ///         KeyType::Alpha,
+    ///         KeyType::Numrow,
+    ///         KeyType::FunctionKey,
+    ///         KeyType::Modifier,
+    ///         KeyType::Numpad,
+    ///         KeyType::Spacebar,
+    ///     ]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn guess_key_type(&self) -> KeyType {
+        if self.is_function_key() {
+            KeyType::FunctionKey
+        } else if self.is_modifier() {
+            KeyType::Modifier
+        } else if self.is_spacebar() {
+            KeyType::Spacebar
+        } else if self.is_numpad() {
+            KeyType::Numpad
+        } else if self.is_navigation() {
+            KeyType::Navigation
+        } else if self.is_alpha() {
+            KeyType::Alpha
+        } else if self.is_numrow() {
+            KeyType::Numrow
+        } else {
+            KeyType::Unknown
+        }
+    }
+
+    fn first_text_legend(&self) -> Option<&str> {
+        (self.present_legends())
+            .map(|legend| legend.text.as_str())
+            .find(|text| !text.trim().is_empty())
+    }
+
+    fn is_navigation(&self) -> bool {
+        self.first_text_legend()
+            .map_or(false, |text| legend_in(text, NAVIGATION))
+    }
+
+    fn is_spacebar(&self) -> bool {
+        match self.first_text_legend() {
+            Some(text) => legend_in(text, SPACEBAR),
+            None => T::from(SPACEBAR_MIN_WIDTH).map_or(false, |min| self.width >= min),
+        }
+    }
+
+    fn is_numpad(&self) -> bool {
+        let is_numpad_char = |c: char| c.is_ascii_digit() || c == '.' || c == ',';
+        let has_navigation =
+            || (self.present_legends()).any(|legend| legend_in(&legend.text, NAVIGATION));
+        self.first_text_legend().map_or(false, |text| {
+            legend_in(text, NUMPAD)
+                || (single_char(text).map_or(false, is_numpad_char) && has_navigation())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Keyboard, Legend};
+
+    use super::*;
+
+    fn key(legends: &[&str]) -> Key {
+        let mut key = Key::default();
+        for (slot, &text) in key.legends.iter_mut().zip(legends) {
+            *slot = (!text.is_empty()).then(|| Legend::new(text));
+        }
+        key
+    }
+
+    #[test]
+    fn test_key_is_alpha() {
+        assert!(key(&["A"]).is_alpha());
+        assert!(key(&["q"]).is_alpha());
+        assert!(key(&["", "Z"]).is_alpha());
+        assert!(!key(&["AB"]).is_alpha());
+        assert!(!key(&["1"]).is_alpha());
+        assert!(!key(&[]).is_alpha());
+
+        // Only ASCII letters are considered alphas
+        assert!(!key(&["Ü"]).is_alpha());
+        assert!(!key(&["Ñ"]).is_alpha());
+    }
+
+    #[test]
+    fn test_key_is_numrow() {
+        assert!(key(&["!", "1"]).is_numrow());
+        assert!(key(&["~", "`"]).is_numrow());
+        assert!(key(&["_", "-"]).is_numrow());
+        assert!(key(&["0"]).is_numrow());
+        assert!(!key(&["{", "["]).is_numrow());
+        assert!(!key(&["\"", "'"]).is_numrow());
+        assert!(!key(&["A"]).is_numrow());
+
+        // QWERTZ and AZERTY
+        assert!(key(&["\"", "2"]).is_numrow());
+        assert!(key(&["°", "^"]).is_numrow());
+        assert!(key(&["1", "&"]).is_numrow());
+        assert!(key(&["2", "é"]).is_numrow());
+    }
+
+    #[test]
+    fn test_key_is_modifier() {
+        for text in [
+            "Shift",
+            "ctrl",
+            "ALT",
+            "Meta",
+            "Caps Lock",
+            "caps  lock",
+            "Tab",
+            "⌘",
+        ] {
+            assert!(key(&[text]).is_modifier(), "{text}");
+        }
+        for text in ["Strg", "Umschalt", "Maj", "Verr Maj", "MAYÚS", "Alt Gr"] {
+            assert!(key(&[text]).is_modifier(), "{text}");
+        }
+        for text in ["Enter", "Shifty", "A", ""] {
+            assert!(!key(&[text]).is_modifier(), "{text}");
+        }
+    }
+
+    #[test]
+    fn test_key_is_function_key() {
+        for text in ["F1", "f12", "F24", " F5 "] {
+            assert!(key(&[text]).is_function_key(), "{text}");
+        }
+        for text in ["F0", "F25", "F01", "F+1", "F", "Fn", "1"] {
+            assert!(!key(&[text]).is_function_key(), "{text}");
+        }
+    }
+
+    #[test]
+    fn test_key_guess_key_type() {
+        assert_eq!(key(&["A"]).guess_key_type(), KeyType::Alpha);
+        assert_eq!(key(&["@", "2"]).guess_key_type(), KeyType::Numrow);
+        assert_eq!(key(&["Shift"]).guess_key_type(), KeyType::Modifier);
+        assert_eq!(key(&["F11"]).guess_key_type(), KeyType::FunctionKey);
+        assert_eq!(key(&["Space"]).guess_key_type(), KeyType::Spacebar);
+        assert_eq!(key(&["Home"]).guess_key_type(), KeyType::Navigation);
+        assert_eq!(key(&["↑"]).guess_key_type(), KeyType::Navigation);
+        assert_eq!(key(&["Num Lock"]).guess_key_type(), KeyType::Numpad);
+        assert_eq!(key(&["7", "Home"]).guess_key_type(), KeyType::Numpad);
+        assert_eq!(key(&[".", "Del"]).guess_key_type(), KeyType::Numpad);
+        assert_eq!(key(&["Enter"]).guess_key_type(), KeyType::Unknown);
+        assert_eq!(key(&[]).guess_key_type(), KeyType::Unknown);
+
+        // International variants
+        assert_eq!(key(&["Strg"]).guess_key_type(), KeyType::Modifier);
+        assert_eq!(key(&["Leertaste"]).guess_key_type(), KeyType::Spacebar);
+        assert_eq!(key(&["Entf"]).guess_key_type(), KeyType::Navigation);
+        assert_eq!(key(&["Suppr"]).guess_key_type(), KeyType::Navigation);
+        assert_eq!(key(&["Bloq Num"]).guess_key_type(), KeyType::Numpad);
+        assert_eq!(key(&["Ü"]).guess_key_type(), KeyType::Unknown);
+
+        let spacebar = Key {
+            width: 6.25,
+            ..Key::default()
+        };
+        assert_eq!(spacebar.guess_key_type(), KeyType::Spacebar);
+    }
+
+    #[test]
+    fn test_key_guess_key_type_layout() {
+        let keyboard: Keyboard = serde_json::from_str(
+            r##"[
+                ["Esc", {"x": 1}, "F1", "F2", "F3", "F4"],
+                [{"y": 0.5}, "~\n`", "!\n1", "@\n2", "#\n3"],
+                [{"w": 1.5}, "Tab", "Q", "W", "E"],
+                [{"w": 1.75}, "Caps Lock", "A", "S", "D"],
+                [{"w": 2.25}, "Shift", "Z", "X", "C"],
+                [{"w": 1.25}, "Ctrl", {"w": 1.25}, "Win", {"w": 1.25}, "Alt", {"w": 6.25}, ""]
+            ]"##,
+        )
+        .unwrap();
+        let count = |typ| {
+            (keyboard.keys.iter())
+                .filter(|key| key.guess_key_type() == typ)
+                .count()
+        };
+
+        assert_eq!(count(KeyType::FunctionKey), 4);
+        assert_eq!(count(KeyType::Numrow), 4);
+        assert_eq!(count(KeyType::Alpha), 9);
+        assert_eq!(count(KeyType::Modifier), 6);
+        assert_eq!(count(KeyType::Spacebar), 1);
+        assert_eq!(count(KeyType::Unknown), 1);
+    }
+}
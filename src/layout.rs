@@ -0,0 +1,612 @@
+use alloc::vec::{self, Vec};
+use core::{
+    cmp::Ordering,
+    ops::{Add, AddAssign},
+    slice,
+};
+
+use num_traits::real::Real;
+
+use crate::{Key, Keyboard, Metadata};
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Creates a new [`Keyboard`] with the given metadata and keys.
+    ///
+    /// Use [`Keyboard::from_iter`] or [`Iterator::collect`] to create a [`Keyboard`] with default
+    /// metadata.
+    #[must_use]
+    pub fn from_keys_with_metadata(
+        metadata: Metadata,
+        keys: impl IntoIterator<Item = Key<T>>,
+    ) -> Self {
+        Self {
+            metadata,
+            keys: keys.into_iter().collect(),
+        }
+    }
+
+    /// Returns an iterator over the layout's keys.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, Key<T>> {
+        self.keys.iter()
+    }
+
+    /// Returns an iterator that allows modifying each of the layout's keys.
+    #[inline]
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, Key<T>> {
+        self.keys.iter_mut()
+    }
+
+    /// Returns a new [`Keyboard`] containing only the keys for which `predicate` returns `true`.
+    ///
+    /// The layout's metadata is preserved.
+    #[must_use]
+    pub fn filter_keys(&self, predicate: impl Fn(&Key<T>) -> bool) -> Self {
+        Self {
+            metadata: self.metadata.clone(),
+            keys: self
+                .keys
+                .iter()
+                .filter(|key| predicate(key))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Retains only the keys for which `predicate` returns `true`, removing all others in place.
+    ///
+    /// This works the same as [`Vec::retain`], preserving the order of the remaining keys.
+    pub fn retain_keys(&mut self, predicate: impl FnMut(&Key<T>) -> bool) {
+        self.keys.retain(predicate);
+    }
+
+    /// Returns a new [`Keyboard`] with all decals removed.
+    #[must_use]
+    pub fn without_decals(&self) -> Self {
+        self.filter_keys(|key| !key.decal)
+    }
+
+    /// Returns a new [`Keyboard`] with all ghosted keys removed.
+    #[must_use]
+    pub fn without_ghosted(&self) -> Self {
+        self.filter_keys(|key| !key.ghosted)
+    }
+
+    /// Returns a new [`Keyboard`] containing only stepped keys.
+    #[must_use]
+    pub fn only_stepped(&self) -> Self {
+        self.filter_keys(|key| key.stepped)
+    }
+
+    // Returns the Y position of each row in ascending order, shared by every API which groups
+    // keys into rows
+    pub(crate) fn row_positions(&self) -> Vec<T> {
+        let mut positions: Vec<_> = self.keys.iter().map(row_position).collect();
+        positions.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        positions.dedup();
+        positions
+    }
+
+    // Returns the (row, column) position of each key in key order, using the same row grouping
+    // as Keyboard::rows
+    pub(crate) fn matrix_positions(&self) -> Vec<(usize, usize)> {
+        let positions = self.row_positions();
+        let mut cols = alloc::vec![0; positions.len()];
+
+        (self.keys.iter())
+            .map(|key| {
+                let row = (positions.iter())
+                    .position(|&y| y == row_position(key))
+                    .unwrap_or_default();
+                let col = cols.get(row).copied().unwrap_or_default();
+                if let Some(next) = cols.get_mut(row) {
+                    *next += 1;
+                }
+                (row, col)
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over the rows of the layout in ascending Y order, where each row
+    /// contains the keys in the order they appear in [`Keyboard::keys`].
+    ///
+    /// Keys are grouped by rounding their `y` field to the nearest whole unit, so keys whose
+    /// positions differ only by rounding error (such as a sum of fractional offsets) are still
+    /// placed in the same row. Rotation is not taken into account, so keys in rotated clusters
+    /// will be grouped with any other keys in the same row even if their physical positions
+    /// differ.
+    pub fn rows(&self) -> impl Iterator<Item = Vec<&Key<T>>> + '_ {
+        self.row_positions().into_iter().map(move |y| {
+            (self.keys.iter())
+                .filter(|key| row_position(key) == y)
+                .collect()
+        })
+    }
+
+    /// Returns the number of rows in the layout. See [`Keyboard::rows`] for details on how keys
+    /// are grouped into rows.
+    #[must_use]
+    pub fn row_count(&self) -> usize {
+        self.row_positions().len()
+    }
+
+    /// Returns an iterator over the keys in the given row. See [`Keyboard::rows`] for details on
+    /// how keys are grouped into rows.
+    ///
+    /// The iterator will be empty if `row` is greater than or equal to [`Keyboard::row_count`].
+    pub fn keys_in_row(&self, row: usize) -> impl Iterator<Item = &Key<T>> {
+        let y = self.row_positions().get(row).copied();
+        (self.keys.iter()).filter(move |key| Some(row_position(key)) == y)
+    }
+}
+
+// The Y position of the row containing key, i.e. its y field rounded to the nearest whole unit
+fn row_position<T: Real>(key: &Key<T>) -> T {
+    key.y.round()
+}
+
+impl<T> IntoIterator for Keyboard<T>
+where
+    T: Real,
+{
+    type Item = Key<T>;
+    type IntoIter = vec::IntoIter<Key<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.keys.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Keyboard<T>
+where
+    T: Real,
+{
+    type Item = &'a Key<T>;
+    type IntoIter = slice::Iter<'a, Key<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Keyboard<T>
+where
+    T: Real,
+{
+    type Item = &'a mut Key<T>;
+    type IntoIter = slice::IterMut<'a, Key<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T> FromIterator<Key<T>> for Keyboard<T>
+where
+    T: Real,
+{
+    /// Creates a [`Keyboard`] from the given keys with default metadata.
+    fn from_iter<I: IntoIterator<Item = Key<T>>>(iter: I) -> Self {
+        Self::from_keys_with_metadata(Metadata::default(), iter)
+    }
+}
+
+impl<T> Extend<Key<T>> for Keyboard<T>
+where
+    T: Real,
+{
+    /// Appends keys to the layout without changing its metadata.
+    fn extend<I: IntoIterator<Item = Key<T>>>(&mut self, iter: I) {
+        self.keys.extend(iter);
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Appends the keys of `other` to the layout without changing its metadata.
+    pub fn extend_from(&mut self, other: Self) {
+        self.keys.extend(other.keys);
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Sorts the layout's keys in place using `comparator`.
+    ///
+    /// The sort is stable, so keys which compare equal keep their original relative order.
+    pub fn reorder_keys(&mut self, comparator: impl Fn(&Key<T>, &Key<T>) -> Ordering) {
+        self.keys.sort_by(comparator);
+    }
+
+    /// Sorts the layout's keys by row then column, where the row and column are found by
+    /// rounding each key's `y` and `x` position to the nearest whole unit.
+    ///
+    /// This uses each key's unrotated position, and keys in the same row and column keep their
+    /// original relative order.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let mut keyboard: Keyboard =
+    ///     serde_json::from_str(r#"[[{"y": 1}, "A", {"x": -2, "y": -1}, "Q", "W"]]"#).unwrap();
+    /// keyboard.sort_by_position();
+    ///
+    /// let legends: Vec<_> = (keyboard.keys.iter())
+    ///     .map(|key| key.legends[0].as_ref().unwrap().text.as_str())
+    ///     .collect();
+    /// assert_eq!(legends, ["Q", "W", "A"]);
+    /// ```
+    pub fn sort_by_position(&mut self) {
+        self.reorder_keys(|a, b| {
+            let position = |key: &Key<T>| (key.y.round(), key.x.round());
+            position(a)
+                .partial_cmp(&position(b))
+                .unwrap_or(Ordering::Equal)
+        });
+    }
+
+    /// Sorts the layout's keys by row then column. This is an alias of
+    /// [`Keyboard::sort_by_position`].
+    #[inline]
+    pub fn sort_by_row_then_column(&mut self) {
+        self.sort_by_position();
+    }
+
+    /// Returns a copy of the layout with its keys sorted by row then column. See
+    /// [`Keyboard::sort_by_position`].
+    #[must_use]
+    pub fn sorted_by_position(&self) -> Self {
+        let mut result = self.clone();
+        result.sort_by_position();
+        result
+    }
+}
+
+impl<T> Add for Keyboard<T>
+where
+    T: Real,
+{
+    type Output = Self;
+
+    /// Concatenates two layouts, keeping the metadata of the left operand. See
+    /// [`Keyboard::extend_from`].
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self.extend_from(rhs);
+        self
+    }
+}
+
+impl<T> AddAssign for Keyboard<T>
+where
+    T: Real,
+{
+    /// Appends the keys of the right operand to the layout. See [`Keyboard::extend_from`].
+    fn add_assign(&mut self, rhs: Self) {
+        self.extend_from(rhs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isclose::assert_is_close;
+
+    use super::*;
+
+    fn keyboard() -> Keyboard {
+        Keyboard {
+            metadata: Metadata {
+                name: "test".into(),
+                ..Metadata::default()
+            },
+            keys: vec![
+                Key::default(),
+                Key {
+                    decal: true,
+                    ..Key::default()
+                },
+                Key {
+                    ghosted: true,
+                    ..Key::default()
+                },
+                Key {
+                    stepped: true,
+                    ..Key::default()
+                },
+                Key {
+                    homing: true,
+                    ..Key::default()
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_keyboard_filter_keys() {
+        let keyboard = keyboard();
+        let filtered = keyboard.filter_keys(|key| key.homing);
+
+        assert_eq!(filtered.metadata, keyboard.metadata);
+        assert_eq!(filtered.keys.len(), 1);
+        assert!(filtered.keys[0].homing);
+        assert_eq!(keyboard.keys.len(), 5);
+    }
+
+    #[test]
+    fn test_keyboard_retain_keys() {
+        let mut keyboard = keyboard();
+        keyboard.retain_keys(|key| !key.homing);
+
+        assert_eq!(keyboard.metadata.name, "test");
+        assert_eq!(keyboard.keys.len(), 4);
+        assert!(keyboard.keys.iter().all(|key| !key.homing));
+    }
+
+    #[test]
+    fn test_keyboard_filter_helpers() {
+        let keyboard = keyboard();
+
+        let filtered = keyboard.without_decals();
+        assert_eq!(filtered.keys.len(), 4);
+        assert!(filtered.keys.iter().all(|key| !key.decal));
+
+        let filtered = keyboard.without_ghosted();
+        assert_eq!(filtered.keys.len(), 4);
+        assert!(filtered.keys.iter().all(|key| !key.ghosted));
+
+        let filtered = keyboard.only_stepped();
+        assert_eq!(filtered.keys.len(), 1);
+        assert!(filtered.keys[0].stepped);
+    }
+
+    #[test]
+    fn test_keyboard_rows() {
+        let keyboard = Keyboard::<f64> {
+            metadata: Metadata::default(),
+            keys: vec![
+                Key {
+                    y: 1.0,
+                    ..Key::default()
+                },
+                Key {
+                    x: 1.0,
+                    y: 1.0,
+                    ..Key::default()
+                },
+                Key::default(),
+                // Rotated cluster sharing y with the first row
+                Key {
+                    y: 1.0,
+                    rotation: 15.0,
+                    rx: 4.0,
+                    ry: 0.0,
+                    ..Key::default()
+                },
+                // Different rotation group, same y again
+                Key {
+                    y: 1.0,
+                    rotation: -15.0,
+                    rx: 8.0,
+                    ry: 2.0,
+                    ..Key::default()
+                },
+                Key {
+                    y: 2.5,
+                    ..Key::default()
+                },
+            ],
+        };
+
+        let rows: Vec<_> = keyboard.rows().collect();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(keyboard.row_count(), 3);
+        assert_eq!(rows[0].len(), 1);
+        assert_eq!(rows[1].len(), 4);
+        assert_eq!(rows[2].len(), 1);
+        assert!(std::ptr::eq(rows[1][0], &keyboard.keys[0]));
+        assert!(std::ptr::eq(rows[1][2], &keyboard.keys[3]));
+        assert!(std::ptr::eq(rows[1][3], &keyboard.keys[4]));
+
+        assert_eq!(keyboard.keys_in_row(0).count(), 1);
+        assert_eq!(keyboard.keys_in_row(1).count(), 4);
+        assert!(keyboard.keys_in_row(2).all(|key| key.y > 2.0));
+        assert_eq!(keyboard.keys_in_row(3).count(), 0);
+
+        // Accumulated offsets which aren't exactly equal still belong to the same row
+        let keyboard = Keyboard::<f64> {
+            metadata: Metadata::default(),
+            keys: vec![
+                Key {
+                    y: 0.1 + 0.2,
+                    ..Key::default()
+                },
+                Key {
+                    x: 1.0,
+                    y: 0.3,
+                    ..Key::default()
+                },
+                Key {
+                    x: 2.0,
+                    y: 1.0 - 0.9 + 0.2,
+                    ..Key::default()
+                },
+                Key {
+                    y: 1.2,
+                    ..Key::default()
+                },
+            ],
+        };
+
+        assert_eq!(keyboard.row_count(), 2);
+        assert_eq!(keyboard.keys_in_row(0).count(), 3);
+        assert_eq!(
+            keyboard.matrix_positions(),
+            [(0, 0), (0, 1), (0, 2), (1, 0)]
+        );
+
+        let keyboard = Keyboard::<f64>::default();
+
+        assert_eq!(keyboard.rows().count(), 0);
+        assert_eq!(keyboard.row_count(), 0);
+        assert_eq!(keyboard.keys_in_row(0).count(), 0);
+    }
+
+    #[test]
+    fn test_keyboard_iter() {
+        let mut keyboard = keyboard();
+
+        assert_eq!(keyboard.iter().count(), keyboard.keys.len());
+        assert!(keyboard.iter().eq(keyboard.keys.iter()));
+
+        keyboard.iter_mut().for_each(|key| key.x = 1.0);
+        for key in &keyboard.keys {
+            assert_is_close!(key.x, 1.0);
+        }
+
+        for key in &mut keyboard {
+            key.y = 2.0;
+        }
+        for key in &keyboard.keys {
+            assert_is_close!(key.y, 2.0);
+        }
+
+        let mut count = 0;
+        for key in &keyboard {
+            assert_is_close!(key.width, 1.0);
+            count += 1;
+        }
+        assert_eq!(count, keyboard.keys.len());
+
+        let expected = keyboard.keys.clone();
+        let keys: Vec<_> = keyboard.into_iter().collect();
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn test_keyboard_from_iter() {
+        let keys = keyboard().keys;
+
+        let keyboard: Keyboard = Vec::new().into_iter().collect();
+        assert_eq!(keyboard, Keyboard::default());
+
+        let keyboard: Keyboard = keys.clone().into_iter().collect();
+        assert_eq!(keyboard.metadata, Metadata::default());
+        assert_eq!(keyboard.keys, keys);
+
+        let metadata = Metadata {
+            name: "test".into(),
+            ..Metadata::default()
+        };
+        let keyboard = Keyboard::from_keys_with_metadata(metadata.clone(), keys.clone());
+        assert_eq!(keyboard.metadata, metadata);
+        assert_eq!(keyboard.keys, keys);
+    }
+
+    #[test]
+    fn test_keyboard_extend() {
+        let mut keyboard = keyboard();
+        let other = Keyboard {
+            metadata: Metadata::default(),
+            keys: vec![Key {
+                x: 2.0,
+                ..Key::default()
+            }],
+        };
+        let expected_len = keyboard.keys.len() + other.keys.len();
+
+        keyboard.extend(other.clone());
+
+        assert_eq!(keyboard.metadata.name, "test");
+        assert_eq!(keyboard.keys.len(), expected_len);
+        assert_eq!(keyboard.keys.last(), other.keys.last());
+    }
+
+    #[test]
+    fn test_keyboard_reorder_keys() {
+        let mut keyboard: Keyboard = (0..5_u8)
+            .map(|i| Key {
+                x: f64::from(4 - i),
+                ..Key::default()
+            })
+            .collect();
+        keyboard.reorder_keys(|a, b| a.x.partial_cmp(&b.x).unwrap());
+
+        let xs: Vec<_> = keyboard.keys.iter().map(|key| key.x).collect();
+        assert_eq!(xs, [0.0, 1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_keyboard_sort_by_position() {
+        // A 4x5 grid with slightly offset keys, shuffled by stepping through it with a stride
+        // coprime to its length
+        let grid: Vec<_> = (0..4_u8)
+            .flat_map(|row| {
+                (0..5_u8).map(move |col| Key {
+                    x: f64::from(col) + 0.1 * f64::from(row),
+                    y: f64::from(row) - 0.2,
+                    ..Key::default()
+                })
+            })
+            .collect();
+        let keyboard: Keyboard = (0..grid.len())
+            .map(|i| grid[(i * 7) % grid.len()].clone())
+            .collect();
+        assert_ne!(keyboard.keys, grid);
+
+        let sorted = keyboard.sorted_by_position();
+        assert_eq!(sorted.keys, grid);
+        assert_eq!(sorted.metadata, keyboard.metadata);
+
+        let mut keyboard = keyboard;
+        keyboard.sort_by_row_then_column();
+        assert_eq!(keyboard, sorted);
+
+        // Keys in the same position keep their order
+        let mut keyboard: Keyboard = [0.1, -0.1, 0.0]
+            .into_iter()
+            .map(|x| Key {
+                x,
+                ..Key::default()
+            })
+            .collect();
+        keyboard.sort_by_position();
+        let xs: Vec<_> = keyboard.keys.iter().map(|key| key.x).collect();
+        assert_eq!(xs, [0.1, -0.1, 0.0]);
+    }
+
+    #[test]
+    fn test_keyboard_add() {
+        let kb1 = keyboard();
+        let kb2 = Keyboard {
+            metadata: Metadata {
+                name: "other".into(),
+                ..Metadata::default()
+            },
+            keys: vec![Key {
+                x: 2.0,
+                ..Key::default()
+            }],
+        };
+
+        let sum = kb1.clone() + kb2.clone();
+        assert_eq!(sum.keys.len(), kb1.keys.len() + kb2.keys.len());
+        assert_eq!(sum.metadata, kb1.metadata);
+        assert_eq!(sum.keys[..kb1.keys.len()], kb1.keys[..]);
+        assert_eq!(sum.keys[kb1.keys.len()..], kb2.keys[..]);
+
+        let mut keyboard = kb1.clone();
+        keyboard += kb2.clone();
+        assert_eq!(keyboard, sum);
+
+        let mut keyboard = kb1;
+        keyboard.extend_from(kb2);
+        assert_eq!(keyboard, sum);
+    }
+}
@@ -0,0 +1,832 @@
+use alloc::string::String;
+#[cfg(feature = "unicode-names")]
+use alloc::string::ToString;
+
+use num_traits::real::Real;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::utils::{alignment_displays, legend_alignment, Alignment, FontSize, LEGEND_MAPPING};
+use crate::{Key, Legend, NUM_LEGENDS};
+
+// The number of points in an inch
+const POINTS_PER_INCH: f64 = 72.0;
+
+/// The row of a legend's visual position on a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LegendRow {
+    /// Top aligned on the key's top face.
+    Top,
+    /// Centred vertically on the key's top face.
+    Middle,
+    /// Bottom aligned on the key's top face.
+    Bottom,
+    /// On the key's front face.
+    Front,
+}
+
+/// The column of a legend's visual position on a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LegendColumn {
+    /// Left aligned.
+    Left,
+    /// Centred horizontally.
+    Center,
+    /// Right aligned.
+    Right,
+}
+
+/// The horizontal position of a legend on a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LegendHAlign {
+    /// Left aligned.
+    Left,
+    /// Centred horizontally.
+    Center,
+    /// Right aligned.
+    Right,
+}
+
+/// The vertical position of a legend on a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LegendVAlign {
+    /// Top aligned on the key's top face.
+    Top,
+    /// Centred vertically on the key's top face.
+    Middle,
+    /// Bottom aligned on the key's top face.
+    Bottom,
+    /// On the key's front face.
+    Front,
+}
+
+impl Legend {
+    /// Returns `true` if the legend's text is a single printable ASCII character, i.e. a character
+    /// in the range `0x20..=0x7E`.
+    ///
+    /// ```
+    /// use kle_serial::Legend;
+    ///
+    /// let legend = |text: &str| Legend { text: text.into(), ..Legend::default() };
+    ///
+    /// assert!(legend("A").is_ascii_printable());
+    /// assert!(!legend("Tab").is_ascii_printable());
+    /// assert!(!legend("←").is_ascii_printable());
+    /// ```
+    #[must_use]
+    pub fn is_ascii_printable(&self) -> bool {
+        let mut chars = self.text.chars();
+        matches!((chars.next(), chars.next()), (Some(' '..='~'), None))
+    }
+
+    /// Returns `true` if the legend's text is a single non-ASCII symbol, such as `←`, `⌘`, or `€`.
+    ///
+    /// The text is considered a single symbol if it is one extended grapheme cluster, so symbols
+    /// followed by a variation selector or made up of several codepoints are included.
+    ///
+    /// ```
+    /// use kle_serial::Legend;
+    ///
+    /// let legend = |text: &str| Legend { text: text.into(), ..Legend::default() };
+    ///
+    /// assert!(legend("←").is_unicode_symbol());
+    /// assert!(legend("⏏\u{FE0F}").is_unicode_symbol());
+    /// assert!(!legend("A").is_unicode_symbol());
+    /// assert!(!legend("←→").is_unicode_symbol());
+    /// ```
+    #[must_use]
+    pub fn is_unicode_symbol(&self) -> bool {
+        !self.text.is_ascii() && self.text.graphemes(true).count() == 1
+    }
+
+    /// Returns the official Unicode name of the legend's text if it is a single codepoint, for
+    /// example `"LEFTWARDS ARROW"` for `←`. Returns [`None`] if the text is empty, is made up of
+    /// more than one codepoint, or the codepoint has no name.
+    ///
+    /// This requires the `unicode-names` feature.
+    ///
+    /// ```
+    /// use kle_serial::Legend;
+    ///
+    /// let legend = |text: &str| Legend { text: text.into(), ..Legend::default() };
+    ///
+    /// assert_eq!(legend("a").unicode_name().as_deref(), Some("LATIN SMALL LETTER A"));
+    /// assert_eq!(legend("←").unicode_name().as_deref(), Some("LEFTWARDS ARROW"));
+    /// assert_eq!(legend("Tab").unicode_name(), None);
+    /// ```
+    #[cfg(feature = "unicode-names")]
+    #[must_use]
+    pub fn unicode_name(&self) -> Option<String> {
+        let mut chars = self.text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => unicode_names2::name(c).map(|name| name.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Returns the legend's rendered size in pixels on a display with the given DPI, based on
+    /// [`FontSize::to_pt`]. Sizes outside of KLE's valid range are clamped.
+    ///
+    /// ```
+    /// use kle_serial::Legend;
+    ///
+    /// let legend = Legend::default();
+    ///
+    /// assert_eq!(legend.pixels_at_dpi(72.0), 10.0);
+    /// assert_eq!(legend.pixels_at_dpi(144.0), 20.0);
+    /// ```
+    #[must_use]
+    pub fn pixels_at_dpi(&self, dpi: f64) -> f64 {
+        FontSize::saturating_new(self.size).to_pt() * dpi / POINTS_PER_INCH
+    }
+
+    /// Returns an iterator over the Unicode codepoints in the legend's text.
+    ///
+    /// Note that a single user-perceived character can be made up of multiple codepoints. Use
+    /// [`Legend::grapheme_count`] or [`Legend::first_grapheme`] where that matters.
+    pub fn codepoints(&self) -> impl Iterator<Item = char> + '_ {
+        self.text.chars()
+    }
+
+    /// Returns the number of extended grapheme clusters (user-perceived characters) in the
+    /// legend's text.
+    ///
+    /// ```
+    /// use kle_serial::Legend;
+    ///
+    /// let legend = Legend {
+    ///     text: "e\u{0301}".into(),
+    ///     ..Legend::default()
+    /// };
+    ///
+    /// assert_eq!(legend.codepoints().count(), 2);
+    /// assert_eq!(legend.grapheme_count(), 1);
+    /// ```
+    #[must_use]
+    pub fn grapheme_count(&self) -> usize {
+        self.text.graphemes(true).count()
+    }
+
+    /// Returns the first extended grapheme cluster in the legend's text, or [`None`] if the text is
+    /// empty.
+    #[must_use]
+    pub fn first_grapheme(&self) -> Option<&str> {
+        self.text.graphemes(true).next()
+    }
+
+    /// Returns the visual position of the legend at `index` in [`Key::legends`], or [`None`] if
+    /// `index` is not less than 12 or KLE cannot display a legend at that position with the given
+    /// [`Alignment`].
+    ///
+    /// [`Key::legends`] is always stored in left to right, top to bottom order, as legends are
+    /// reordered using the inverse of [`LEGEND_MAPPING`] when deserialising. Indices `0..=8` are
+    /// therefore the top face in row major order and `9..=11` are the front legends, regardless
+    /// of alignment. The alignment only determines which of these positions KLE displays, for
+    /// example the default alignment centres front legends so only index 10 is displayed on the
+    /// front of the key.
+    ///
+    /// ```
+    /// use kle_serial::{Alignment, Legend, LegendColumn, LegendRow};
+    ///
+    /// let alignment = Alignment::default();
+    ///
+    /// assert_eq!(
+    ///     Legend::alignment_position(0, alignment),
+    ///     Some((LegendRow::Top, LegendColumn::Left))
+    /// );
+    /// assert_eq!(
+    ///     Legend::alignment_position(8, alignment),
+    ///     Some((LegendRow::Bottom, LegendColumn::Right))
+    /// );
+    /// assert_eq!(Legend::alignment_position(9, alignment), None);
+    /// assert_eq!(Legend::alignment_position(12, alignment), None);
+    /// ```
+    #[must_use]
+    pub fn alignment_position(
+        index: usize,
+        alignment: Alignment,
+    ) -> Option<(LegendRow, LegendColumn)> {
+        alignment_displays(alignment, index).then(|| legend_position(index))
+    }
+
+    /// Returns the visual position of the legend at `index` in KLE's legend order with the given
+    /// [`Alignment`].
+    ///
+    /// Unlike [`Legend::alignment_position`], `index` is the position of the legend in the
+    /// newline-separated KLE legend string rather than in [`Key::legends`]. It is mapped to a
+    /// [`Key::legends`] index using [`LEGEND_MAPPING`].
+    ///
+    /// ```
+    /// use kle_serial::{Alignment, Legend, LegendHAlign, LegendVAlign};
+    ///
+    /// let alignment = Alignment::default();
+    ///
+    /// assert_eq!(
+    ///     Legend::visual_position(1, alignment),
+    ///     (LegendHAlign::Left, LegendVAlign::Bottom)
+    /// );
+    /// assert_eq!(
+    ///     Legend::visual_position(4, alignment),
+    ///     (LegendHAlign::Center, LegendVAlign::Front)
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than 12.
+    #[must_use]
+    pub fn visual_position(index: usize, alignment: Alignment) -> (LegendHAlign, LegendVAlign) {
+        let (row, column) = legend_position(LEGEND_MAPPING[usize::from(alignment)][index]);
+
+        let halign = match column {
+            LegendColumn::Left => LegendHAlign::Left,
+            LegendColumn::Center => LegendHAlign::Center,
+            LegendColumn::Right => LegendHAlign::Right,
+        };
+        let valign = match row {
+            LegendRow::Top => LegendVAlign::Top,
+            LegendRow::Middle => LegendVAlign::Middle,
+            LegendRow::Bottom => LegendVAlign::Bottom,
+            LegendRow::Front => LegendVAlign::Front,
+        };
+
+        (halign, valign)
+    }
+}
+
+// Returns the visual position of index in Key::legends, which is stored in row major order
+fn legend_position(index: usize) -> (LegendRow, LegendColumn) {
+    let row = match index / 3 {
+        0 => LegendRow::Top,
+        1 => LegendRow::Middle,
+        2 => LegendRow::Bottom,
+        _ => LegendRow::Front,
+    };
+    let column = match index % 3 {
+        0 => LegendColumn::Left,
+        1 => LegendColumn::Center,
+        _ => LegendColumn::Right,
+    };
+
+    (row, column)
+}
+
+impl<T> Key<T>
+where
+    T: Real,
+{
+    /// Returns an iterator over the key's legends along with their index in [`Key::legends`],
+    /// skipping any positions with no legend.
+    #[inline]
+    pub fn legends_iter(&self) -> impl Iterator<Item = (usize, &Legend)> {
+        (self.legends.iter().enumerate()).filter_map(|(i, l)| l.as_ref().map(|l| (i, l)))
+    }
+
+    /// Returns a mutable iterator over the key's legends along with their index in
+    /// [`Key::legends`], skipping any positions with no legend.
+    #[inline]
+    pub fn legends_iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut Legend)> {
+        (self.legends.iter_mut().enumerate()).filter_map(|(i, l)| l.as_mut().map(|l| (i, l)))
+    }
+
+    /// Returns an iterator over the key's legends, skipping any positions with no legend.
+    #[inline]
+    pub fn present_legends(&self) -> impl Iterator<Item = &Legend> {
+        self.legends.iter().flatten()
+    }
+
+    /// Returns a mutable iterator over the key's legends, skipping any positions with no legend.
+    #[inline]
+    pub fn present_legends_mut(&mut self) -> impl Iterator<Item = &mut Legend> {
+        self.legends.iter_mut().flatten()
+    }
+
+    /// Returns `true` if the key has a legend at `index`. Returns `false` if `index` is out of
+    /// range.
+    #[inline]
+    #[must_use]
+    pub fn has_legend(&self, index: usize) -> bool {
+        self.legends.get(index).map_or(false, Option::is_some)
+    }
+
+    /// Returns the number of legends on the key.
+    #[inline]
+    #[must_use]
+    pub fn legend_count(&self) -> usize {
+        self.present_legends().count()
+    }
+
+    /// Returns the text of the legend at `index` in [`Key::legends`], or [`None`] if there is no
+    /// legend at that position or `index` is out of range.
+    ///
+    /// Index 0 is the top left legend and index 11 is the bottom right legend on the front of the
+    /// key, as shown in the diagram for [`Key::legends`].
+    #[inline]
+    #[must_use]
+    pub fn legend_text_at(&self, index: usize) -> Option<&str> {
+        (self.legends.get(index)?.as_ref()).map(|legend| legend.text.as_str())
+    }
+
+    /// Returns the text of each of the key's legends, in the same order as [`Key::legends`].
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["A\n\n\nB"]]"#).unwrap();
+    /// let legends = keyboard.keys[0].legends_as_strings();
+    ///
+    /// assert_eq!(legends[0].as_deref(), Some("A"));
+    /// assert_eq!(legends[8].as_deref(), Some("B"));
+    /// assert_eq!(legends[1], None);
+    /// ```
+    #[must_use]
+    pub fn legends_as_strings(&self) -> [Option<String>; NUM_LEGENDS] {
+        core::array::from_fn(|i| self.legends[i].as_ref().map(|legend| legend.text.clone()))
+    }
+
+    /// Returns the text of each of the key's legends, in the same order as [`Key::legends`].
+    /// Positions with no legend are returned as empty strings.
+    #[must_use]
+    pub fn legend_strings_lossy(&self) -> [String; NUM_LEGENDS] {
+        self.legends_as_strings().map(Option::unwrap_or_default)
+    }
+
+    /// Returns the text of the first legend in [`Key::legends`], or [`None`] if the key has no
+    /// legends.
+    ///
+    /// Legends are checked in index order, so this is usually the top left legend if present.
+    #[inline]
+    #[must_use]
+    pub fn first_legend_text(&self) -> Option<&str> {
+        self.present_legends()
+            .next()
+            .map(|legend| legend.text.as_str())
+    }
+
+    /// Returns the key's primary legend, or [`None`] if it has no primary legend.
+    ///
+    /// This is the first legend in KLE's legend order. For most keys this is the top left legend,
+    /// but for keys whose legends are centred it is the centred legend. The legend order is
+    /// determined from the positions of the key's legends, since [`Key`] does not store KLE's
+    /// alignment.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["!\n1", {"a": 7}, "Tab"]]"#).unwrap();
+    ///
+    /// assert_eq!(keyboard.keys[0].primary_legend().unwrap().text, "!");
+    /// assert_eq!(keyboard.keys[0].secondary_legend().unwrap().text, "1");
+    /// assert_eq!(keyboard.keys[1].primary_legend().unwrap().text, "Tab");
+    /// ```
+    #[must_use]
+    pub fn primary_legend(&self) -> Option<&Legend> {
+        self.legend_in_kle_order(0)
+    }
+
+    /// Returns the key's secondary legend, or [`None`] if it has no secondary legend.
+    ///
+    /// This is the second legend in KLE's legend order, which is typically the unshifted legend
+    /// below the [primary legend](Key::primary_legend), such as the `1` on a `!` / `1` key. See
+    /// [`Key::primary_legend`] for details on how the legend order is determined.
+    #[must_use]
+    pub fn secondary_legend(&self) -> Option<&Legend> {
+        self.legend_in_kle_order(1)
+    }
+
+    /// Returns the key's front legend, or [`None`] if it has no front legend.
+    ///
+    /// This is the fifth legend in KLE's legend order, which is printed on the front face of the
+    /// key. With KLE's default alignment this is the centre front legend. See
+    /// [`Key::primary_legend`] for details on how the legend order is determined.
+    #[must_use]
+    pub fn front_legend(&self) -> Option<&Legend> {
+        self.legend_in_kle_order(4)
+    }
+
+    fn legend_in_kle_order(&self, kle_index: usize) -> Option<&Legend> {
+        let mapping = LEGEND_MAPPING[usize::from(legend_alignment(&self.legends))];
+        self.legends[mapping[kle_index]].as_ref()
+    }
+
+    /// Sets the text of the legend at `index` in [`Key::legends`] and returns `true`. If there is
+    /// no legend at that position a new [`Legend`] is created with the default size and colour.
+    ///
+    /// Returns `false` without changing the key if `index` is not less than 12.
+    pub fn set_legend_text(&mut self, index: usize, text: &str) -> bool {
+        if let Some(slot) = self.legends.get_mut(index) {
+            slot.get_or_insert_with(Legend::default).text = text.into();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the key with the legend at `index` in [`Key::legends`] replaced by `legend`.
+    ///
+    /// ```
+    /// use kle_serial::{Key, Legend};
+    ///
+    /// let key = Key::<f64>::default().with_legend_at(
+    ///     0,
+    ///     Legend {
+    ///         text: "!".into(),
+    ///         ..Legend::default()
+    ///     },
+    /// );
+    ///
+    /// assert_eq!(key.legend_text_at(0), Some("!"));
+    /// ```
+    ///
+    /// The key is returned unchanged if `index` is not less than 12.
+    #[must_use]
+    pub fn with_legend_at(mut self, index: usize, legend: Legend) -> Self {
+        if let Some(slot) = self.legends.get_mut(index) {
+            *slot = Some(legend);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isclose::assert_is_close;
+
+    use super::*;
+    use crate::utils::realign_legends;
+
+    fn legend(text: &str) -> Legend {
+        Legend {
+            text: text.into(),
+            ..Legend::default()
+        }
+    }
+
+    #[test]
+    fn test_legend_is_ascii_printable() {
+        assert!(legend("a").is_ascii_printable());
+        assert!(legend(" ").is_ascii_printable());
+        assert!(legend("~").is_ascii_printable());
+
+        assert!(!legend("").is_ascii_printable());
+        assert!(!legend("\t").is_ascii_printable());
+        assert!(!legend("\u{7F}").is_ascii_printable());
+        assert!(!legend("ab").is_ascii_printable());
+        assert!(!legend("é").is_ascii_printable());
+    }
+
+    #[test]
+    fn test_legend_is_unicode_symbol() {
+        assert!(legend("←").is_unicode_symbol());
+        assert!(legend("⌘").is_unicode_symbol());
+        assert!(legend("€").is_unicode_symbol());
+        assert!(legend("e\u{0301}").is_unicode_symbol());
+        assert!(legend("⏏\u{FE0F}").is_unicode_symbol());
+
+        assert!(!legend("").is_unicode_symbol());
+        assert!(!legend("A").is_unicode_symbol());
+        assert!(!legend("Esc").is_unicode_symbol());
+        assert!(!legend("⌘C").is_unicode_symbol());
+        assert!(!legend("←→").is_unicode_symbol());
+    }
+
+    #[cfg(feature = "unicode-names")]
+    #[test]
+    fn test_legend_unicode_name() {
+        assert_eq!(
+            legend("a").unicode_name().as_deref(),
+            Some("LATIN SMALL LETTER A")
+        );
+        assert_eq!(
+            legend("←").unicode_name().as_deref(),
+            Some("LEFTWARDS ARROW")
+        );
+
+        assert_eq!(legend("").unicode_name(), None);
+        assert_eq!(legend("Esc").unicode_name(), None);
+        assert_eq!(legend("e\u{0301}").unicode_name(), None);
+    }
+
+    #[test]
+    fn test_legend_alignment_position() {
+        use LegendColumn::{Center, Left, Right};
+        use LegendRow::{Bottom, Front, Middle, Top};
+
+        let expected = [
+            (Top, Left),
+            (Top, Center),
+            (Top, Right),
+            (Middle, Left),
+            (Middle, Center),
+            (Middle, Right),
+            (Bottom, Left),
+            (Bottom, Center),
+            (Bottom, Right),
+            (Front, Left),
+            (Front, Center),
+            (Front, Right),
+        ];
+
+        // Every position can be displayed without any centering
+        let none = Alignment::new(0).unwrap();
+        let positions = (0..NUM_LEGENDS).map(|i| Legend::alignment_position(i, none));
+        assert!(positions.eq(expected.map(Some)));
+
+        // The default alignment only displays the centre front legend
+        let default = Alignment::new(4).unwrap();
+        assert_eq!(Legend::alignment_position(0, default), Some((Top, Left)));
+        assert_eq!(
+            Legend::alignment_position(8, default),
+            Some((Bottom, Right))
+        );
+        assert_eq!(Legend::alignment_position(9, default), None);
+        assert_eq!(
+            Legend::alignment_position(10, default),
+            Some((Front, Center))
+        );
+        assert_eq!(Legend::alignment_position(11, default), None);
+
+        // Legends parsed from KLE with the default alignment end up in the expected positions
+        let key = Key::<f64> {
+            legends: realign_legends(
+                (0..NUM_LEGENDS).map(|i| Some(legend(&i.to_string()))),
+                default,
+            ),
+            ..Key::default()
+        };
+        assert_eq!(key.legend_text_at(0), Some("0"));
+        assert_eq!(key.legend_text_at(8), Some("3"));
+
+        let centered = Alignment::max();
+        assert_eq!(Legend::alignment_position(0, centered), None);
+        assert_eq!(
+            Legend::alignment_position(4, centered),
+            Some((Middle, Center))
+        );
+
+        assert_eq!(Legend::alignment_position(NUM_LEGENDS, default), None);
+        assert_eq!(Legend::alignment_position(usize::MAX, none), None);
+    }
+
+    #[test]
+    fn test_legend_visual_position() {
+        use LegendHAlign::{Center, Left, Right};
+        use LegendVAlign::{Bottom, Front, Middle, Top};
+
+        let expected = [
+            (Left, Top),
+            (Left, Bottom),
+            (Right, Top),
+            (Right, Bottom),
+            (Center, Front),
+            (Left, Front),
+            (Left, Middle),
+            (Right, Middle),
+            (Center, Top),
+            (Center, Middle),
+            (Center, Bottom),
+            (Right, Front),
+        ];
+
+        let default = Alignment::default();
+        let positions = (0..NUM_LEGENDS).map(|i| Legend::visual_position(i, default));
+        assert!(positions.eq(expected));
+
+        let centered = Alignment::max();
+        assert_eq!(Legend::visual_position(0, centered), (Center, Middle));
+        assert_eq!(Legend::visual_position(4, centered), (Center, Front));
+    }
+
+    #[test]
+    fn test_legend_graphemes() {
+        let legend = |text: &str| Legend {
+            text: text.into(),
+            ..Legend::default()
+        };
+
+        // Combining acute accent, flag emoji, and ZWJ emoji sequence
+        for text in [
+            "a\u{0301}",
+            "\u{1F1EC}\u{1F1E7}",
+            "\u{1F469}\u{200D}\u{1F4BB}",
+        ] {
+            let legend = legend(text);
+            assert!(legend.codepoints().count() > 1);
+            assert_eq!(legend.grapheme_count(), 1);
+            assert_eq!(legend.first_grapheme(), Some(text));
+        }
+
+        let legend = legend("A\u{0301}b");
+        assert!(legend.codepoints().eq(['A', '\u{0301}', 'b']));
+        assert_eq!(legend.grapheme_count(), 2);
+        assert_eq!(legend.first_grapheme(), Some("A\u{0301}"));
+
+        let empty = Legend::default();
+        assert_eq!(empty.grapheme_count(), 0);
+        assert_eq!(empty.first_grapheme(), None);
+    }
+
+    #[test]
+    fn test_legend_pixels_at_dpi() {
+        let legend = |size| Legend {
+            size,
+            ..Legend::default()
+        };
+
+        assert_is_close!(legend(3).pixels_at_dpi(72.0), 10.0);
+        assert_is_close!(legend(3).pixels_at_dpi(96.0), 40.0 / 3.0);
+        assert_is_close!(legend(6).pixels_at_dpi(96.0), 80.0 / 3.0);
+        assert_is_close!(legend(12).pixels_at_dpi(72.0), 30.0);
+    }
+
+    #[test]
+    fn test_key_legends_iter() {
+        let legend = |text: &str| Legend {
+            text: text.into(),
+            ..Legend::default()
+        };
+        let mut key = Key::<f64>::default();
+        key.legends[0] = Some(legend("A"));
+        key.legends[4] = Some(legend("B"));
+        key.legends[11] = Some(legend("C"));
+
+        let indices: Vec<_> = key.legends_iter().map(|(i, _)| i).collect();
+        assert_eq!(indices, [0, 4, 11]);
+
+        let texts: Vec<_> = key.present_legends().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts, ["A", "B", "C"]);
+
+        for (i, legend) in key.legends_iter_mut() {
+            legend.size = i;
+        }
+        for legend in key.present_legends_mut() {
+            legend.text.push('!');
+        }
+        assert_eq!(
+            key.legends[4],
+            Some(Legend {
+                size: 4,
+                ..legend("B!")
+            })
+        );
+
+        assert!(key.has_legend(0));
+        assert!(!key.has_legend(1));
+        assert!(!key.has_legend(12));
+        assert_eq!(key.legend_count(), 3);
+        assert_eq!(Key::<f64>::default().legend_count(), 0);
+    }
+
+    #[test]
+    fn test_key_legend_text_at() {
+        // KLE's legend order for the default alignment is top left, bottom left, top right, ...
+        let legends = ["A", "B", "C", "D"].map(|text| {
+            Some(Legend {
+                text: text.into(),
+                ..Legend::default()
+            })
+        });
+        let key = Key::<f64> {
+            legends: realign_legends(legends, Alignment::default()),
+            ..Key::default()
+        };
+
+        assert_eq!(key.legend_text_at(0), Some("A")); // top left
+        assert_eq!(key.legend_text_at(2), Some("C")); // top right
+        assert_eq!(key.legend_text_at(6), Some("B")); // bottom left
+        assert_eq!(key.legend_text_at(8), Some("D")); // bottom right
+        assert_eq!(key.legend_text_at(1), None);
+        assert_eq!(key.legend_text_at(12), None);
+    }
+
+    #[test]
+    fn test_key_legends_as_strings() {
+        let keyboard: crate::Keyboard = serde_json::from_str(r#"[["A", "B\nC\n\n\nD"]]"#).unwrap();
+
+        let legends = keyboard.keys[0].legends_as_strings();
+        assert_eq!(legends[0], Some("A".to_string()));
+        assert!(legends[1..].iter().all(Option::is_none));
+
+        let legends = keyboard.keys[1].legends_as_strings();
+        assert_eq!(legends[0], Some("B".to_string()));
+        assert_eq!(legends[6], Some("C".to_string()));
+        assert_eq!(legends[10], Some("D".to_string()));
+        assert_eq!(legends.iter().flatten().count(), 3);
+
+        assert_eq!(
+            Key::<f64>::default().legends_as_strings(),
+            core::array::from_fn(|_| None)
+        );
+    }
+
+    #[test]
+    fn test_key_legend_strings_lossy() {
+        let mut key = Key::<f64>::default();
+        key.set_legend_text(4, "A");
+        let legends = key.legend_strings_lossy();
+
+        assert_eq!(legends[4], "A");
+        assert!((legends.iter().enumerate()).all(|(i, text)| i == 4 || text.is_empty()));
+    }
+
+    #[test]
+    fn test_key_first_legend_text() {
+        let mut key = Key::<f64>::default();
+        assert_eq!(key.first_legend_text(), None);
+
+        key.set_legend_text(9, "B");
+        assert_eq!(key.first_legend_text(), Some("B"));
+
+        key.set_legend_text(5, "A");
+        assert_eq!(key.first_legend_text(), Some("A"));
+    }
+
+    #[test]
+    fn test_key_primary_secondary_front_legend() {
+        fn text(legend: Option<&Legend>) -> Option<&str> {
+            legend.map(|legend| legend.text.as_str())
+        }
+
+        let keyboard: crate::Keyboard = serde_json::from_str(
+            r#"[[
+                "!\n1\n\n\nFn",
+                "Q",
+                "\nShift",
+                {"a": 7}, "Tab",
+                {"a": 0}, "A\nB\nC\nD\nE"
+            ]]"#,
+        )
+        .unwrap();
+
+        let key = &keyboard.keys[0];
+        assert_eq!(text(key.primary_legend()), Some("!"));
+        assert_eq!(text(key.secondary_legend()), Some("1"));
+        assert_eq!(text(key.front_legend()), Some("Fn"));
+        assert!(key.legends[10].is_some());
+
+        let key = &keyboard.keys[1];
+        assert_eq!(text(key.primary_legend()), Some("Q"));
+        assert_eq!(text(key.secondary_legend()), None);
+        assert_eq!(text(key.front_legend()), None);
+
+        let key = &keyboard.keys[2];
+        assert_eq!(text(key.primary_legend()), None);
+        assert_eq!(text(key.secondary_legend()), Some("Shift"));
+
+        // Centred legends
+        let key = &keyboard.keys[3];
+        assert_eq!(text(key.primary_legend()), Some("Tab"));
+        assert!(key.legends[4].is_some());
+
+        // With alignment 0 the front legend is on the left
+        let key = &keyboard.keys[4];
+        assert_eq!(text(key.primary_legend()), Some("A"));
+        assert_eq!(text(key.secondary_legend()), Some("B"));
+        assert_eq!(text(key.front_legend()), Some("E"));
+        assert!(key.legends[9].is_some());
+
+        let key = Key::<f64>::default();
+        assert_eq!(key.primary_legend(), None);
+        assert_eq!(key.secondary_legend(), None);
+        assert_eq!(key.front_legend(), None);
+    }
+
+    #[test]
+    fn test_key_set_legend_text() {
+        let mut key = Key::<f64>::default();
+        assert!(key.set_legend_text(3, "A"));
+        assert_eq!(
+            key.legends[3],
+            Some(Legend {
+                text: "A".into(),
+                ..Legend::default()
+            })
+        );
+
+        key.legends[3].as_mut().unwrap().size = 5;
+        key.set_legend_text(3, "B");
+        assert_eq!(key.legend_text_at(3), Some("B"));
+        assert_eq!(key.legends[3].as_ref().unwrap().size, 5);
+        assert_eq!(key.legend_count(), 1);
+
+        assert!(!key.set_legend_text(12, "C"));
+        assert_eq!(key.legend_count(), 1);
+    }
+
+    #[test]
+    fn test_key_with_legend_at() {
+        let legend = Legend {
+            text: "A".into(),
+            size: 4,
+            ..Legend::default()
+        };
+        let key = Key::<f64>::default()
+            .with_legend_at(11, legend.clone())
+            .with_legend_at(0, Legend::default());
+
+        assert_eq!(key.legends[11], Some(legend));
+        assert_eq!(key.legend_text_at(0), Some(""));
+        assert_eq!(key.legend_count(), 2);
+
+        assert_eq!(key.clone().with_legend_at(12, Legend::default()), key);
+    }
+}
@@ -0,0 +1,359 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use num_traits::real::Real;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{Map, Value};
+
+use crate::de::{KleLegendsOrProps, KleMetadata, KlePropsObject};
+use crate::strict::METADATA_FIELDS;
+use crate::{Keyboard, Metadata};
+
+// Key properties recognised by KLE
+const PROPS_FIELDS: [&str; 25] = [
+    "x", "y", "w", "h", "x2", "y2", "w2", "h2", "r", "rx", "ry", "l", "n", "d", "g", "sm", "sb",
+    "st", "c", "t", "a", "p", "f", "f2", "fa",
+];
+
+// The field name used in warnings for legends which are neither a string nor an object
+const LEGEND_FIELD: &str = "legend";
+
+/// An invalid value in a key's properties or legends which was skipped by
+/// [`Keyboard::try_from_str_lenient`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyParseWarning {
+    /// The index of the row, not including the metadata object.
+    pub row: usize,
+    /// The index of the item within the row's JSON array.
+    pub col: usize,
+    /// The name of the invalid property, or `"legend"` if the item was not a valid legend.
+    pub field: &'static str,
+    /// A description of the error.
+    pub error: String,
+}
+
+impl fmt::Display for KeyParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid {} at row {}, key {}: {}",
+            self.field, self.row, self.col, self.error
+        )
+    }
+}
+
+/// A recoverable error found when deserialising a layout with
+/// [`Keyboard::try_from_str_lenient`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseWarning {
+    /// A key property or legend was invalid. Invalid properties are ignored and invalid legends
+    /// are replaced by a key with no legends.
+    Key(KeyParseWarning),
+    /// A metadata field was invalid and was replaced by its default value.
+    Metadata {
+        /// The name of the invalid field.
+        field: &'static str,
+        /// A description of the error.
+        error: String,
+    },
+    /// A row was not a JSON array and was skipped.
+    Row {
+        /// The index of the row, not including the metadata object.
+        row: usize,
+    },
+    /// The input could not be deserialised as a KLE layout at all, so an empty layout was
+    /// returned.
+    Layout {
+        /// A description of the error.
+        error: String,
+    },
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key(warning) => write!(f, "{warning}"),
+            Self::Metadata { field, error } => {
+                write!(f, "invalid metadata field {field:?}: {error}")
+            }
+            Self::Row { row } => write!(f, "row {row} is not an array"),
+            Self::Layout { error } => write!(f, "invalid layout: {error}"),
+        }
+    }
+}
+
+impl From<KeyParseWarning> for ParseWarning {
+    fn from(warning: KeyParseWarning) -> Self {
+        Self::Key(warning)
+    }
+}
+
+// Removes each field which fails to deserialise on its own, returning the field names and errors
+fn remove_invalid_fields<D>(
+    object: &mut Map<String, Value>,
+    fields: &[&'static str],
+) -> Vec<(&'static str, String)>
+where
+    D: DeserializeOwned,
+{
+    let invalid: Vec<_> = (fields.iter())
+        .filter_map(|&field| {
+            let value = object.get(field)?.clone();
+            let single = Value::Object(core::iter::once((field.to_string(), value)).collect());
+            serde_json::from_value::<D>(single)
+                .err()
+                .map(|error| (field, error.to_string()))
+        })
+        .collect();
+
+    for (field, _) in &invalid {
+        object.remove(*field);
+    }
+
+    invalid
+}
+
+fn recover_row<T>(row: usize, keys: &mut [Value], warnings: &mut Vec<ParseWarning>)
+where
+    T: Real + DeserializeOwned,
+{
+    for (col, key) in keys.iter_mut().enumerate() {
+        if let Some(props) = key.as_object_mut() {
+            let invalid = remove_invalid_fields::<KlePropsObject<T>>(props, &PROPS_FIELDS);
+            warnings.extend(invalid.into_iter().map(|(field, error)| {
+                ParseWarning::Key(KeyParseWarning {
+                    row,
+                    col,
+                    field,
+                    error,
+                })
+            }));
+        } else if let Err(error) = KleLegendsOrProps::<T>::deserialize(&*key) {
+            // Keep a blank key in place so the positions of the following keys are unaffected
+            warnings.push(ParseWarning::Key(KeyParseWarning {
+                row,
+                col,
+                field: LEGEND_FIELD,
+                error: error.to_string(),
+            }));
+            *key = Value::String(String::new());
+        }
+    }
+}
+
+fn recover_layout<T>(value: &mut Value, warnings: &mut Vec<ParseWarning>)
+where
+    T: Real + DeserializeOwned,
+{
+    // Anything that isn't an array is left for the deserialiser to reject
+    let items = match value.as_array_mut() {
+        Some(items) => items,
+        None => return,
+    };
+    let mut rows = core::mem::take(items).into_iter().peekable();
+
+    if let Some(mut metadata) = rows.next_if(Value::is_object) {
+        if let Some(map) = metadata.as_object_mut() {
+            let invalid = remove_invalid_fields::<KleMetadata>(map, &METADATA_FIELDS);
+            warnings.extend(
+                (invalid.into_iter()).map(|(field, error)| ParseWarning::Metadata { field, error }),
+            );
+        }
+        items.push(metadata);
+    }
+
+    for (row, mut item) in rows.enumerate() {
+        if let Some(keys) = item.as_array_mut() {
+            recover_row::<T>(row, keys, warnings);
+            items.push(item);
+        } else {
+            warnings.push(ParseWarning::Row { row });
+        }
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real + DeserializeOwned,
+{
+    /// Deserialises a [`Keyboard`] from a string containing KLE JSON data, recovering from errors
+    /// in individual keys where possible.
+    ///
+    /// Where the regular deserialiser rejects the whole layout, this skips over the invalid parts
+    /// and returns as much of the layout as possible along with a [`ParseWarning`] for each issue:
+    ///
+    /// * invalid key properties (such as an unparsable colour) are ignored, as if they were not
+    ///   set;
+    /// * items in a row which are neither a legend string nor a properties object are replaced by
+    ///   a key with no legends, so the positions of the following keys are unaffected;
+    /// * invalid metadata fields are replaced by their default values; and
+    /// * rows which are not JSON arrays are skipped.
+    ///
+    /// If the input is not valid JSON or is not a JSON array, an empty layout is returned along
+    /// with a [`ParseWarning::Layout`].
+    ///
+    /// ```
+    /// use kle_serial::{Keyboard, ParseWarning};
+    ///
+    /// let (keyboard, warnings) =
+    ///     Keyboard::<f64>::try_from_str_lenient(r#"[["A", {"c": "not a colour"}, "B", "C"]]"#);
+    ///
+    /// assert_eq!(keyboard.keys.len(), 3);
+    /// assert_eq!(warnings.len(), 1);
+    /// assert!(matches!(&warnings[0], ParseWarning::Key(warning) if warning.field == "c"));
+    /// ```
+    #[must_use]
+    pub fn try_from_str_lenient(s: &str) -> (Self, Vec<ParseWarning>) {
+        let mut warnings = Vec::new();
+
+        let result = serde_json::from_str::<Value>(s).and_then(|mut value| {
+            recover_layout::<T>(&mut value, &mut warnings);
+            serde_json::from_value(value)
+        });
+
+        match result {
+            Ok(keyboard) => (keyboard, warnings),
+            Err(error) => {
+                warnings.push(ParseWarning::Layout {
+                    error: error.to_string(),
+                });
+                let keyboard = Self {
+                    metadata: Metadata::default(),
+                    keys: Vec::new(),
+                };
+                (keyboard, warnings)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use isclose::assert_is_close;
+
+    use super::*;
+    use crate::Key;
+
+    #[test]
+    fn test_parse_warning_display() {
+        let warning = ParseWarning::Key(KeyParseWarning {
+            row: 1,
+            col: 2,
+            field: "c",
+            error: "bad colour".into(),
+        });
+        assert_eq!(
+            format!("{warning}"),
+            "invalid c at row 1, key 2: bad colour"
+        );
+
+        let warning = ParseWarning::Metadata {
+            field: "pcb",
+            error: "not a bool".into(),
+        };
+        assert_eq!(
+            format!("{warning}"),
+            r#"invalid metadata field "pcb": not a bool"#
+        );
+
+        let warning = ParseWarning::Row { row: 3 };
+        assert_eq!(format!("{warning}"), "row 3 is not an array");
+
+        let warning = ParseWarning::Layout {
+            error: "EOF".into(),
+        };
+        assert_eq!(format!("{warning}"), "invalid layout: EOF");
+    }
+
+    #[test]
+    fn test_keyboard_try_from_str_lenient() {
+        let (keyboard, warnings) = Keyboard::<f64>::try_from_str_lenient(
+            r##"[
+                ["A", "B", "C"],
+                ["D", {"c": "#nope", "w": 2}, "E", "F"],
+                ["G", "H", "I"]
+            ]"##,
+        );
+
+        assert_eq!(keyboard.keys.len(), 9);
+        let legends: Vec<_> = (keyboard.keys.iter())
+            .map(|key| key.legends[0].as_ref().unwrap().text.as_str())
+            .collect();
+        assert_eq!(legends, ["A", "B", "C", "D", "E", "F", "G", "H", "I"]);
+
+        // The other properties in the object with the bad colour are still applied
+        assert_eq!(keyboard.keys[4].color, Key::<f64>::default().color);
+        assert_is_close!(keyboard.keys[4].width, 2.0);
+        assert_is_close!(keyboard.keys[5].x, 3.0);
+
+        assert_eq!(warnings.len(), 1);
+        assert_matches!(
+            &warnings[0],
+            ParseWarning::Key(KeyParseWarning {
+                row: 1,
+                col: 1,
+                field: "c",
+                ..
+            })
+        );
+    }
+
+    #[test]
+    fn test_keyboard_try_from_str_lenient_recovery() {
+        let (keyboard, warnings) = Keyboard::<f64>::try_from_str_lenient(
+            r#"[
+                {"name": "test", "pcb": "yes"},
+                ["A", 42, "B"],
+                "not a row",
+                [{"a": 9, "f": 3}, "C"]
+            ]"#,
+        );
+
+        assert_eq!(keyboard.metadata.name, "test");
+        assert!(!keyboard.metadata.pcb_mount);
+        assert_eq!(keyboard.keys.len(), 4);
+        assert!(keyboard.keys[1].legends.iter().all(Option::is_none));
+        assert_is_close!(keyboard.keys[2].x, 2.0);
+        assert_is_close!(keyboard.keys[3].y, 1.0);
+        assert_eq!(keyboard.keys[3].legends[0].as_ref().unwrap().size, 3);
+
+        assert_matches!(
+            warnings.as_slice(),
+            [
+                ParseWarning::Metadata { field: "pcb", .. },
+                ParseWarning::Key(KeyParseWarning {
+                    row: 0,
+                    col: 1,
+                    field: "legend",
+                    ..
+                }),
+                ParseWarning::Row { row: 1 },
+                ParseWarning::Key(KeyParseWarning {
+                    row: 2,
+                    col: 0,
+                    field: "a",
+                    ..
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keyboard_try_from_str_lenient_invalid() {
+        let (keyboard, warnings) = Keyboard::<f64>::try_from_str_lenient(r#"[["A"]"#);
+        assert!(keyboard.keys.is_empty());
+        assert_matches!(warnings.as_slice(), [ParseWarning::Layout { .. }]);
+
+        let (keyboard, warnings) = Keyboard::<f64>::try_from_str_lenient("null");
+        assert!(keyboard.keys.is_empty());
+        assert_matches!(warnings.as_slice(), [ParseWarning::Layout { .. }]);
+
+        let (keyboard, warnings) = Keyboard::<f64>::try_from_str_lenient(r#"[["A", "B"]]"#);
+        assert_eq!(keyboard.keys.len(), 2);
+        assert!(warnings.is_empty());
+    }
+}
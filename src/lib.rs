@@ -1,8 +1,12 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(missing_docs, dead_code)]
 #![warn(clippy::all, clippy::pedantic, clippy::cargo)]
+// The no_alloc feature name is negative, but it is additive and only enables extra types
+#![allow(clippy::negative_feature_names)]
 
 //! A Rust library for deserialising [Keyboard Layout Editor] files. Designed to be used in
-//! conjunction with [`serde_json`] to deserialize JSON files exported from KLE.
+//! conjunction with [`serde_json`] to deserialize JSON files exported from KLE. Layouts can also be
+//! serialised back into KLE's JSON format so they can be imported into KLE.
 //!
 //! # Example
 //!
@@ -32,33 +36,157 @@
 //! assert!(keyboard.keys[0].legends[1].is_none());
 //! ```
 //!
+//! # Coordinate types
+//!
+//! Types such as [`Key`] and [`Keyboard`] are generic over their coordinate type, which defaults to
+//! `f64`. Any type implementing [`num_traits::real::Real`] can be used, which includes every
+//! [`num_traits::Float`] type such as `f32`. `Real` in turn implies [`num_traits::Zero`] and
+//! [`num_traits::One`], which are used for default positions and sizes.
+//!
+//! Keys are compared approximately, so [`Key`] does not implement [`Eq`] or
+//! [`Hash`](core::hash::Hash). To store keys in a `HashMap` or `HashSet`, use a hashable float type
+//! such as `OrderedFloat<f64>` from the [`ordered-float`] crate and wrap the keys in an
+//! [`OrderedKey`]. Since `OrderedFloat` is totally ordered, such keys can also be sorted by
+//! position using [`slice::sort_by_key`].
+//!
+//! # Features
+//!
+//! * `std` (enabled by default): Enables support for the standard library. Disabling this allows
+//!   the crate to be used in `no_std` environments which provide `alloc`. Without `std`, only hex
+//!   colour values are supported when deserialising and `Keyboard::from_reader` is unavailable.
+//! * `json` (enabled by default): Enables integration with [`serde_json`], such as
+//!   `Keyboard::from_str` and conversions to and from `serde_json::Value`. Unlike
+//!   `serde_json::from_str`, errors returned by `Keyboard::from_str` include the location of the
+//!   offending key.
+//! * `wasm`: Enables JavaScript bindings using [`wasm-bindgen`] in the `wasm` module. This also
+//!   enables the `std` and `json` features.
+//! * `url-codec`: Enables `KleUrlCodec` for encoding layouts into and decoding layouts from URL
+//!   fragments. This also enables the `json` feature.
+//! * `no_alloc`: Enables `FixedKeyboard`, which stores up to a fixed number of keys in an array
+//!   rather than a `Vec`, and `FixedKeyboardDeserializer`, which deserialises a layout directly into
+//!   an existing `FixedKeyboard` without buffering the layout. Keys still allocate for their text,
+//!   so this does not remove the need for an allocator.
+//! * `unicode-names`: Enables `Legend::unicode_name` for looking up the Unicode names of legends
+//!   using [`unicode_names2`].
+//!
 //! [Keyboard Layout Editor]: http://www.keyboard-layout-editor.com/
 //! [`serde_json`]: https://crates.io/crates/serde_json
+//! [`wasm-bindgen`]: https://crates.io/crates/wasm-bindgen
+//! [`ordered-float`]: https://crates.io/crates/ordered-float
+//! [`unicode_names2`]: https://crates.io/crates/unicode_names2
 //! [example]: https://raw.githubusercontent.com/staticintlucas/kle-serial-rs/main/doc/example.png
 
+extern crate alloc;
+
+mod accessibility;
+mod adjacency;
+mod background;
+mod builder;
+mod classify;
+mod color;
+mod cutout;
 mod de;
+#[cfg(feature = "json")]
+mod diagnostics;
+mod diff;
+mod display;
+mod export;
 pub mod f32;
 pub mod f64;
+mod filter;
+#[cfg(feature = "no_alloc")]
+mod fixed;
+mod geom;
+mod grid;
+#[cfg(feature = "std")]
+mod group;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "kdtree")]
+mod kdtree;
+mod keytype;
+mod layout;
+mod legend;
+#[cfg(feature = "json")]
+mod lenient;
+mod ordered;
+mod profile;
+#[cfg(test)]
+mod proptest_tests;
+mod ser;
+mod stabilizer;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(all(feature = "json", feature = "std"))]
+mod stream;
+#[cfg(feature = "json")]
+mod strict;
+mod switch;
+mod transform;
+#[cfg(feature = "url-codec")]
+mod url;
 mod utils;
+mod validate;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
-use num_traits::real::Real;
-use serde::Deserialize;
+use alloc::{string::String, vec::Vec};
 
-use de::{KleKeyboard, KleLayoutIterator};
-use utils::FontSize;
+use num_traits::real::Real;
+use serde::{Deserialize, Serialize};
+
+pub use accessibility::AccessibilityIssue;
+pub use builder::{BuilderError, KeyBuilder, KeyboardBuilder, MetadataBuilder};
+pub use classify::LayoutType;
+pub use color::{ColorExt, KleColor, ParseColorError};
+pub use cutout::CutoutShape;
+use de::KleKeyboard;
+pub use de::{KleLayoutIterator, KleLegendsOrProps, KleProps, KlePropsObject};
+#[cfg(feature = "json")]
+pub use diagnostics::{CollectingDiagnostics, DiagnosticKind, ParseDiagnostics};
+pub use diff::KeyboardDiff;
+#[cfg(feature = "json")]
+pub use export::QmkOptions;
+pub use export::{SvgOptions, ZmkOptions};
+#[cfg(feature = "no_alloc")]
+pub use fixed::{FixedCapacityError, FixedKeyboard, FixedKeyboardDeserializer};
+pub use geom::ClipMode;
+pub use grid::{KeyGrid, KeyGridIter, MatrixCollisionError};
+#[cfg(all(feature = "json", feature = "std"))]
+pub use json::FileError;
+#[cfg(feature = "json")]
+pub use json::{normalize_kle_json, KeyLocation, ParseError};
+#[cfg(feature = "kdtree")]
+pub use kdtree::KdTree;
+pub use keytype::KeyType;
+pub use legend::{LegendColumn, LegendHAlign, LegendRow, LegendVAlign};
+#[cfg(feature = "json")]
+pub use lenient::{KeyParseWarning, ParseWarning};
+pub use ordered::OrderedKey;
+pub use profile::Profile;
+#[cfg(feature = "std")]
+pub use stats::KeyboardStatistics;
+#[cfg(all(feature = "json", feature = "std"))]
+pub use stream::StreamingKeyIterator;
+#[cfg(feature = "json")]
+pub use strict::StrictError;
+#[cfg(feature = "url-codec")]
+pub use url::{KleUrlCodec, UrlDecodeError};
+pub use utils::{
+    realign_legends, unalign_legends, Alignment, BoundsError, FontSize, LEGEND_MAPPING,
+};
+pub use validate::ValidationWarning;
 
 /// Colour type used for deserialising. Type alias of [`rgb::RGBA8`].
 pub type Color = rgb::RGBA8;
 
 const NUM_LEGENDS: usize = 12; // Number of legends on a key
 
-pub(crate) mod color {
-    use crate::Color;
+/// The size of one keyboard unit in millimetres.
+pub const UNIT_MM: f64 = 19.05;
 
-    pub(crate) const BACKGROUND: Color = Color::new(0xEE, 0xEE, 0xEE, 0xFF); // #EEEEEE
-    pub(crate) const KEY: Color = Color::new(0xCC, 0xCC, 0xCC, 0xFF); // #CCCCCC
-    pub(crate) const LEGEND: Color = Color::new(0x00, 0x00, 0x00, 0xFF); // #000000
-}
+/// The size of one keyboard unit in inches.
+pub const UNIT_IN: f64 = 0.75;
 
 /// A struct representing a single legend.
 ///
@@ -71,7 +199,7 @@ pub(crate) mod color {
 /// [`kle-serial`]: https://github.com/ijprest/kle-serial
 ///
 /// </div>
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Legend {
     /// The legend's text.
     pub text: String,
@@ -92,7 +220,9 @@ impl Default for Legend {
 }
 
 /// A struct representing a key switch.
-#[derive(Debug, Clone, Default, PartialEq)]
+///
+/// Switches are ordered by mount, then brand, then type.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Switch {
     /// The switch mount. Typically either `"cherry"` or `"alps"`.
     pub mount: String,
@@ -103,7 +233,13 @@ pub struct Switch {
 }
 
 /// A struct representing a single key.
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Keys are compared using approximate equality for their position, size, and rotation, so small
+/// floating point errors such as `0.1 + 0.2 != 0.3` do not cause otherwise identical keys to
+/// compare unequal. Values are considered equal if they differ by at most the square root of
+/// [`T::epsilon`](Real::epsilon) relative to their magnitude (or absolutely for magnitudes
+/// below 1).
+#[derive(Debug, Clone)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct Key<T = f64>
 where
@@ -224,7 +360,7 @@ where
 {
     fn default() -> Self {
         Self {
-            legends: std::array::from_fn(|_| None),
+            legends: core::array::from_fn(|_| None),
             color: color::KEY,
             x: T::zero(),
             y: T::zero(),
@@ -247,6 +383,60 @@ where
     }
 }
 
+impl<T> PartialEq for Key<T>
+where
+    T: Real,
+{
+    fn eq(&self, other: &Self) -> bool {
+        let approx_eq = |a: T, b: T| {
+            let scale = a.abs().max(b.abs()).max(T::one());
+            (a - b).abs() <= T::epsilon().sqrt() * scale
+        };
+
+        let reals = |key: &Self| {
+            [
+                key.x,
+                key.y,
+                key.width,
+                key.height,
+                key.x2,
+                key.y2,
+                key.width2,
+                key.height2,
+                key.rotation,
+                key.rx,
+                key.ry,
+            ]
+        };
+
+        (reals(self).into_iter().zip(reals(other))).all(|(a, b)| approx_eq(a, b))
+            && self.legends == other.legends
+            && self.color == other.color
+            && self.profile == other.profile
+            && self.switch == other.switch
+            && self.ghosted == other.ghosted
+            && self.stepped == other.stepped
+            && self.homing == other.homing
+            && self.decal == other.decal
+    }
+}
+
+/// An axis-aligned rectangle, measured in keyboard units (typically 19.05 mm or 0.75 in).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect<T = f64>
+where
+    T: Real,
+{
+    /// The X position of the left edge of the rectangle.
+    pub x: T,
+    /// The Y position of the top edge of the rectangle.
+    pub y: T,
+    /// The width of the rectangle.
+    pub width: T,
+    /// The height of the rectangle.
+    pub height: T,
+}
+
 /// The background style of a KLE layout.
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct Background {
@@ -289,6 +479,9 @@ pub struct Metadata {
     /// Notes for the layout. KLE expects GitHub-flavoured Markdown and can render this using the
     /// *preview* button, but any string data is considered valid.
     pub notes: String,
+    /// Custom CSS for the layout. KLE injects this into the page in a `<style>` tag, allowing
+    /// layouts to define custom fonts, icons, and legend styling.
+    pub css: String,
 }
 
 impl Default for Metadata {
@@ -303,11 +496,15 @@ impl Default for Metadata {
             plate_mount: false,
             pcb_mount: false,
             notes: String::new(),
+            css: String::new(),
         }
     }
 }
 
 /// A keyboard deserialised from a KLE JSON file.
+///
+/// This can also be serialised back into KLE's JSON format. Keys are written in order, starting a
+/// new row whenever a key's `y` position or rotation changes.
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct Keyboard<T = f64>
 where
@@ -323,7 +520,7 @@ impl<'de, T> Deserialize<'de> for Keyboard<T>
 where
     T: Real + Deserialize<'de>,
 {
-    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
@@ -336,7 +533,23 @@ where
     }
 }
 
+impl<T> Serialize for Keyboard<T>
+where
+    T: Real + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        KleKeyboard::from(self).serialize(serializer)
+    }
+}
+
 /// An iterator of [`Key`]s deserialised from a KLE JSON file.
+///
+/// The number of keys is known ahead of time, so this implements [`ExactSizeIterator`]. It also
+/// implements [`DoubleEndedIterator`], but since each key's properties depend on the keys before
+/// it, the first call to [`next_back`](DoubleEndedIterator::next_back) builds all remaining keys.
 #[derive(Debug, Clone)]
 pub struct KeyIterator<T = f64>(KleLayoutIterator<T>)
 where
@@ -365,6 +578,77 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         self.0.next()
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for KeyIterator<T> where T: Real {}
+
+impl<T> DoubleEndedIterator for KeyIterator<T>
+where
+    T: Real,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+/// A KLE layout deserialised into its [`Metadata`] and a lazy iterator of [`Key`]s.
+///
+/// Unlike [`KeyIterator`] this retains the layout's metadata, and unlike [`Keyboard`] keys are only
+/// built as they are iterated over.
+#[derive(Debug, Clone)]
+pub struct LayoutDeserializer<T = f64>
+where
+    T: Real,
+{
+    metadata: Metadata,
+    keys: KeyIterator<T>,
+}
+
+impl<T> LayoutDeserializer<T>
+where
+    T: Real,
+{
+    /// Returns the layout's metadata.
+    #[inline]
+    #[must_use]
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Converts this into an iterator over the layout's keys, discarding the metadata.
+    #[inline]
+    #[must_use]
+    pub fn into_key_iter(self) -> KeyIterator<T> {
+        self.keys
+    }
+
+    /// Splits this into the layout's metadata and an iterator over its keys.
+    #[inline]
+    #[must_use]
+    pub fn into_parts(self) -> (Metadata, KeyIterator<T>) {
+        (self.metadata, self.keys)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for LayoutDeserializer<T>
+where
+    T: Real + Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let KleKeyboard { meta, layout } = KleKeyboard::deserialize(deserializer)?;
+
+        Ok(Self {
+            metadata: meta.into(),
+            keys: KeyIterator(KleLayoutIterator::new(layout)),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -429,6 +713,100 @@ mod tests {
         assert_eq!(meta.notes, "");
     }
 
+    #[test]
+    fn test_key_eq() {
+        let json = r#"[[{"x": 0.1, "w": 1.3, "r": 15}, "A"]]"#;
+        let key1 = serde_json::from_str::<Keyboard>(json)
+            .unwrap()
+            .keys
+            .remove(0);
+        let key2 = serde_json::from_str::<Keyboard>(json)
+            .unwrap()
+            .keys
+            .remove(0);
+
+        assert_eq!(key1, key2);
+
+        let key3 = Key {
+            x: key1.x + 1e-10,
+            ..key1.clone()
+        };
+        assert_eq!(key1, key3);
+
+        let key4 = Key {
+            x: 0.1 + 0.2,
+            ..Key::default()
+        };
+        assert_eq!(
+            key4,
+            Key {
+                x: 0.3,
+                ..Key::default()
+            }
+        );
+
+        assert_ne!(
+            key1,
+            Key {
+                x: key1.x + 1e-3,
+                ..key1.clone()
+            }
+        );
+        assert_ne!(
+            key1,
+            Key {
+                homing: true,
+                ..key1.clone()
+            }
+        );
+        assert_ne!(
+            Key::<f32>::default(),
+            Key::<f32> {
+                y: 0.01,
+                ..Key::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_keyboard_deserialize_f32() {
+        let json = r#"[
+            [{"x": 0.1, "w": 1.3}, "A", {"x": 0.35, "y": 0.6, "r": 12.7, "rx": 3.15}, "B"],
+            [{"w2": 1.45, "h2": 0.85, "x2": -0.2, "y2": 0.15}, "C", {"w": 6.25}, "D"]
+        ]"#;
+        let kb32: Keyboard<f32> = serde_json::from_str(json).unwrap();
+        let kb64: Keyboard<f64> = serde_json::from_str(json).unwrap();
+
+        assert_eq!(kb32.keys.len(), kb64.keys.len());
+        for (k32, k64) in kb32.keys.iter().zip(kb64.keys.iter()) {
+            let fields = |k: &Key<f32>| {
+                [
+                    k.x, k.y, k.width, k.height, k.x2, k.y2, k.width2, k.height2, k.rotation, k.rx,
+                    k.ry,
+                ]
+            };
+            #[allow(clippy::cast_possible_truncation)]
+            let expected = fields(&Key {
+                x: k64.x as f32,
+                y: k64.y as f32,
+                width: k64.width as f32,
+                height: k64.height as f32,
+                x2: k64.x2 as f32,
+                y2: k64.y2 as f32,
+                width2: k64.width2 as f32,
+                height2: k64.height2 as f32,
+                rotation: k64.rotation as f32,
+                rx: k64.rx as f32,
+                ry: k64.ry as f32,
+                ..Key::default()
+            });
+
+            for (value, exp) in fields(k32).into_iter().zip(expected) {
+                assert_is_close!(value, exp);
+            }
+        }
+    }
+
     #[test]
     fn test_keyboard_deserialize() {
         let kb: Keyboard = serde_json::from_str(
@@ -463,9 +841,56 @@ mod tests {
         assert_eq!(kb.metadata.notes, "'tis a test");
         assert_eq!(kb.keys.len(), 0);
 
+        let kb: Keyboard =
+            serde_json::from_str(r#"[{"css": ".key { font-family: serif; }"}, ["A"]]"#).unwrap();
+        assert_eq!(kb.metadata.css, ".key { font-family: serif; }");
+        assert_eq!(kb.keys.len(), 1);
+
         assert!(serde_json::from_str::<Keyboard>("null").is_err());
     }
 
+    #[test]
+    fn test_keyboard_serialize() {
+        let kb: Keyboard = serde_json::from_str(r#"[["A", "B"], ["C"]]"#).unwrap();
+        assert_eq!(serde_json::to_string(&kb).unwrap(), r#"[["A","B"],["C"]]"#);
+
+        let kb: Keyboard = serde_json::from_str(r#"[{"notes": "'tis a test"}]"#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&kb).unwrap(),
+            r#"[{"notes":"'tis a test"}]"#
+        );
+
+        let kb: Keyboard = serde_json::from_str(
+            r##"[
+                {
+                    "name": "test",
+                    "backcolor": "#ff0000",
+                    "background": {"name": "name", "style": "style"}
+                },
+                [
+                    {"r": 15, "rx": 1, "ry": 2, "c": "#00ff00", "t": "#ff0000\n\n#0000ff", "f": 5},
+                    "A",
+                    {"x": 0.25, "w": 1.25, "h": 2, "w2": 1.5, "h2": 1, "x2": -0.25},
+                    "B\nC"
+                ],
+                [
+                    {
+                        "y": 0.5, "l": true, "n": true, "d": true, "g": true, "p": "DSA",
+                        "sm": "cherry", "sb": "cherry", "st": "MX1A-11xx", "fa": [1, 2, 3]
+                    },
+                    "D\nE\nF"
+                ],
+                [
+                    {"r": 0, "rx": 0, "ry": 0, "a": 7},
+                    "G"
+                ]
+            ]"##,
+        )
+        .unwrap();
+        let json = serde_json::to_string(&kb).unwrap();
+        assert_eq!(serde_json::from_str::<Keyboard>(&json).unwrap(), kb);
+    }
+
     #[test]
     fn test_key_iterator_deserialize() {
         let keys: Vec<_> = serde_json::from_str::<KeyIterator>(
@@ -507,4 +932,81 @@ mod tests {
 
         assert!(serde_json::from_str::<KeyIterator>("null").is_err());
     }
+
+    #[test]
+    fn test_key_iterator_exact_size() {
+        let json = r#"[{"name": "test"}, [{"a": 4}, "A", {"x": 1}, "B"], [], ["C", "D"]]"#;
+        let mut iter = serde_json::from_str::<KeyIterator>(json).unwrap();
+
+        assert_eq!(iter.len(), 4);
+        iter.next();
+        assert_eq!(iter.len(), 3);
+        iter.next_back();
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.len(), iter.collect::<Vec<_>>().len());
+
+        let iter = serde_json::from_str::<KeyIterator>(r#"[{"name": "test"}]"#).unwrap();
+        assert_eq!(iter.len(), 0);
+    }
+
+    #[test]
+    fn test_key_iterator_double_ended() {
+        let json = r#"[[{"a": 4}, "A", {"x": 1}, "B"], [{"y": 0.5}, "C", "D"]]"#;
+        let forward: Vec<_> = serde_json::from_str::<KeyIterator>(json).unwrap().collect();
+        let reverse: Vec<_> = serde_json::from_str::<KeyIterator>(json)
+            .unwrap()
+            .rev()
+            .collect();
+
+        assert_eq!(reverse, forward.into_iter().rev().collect::<Vec<_>>());
+
+        let mut iter = serde_json::from_str::<KeyIterator>(json).unwrap();
+        let text = |key: Option<Key>| key.unwrap().legends[0].take().unwrap().text;
+
+        assert_eq!(text(iter.next()), "A");
+        assert_eq!(text(iter.next_back()), "D");
+        assert_eq!(text(iter.next()), "B");
+        assert_eq!(text(iter.next_back()), "C");
+        assert!(iter.next().is_none());
+        assert!(iter.next_back().is_none());
+    }
+
+    #[test]
+    fn test_layout_deserializer_deserialize() {
+        let result: LayoutDeserializer = serde_json::from_str(
+            r#"[
+                {
+                    "name": "test",
+                    "author": "somebody"
+                },
+                [
+                    "A",
+                    "B",
+                    "C"
+                ],
+                [
+                    "D"
+                ]
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(result.metadata().name, "test");
+        assert_eq!(result.metadata().author, "somebody");
+
+        let (metadata, keys) = result.clone().into_parts();
+        assert_eq!(&metadata, result.metadata());
+        assert_eq!(keys.count(), 4);
+
+        let keys: Vec<_> = result.into_key_iter().collect();
+        assert_eq!(keys.len(), 4);
+        assert_is_close!(keys[3].y, 1.0);
+
+        let result: LayoutDeserializer<f32> = serde_json::from_str(r#"[["A"]]"#).unwrap();
+
+        assert_eq!(result.metadata(), &Metadata::default());
+        assert_eq!(result.into_key_iter().count(), 1);
+
+        assert!(serde_json::from_str::<LayoutDeserializer>("null").is_err());
+    }
 }
@@ -0,0 +1,190 @@
+use core::hash::{Hash, Hasher};
+
+use num_traits::real::Real;
+
+use crate::Key;
+
+/// A wrapper around a [`Key`] which compares keys exactly, allowing them to be used in a `HashMap`
+/// or `HashSet`.
+///
+/// [`Key`]'s [`PartialEq`] implementation compares coordinates approximately, so it cannot
+/// implement [`Eq`] or [`Hash`]. `OrderedKey` compares every field exactly instead, and implements
+/// [`Eq`] and [`Hash`] when the coordinate type does. The primitive float types do not, so this is
+/// intended to be used with a type such as [`OrderedFloat<f64>`] from the [`ordered-float`]
+/// crate.
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use kle_serial::{Key, OrderedKey};
+/// use ordered_float::OrderedFloat;
+///
+/// let key = Key::<OrderedFloat<f64>> {
+///     x: OrderedFloat(1.0),
+///     ..Key::default()
+/// };
+///
+/// let mut counts = HashMap::new();
+/// *counts.entry(OrderedKey::from(key.clone())).or_insert(0) += 1;
+/// *counts.entry(OrderedKey::from(key)).or_insert(0) += 1;
+///
+/// assert_eq!(counts.len(), 1);
+/// ```
+///
+/// [`OrderedFloat<f64>`]: https://docs.rs/ordered-float/latest/ordered_float/struct.OrderedFloat.html
+/// [`ordered-float`]: https://crates.io/crates/ordered-float
+#[derive(Debug, Clone)]
+pub struct OrderedKey<T>(pub Key<T>)
+where
+    T: Real;
+
+impl<T> OrderedKey<T>
+where
+    T: Real,
+{
+    /// Consumes the wrapper and returns the inner [`Key`].
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> Key<T> {
+        self.0
+    }
+
+    fn reals(&self) -> [T; 11] {
+        let key = &self.0;
+        [
+            key.x,
+            key.y,
+            key.width,
+            key.height,
+            key.x2,
+            key.y2,
+            key.width2,
+            key.height2,
+            key.rotation,
+            key.rx,
+            key.ry,
+        ]
+    }
+
+    fn flags(&self) -> [bool; 4] {
+        let key = &self.0;
+        [key.ghosted, key.stepped, key.homing, key.decal]
+    }
+}
+
+impl<T> From<Key<T>> for OrderedKey<T>
+where
+    T: Real,
+{
+    fn from(key: Key<T>) -> Self {
+        Self(key)
+    }
+}
+
+impl<T> From<OrderedKey<T>> for Key<T>
+where
+    T: Real,
+{
+    fn from(key: OrderedKey<T>) -> Self {
+        key.0
+    }
+}
+
+impl<T> PartialEq for OrderedKey<T>
+where
+    T: Real,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.reals() == other.reals()
+            && self.flags() == other.flags()
+            && self.0.legends == other.0.legends
+            && self.0.color == other.0.color
+            && self.0.profile == other.0.profile
+            && self.0.switch == other.0.switch
+    }
+}
+
+impl<T> Eq for OrderedKey<T> where T: Real + Eq {}
+
+impl<T> Hash for OrderedKey<T>
+where
+    T: Real + Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.reals().hash(state);
+        self.flags().hash(state);
+        self.0.legends.hash(state);
+        self.0.color.hash(state);
+        self.0.profile.hash(state);
+        self.0.switch.hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ordered_float::OrderedFloat;
+
+    use super::*;
+    use crate::Legend;
+
+    type Float = OrderedFloat<f64>;
+
+    #[test]
+    fn test_ordered_key_eq() {
+        let key = Key::<Float> {
+            x: OrderedFloat(1.0),
+            ..Key::default()
+        };
+        assert_eq!(OrderedKey::from(key.clone()), OrderedKey::from(key.clone()));
+
+        // Unlike Key, OrderedKey compares coordinates exactly
+        let other = Key {
+            x: OrderedFloat(1.0 + 1e-12),
+            ..key.clone()
+        };
+        assert_eq!(key, other);
+        assert_ne!(OrderedKey::from(key.clone()), OrderedKey::from(other));
+
+        let other = Key {
+            homing: true,
+            ..key.clone()
+        };
+        assert_ne!(OrderedKey::from(key.clone()), OrderedKey::from(other));
+
+        assert_eq!(OrderedKey::from(key.clone()).into_inner(), key);
+        assert_eq!(Key::from(OrderedKey::from(key.clone())), key);
+    }
+
+    #[test]
+    fn test_ordered_key_hash_map() {
+        let key = |x: f64, text: &str| {
+            OrderedKey::from(
+                Key::<Float> {
+                    x: OrderedFloat(x),
+                    ..Key::default()
+                }
+                .with_legend_at(
+                    0,
+                    Legend {
+                        text: text.into(),
+                        ..Legend::default()
+                    },
+                ),
+            )
+        };
+
+        let mut map = HashMap::new();
+        map.insert(key(0.0, "A"), 1);
+        map.insert(key(1.0, "B"), 2);
+        map.insert(key(0.0, "B"), 3);
+        map.insert(key(0.0, "A"), 4);
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map[&key(0.0, "A")], 4);
+        assert_eq!(map[&key(1.0, "B")], 2);
+        assert_eq!(map[&key(0.0, "B")], 3);
+        assert!(!map.contains_key(&key(2.0, "A")));
+    }
+}
@@ -0,0 +1,161 @@
+use alloc::string::String;
+use core::{convert::Infallible, fmt, str::FromStr};
+
+use num_traits::real::Real;
+
+use crate::Key;
+
+/// A well-known keycap profile.
+///
+/// This can be parsed from the free-form [`Key::profile`] field using [`Key::profile_type`] or
+/// [`Profile::from_str`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// DSA profile.
+    Dsa,
+    /// SA profile.
+    Sa,
+    /// OEM profile.
+    Oem,
+    /// Cherry profile.
+    Cherry,
+    /// Alps profile.
+    Alps,
+    /// XDA profile.
+    Xda,
+    /// KAT profile.
+    Kat,
+    /// KAM profile.
+    Kam,
+    /// G20 profile.
+    G20,
+    /// Any other profile. This contains the original profile string.
+    Custom(String),
+}
+
+const PROFILES: [(&str, Profile); 9] = [
+    ("DSA", Profile::Dsa),
+    ("SA", Profile::Sa),
+    ("OEM", Profile::Oem),
+    ("Cherry", Profile::Cherry),
+    ("Alps", Profile::Alps),
+    ("XDA", Profile::Xda),
+    ("KAT", Profile::Kat),
+    ("KAM", Profile::Kam),
+    ("G20", Profile::G20),
+];
+
+impl FromStr for Profile {
+    type Err = Infallible;
+
+    /// Parses a [`Profile`] from a string such as KLE's `"<profile> [<row>]"` format.
+    ///
+    /// Each whitespace separated word is compared to the known profiles case-insensitively, so
+    /// `"sa"`, `"SA"`, and `"SA R3"` are all parsed as [`Profile::Sa`]. Strings without a known
+    /// profile are parsed as [`Profile::Custom`], so this never fails.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let profile = s
+            .split_whitespace()
+            .find_map(|word| {
+                PROFILES
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(word))
+                    .map(|(_, profile)| profile.clone())
+            })
+            .unwrap_or_else(|| Self::Custom(s.into()));
+
+        Ok(profile)
+    }
+}
+
+impl From<&str> for Profile {
+    fn from(value: &str) -> Self {
+        match value.parse() {
+            Ok(profile) => profile,
+            Err(never) => match never {},
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Custom(name) => name.as_str(),
+            profile => PROFILES
+                .iter()
+                .find(|(_, p)| p == profile)
+                .map_or("", |(name, _)| name),
+        };
+        f.write_str(name)
+    }
+}
+
+impl<T> Key<T>
+where
+    T: Real,
+{
+    /// Returns the key's keycap [`Profile`] parsed from [`Key::profile`], or [`None`] if the
+    /// profile is empty.
+    ///
+    /// ```
+    /// use kle_serial::{Key, Profile};
+    ///
+    /// let key = Key::<f64> { profile: "DSA R1".into(), ..Key::default() };
+    /// assert_eq!(key.profile_type(), Some(Profile::Dsa));
+    ///
+    /// assert_eq!(Key::<f64>::default().profile_type(), None);
+    /// ```
+    #[must_use]
+    pub fn profile_type(&self) -> Option<Profile> {
+        (!self.profile.trim().is_empty()).then(|| Profile::from(self.profile.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+
+    #[test]
+    fn test_profile_from_str() {
+        assert_eq!(Profile::from_str("DSA"), Ok(Profile::Dsa));
+        assert_eq!(Profile::from_str("dsa"), Ok(Profile::Dsa));
+        assert_eq!(Profile::from_str("SA R3"), Ok(Profile::Sa));
+        assert_eq!(Profile::from_str("R1 oem"), Ok(Profile::Oem));
+        assert_eq!(Profile::from_str("cherry"), Ok(Profile::Cherry));
+        assert_eq!(Profile::from_str("Alps"), Ok(Profile::Alps));
+        assert_eq!(Profile::from_str("XDA"), Ok(Profile::Xda));
+        assert_eq!(Profile::from_str("KAT"), Ok(Profile::Kat));
+        assert_eq!(Profile::from_str("kam"), Ok(Profile::Kam));
+        assert_eq!(Profile::from_str("G20"), Ok(Profile::G20));
+        assert_eq!(
+            Profile::from_str("CHICKLET"),
+            Ok(Profile::Custom("CHICKLET".into()))
+        );
+        assert_eq!(Profile::from("DSAX"), Profile::Custom("DSAX".into()));
+    }
+
+    #[test]
+    fn test_profile_display() {
+        assert_eq!(format!("{}", Profile::Dsa), "DSA");
+        assert_eq!(format!("{}", Profile::Cherry), "Cherry");
+        assert_eq!(format!("{}", Profile::Custom("FLAT".into())), "FLAT");
+    }
+
+    #[test]
+    fn test_key_profile_type() {
+        let key = |profile: &str| Key::<f64> {
+            profile: profile.into(),
+            ..Key::default()
+        };
+
+        assert_eq!(key("").profile_type(), None);
+        assert_eq!(key(" ").profile_type(), None);
+        assert_eq!(key("SA R1").profile_type(), Some(Profile::Sa));
+        assert_eq!(
+            key("DCS").profile_type(),
+            Some(Profile::Custom("DCS".into()))
+        );
+    }
+}
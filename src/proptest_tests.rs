@@ -0,0 +1,197 @@
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use proptest::prelude::*;
+
+use crate::de::{KleKeyboard, KleLayoutIterator, KleLegendsOrProps, KleMetadata, KlePropsObject};
+use crate::utils::{realign_legends, unalign_legends, Alignment, FontSize};
+use crate::{Color, Keyboard, Legend, NUM_LEGENDS};
+
+// Positions and sizes are generated as multiples of 0.25 so they are exactly representable and
+// don't accumulate rounding errors between keys
+fn position() -> impl Strategy<Value = f64> {
+    (-16..=16_i32).prop_map(|v| f64::from(v) / 4.0)
+}
+
+fn size() -> impl Strategy<Value = f64> {
+    (1..=12_i32).prop_map(|v| f64::from(v) / 4.0)
+}
+
+fn angle() -> impl Strategy<Value = f64> {
+    (-12..=12_i32).prop_map(|v| f64::from(v) * 15.0)
+}
+
+fn text() -> impl Strategy<Value = String> {
+    "[A-Za-z0-9]{0,3}"
+}
+
+fn color() -> impl Strategy<Value = Color> {
+    (any::<u8>(), any::<u8>(), any::<u8>()).prop_map(|(r, g, b)| Color::new(r, g, b, u8::MAX))
+}
+
+fn alignment() -> impl Strategy<Value = Alignment> {
+    (0..=7_usize).prop_map(|a| Alignment::new(a).unwrap())
+}
+
+fn font_size() -> impl Strategy<Value = FontSize> {
+    (1..=9_usize).prop_map(|f| FontSize::new(f).unwrap())
+}
+
+fn legend() -> impl Strategy<Value = Legend> {
+    ("[A-Za-z0-9]{1,3}", 1..=9_usize, color()).prop_map(|(text, size, color)| Legend {
+        text,
+        size,
+        color,
+    })
+}
+
+fn legends() -> impl Strategy<Value = [Option<Legend>; NUM_LEGENDS]> {
+    prop::collection::vec(prop::option::of(legend()), NUM_LEGENDS)
+        .prop_map(|legends| legends.try_into().unwrap())
+}
+
+impl Arbitrary for KlePropsObject<f64> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        use prop::option::of;
+
+        let geometry = (
+            of(position()),
+            of(position()),
+            of(size()),
+            of(size()),
+            of(position()),
+            of(position()),
+            of(size()),
+            of(size()),
+            of(angle()),
+            of(position()),
+            of(position()),
+        );
+        let properties = (
+            of(any::<bool>()),
+            of(any::<bool>()),
+            of(any::<bool>()),
+            of(any::<bool>()),
+            of(text()),
+            of(text()),
+            of(text()),
+            of(color()),
+            of(prop::collection::vec(of(color()), 0..=NUM_LEGENDS)),
+            of(alignment()),
+            of(text()),
+            of(font_size()),
+        );
+        let font_sizes = (
+            of(font_size()),
+            of(prop::collection::vec(font_size(), 0..=NUM_LEGENDS)),
+        );
+
+        (geometry, properties, font_sizes)
+            .prop_map(
+                |(
+                    (x, y, w, h, x2, y2, w2, h2, r, rx, ry),
+                    (l, n, d, g, sm, sb, st, c, t, a, p, f),
+                    (f2, fa),
+                )| Self {
+                    x,
+                    y,
+                    w,
+                    h,
+                    x2,
+                    y2,
+                    w2,
+                    h2,
+                    r,
+                    rx,
+                    ry,
+                    l,
+                    n,
+                    d,
+                    g,
+                    sm,
+                    sb,
+                    st,
+                    c,
+                    t,
+                    a,
+                    p,
+                    f,
+                    f2,
+                    fa,
+                },
+            )
+            .boxed()
+    }
+}
+
+fn kle_keyboard() -> impl Strategy<Value = KleKeyboard<f64>> {
+    let item = prop_oneof![
+        any::<KlePropsObject<f64>>().prop_map(|props| KleLegendsOrProps::Props(Box::new(props))),
+        prop::collection::vec(text(), 0..=NUM_LEGENDS)
+            .prop_map(|legends| KleLegendsOrProps::Legend(legends.join("\n"))),
+    ];
+    let row = prop::collection::vec(item, 0..8);
+
+    prop::collection::vec(row, 0..6).prop_map(|layout| KleKeyboard {
+        meta: KleMetadata::default(),
+        layout,
+    })
+}
+
+fn keyboard_from_kle(kle: KleKeyboard<f64>) -> Keyboard<f64> {
+    Keyboard {
+        metadata: kle.meta.into(),
+        keys: KleLayoutIterator::new(kle.layout).collect(),
+    }
+}
+
+fn sorted_texts<'a>(legends: impl IntoIterator<Item = &'a Option<Legend>>) -> Vec<&'a str> {
+    let mut texts: Vec<_> = (legends.into_iter().flatten())
+        .map(|legend| legend.text.as_str())
+        .collect();
+    texts.sort_unstable();
+    texts
+}
+
+proptest! {
+    // Note realign_legends is not idempotent since it is a permutation which depends on the
+    // alignment. Instead we check it is the exact inverse of unalign_legends in both directions,
+    // which is the property the (de)serialiser relies on
+    #[test]
+    fn test_realign_unalign_round_trip(legends in legends(), alignment in alignment()) {
+        let realigned = realign_legends(legends.clone(), alignment);
+        prop_assert_eq!(unalign_legends(realigned.clone(), alignment), legends.clone());
+        prop_assert_eq!(realign_legends(unalign_legends(legends.clone(), alignment), alignment), legends);
+    }
+
+    #[test]
+    fn test_realign_preserves_legends(legends in legends(), alignment in alignment()) {
+        let realigned = realign_legends(legends.clone(), alignment);
+        prop_assert_eq!(sorted_texts(&realigned), sorted_texts(&legends));
+
+        let unaligned = unalign_legends(legends.clone(), alignment);
+        prop_assert_eq!(sorted_texts(&unaligned), sorted_texts(&legends));
+    }
+
+    #[test]
+    fn test_deserialize_does_not_panic(kle in kle_keyboard()) {
+        let num_keys = (kle.layout.iter().flatten())
+            .filter(|item| matches!(item, KleLegendsOrProps::Legend(_)))
+            .count();
+
+        let keyboard = keyboard_from_kle(kle);
+        prop_assert_eq!(keyboard.keys.len(), num_keys);
+    }
+
+    #[test]
+    fn test_serialize_round_trip(kle in kle_keyboard()) {
+        let keyboard = keyboard_from_kle(kle);
+
+        let json = serde_json::to_string(&keyboard).unwrap();
+        let result: Keyboard<f64> = serde_json::from_str(&json).unwrap();
+
+        prop_assert_eq!(result, keyboard);
+    }
+}
@@ -0,0 +1,349 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::mem;
+
+use num_traits::real::Real;
+use serde::{Serialize, Serializer};
+
+use crate::{
+    de::{KleBackground, KleKeyboard, KleLegendsOrProps, KleMetadata, KleProps, KlePropsObject},
+    Background, Keyboard, Metadata,
+};
+
+impl From<&Background> for KleBackground {
+    fn from(value: &Background) -> Self {
+        Self {
+            name: Some(value.name.clone()),
+            style: Some(value.style.clone()),
+        }
+    }
+}
+
+impl From<&Metadata> for KleMetadata {
+    fn from(value: &Metadata) -> Self {
+        let default = Metadata::default();
+
+        Self {
+            author: (value.author != default.author).then(|| value.author.clone()),
+            backcolor: (value.background_color != default.background_color)
+                .then_some(value.background_color),
+            background: (value.background != default.background)
+                .then(|| KleBackground::from(&value.background)),
+            name: (value.name != default.name).then(|| value.name.clone()),
+            notes: (value.notes != default.notes).then(|| value.notes.clone()),
+            radii: (value.radii != default.radii).then(|| value.radii.clone()),
+            switch_mount: (value.switch.mount != default.switch.mount)
+                .then(|| value.switch.mount.clone()),
+            switch_brand: (value.switch.brand != default.switch.brand)
+                .then(|| value.switch.brand.clone()),
+            switch_type: (value.switch.typ != default.switch.typ).then(|| value.switch.typ.clone()),
+            css: (value.css != default.css).then(|| value.css.clone()),
+            pcb: (value.pcb_mount != default.pcb_mount).then_some(value.pcb_mount),
+            plate: (value.plate_mount != default.plate_mount).then_some(value.plate_mount),
+        }
+    }
+}
+
+impl<T> From<&Keyboard<T>> for KleKeyboard<T>
+where
+    T: Real,
+{
+    fn from(value: &Keyboard<T>) -> Self {
+        // We track the same state as the deserialiser does, so we only need to emit properties
+        // which differ from what KLE already expects
+        let mut state = KleProps::default();
+        let mut layout = Vec::new();
+        let mut row = Vec::new();
+
+        for key in &value.keys {
+            if !row.is_empty() && state.is_new_line(key) {
+                layout.push(mem::take(&mut row));
+                state.next_line();
+            }
+
+            let (props, legends) = state.encode_key(key);
+            if props != KlePropsObject::default() {
+                state.update(props.clone());
+                row.push(KleLegendsOrProps::Props(Box::new(props)));
+            }
+            row.push(KleLegendsOrProps::Legend(legends));
+            state.next_key();
+        }
+
+        if !row.is_empty() {
+            layout.push(row);
+        }
+
+        Self {
+            meta: KleMetadata::from(&value.metadata),
+            layout,
+        }
+    }
+}
+
+// Rewrites the rows of a KLE layout with the fewest properties needed to produce the same keys.
+// Properties matching the current state are dropped (including objects which only set defaults
+// and x offsets which KLE already applies when resetting to rx), and consecutive property objects
+// before a key are merged into one. Row boundaries are kept as they are.
+#[cfg(feature = "json")]
+pub(crate) fn normalize_layout<T>(
+    layout: Vec<Vec<KleLegendsOrProps<T>>>,
+) -> Vec<Vec<KleLegendsOrProps<T>>>
+where
+    T: Real,
+{
+    // The state used to decode the original layout and the state KLE will have when reading the
+    // normalised layout. These can differ in ways which don't affect the keys, e.g. the font size
+    // of a legend position which is never used
+    let mut decode = KleProps::default();
+    let mut encode = KleProps::default();
+
+    (layout.into_iter().enumerate())
+        .map(|(i, items)| {
+            if i > 0 {
+                decode.next_line();
+                encode.next_line();
+            }
+
+            let mut row = Vec::new();
+            let mut pending = Vec::new();
+            for item in items {
+                match item {
+                    KleLegendsOrProps::Props(props) => {
+                        decode.update((*props).clone());
+                        pending.push(props);
+                    }
+                    KleLegendsOrProps::Legend(legends) => {
+                        let key = decode.build_key(&legends);
+                        let (props, legends) = encode.encode_key(&key);
+                        if props != KlePropsObject::default() {
+                            encode.update(props.clone());
+                            row.push(KleLegendsOrProps::Props(Box::new(props)));
+                        }
+                        row.push(KleLegendsOrProps::Legend(legends));
+                        decode.next_key();
+                        encode.next_key();
+                        pending.clear();
+                    }
+                }
+            }
+
+            // Properties after the last key in a row still carry over to the next row, so they
+            // are kept as is unless they are empty
+            for props in pending {
+                if *props != KlePropsObject::default() {
+                    encode.update((*props).clone());
+                    row.push(KleLegendsOrProps::Props(props));
+                }
+            }
+
+            row
+        })
+        .collect()
+}
+
+impl Serialize for Background {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        KleBackground::from(self).serialize(serializer)
+    }
+}
+
+impl Serialize for Metadata {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        KleMetadata::from(self).serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use isclose::assert_is_close;
+
+    use crate::{Color, Key, Legend, Switch};
+
+    use super::*;
+
+    #[test]
+    fn test_kle_background_from() {
+        let bg = KleBackground::from(&Background {
+            name: "name".into(),
+            style: "style".into(),
+        });
+
+        assert_eq!(bg.name.unwrap(), "name");
+        assert_eq!(bg.style.unwrap(), "style");
+    }
+
+    #[test]
+    fn test_kle_metadata_from() {
+        let md = KleMetadata::from(&Metadata::default());
+
+        assert_eq!(md, KleMetadata::default());
+
+        let md = KleMetadata::from(&Metadata {
+            background_color: Color::new(204, 34, 34, 255),
+            background: Background {
+                name: "name".into(),
+                style: "style".into(),
+            },
+            radii: "radii".into(),
+            name: "name".into(),
+            author: "author".into(),
+            switch: Switch {
+                mount: "switch_mount".into(),
+                brand: "switch_brand".into(),
+                typ: "switch_type".into(),
+            },
+            plate_mount: true,
+            pcb_mount: true,
+            notes: "notes".into(),
+            css: "css".into(),
+        });
+
+        assert_eq!(md.author.unwrap(), "author");
+        assert_eq!(md.backcolor.unwrap(), Color::new(204, 34, 34, 255));
+        assert_eq!(
+            md.background.as_ref().unwrap().name.as_ref().unwrap(),
+            "name"
+        );
+        assert_eq!(
+            md.background.as_ref().unwrap().style.as_ref().unwrap(),
+            "style"
+        );
+        assert_eq!(md.name.unwrap(), "name");
+        assert_eq!(md.notes.unwrap(), "notes");
+        assert_eq!(md.css.unwrap(), "css");
+        assert_eq!(md.radii.unwrap(), "radii");
+        assert_eq!(md.switch_mount.unwrap(), "switch_mount");
+        assert_eq!(md.switch_brand.unwrap(), "switch_brand");
+        assert_eq!(md.switch_type.unwrap(), "switch_type");
+        assert!(md.pcb.unwrap());
+        assert!(md.plate.unwrap());
+    }
+
+    #[test]
+    fn test_kle_keyboard_from() {
+        let kle = KleKeyboard::from(&Keyboard::<f64> {
+            metadata: Metadata::default(),
+            keys: vec![
+                Key::default(),
+                Key {
+                    x: 1.0,
+                    ..Key::default()
+                },
+                Key {
+                    x: 2.5,
+                    width: 1.5,
+                    ..Key::default()
+                },
+                Key {
+                    y: 1.0,
+                    ..Key::default()
+                },
+                Key {
+                    y: 1.0,
+                    rotation: 15.0,
+                    rx: 1.0,
+                    ..Key::default()
+                },
+            ],
+        });
+
+        assert_eq!(kle.meta, KleMetadata::default());
+        assert_eq!(kle.layout.len(), 3);
+        assert_eq!(kle.layout[0].len(), 4);
+        assert_eq!(kle.layout[1].len(), 1);
+        assert_eq!(kle.layout[2].len(), 2);
+
+        match &kle.layout[0][2] {
+            KleLegendsOrProps::Props(props) => {
+                assert_is_close!(props.x.unwrap(), 0.5);
+                assert_is_close!(props.w.unwrap(), 1.5);
+            }
+            KleLegendsOrProps::Legend(_) => panic!("expected props"),
+        }
+
+        match &kle.layout[2][0] {
+            KleLegendsOrProps::Props(props) => {
+                assert_is_close!(props.r.unwrap(), 15.0);
+                assert_is_close!(props.rx.unwrap(), 1.0);
+                assert_is_close!(props.x.unwrap(), -1.0);
+                assert_is_close!(props.y.unwrap(), 1.0);
+                assert!(props.ry.is_none());
+            }
+            KleLegendsOrProps::Legend(_) => panic!("expected props"),
+        }
+
+        // The default alignment can already show a centred front legend, so it is kept
+        let mut key = Key::default();
+        key.legends[4] = Some(Legend {
+            text: "A".into(),
+            ..Legend::default()
+        });
+        let kle = KleKeyboard::from(&Keyboard::<f64> {
+            metadata: Metadata::default(),
+            keys: vec![key.clone()],
+        });
+        assert_eq!(kle.layout[0].len(), 1);
+        assert_matches!(&kle.layout[0][0], KleLegendsOrProps::Legend(legend) if legend == "\n\n\n\n\n\n\n\n\nA");
+
+        // But not a front left legend, so the alignment has to change
+        key.legends[4] = None;
+        key.legends[9] = Some(Legend {
+            text: "B".into(),
+            ..Legend::default()
+        });
+        let kle = KleKeyboard::from(&Keyboard::<f64> {
+            metadata: Metadata::default(),
+            keys: vec![Key::default(), key],
+        });
+        assert_eq!(kle.layout[0].len(), 3);
+        assert_matches!(&kle.layout[0][0], KleLegendsOrProps::Legend(legend) if legend.is_empty());
+        assert_matches!(&kle.layout[0][1], KleLegendsOrProps::Props(props) if props.a.map(usize::from) == Some(3));
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_normalize_layout() {
+        use crate::de::KleLayoutIterator;
+
+        // Redundant properties left behind by editing in KLE: defaults set explicitly, several
+        // props objects before one key, x offsets undoing the reset to rx = 0, and empty objects
+        let bloated = r##"[
+            [{"a": 4, "rx": 0, "x": 0}, "Esc", {"c": "#cccccc"}, {"a": 4, "x": 0}, "F1",
+                {"f": 3}, {"t": "#000000"}, "F2"],
+            [{"r": 0, "rx": 0, "ry": 1, "y": 0}, "Q", {"x": 0}, "W", {"a": 4}, "E"],
+            [{"rx": 3, "ry": 2, "x": -3}, "A", {"c": "#cccccc", "t": "#000000"}, "S", "D"],
+            [{"x": -3}, "Z", {"f": 3}, {}, "X", "C", {}],
+            [{"x": -3}, "V", {"c": "#ff0000"}],
+            ["N"]
+        ]"##;
+        let layout = serde_json::from_str::<KleKeyboard<f64>>(bloated)
+            .unwrap()
+            .layout;
+        let normalized = normalize_layout(layout.clone());
+
+        assert_eq!(
+            serde_json::to_string(&normalized).unwrap(),
+            r##"[["Esc","F1","F2"],[{"ry":1.0},"Q","W","E"],[{"x":-3.0,"rx":3.0,"ry":2.0},"A","S","D"],[{"x":-3.0},"Z","X","C"],[{"x":-3.0},"V",{"c":"#ff0000"}],["N"]]"##
+        );
+
+        let keys: Vec<_> = KleLayoutIterator::new(layout.clone()).collect();
+        assert_eq!(
+            KleLayoutIterator::new(normalized.clone()).collect::<Vec<_>>(),
+            keys
+        );
+        assert_eq!(keys[13].color, Color::new(255, 0, 0, 255));
+
+        // Normalising is idempotent
+        assert_eq!(
+            serde_json::to_string(&normalize_layout(normalized.clone())).unwrap(),
+            serde_json::to_string(&normalized).unwrap()
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use num_traits::real::Real;
+
+use crate::{Key, Keyboard};
+
+fn units<T: Real>(units: u8) -> T {
+    T::from(units).unwrap_or_else(T::zero)
+}
+
+impl<T> Key<T>
+where
+    T: Real,
+{
+    // The length of the key along its longest side, which is the direction a stabilizer spans
+    fn stabilizer_length(&self) -> T {
+        self.width.max(self.height)
+    }
+
+    /// Returns the number of stabilizers the key is likely to need, based on its size.
+    ///
+    /// This is a heuristic using the longest side of the key's primary rectangle, so vertical keys
+    /// such as a numpad plus are handled the same as horizontal ones:
+    ///
+    /// * Keys shorter than 2u and decals need no stabilizers.
+    /// * Keys from 2u up to 3u need one stabilizer.
+    /// * Keys from 3u up to 6u need two stabilizers.
+    /// * Keys 6u or longer (i.e. spacebars) need one spacebar stabilizer.
+    #[must_use]
+    pub fn stabilizer_count(&self) -> usize {
+        let length = self.stabilizer_length();
+
+        if self.decal {
+            0
+        } else if length >= units(6) {
+            1
+        } else if length >= units(3) {
+            2
+        } else {
+            usize::from(length >= units(2))
+        }
+    }
+
+    /// Returns the size in keyboard units of the stabilizers the key is likely to need, or
+    /// [`None`] if it does not need any. See [`Key::stabilizer_count`] for details.
+    ///
+    /// Keys shorter than 6u use 2u stabilizers, while spacebars use a stabilizer matching the
+    /// length of the key, such as 6.25u or 7u.
+    #[must_use]
+    pub fn stabilizer_size(&self) -> Option<T> {
+        let length = self.stabilizer_length();
+
+        match self.stabilizer_count() {
+            0 => None,
+            _ if length >= units(6) => Some(length),
+            _ => Some(units(2)),
+        }
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Returns the total number of stabilizers needed for the layout grouped by size, in
+    /// ascending order of size. See [`Key::stabilizer_count`] and [`Key::stabilizer_size`] for
+    /// details.
+    ///
+    /// This returns a sorted [`Vec`] of `(size, count)` pairs rather than a map keyed by size, as
+    /// the generic coordinate type `T` does not implement [`Eq`] or [`Hash`](core::hash::Hash)
+    /// and `HashMap` is not available without the `std` feature. The pairs can be collected into
+    /// a map using a hashable float wrapper such as `OrderedFloat` from the [`ordered-float`]
+    /// crate if needed.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard =
+    ///     serde_json::from_str(r#"[[{"w": 2.25}, "Shift", {"w": 2.75}, "Shift"], [{"w": 6.25}, ""]]"#)
+    ///         .unwrap();
+    ///
+    /// assert_eq!(keyboard.stabilizers(), [(2.0, 2), (6.25, 1)]);
+    /// ```
+    ///
+    /// [`ordered-float`]: https://crates.io/crates/ordered-float
+    #[must_use]
+    pub fn stabilizers(&self) -> Vec<(T, usize)> {
+        let mut stabilizers: Vec<(T, usize)> = Vec::new();
+
+        for key in &self.keys {
+            if let Some(size) = key.stabilizer_size() {
+                let count = key.stabilizer_count();
+                match stabilizers.iter_mut().find(|(s, _)| *s == size) {
+                    Some((_, total)) => *total += count,
+                    None => stabilizers.push((size, count)),
+                }
+            }
+        }
+
+        stabilizers.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        stabilizers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(width: f64, height: f64) -> Key {
+        Key {
+            width,
+            height,
+            ..Key::default()
+        }
+    }
+
+    #[test]
+    fn test_key_stabilizer_count() {
+        assert_eq!(key(1.0, 1.0).stabilizer_count(), 0);
+        assert_eq!(key(1.75, 1.0).stabilizer_count(), 0);
+        assert_eq!(key(2.0, 1.0).stabilizer_count(), 1);
+        assert_eq!(key(2.75, 1.0).stabilizer_count(), 1);
+        assert_eq!(key(1.0, 2.0).stabilizer_count(), 1);
+        assert_eq!(key(4.0, 1.0).stabilizer_count(), 2);
+        assert_eq!(key(6.25, 1.0).stabilizer_count(), 1);
+        assert_eq!(key(f64::NAN, 1.0).stabilizer_count(), 0);
+
+        let decal = Key {
+            decal: true,
+            ..key(6.25, 1.0)
+        };
+        assert_eq!(decal.stabilizer_count(), 0);
+    }
+
+    #[test]
+    fn test_key_stabilizer_size() {
+        assert_eq!(key(1.5, 1.0).stabilizer_size(), None);
+        assert_eq!(key(2.25, 1.0).stabilizer_size(), Some(2.0));
+        assert_eq!(key(1.0, 2.0).stabilizer_size(), Some(2.0));
+        assert_eq!(key(4.0, 1.0).stabilizer_size(), Some(2.0));
+        assert_eq!(key(6.25, 1.0).stabilizer_size(), Some(6.25));
+        assert_eq!(key(7.0, 1.0).stabilizer_size(), Some(7.0));
+    }
+
+    #[test]
+    fn test_keyboard_stabilizers() {
+        // Standard 104 key ANSI layout
+        let keyboard: Keyboard = serde_json::from_str(
+            r#"[
+                ["", {"x": 1}, "", "", "", "", {"x": 0.5}, "", "", "", "", {"x": 0.5}, "", "", "",
+                    "", {"x": 0.25}, "", "", ""],
+                [{"y": 0.5}, "", "", "", "", "", "", "", "", "", "", "", "", "", {"w": 2}, "",
+                    {"x": 0.25}, "", "", "", {"x": 0.25}, "", "", "", ""],
+                [{"w": 1.5}, "", "", "", "", "", "", "", "", "", "", "", "", "", {"w": 1.5}, "",
+                    {"x": 0.25}, "", "", "", {"x": 0.25}, "", "", "", {"h": 2}, ""],
+                [{"w": 1.75}, "", "", "", "", "", "", "", "", "", "", "", "", {"w": 2.25}, "",
+                    {"x": 3.5}, "", "", ""],
+                [{"w": 2.25}, "", "", "", "", "", "", "", "", "", "", "", {"w": 2.75}, "",
+                    {"x": 1.25}, "", {"x": 1.25}, "", "", "", {"h": 2}, ""],
+                [{"w": 1.25}, "", {"w": 1.25}, "", {"w": 1.25}, "", {"w": 6.25}, "", {"w": 1.25},
+                    "", {"w": 1.25}, "", {"w": 1.25}, "", {"w": 1.25}, "", {"x": 0.25}, "", "", "",
+                    {"x": 0.25, "w": 2}, "", ""]
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(keyboard.keys.len(), 104);
+
+        // Backspace, enter, both shifts, numpad plus, numpad enter, and numpad zero use 2u
+        // stabilizers, and the spacebar uses a 6.25u stabilizer
+        assert_eq!(keyboard.stabilizers(), [(2.0, 7), (6.25, 1)]);
+
+        // The pairs can be collected into a map keyed by size
+        let map: std::collections::HashMap<_, _> = (keyboard.stabilizers().into_iter())
+            .map(|(size, count)| (ordered_float::OrderedFloat(size), count))
+            .collect();
+        assert_eq!(map[&ordered_float::OrderedFloat(6.25)], 1);
+
+        assert!(Keyboard::<f64>::default().stabilizers().is_empty());
+    }
+}
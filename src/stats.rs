@@ -0,0 +1,180 @@
+use std::collections::HashSet;
+
+use num_traits::real::Real;
+
+use crate::Keyboard;
+
+/// Summary statistics for a layout, returned by [`Keyboard::statistics`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyboardStatistics<T = f64>
+where
+    T: Real,
+{
+    /// The number of keys in the layout, including decals.
+    pub key_count: usize,
+    /// The number of rows in the layout. See [`Keyboard::rows`] for how keys are grouped into
+    /// rows.
+    pub row_count: usize,
+    /// The total visual area of all keys excluding decals, in square keyboard units. See
+    /// [`Keyboard::total_key_area`].
+    pub total_area: T,
+    /// The mean width of the keys, or zero if the layout has no keys.
+    pub average_key_width: T,
+    /// The mean height of the keys, or zero if the layout has no keys.
+    pub average_key_height: T,
+    /// The distinct non-empty switch brands used in the layout.
+    pub unique_switch_brands: HashSet<String>,
+    /// The distinct non-empty keycap profiles used in the layout.
+    pub unique_profiles: HashSet<String>,
+    /// Whether any key has a non-zero rotation.
+    pub has_rotated_keys: bool,
+    /// Whether any key is stepped.
+    pub has_stepped_keys: bool,
+    /// Whether any key is shaped like an ISO enter. See [`Key::is_iso_enter`](crate::Key::is_iso_enter).
+    pub has_iso_enter: bool,
+    /// The total number of legends on all keys.
+    pub legend_count: usize,
+    /// The mean number of legends per key, or zero if the layout has no keys.
+    pub legend_density: f64,
+    /// The number of distinct key and legend colours used in the layout.
+    pub color_count: usize,
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Returns summary statistics for the layout.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["A", {"w": 2}, "B"], ["C"]]"#).unwrap();
+    /// let stats = keyboard.statistics();
+    ///
+    /// assert_eq!(stats.key_count, 3);
+    /// assert_eq!(stats.row_count, 2);
+    /// assert_eq!(stats.total_area, 4.0);
+    /// assert_eq!(stats.legend_count, 3);
+    /// ```
+    #[must_use]
+    pub fn statistics(&self) -> KeyboardStatistics<T> {
+        let mut colors = HashSet::new();
+        let mut stats = KeyboardStatistics {
+            key_count: self.keys.len(),
+            row_count: self.row_count(),
+            total_area: T::zero(),
+            average_key_width: T::zero(),
+            average_key_height: T::zero(),
+            unique_switch_brands: HashSet::new(),
+            unique_profiles: HashSet::new(),
+            has_rotated_keys: false,
+            has_stepped_keys: false,
+            has_iso_enter: false,
+            legend_count: 0,
+            legend_density: 0.0,
+            color_count: 0,
+        };
+
+        for key in &self.keys {
+            if !key.decal {
+                stats.total_area = stats.total_area + key.visual_area();
+            }
+            stats.average_key_width = stats.average_key_width + key.width;
+            stats.average_key_height = stats.average_key_height + key.height;
+
+            if !key.switch.brand.is_empty() {
+                stats.unique_switch_brands.insert(key.switch.brand.clone());
+            }
+            if !key.profile.is_empty() {
+                stats.unique_profiles.insert(key.profile.clone());
+            }
+
+            stats.has_rotated_keys |= key.rotation != T::zero();
+            stats.has_stepped_keys |= key.stepped;
+            stats.has_iso_enter |= key.is_iso_enter();
+
+            colors.insert(key.color);
+            for legend in key.present_legends() {
+                colors.insert(legend.color);
+                stats.legend_count += 1;
+            }
+        }
+
+        if stats.key_count > 0 {
+            let count = T::from(stats.key_count).unwrap_or_else(T::one);
+            stats.average_key_width = stats.average_key_width / count;
+            stats.average_key_height = stats.average_key_height / count;
+
+            #[allow(clippy::cast_precision_loss)] // counts are far below 2^52
+            let density = stats.legend_count as f64 / stats.key_count as f64;
+            stats.legend_density = density;
+        }
+        stats.color_count = colors.len();
+
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isclose::assert_is_close;
+
+    use super::*;
+
+    #[test]
+    fn test_keyboard_statistics() {
+        let keyboard: Keyboard = serde_json::from_str(
+            r##"[
+                [{"c": "#ff0000", "p": "DSA", "sb": "cherry"}, "Esc", {"x": 0.5}, "F1\n\n\nF1", "F2"],
+                [{"c": "#cccccc", "t": "#0000ff", "p": "", "sb": "gateron"}, "Q",
+                    {"x": 0.25, "w": 1.25, "h": 2, "w2": 1.5, "h2": 1, "x2": -0.25}, "Enter"],
+                [{"r": 15, "rx": 5, "ry": 2, "p": "SA R1", "sb": "cherry"}, {"l": true, "w": 1.75}, "Caps",
+                    {"d": true}, "Decal"]
+            ]"##,
+        )
+        .unwrap();
+        let stats = keyboard.statistics();
+
+        assert_eq!(stats.key_count, 7);
+        assert_eq!(stats.row_count, 3);
+        // 4 regular keys + 2.75 for the ISO enter + 1.75 for caps lock, excluding the decal
+        assert_is_close!(stats.total_area, 8.5);
+        assert_is_close!(stats.average_key_width, 8.0 / 7.0);
+        assert_is_close!(stats.average_key_height, 8.0 / 7.0);
+        assert_eq!(
+            stats.unique_switch_brands,
+            HashSet::from(["cherry".into(), "gateron".into()])
+        );
+        assert_eq!(
+            stats.unique_profiles,
+            HashSet::from(["DSA".into(), "SA R1".into()])
+        );
+        assert!(stats.has_rotated_keys);
+        assert!(stats.has_stepped_keys);
+        assert!(stats.has_iso_enter);
+        assert_eq!(stats.legend_count, 8);
+        assert_is_close!(stats.legend_density, 8.0 / 7.0);
+        // Red, grey, black (default legends), and blue
+        assert_eq!(stats.color_count, 4);
+    }
+
+    #[test]
+    fn test_keyboard_statistics_empty() {
+        let stats = Keyboard::<f64>::default().statistics();
+
+        assert_eq!(stats.key_count, 0);
+        assert_eq!(stats.row_count, 0);
+        assert_is_close!(stats.total_area, 0.0);
+        assert_is_close!(stats.average_key_width, 0.0);
+        assert_is_close!(stats.average_key_height, 0.0);
+        assert!(stats.unique_switch_brands.is_empty());
+        assert!(stats.unique_profiles.is_empty());
+        assert!(!stats.has_rotated_keys);
+        assert!(!stats.has_stepped_keys);
+        assert!(!stats.has_iso_enter);
+        assert_eq!(stats.legend_count, 0);
+        assert_is_close!(stats.legend_density, 0.0);
+        assert_eq!(stats.color_count, 0);
+    }
+}
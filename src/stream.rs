@@ -0,0 +1,310 @@
+use std::io::{self, Read};
+use std::vec;
+
+use num_traits::real::Real;
+use serde::de::{DeserializeOwned, Error as _};
+
+use crate::de::{KleLegendsOrProps, KleMetadata, KleProps};
+use crate::{Key, Metadata};
+
+// A reader that allows a single byte to be pushed back after peeking at it.
+//
+// serde's SeqAccess can only be driven from inside a Visitor, which runs to completion before
+// returning control to the caller, so it can't be used to implement a lazy Iterator over the rows
+// of a layout. Instead this reads only the structural tokens of the outer array (`[`, `,`, and
+// `]`) itself and hands each element to serde_json to deserialise, so no JSON values are parsed
+// here.
+#[derive(Debug)]
+struct PeekReader<R> {
+    reader: R,
+    peeked: Option<u8>,
+}
+
+impl<R> PeekReader<R>
+where
+    R: Read,
+{
+    fn next_byte(&mut self) -> Result<Option<u8>, serde_json::Error> {
+        if let Some(byte) = self.peeked.take() {
+            return Ok(Some(byte));
+        }
+
+        let mut buf = [0];
+        loop {
+            match self.reader.read(&mut buf) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(buf[0])),
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(serde_json::Error::io(error)),
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Option<u8>, serde_json::Error> {
+        loop {
+            match self.next_byte()? {
+                Some(b' ' | b'\n' | b'\r' | b'\t') => {}
+                byte => return Ok(byte),
+            }
+        }
+    }
+
+    fn peek_token(&mut self) -> Result<Option<u8>, serde_json::Error> {
+        let token = self.next_token()?;
+        self.peeked = token;
+        Ok(token)
+    }
+
+    fn deserialize<T>(&mut self) -> Result<T, serde_json::Error>
+    where
+        T: DeserializeOwned,
+    {
+        // Only JSON objects, arrays, and strings are deserialised this way. These are all
+        // self-delimiting, so serde_json will not consume any bytes after the end of the value
+        T::deserialize(&mut serde_json::Deserializer::from_reader(self))
+    }
+}
+
+impl<R> Read for PeekReader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match (self.peeked.take(), buf.first_mut()) {
+            (Some(byte), Some(first)) => {
+                *first = byte;
+                Ok(1)
+            }
+            (peeked, _) => {
+                self.peeked = peeked;
+                self.reader.read(buf)
+            }
+        }
+    }
+}
+
+/// An iterator of [`Key`]s which deserialises a KLE JSON layout from a reader one row at a time.
+///
+/// Unlike [`Keyboard`](crate::Keyboard) and [`KeyIterator`](crate::KeyIterator), this does not
+/// read the entire layout into memory before building keys, which reduces peak memory usage for
+/// large layouts. The layout's metadata is deserialised eagerly when the iterator is created.
+///
+/// The reader is read one byte at a time, so it should usually be wrapped in a
+/// [`BufReader`](std::io::BufReader).
+///
+/// ```
+/// use kle_serial::StreamingKeyIterator;
+///
+/// let json = r#"[{"name": "example"}, ["A", "B"], ["C"]]"#;
+/// let iter = StreamingKeyIterator::<_>::new(json.as_bytes()).unwrap();
+///
+/// assert_eq!(iter.metadata().name, "example");
+///
+/// let keys: Result<Vec<_>, _> = iter.collect();
+/// assert_eq!(keys.unwrap().len(), 3);
+/// ```
+#[derive(Debug)]
+pub struct StreamingKeyIterator<R, T = f64>
+where
+    T: Real,
+{
+    reader: PeekReader<R>,
+    metadata: Metadata,
+    state: KleProps<T>,
+    key_iter: vec::IntoIter<KleLegendsOrProps<T>>,
+    row_count: usize,
+    done: bool,
+}
+
+impl<R, T> StreamingKeyIterator<R, T>
+where
+    R: Read,
+    T: Real + DeserializeOwned,
+{
+    /// Creates a new [`StreamingKeyIterator`], reading the start of the layout and its metadata
+    /// from `reader`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`serde_json::Error`] if reading fails or the start of the data is not a valid
+    /// KLE layout.
+    pub fn new(reader: R) -> Result<Self, serde_json::Error> {
+        let mut reader = PeekReader {
+            reader,
+            peeked: None,
+        };
+
+        if reader.next_token()? != Some(b'[') {
+            return Err(serde_json::Error::custom("expected a sequence"));
+        }
+
+        let mut result = Self {
+            reader,
+            metadata: Metadata::default(),
+            state: KleProps::default(),
+            key_iter: Vec::new().into_iter(),
+            row_count: 0,
+            done: false,
+        };
+
+        match result.reader.peek_token()? {
+            Some(b'{') => {
+                result.metadata = result.reader.deserialize::<KleMetadata>()?.into();
+                result.next_separator()?;
+            }
+            Some(b']') => {
+                result.reader.next_token()?;
+                result.done = true;
+            }
+            _ => {}
+        }
+
+        Ok(result)
+    }
+
+    /// Returns the layout's metadata.
+    #[inline]
+    #[must_use]
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn next_separator(&mut self) -> Result<(), serde_json::Error> {
+        if self.done {
+            return Ok(());
+        }
+        match self.reader.next_token()? {
+            Some(b',') => Ok(()),
+            Some(b']') => {
+                self.done = true;
+                Ok(())
+            }
+            _ => Err(serde_json::Error::custom("expected `,` or `]`")),
+        }
+    }
+
+    fn next_row(&mut self) -> Result<Option<Vec<KleLegendsOrProps<T>>>, serde_json::Error> {
+        if self.done {
+            return Ok(None);
+        }
+
+        // Rows are only read at the start of the layout (where new already handled an empty
+        // layout) or after a `,` separator, so a `]` here always follows a trailing comma
+        match self.reader.peek_token()? {
+            Some(b'[') => {}
+            Some(b']') => return Err(serde_json::Error::custom("trailing comma")),
+            _ => return Err(serde_json::Error::custom("expected a sequence")),
+        }
+
+        let row = self.reader.deserialize()?;
+        self.next_separator()?;
+        Ok(Some(row))
+    }
+}
+
+impl<R, T> Iterator for StreamingKeyIterator<R, T>
+where
+    R: Read,
+    T: Real + DeserializeOwned,
+{
+    type Item = Result<Key<T>, serde_json::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let legends = loop {
+            match self.key_iter.next() {
+                Some(KleLegendsOrProps::Props(props)) => self.state.update(*props),
+                Some(KleLegendsOrProps::Legend(str)) => break str,
+                None => match self.next_row() {
+                    Ok(Some(row)) => {
+                        if self.row_count > 0 {
+                            self.state.next_line();
+                        }
+                        self.row_count += 1;
+                        self.key_iter = row.into_iter();
+                    }
+                    Ok(None) => return None,
+                    Err(error) => {
+                        self.done = true;
+                        return Some(Err(error));
+                    }
+                },
+            }
+        };
+
+        let key = self.state.build_key(&legends);
+        self.state.next_key();
+
+        Some(Ok(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isclose::assert_is_close;
+
+    use crate::Keyboard;
+
+    use super::*;
+
+    #[test]
+    fn test_streaming_key_iterator() {
+        let json = r#" [ {"name": "test"} , ["A", {"x": 1}, "B"],
+            [{"y": 0.5}, "C"] ] "#;
+        let iter = StreamingKeyIterator::<_, f64>::new(json.as_bytes()).unwrap();
+
+        assert_eq!(iter.metadata().name, "test");
+
+        let keys = iter.collect::<Result<Vec<_>, _>>().unwrap();
+        let expected = serde_json::from_str::<Keyboard>(json).unwrap().keys;
+
+        assert_eq!(keys.len(), 3);
+        for (key, exp) in keys.iter().zip(expected.iter()) {
+            assert_is_close!(key.x, exp.x);
+            assert_is_close!(key.y, exp.y);
+            assert_eq!(key.legends[0], exp.legends[0]);
+        }
+        assert_is_close!(keys[1].x, 2.0);
+        assert_is_close!(keys[2].y, 1.5);
+    }
+
+    #[test]
+    fn test_streaming_key_iterator_no_metadata() {
+        let iter = StreamingKeyIterator::<_, f32>::new(&br#"[["A"], ["B"]]"#[..]).unwrap();
+
+        assert_eq!(iter.metadata(), &Metadata::default());
+        assert_eq!(iter.count(), 2);
+
+        let iter = StreamingKeyIterator::<_, f32>::new(&b"[]"[..]).unwrap();
+        assert_eq!(iter.count(), 0);
+
+        let iter = StreamingKeyIterator::<_, f32>::new(&br#"[{"name": "test"}]"#[..]).unwrap();
+        assert_eq!(iter.metadata().name, "test");
+        assert_eq!(iter.count(), 0);
+    }
+
+    #[test]
+    fn test_streaming_key_iterator_invalid() {
+        assert!(StreamingKeyIterator::<_, f64>::new(&b"{}"[..]).is_err());
+        assert!(StreamingKeyIterator::<_, f64>::new(&b"[{"[..]).is_err());
+        assert!(StreamingKeyIterator::<_, f64>::new(&b"[{} "[..]).is_err());
+
+        let mut iter = StreamingKeyIterator::<_, f64>::new(&br#"[["A"], 1]"#[..]).unwrap();
+        assert!(iter.next().unwrap().is_ok());
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+
+        let mut iter = StreamingKeyIterator::<_, f64>::new(&br#"[["A"] ["B"]]"#[..]).unwrap();
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+
+        // Trailing commas are rejected like serde_json does
+        let mut iter = StreamingKeyIterator::<_, f64>::new(&br#"[{"name": "x"},]"#[..]).unwrap();
+        assert!(iter.next().unwrap().is_err());
+        assert!(serde_json::from_str::<Keyboard>(r#"[{"name": "x"},]"#).is_err());
+
+        let mut iter = StreamingKeyIterator::<_, f64>::new(&br#"[["A"],]"#[..]).unwrap();
+        assert!(iter.next().unwrap().is_ok());
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+    }
+}
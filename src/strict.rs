@@ -0,0 +1,309 @@
+use alloc::string::String;
+use core::fmt;
+
+use num_traits::real::Real;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::Keyboard;
+
+// Metadata fields recognised by KLE
+pub(crate) const METADATA_FIELDS: [&str; 12] = [
+    "author",
+    "backcolor",
+    "background",
+    "name",
+    "notes",
+    "radii",
+    "switchMount",
+    "switchBrand",
+    "switchType",
+    "css",
+    "pcb",
+    "plate",
+];
+
+// KLE clamps font sizes to this range
+pub(crate) const MIN_FONT_SIZE: usize = 1;
+pub(crate) const MAX_FONT_SIZE: usize = 9;
+pub(crate) const MAX_ALIGNMENT: usize = 7;
+
+/// An error returned when strictly deserialising a KLE layout using [`Keyboard::from_str_strict`].
+#[derive(Debug)]
+pub enum StrictError {
+    /// The input is not valid JSON or could not be deserialised as a KLE layout.
+    Json(serde_json::Error),
+    /// A rotation property (`r`, `rx`, or `ry`) appeared somewhere other than the start of a row.
+    RotationNotAtStartOfRow {
+        /// The index of the row, not including the metadata object.
+        row: usize,
+    },
+    /// A font size was outside the range `1..=9`.
+    FontSizeOutOfRange {
+        /// The invalid font size.
+        value: usize,
+    },
+    /// An alignment was outside the range `0..=7`.
+    AlignmentOutOfRange {
+        /// The invalid alignment.
+        value: usize,
+    },
+    /// The metadata object contained a field that is not recognised by KLE.
+    UnknownMetadataField {
+        /// The name of the unknown field.
+        field: String,
+    },
+}
+
+impl fmt::Display for StrictError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(error) => write!(f, "{error}"),
+            Self::RotationNotAtStartOfRow { row } => {
+                write!(f, "rotation property not at start of row {row}")
+            }
+            Self::FontSizeOutOfRange { value } => write!(
+                f,
+                "font size {value} is out of range, expected \
+                    {MIN_FONT_SIZE} <= size <= {MAX_FONT_SIZE}"
+            ),
+            Self::AlignmentOutOfRange { value } => write!(
+                f,
+                "alignment {value} is out of range, expected 0 <= alignment <= {MAX_ALIGNMENT}"
+            ),
+            Self::UnknownMetadataField { field } => write!(f, "unknown metadata field {field:?}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StrictError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Json(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for StrictError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+pub(crate) fn to_usize(value: &Value) -> Option<usize> {
+    // Values which are not unsigned integers are left for the deserialiser to reject
+    value
+        .as_u64()
+        .map(|value| usize::try_from(value).unwrap_or(usize::MAX))
+}
+
+fn check_metadata(metadata: &Map<String, Value>) -> Result<(), StrictError> {
+    match metadata
+        .keys()
+        .find(|field| !METADATA_FIELDS.contains(&field.as_str()))
+    {
+        Some(field) => Err(StrictError::UnknownMetadataField {
+            field: field.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+fn check_font_size(value: usize, allow_zero: bool) -> Result<(), StrictError> {
+    if (MIN_FONT_SIZE..=MAX_FONT_SIZE).contains(&value) || (allow_zero && value == 0) {
+        Ok(())
+    } else {
+        Err(StrictError::FontSizeOutOfRange { value })
+    }
+}
+
+fn check_props(props: &Map<String, Value>, row: usize, index: usize) -> Result<(), StrictError> {
+    if index != 0 && ["r", "rx", "ry"].iter().any(|&k| props.contains_key(k)) {
+        return Err(StrictError::RotationNotAtStartOfRow { row });
+    }
+
+    for size in ["f", "f2"].iter().filter_map(|&k| props.get(k)) {
+        if let Some(value) = to_usize(size) {
+            check_font_size(value, false)?;
+        }
+    }
+
+    // KLE uses 0 in the font size array for legends using the default size
+    if let Some(sizes) = props.get("fa").and_then(Value::as_array) {
+        for value in sizes.iter().filter_map(to_usize) {
+            check_font_size(value, true)?;
+        }
+    }
+
+    match props.get("a").and_then(to_usize) {
+        Some(value) if value > MAX_ALIGNMENT => Err(StrictError::AlignmentOutOfRange { value }),
+        _ => Ok(()),
+    }
+}
+
+// A visitor for the metadata and property objects of a KLE layout in JSON form, used to inspect
+// or fix up a layout before deserialising it
+pub(crate) trait LayoutVisitor {
+    type Error;
+
+    fn visit_metadata(&mut self, metadata: &mut Map<String, Value>) -> Result<(), Self::Error>;
+
+    // `col` is the index of the properties object within the row's array, including legends
+    fn visit_props(
+        &mut self,
+        props: &mut Map<String, Value>,
+        row: usize,
+        col: usize,
+    ) -> Result<(), Self::Error>;
+}
+
+pub(crate) fn walk_layout<V>(value: &mut Value, visitor: &mut V) -> Result<(), V::Error>
+where
+    V: LayoutVisitor,
+{
+    // Anything that isn't structured like a KLE layout is left for the deserialiser to reject
+    let items = match value.as_array_mut() {
+        Some(items) => items,
+        None => return Ok(()),
+    };
+
+    let rows = match items.split_first_mut() {
+        Some((Value::Object(metadata), rows)) => {
+            visitor.visit_metadata(metadata)?;
+            rows
+        }
+        _ => &mut items[..],
+    };
+
+    for (row, keys) in rows.iter_mut().enumerate() {
+        for (col, props) in (keys.as_array_mut().into_iter().flatten().enumerate())
+            .filter_map(|(i, k)| k.as_object_mut().map(|k| (i, k)))
+        {
+            visitor.visit_props(props, row, col)?;
+        }
+    }
+
+    Ok(())
+}
+
+struct StrictChecker;
+
+impl LayoutVisitor for StrictChecker {
+    type Error = StrictError;
+
+    fn visit_metadata(&mut self, metadata: &mut Map<String, Value>) -> Result<(), StrictError> {
+        check_metadata(metadata)
+    }
+
+    fn visit_props(
+        &mut self,
+        props: &mut Map<String, Value>,
+        row: usize,
+        col: usize,
+    ) -> Result<(), StrictError> {
+        check_props(props, row, col)
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real + DeserializeOwned,
+{
+    /// Deserialises a [`Keyboard`] from a string containing KLE JSON data, rejecting any input that
+    /// does not strictly conform to KLE's format.
+    ///
+    /// In addition to the checks performed by the regular deserialiser, this rejects layouts where:
+    ///
+    /// * rotation properties (`r`, `rx`, or `ry`) appear anywhere other than the start of a row;
+    /// * font sizes (`f`, `f2`, or `fa`) are outside the range `1..=9`. A size of 0 is still
+    ///   allowed in `fa`, where KLE uses it to indicate the default size;
+    /// * alignments (`a`) are outside the range `0..=7`; or
+    /// * the metadata contains fields not recognised by KLE.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`StrictError`] describing the first violation found, or
+    /// [`StrictError::Json`] if the input is not a valid KLE layout.
+    pub fn from_str_strict(s: &str) -> Result<Self, StrictError> {
+        let mut value: Value = serde_json::from_str(s)?;
+        walk_layout(&mut value, &mut StrictChecker)?;
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    #[test]
+    fn test_strict_error_display() {
+        let error = StrictError::RotationNotAtStartOfRow { row: 2 };
+        assert_eq!(
+            format!("{error}"),
+            "rotation property not at start of row 2"
+        );
+
+        let error = StrictError::FontSizeOutOfRange { value: 0 };
+        assert_eq!(
+            format!("{error}"),
+            "font size 0 is out of range, expected 1 <= size <= 9"
+        );
+
+        let error = StrictError::AlignmentOutOfRange { value: 8 };
+        assert_eq!(
+            format!("{error}"),
+            "alignment 8 is out of range, expected 0 <= alignment <= 7"
+        );
+
+        let error = StrictError::UnknownMetadataField {
+            field: "foo".into(),
+        };
+        assert_eq!(format!("{error}"), r#"unknown metadata field "foo""#);
+    }
+
+    #[test]
+    fn test_keyboard_from_str_strict() {
+        let keyboard = Keyboard::<f64>::from_str_strict(
+            r#"[
+                {"name": "test", "switchMount": "cherry"},
+                [{"r": 15, "rx": 1, "a": 7, "f": 9, "fa": [0, 2]}, "A", {"f": 1}, "B"],
+                ["C"]
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(keyboard.metadata.name, "test");
+        assert_eq!(keyboard.keys.len(), 3);
+
+        let result = Keyboard::<f64>::from_str_strict(r#"[["A", {"r": 15}, "B"]]"#);
+        assert_matches!(result, Err(StrictError::RotationNotAtStartOfRow { row: 0 }));
+
+        let result = Keyboard::<f64>::from_str_strict(r#"[{}, ["A"], ["B", {"ry": 1}, "C"]]"#);
+        assert_matches!(result, Err(StrictError::RotationNotAtStartOfRow { row: 1 }));
+
+        let result = Keyboard::<f64>::from_str_strict(r#"[[{"f": 0}, "A"]]"#);
+        assert_matches!(result, Err(StrictError::FontSizeOutOfRange { value: 0 }));
+
+        let result = Keyboard::<f64>::from_str_strict(r#"[[{"f2": 10}, "A"]]"#);
+        assert_matches!(result, Err(StrictError::FontSizeOutOfRange { value: 10 }));
+
+        let result = Keyboard::<f64>::from_str_strict(r#"[[{"fa": [3, 12]}, "A"]]"#);
+        assert_matches!(result, Err(StrictError::FontSizeOutOfRange { value: 12 }));
+
+        let result = Keyboard::<f64>::from_str_strict(r#"[[{"a": 8}, "A"]]"#);
+        assert_matches!(result, Err(StrictError::AlignmentOutOfRange { value: 8 }));
+
+        let result = Keyboard::<f64>::from_str_strict(r#"[{"foo": "bar"}, ["A"]]"#);
+        assert_matches!(result, Err(StrictError::UnknownMetadataField { field }) if field == "foo");
+
+        let result = Keyboard::<f64>::from_str_strict("{");
+        assert_matches!(result, Err(StrictError::Json(_)));
+
+        let result = Keyboard::<f64>::from_str_strict("null");
+        assert_matches!(result, Err(StrictError::Json(_)));
+    }
+}
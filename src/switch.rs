@@ -0,0 +1,339 @@
+use alloc::{format, string::String};
+
+use num_traits::real::Real;
+
+use crate::{Key, Keyboard, Metadata, Switch};
+
+impl Switch {
+    /// Returns whether the switch has no mount, brand, or type set, the same as
+    /// [`Switch::default`].
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.mount.is_empty() && self.brand.is_empty() && self.typ.is_empty()
+    }
+
+    /// Returns a human readable name for the switch.
+    ///
+    /// The brand is converted to title case since KLE uses lowercase brand names. The type is used
+    /// as is, since KLE uses either a part number or colour depending on the brand. Empty parts are
+    /// omitted.
+    ///
+    /// ```
+    /// use kle_serial::Switch;
+    ///
+    /// let switch = Switch {
+    ///     mount: "cherry".into(),
+    ///     brand: "cherry".into(),
+    ///     typ: "MX1A-11xx".into(),
+    /// };
+    ///
+    /// assert_eq!(switch.display_name(), "Cherry MX1A-11xx");
+    /// ```
+    #[must_use]
+    pub fn display_name(&self) -> String {
+        let brand = title_case(&self.brand);
+        match (brand.is_empty(), self.typ.is_empty()) {
+            (false, false) => format!("{brand} {}", self.typ),
+            (false, true) => brand,
+            (true, _) => self.typ.clone(),
+        }
+    }
+
+    /// Formats the switch in KLE's `brand/type` style.
+    ///
+    /// ```
+    /// use kle_serial::Switch;
+    ///
+    /// let switch = Switch {
+    ///     mount: "cherry".into(),
+    ///     brand: "cherry".into(),
+    ///     typ: "MX1A-11xx".into(),
+    /// };
+    ///
+    /// assert_eq!(switch.to_kle_string(), "cherry/MX1A-11xx");
+    /// ```
+    #[must_use]
+    pub fn to_kle_string(&self) -> String {
+        format!("{}/{}", self.brand, self.typ)
+    }
+
+    /// Returns the switch with its mount set to `mount`.
+    #[inline]
+    #[must_use]
+    pub fn with_mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into();
+        self
+    }
+
+    /// Returns the switch with its brand set to `brand`.
+    #[inline]
+    #[must_use]
+    pub fn with_brand(mut self, brand: impl Into<String>) -> Self {
+        self.brand = brand.into();
+        self
+    }
+
+    /// Returns the switch with its type set to `typ`.
+    #[inline]
+    #[must_use]
+    pub fn with_type(mut self, typ: impl Into<String>) -> Self {
+        self.typ = typ.into();
+        self
+    }
+
+    /// Returns whether the switch matches `pattern`.
+    ///
+    /// Each of the pattern's mount, brand, and type must be a case-insensitive substring of the
+    /// switch's corresponding field. Empty fields in the pattern match anything.
+    ///
+    /// ```
+    /// use kle_serial::Switch;
+    ///
+    /// let switch = Switch::default().with_brand("Cherry MX").with_type("Red");
+    ///
+    /// assert!(switch.matches(&Switch::default().with_brand("cherry")));
+    /// assert!(!switch.matches(&Switch::default().with_brand("cherry").with_type("blue")));
+    /// ```
+    #[must_use]
+    pub fn matches(&self, pattern: &Self) -> bool {
+        let contains =
+            |value: &str, pattern: &str| value.to_lowercase().contains(&pattern.to_lowercase());
+
+        contains(&self.mount, &pattern.mount)
+            && contains(&self.brand, &pattern.brand)
+            && contains(&self.typ, &pattern.typ)
+    }
+}
+
+impl Metadata {
+    /// Returns the switch used by `key`, which is the key's own switch if it overrides the
+    /// layout's global switch, or [`Metadata::switch`] otherwise.
+    ///
+    /// A key overrides the global switch if any of its switch's fields are set, in which case the
+    /// key's switch is returned as is, even if some of its fields are empty. See
+    /// [`Key::has_switch_override`].
+    #[inline]
+    #[must_use]
+    pub fn effective_switch_for<'a, T: Real>(&'a self, key: &'a Key<T>) -> &'a Switch {
+        if key.has_switch_override() {
+            &key.switch
+        } else {
+            &self.switch
+        }
+    }
+}
+
+impl<T> Key<T>
+where
+    T: Real,
+{
+    /// Returns whether the key's switch overrides the layout's global switch, i.e. any of its
+    /// fields are set.
+    #[inline]
+    #[must_use]
+    pub fn has_switch_override(&self) -> bool {
+        !self.switch.is_empty()
+    }
+
+    /// Returns the switch used by the key given the layout's metadata. This is equivalent to
+    /// [`Metadata::effective_switch_for`].
+    #[inline]
+    #[must_use]
+    pub fn effective_switch_given_metadata<'a>(&'a self, meta: &'a Metadata) -> &'a Switch {
+        meta.effective_switch_for(self)
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Returns an iterator over the keys whose switch [matches](Switch::matches) `pattern`.
+    pub fn keys_with_switch<'a>(&'a self, pattern: &'a Switch) -> impl Iterator<Item = &'a Key<T>> {
+        (self.keys.iter()).filter(move |key| key.switch.matches(pattern))
+    }
+}
+
+fn title_case(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut word_start = true;
+    for c in s.chars() {
+        if word_start {
+            result.extend(c.to_uppercase());
+        } else {
+            result.push(c);
+        }
+        word_start = c.is_whitespace() || c == '-';
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::*;
+
+    #[test]
+    fn test_switch_is_empty() {
+        assert!(Switch::default().is_empty());
+
+        let switch = Switch {
+            mount: "alps".into(),
+            ..Switch::default()
+        };
+        assert!(!switch.is_empty());
+    }
+
+    #[test]
+    fn test_switch_display_name() {
+        let switch = Switch {
+            mount: "cherry".into(),
+            brand: "cherry".into(),
+            typ: "MX1A-11xx".into(),
+        };
+        assert_eq!(switch.display_name(), "Cherry MX1A-11xx");
+
+        let switch = Switch {
+            brand: "matias".into(),
+            ..Switch::default()
+        };
+        assert_eq!(switch.display_name(), "Matias");
+
+        let switch = Switch {
+            typ: "red".into(),
+            ..Switch::default()
+        };
+        assert_eq!(switch.display_name(), "red");
+
+        assert_eq!(Switch::default().display_name(), "");
+    }
+
+    #[test]
+    fn test_switch_to_kle_string() {
+        let switch = Switch {
+            mount: "alps".into(),
+            brand: "alps".into(),
+            typ: "SKCM Blue".into(),
+        };
+        assert_eq!(switch.to_kle_string(), "alps/SKCM Blue");
+    }
+
+    #[test]
+    fn test_switch_ord() {
+        let switch = |mount: &str, brand: &str, typ: &str| {
+            Switch::default()
+                .with_mount(mount)
+                .with_brand(brand)
+                .with_type(typ)
+        };
+
+        let mut switches = vec![
+            switch("cherry", "gateron", "red"),
+            switch("alps", "matias", "click"),
+            switch("cherry", "cherry", "red"),
+            switch("cherry", "cherry", "blue"),
+        ];
+        switches.sort();
+
+        assert_eq!(
+            switches,
+            [
+                switch("alps", "matias", "click"),
+                switch("cherry", "cherry", "blue"),
+                switch("cherry", "cherry", "red"),
+                switch("cherry", "gateron", "red"),
+            ]
+        );
+
+        let set: std::collections::HashSet<_> = switches.iter().chain(&switches).collect();
+        assert_eq!(set.len(), 4);
+    }
+
+    #[test]
+    fn test_switch_matches() {
+        let pattern = Switch::default().with_brand("cherry");
+
+        assert!(Switch::default().with_brand("Cherry").matches(&pattern));
+        assert!(Switch::default().with_brand("cherry MX").matches(&pattern));
+        assert!(!Switch::default().with_brand("gateron").matches(&pattern));
+        assert!(Switch::default().matches(&Switch::default()));
+        assert!(!Switch::default().matches(&pattern));
+
+        let pattern = pattern.with_mount("CHERRY").with_type("mx1a");
+        let switch = Switch::default()
+            .with_mount("cherry")
+            .with_brand("cherry")
+            .with_type("MX1A-11xx");
+        assert!(switch.matches(&pattern));
+        assert!(!switch.clone().with_mount("alps").matches(&pattern));
+    }
+
+    #[test]
+    fn test_keyboard_keys_with_switch() {
+        let key = |brand: &str| Key {
+            switch: Switch::default().with_brand(brand),
+            ..Key::default()
+        };
+        let keyboard: Keyboard = [key("Cherry"), key("gateron"), key("cherry MX"), key("")]
+            .into_iter()
+            .collect();
+
+        let pattern = Switch::default().with_brand("cherry");
+        let keys: Vec<_> = keyboard.keys_with_switch(&pattern).collect();
+        assert_eq!(keys, [&keyboard.keys[0], &keyboard.keys[2]]);
+
+        assert_eq!(keyboard.keys_with_switch(&Switch::default()).count(), 4);
+    }
+
+    #[test]
+    fn test_metadata_effective_switch_for() {
+        let metadata = Metadata {
+            switch: Switch::default()
+                .with_mount("cherry")
+                .with_brand("cherry")
+                .with_type("MX1A-11xx"),
+            ..Metadata::default()
+        };
+
+        let key = Key::<f64>::default();
+        assert!(!key.has_switch_override());
+        assert_eq!(metadata.effective_switch_for(&key), &metadata.switch);
+        assert_eq!(
+            key.effective_switch_given_metadata(&metadata),
+            &metadata.switch
+        );
+
+        let key = Key::<f64> {
+            switch: Switch::default().with_brand("gateron"),
+            ..Key::default()
+        };
+        assert!(key.has_switch_override());
+        assert_eq!(metadata.effective_switch_for(&key), &key.switch);
+        assert_eq!(key.effective_switch_given_metadata(&metadata), &key.switch);
+
+        let key = Key::<f64> {
+            switch: Switch::default()
+                .with_mount("alps")
+                .with_brand("alps")
+                .with_type("SKCM Blue"),
+            ..Key::default()
+        };
+        assert_eq!(metadata.effective_switch_for(&key), &key.switch);
+
+        let empty = Metadata::default();
+        assert_eq!(
+            empty.effective_switch_for(&Key::<f64>::default()),
+            &Switch::default()
+        );
+    }
+
+    #[test]
+    fn test_title_case() {
+        assert_eq!(title_case("cherry"), "Cherry");
+        assert_eq!(title_case("kailh box"), "Kailh Box");
+        assert_eq!(title_case("te-connectivity"), "Te-Connectivity");
+        assert_eq!(title_case(""), "");
+    }
+}
@@ -0,0 +1,612 @@
+use alloc::vec::Vec;
+
+use num_traits::real::Real;
+
+use crate::geom::shape_epsilon;
+use crate::{Key, Keyboard};
+
+impl<T> Key<T>
+where
+    T: Real,
+{
+    /// Moves the key by `(dx, dy)` keyboard units, including its centre of rotation.
+    pub fn translate(&mut self, dx: T, dy: T) {
+        self.x = self.x + dx;
+        self.y = self.y + dy;
+        self.rx = self.rx + dx;
+        self.ry = self.ry + dy;
+    }
+
+    /// Returns a copy of the key moved by `(dx, dy)` keyboard units, including its centre of
+    /// rotation. See [`Key::translate`].
+    ///
+    /// ```
+    /// use kle_serial::Key;
+    ///
+    /// let key = Key::<f64>::default().clone_with_offset(1.0, 0.5);
+    ///
+    /// assert_eq!((key.x, key.y), (1.0, 0.5));
+    /// ```
+    #[must_use]
+    pub fn clone_with_offset(&self, dx: T, dy: T) -> Self {
+        let mut key = self.clone();
+        key.translate(dx, dy);
+        key
+    }
+
+    /// Returns a copy of the key moved to the position `(x, y)`. The centre of rotation is moved
+    /// by the same amount as the key.
+    #[must_use]
+    pub fn clone_at(&self, x: T, y: T) -> Self {
+        self.clone_with_offset(x - self.x, y - self.y)
+    }
+
+    /// Rotates the key by `degrees` clockwise around the point `(cx, cy)`.
+    ///
+    /// The key's rotation is increased by `degrees` and its centre of rotation is rotated around
+    /// `(cx, cy)`. The key's unrotated position moves with its centre of rotation, so the key's
+    /// final position is rotated around `(cx, cy)` as expected.
+    pub fn rotate_around(&mut self, degrees: T, cx: T, cy: T) {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let (dx, dy) = (self.rx - cx, self.ry - cy);
+        let (rx, ry) = (cx + dx * cos - dy * sin, cy + dx * sin + dy * cos);
+
+        self.translate(rx - self.rx, ry - self.ry);
+        self.rotation = self.rotation + degrees;
+    }
+
+    /// Scales the key's position and size by `sx` horizontally and `sy` vertically, including its
+    /// centre of rotation and secondary rectangle. The rotation angle is unchanged.
+    pub fn scale_xy(&mut self, sx: T, sy: T) {
+        self.x = self.x * sx;
+        self.y = self.y * sy;
+        self.width = self.width * sx;
+        self.height = self.height * sy;
+        self.x2 = self.x2 * sx;
+        self.y2 = self.y2 * sy;
+        self.width2 = self.width2 * sx;
+        self.height2 = self.height2 * sy;
+        self.rx = self.rx * sx;
+        self.ry = self.ry * sy;
+    }
+
+    /// Returns a copy of the key reflected about the vertical line `x = axis_x`.
+    ///
+    /// The key's rotation and centre of rotation are reflected too, as is the secondary rectangle
+    /// used by stepped and L-shaped keys. Legends are not reordered.
+    #[must_use]
+    pub fn mirror_horizontal(&self, axis_x: T) -> Self {
+        let two = T::one() + T::one();
+        Self {
+            x: two * axis_x - (self.x + self.width),
+            x2: self.width - (self.x2 + self.width2),
+            rotation: -self.rotation,
+            rx: two * axis_x - self.rx,
+            ..self.clone()
+        }
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Moves all keys in the layout by `(dx, dy)` keyboard units, including their centres of
+    /// rotation.
+    pub fn translate(&mut self, dx: T, dy: T) {
+        for key in &mut self.keys {
+            key.translate(dx, dy);
+        }
+    }
+
+    /// Consumes the layout and returns it with all keys moved by `(dx, dy)` keyboard units. See
+    /// [`Keyboard::translate`].
+    #[must_use]
+    pub fn translate_into(mut self, dx: T, dy: T) -> Self {
+        self.translate(dx, dy);
+        self
+    }
+
+    /// Returns a copy of the layout with all keys moved by `(dx, dy)` keyboard units. See
+    /// [`Keyboard::translate`].
+    ///
+    /// This is useful for positioning one layout relative to another before combining them.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let main: Keyboard = serde_json::from_str(r#"[["A", "B"]]"#).unwrap();
+    /// let numpad: Keyboard = serde_json::from_str(r#"[["7", "8", "9"]]"#).unwrap();
+    /// let combined = main + numpad.with_offset(2.5, 0.0);
+    ///
+    /// assert_eq!(combined.keys[2].x, 2.5);
+    /// ```
+    #[must_use]
+    pub fn with_offset(&self, dx: T, dy: T) -> Self {
+        self.clone().translate_into(dx, dy)
+    }
+
+    /// Returns a copy of the layout rotated by `degrees` clockwise around the point `(cx, cy)`.
+    ///
+    /// See [`Key::rotate_around`] for details of how each key is rotated.
+    #[must_use]
+    pub fn rotate_layout(&self, degrees: T, cx: T, cy: T) -> Self {
+        let mut result = self.clone();
+        result.rotate_layout_mut(degrees, cx, cy);
+        result
+    }
+
+    /// Rotates the layout in place by `degrees` clockwise around the point `(cx, cy)`. See
+    /// [`Keyboard::rotate_layout`].
+    pub fn rotate_layout_mut(&mut self, degrees: T, cx: T, cy: T) {
+        for key in &mut self.keys {
+            key.rotate_around(degrees, cx, cy);
+        }
+    }
+
+    /// Returns a copy of the layout scaled uniformly by `factor` about the origin. See
+    /// [`Keyboard::scale_xy`].
+    #[must_use]
+    pub fn scale(&self, factor: T) -> Self {
+        self.scale_xy(factor, factor)
+    }
+
+    /// Returns a copy of the layout scaled by `sx` horizontally and `sy` vertically about the
+    /// origin.
+    ///
+    /// All positions and sizes are scaled, but rotation angles are unchanged. Note that this means
+    /// rotated keys are not distorted when `sx != sy`; they keep their rotation while their size
+    /// and centre of rotation are scaled.
+    #[must_use]
+    pub fn scale_xy(&self, sx: T, sy: T) -> Self {
+        let mut result = self.clone();
+        for key in &mut result.keys {
+            key.scale_xy(sx, sy);
+        }
+        result
+    }
+
+    /// Returns a copy of the layout reflected left to right.
+    ///
+    /// Keys are reflected about the vertical line through the centre of the layout's
+    /// [bounding box](Keyboard::bounding_box), so the bounding box is unchanged. See
+    /// [`Key::mirror_horizontal`] for details.
+    #[must_use]
+    pub fn mirror_horizontal(&self) -> Self {
+        let axis_x = self.bounding_box().map_or(T::zero(), |bbox| {
+            bbox.x + bbox.width / (T::one() + T::one())
+        });
+
+        Self {
+            metadata: self.metadata.clone(),
+            keys: (self.keys.iter())
+                .map(|key| key.mirror_horizontal(axis_x))
+                .collect(),
+        }
+    }
+
+    /// Returns a copy of the layout with the row at `row_y` repeated `times` times below it.
+    ///
+    /// All keys whose `y` is approximately equal to `row_y` are duplicated, with the `i`th copy
+    /// moved down by `dy * i` keyboard units using [`Key::clone_with_offset`]. The copies are
+    /// appended after the original keys.
+    ///
+    /// ```
+    /// use kle_serial::Keyboard;
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["7", "8", "9"]]"#).unwrap();
+    /// let numpad = keyboard.repeat_row(0.0, 2, 1.0);
+    ///
+    /// assert_eq!(numpad.keys.len(), 9);
+    /// assert_eq!(numpad.keys[8].y, 2.0);
+    /// ```
+    #[must_use]
+    pub fn repeat_row(&self, row_y: T, times: usize, dy: T) -> Self {
+        let row: Vec<_> = (self.keys.iter())
+            .filter(|key| (key.y - row_y).abs() <= shape_epsilon())
+            .collect();
+
+        let copies = (1..=times).flat_map(|i| {
+            let offset = dy * T::from(i).unwrap_or_else(T::zero);
+            row.iter()
+                .map(move |key| key.clone_with_offset(T::zero(), offset))
+        });
+
+        Self {
+            metadata: self.metadata.clone(),
+            keys: self.keys.iter().cloned().chain(copies).collect(),
+        }
+    }
+
+    /// Returns a copy of the layout tiled `repeat_x` times horizontally and `repeat_y` times
+    /// vertically.
+    ///
+    /// Each copy is offset by the size of the layout's [bounding box](Keyboard::bounding_box) plus
+    /// `gap_x` or `gap_y` keyboard units. Copies are ordered left to right, then top to bottom,
+    /// starting with the original layout. A layout with no keys is returned unchanged.
+    #[must_use]
+    pub fn tile(&self, repeat_x: usize, repeat_y: usize, gap_x: T, gap_y: T) -> Self {
+        let bbox = match self.bounding_box() {
+            Some(bbox) => bbox,
+            None => return self.clone(),
+        };
+        let (step_x, step_y) = (bbox.width + gap_x, bbox.height + gap_y);
+
+        let keys = (0..repeat_y)
+            .flat_map(|j| (0..repeat_x).map(move |i| (i, j)))
+            .flat_map(|(i, j)| {
+                let dx = step_x * T::from(i).unwrap_or_else(T::zero);
+                let dy = step_y * T::from(j).unwrap_or_else(T::zero);
+                self.keys
+                    .iter()
+                    .map(move |key| key.clone_with_offset(dx, dy))
+            })
+            .collect();
+
+        Self {
+            metadata: self.metadata.clone(),
+            keys,
+        }
+    }
+
+    /// Moves all keys in the layout so the top left corner of its bounding box is at `(0, 0)`.
+    ///
+    /// This uses [`Keyboard::bounding_box`], so it takes into account both the keys' rotation and
+    /// the secondary rectangle used by stepped and L-shaped keys. A layout with no keys is left
+    /// unchanged.
+    pub fn normalize_origin(&mut self) {
+        if let Some(bbox) = self.bounding_box() {
+            self.translate(-bbox.x, -bbox.y);
+        }
+    }
+
+    /// Consumes the layout and returns it with its origin normalised. See
+    /// [`Keyboard::normalize_origin`].
+    #[must_use]
+    pub fn normalize_origin_into(mut self) -> Self {
+        self.normalize_origin();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use isclose::assert_is_close;
+
+    use super::*;
+    use crate::Color;
+
+    fn keyboard() -> Keyboard {
+        Keyboard {
+            keys: vec![
+                // ISO enter
+                Key {
+                    x: 3.25,
+                    y: 2.0,
+                    width: 1.25,
+                    height: 2.0,
+                    x2: -0.25,
+                    width2: 1.5,
+                    height2: 1.0,
+                    ..Key::default()
+                },
+                Key {
+                    x: 5.0,
+                    y: 4.0,
+                    rotation: 30.0,
+                    rx: 5.0,
+                    ry: 4.0,
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        }
+    }
+
+    #[test]
+    fn test_keyboard_translate() {
+        let keyboard = keyboard().translate_into(1.0, -2.0);
+
+        assert_is_close!(keyboard.keys[0].x, 4.25);
+        assert_is_close!(keyboard.keys[0].y, 0.0);
+        assert_is_close!(keyboard.keys[0].rx, 1.0);
+        assert_is_close!(keyboard.keys[0].ry, -2.0);
+        assert_is_close!(keyboard.keys[1].x, 6.0);
+        assert_is_close!(keyboard.keys[1].y, 2.0);
+        assert_is_close!(keyboard.keys[1].rx, 6.0);
+        assert_is_close!(keyboard.keys[1].ry, 2.0);
+    }
+
+    #[test]
+    fn test_keyboard_with_offset() {
+        let keyboard = keyboard();
+        let offset = keyboard.with_offset(1.0, -2.0);
+
+        assert_eq!(offset, keyboard.clone().translate_into(1.0, -2.0));
+        assert_eq!(offset.metadata, keyboard.metadata);
+        assert_is_close!(offset.keys[1].x, keyboard.keys[1].x + 1.0);
+    }
+
+    #[test]
+    fn test_key_clone_with_offset() {
+        let key = Key::<f64> {
+            color: Color::new(0x12, 0x34, 0x56, 0xFF),
+            ..Key::default()
+        };
+        let offset = key.clone_with_offset(1.0, 0.0);
+
+        assert_is_close!(offset.x, 1.0);
+        assert_is_close!(offset.y, 0.0);
+        assert_is_close!(offset.rx, 1.0);
+        assert_is_close!(offset.ry, 0.0);
+        assert_eq!(
+            offset,
+            Key {
+                x: 1.0,
+                rx: 1.0,
+                ..key
+            }
+        );
+
+        let key = keyboard().keys.remove(1);
+        let offset = key.clone_with_offset(-1.0, 2.5);
+
+        assert_is_close!(offset.x, 4.0);
+        assert_is_close!(offset.y, 6.5);
+        assert_is_close!(offset.rx, 4.0);
+        assert_is_close!(offset.ry, 6.5);
+        assert_is_close!(offset.rotation, key.rotation);
+    }
+
+    #[test]
+    fn test_key_clone_at() {
+        let key = keyboard().keys.remove(1);
+        let moved = key.clone_at(1.0, 2.0);
+
+        assert_is_close!(moved.x, 1.0);
+        assert_is_close!(moved.y, 2.0);
+        assert_is_close!(moved.rx, 1.0);
+        assert_is_close!(moved.ry, 2.0);
+        assert_eq!(moved.clone_at(key.x, key.y), key);
+
+        let iso_enter = keyboard().keys.remove(0);
+        let moved = iso_enter.clone_at(0.0, 0.0);
+
+        assert_is_close!(moved.x, 0.0);
+        assert_is_close!(moved.y, 0.0);
+        assert_is_close!(moved.rx, -3.25);
+        assert_is_close!(moved.ry, -2.0);
+        assert_eq!(moved.legends, iso_enter.legends);
+        assert_eq!(moved.switch, iso_enter.switch);
+    }
+
+    #[test]
+    fn test_key_mirror_horizontal() {
+        let iso_enter = keyboard().keys.remove(0);
+        let mirrored = iso_enter.mirror_horizontal(5.0);
+
+        assert_is_close!(mirrored.x, 5.5);
+        assert_is_close!(mirrored.x2, 0.0);
+        assert_is_close!(mirrored.true_x(), 5.5);
+        assert_is_close!(mirrored.true_width(), 1.5);
+        assert_eq!(mirrored.mirror_horizontal(5.0), iso_enter);
+
+        let key = keyboard().keys.remove(1);
+        let mirrored = key.mirror_horizontal(0.0);
+
+        assert_is_close!(mirrored.x, -6.0);
+        assert_is_close!(mirrored.rotation, -30.0);
+        assert_is_close!(mirrored.rx, -5.0);
+        assert_is_close!(mirrored.ry, 4.0);
+        assert_eq!(mirrored.mirror_horizontal(0.0), key);
+
+        // Each corner should be the reflection of the opposite corner horizontally
+        let (corners, mirrored) = (key.corners(), mirrored.corners());
+        for (i, j) in [(0, 1), (1, 0), (2, 3), (3, 2)] {
+            assert_is_close!(mirrored[i].0, -corners[j].0);
+            assert_is_close!(mirrored[i].1, corners[j].1);
+        }
+    }
+
+    #[test]
+    fn test_keyboard_mirror_horizontal() {
+        let keyboard = keyboard();
+        let mirrored = keyboard.mirror_horizontal();
+        let (bbox, mirrored_bbox) = (
+            keyboard.bounding_box().unwrap(),
+            mirrored.bounding_box().unwrap(),
+        );
+
+        assert_is_close!(mirrored_bbox.x, bbox.x);
+        assert_is_close!(mirrored_bbox.y, bbox.y);
+        assert_is_close!(mirrored_bbox.width, bbox.width);
+        assert_is_close!(mirrored_bbox.height, bbox.height);
+        assert_eq!(mirrored.mirror_horizontal(), keyboard);
+    }
+
+    #[test]
+    fn test_key_rotate_around() {
+        let mut key = Key::<f64> {
+            x: 1.0,
+            ..Key::default()
+        };
+        key.rotate_around(90.0, 0.5, 0.5);
+
+        assert_is_close!(key.rotation, 90.0);
+        let expected = [(1.0, 1.0), (1.0, 2.0), (0.0, 2.0), (0.0, 1.0)];
+        for ((x, y), (ex, ey)) in key.corners().into_iter().zip(expected) {
+            assert_is_close!(x, ex);
+            assert_is_close!(y, ey);
+        }
+    }
+
+    #[test]
+    fn test_keyboard_rotate_layout() {
+        let keyboard = keyboard();
+        let rotated = keyboard.rotate_layout(360.0, 2.0, -1.0);
+
+        for (key, rotated) in keyboard.keys.iter().zip(&rotated.keys) {
+            assert_is_close!(rotated.x, key.x);
+            assert_is_close!(rotated.y, key.y);
+            assert_is_close!(rotated.rx, key.rx);
+            assert_is_close!(rotated.ry, key.ry);
+            assert_is_close!(rotated.rotation, key.rotation + 360.0);
+
+            for (corner, expected) in rotated.all_corners().into_iter().zip(key.all_corners()) {
+                assert_is_close!(corner.0, expected.0);
+                assert_is_close!(corner.1, expected.1);
+            }
+        }
+
+        let mut rotated = keyboard.clone();
+        rotated.rotate_layout_mut(-45.0, 1.0, 1.0);
+        let rotated = rotated.rotate_layout(45.0, 1.0, 1.0);
+
+        assert_eq!(rotated, keyboard);
+    }
+
+    #[test]
+    fn test_keyboard_scale() {
+        let keyboard = keyboard();
+
+        assert_eq!(keyboard.scale(1.0), keyboard);
+
+        let scaled = keyboard.scale(2.0);
+        for (key, scaled) in keyboard.keys.iter().zip(&scaled.keys) {
+            assert_is_close!(scaled.x, key.x * 2.0);
+            assert_is_close!(scaled.y, key.y * 2.0);
+            assert_is_close!(scaled.width, key.width * 2.0);
+            assert_is_close!(scaled.height, key.height * 2.0);
+            assert_is_close!(scaled.x2, key.x2 * 2.0);
+            assert_is_close!(scaled.y2, key.y2 * 2.0);
+            assert_is_close!(scaled.width2, key.width2 * 2.0);
+            assert_is_close!(scaled.height2, key.height2 * 2.0);
+            assert_is_close!(scaled.rx, key.rx * 2.0);
+            assert_is_close!(scaled.ry, key.ry * 2.0);
+            assert_is_close!(scaled.rotation, key.rotation);
+        }
+
+        let bbox = keyboard.bounding_box().unwrap();
+        let scaled_bbox = scaled.bounding_box().unwrap();
+        assert_is_close!(scaled_bbox.width, bbox.width * 2.0);
+        assert_is_close!(scaled_bbox.height, bbox.height * 2.0);
+
+        for (key, scaled) in keyboard.keys.iter().zip(&scaled.keys) {
+            let (width, height) = key.physical_size_mm();
+            let (scaled_width, scaled_height) = scaled.physical_size_mm();
+            assert_is_close!(scaled_width, width * 2.0);
+            assert_is_close!(scaled_height, height * 2.0);
+        }
+    }
+
+    #[test]
+    fn test_keyboard_scale_xy() {
+        let keyboard = Keyboard::<f64> {
+            keys: vec![Key {
+                x: 1.0,
+                y: 2.0,
+                width: 1.5,
+                ..Key::default()
+            }],
+            ..Keyboard::default()
+        };
+        let scaled = keyboard.scale_xy(2.0, 0.5);
+        let key = &scaled.keys[0];
+
+        assert_is_close!(key.x, 2.0);
+        assert_is_close!(key.y, 1.0);
+        assert_is_close!(key.width, 3.0);
+        assert_is_close!(key.height, 0.5);
+        assert_is_close!(key.width2, 2.0);
+        assert_is_close!(key.height2, 0.5);
+    }
+
+    #[test]
+    fn test_keyboard_repeat_row() {
+        let row = Keyboard::<f64> {
+            keys: vec![
+                Key::default(),
+                Key {
+                    x: 1.0,
+                    ..Key::default()
+                },
+                Key {
+                    x: 2.0,
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        };
+        let repeated = row.repeat_row(0.0, 3, 1.0);
+
+        assert_eq!(repeated.keys.len(), 12);
+        assert_eq!(repeated.keys[..3], row.keys[..]);
+        for (key, (x, y)) in repeated
+            .keys
+            .iter()
+            .zip((0..4).flat_map(|y| (0..3).map(move |x| (x, y))))
+        {
+            assert_is_close!(key.x, f64::from(x));
+            assert_is_close!(key.y, f64::from(y));
+            assert_is_close!(key.ry, f64::from(y));
+        }
+
+        // Only keys in the given row are repeated
+        let keyboard = keyboard().repeat_row(4.0 + 1e-9, 2, -0.5);
+        assert_eq!(keyboard.keys.len(), 4);
+        assert_is_close!(keyboard.keys[2].y, 3.5);
+        assert_is_close!(keyboard.keys[3].y, 3.0);
+
+        assert_eq!(keyboard.repeat_row(10.0, 5, 1.0), keyboard);
+        assert_eq!(keyboard.repeat_row(4.0, 0, 1.0), keyboard);
+    }
+
+    #[test]
+    fn test_keyboard_tile() {
+        let keyboard = Keyboard::<f64> {
+            keys: vec![
+                Key::default(),
+                Key {
+                    x: 1.0,
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        };
+        let tiled = keyboard.tile(3, 2, 0.5, 0.25);
+
+        assert_eq!(tiled.keys.len(), 12);
+        let bbox = tiled.bounding_box().unwrap();
+        assert_is_close!(bbox.width, 7.0);
+        assert_is_close!(bbox.height, 2.25);
+        assert_is_close!(tiled.keys[2].x, 2.5);
+        assert_is_close!(tiled.keys[6].x, 0.0);
+        assert_is_close!(tiled.keys[6].y, 1.25);
+
+        assert_eq!(keyboard.tile(1, 1, 1.0, 1.0), keyboard);
+        assert!(keyboard.tile(0, 2, 0.0, 0.0).keys.is_empty());
+        assert!(Keyboard::<f64>::default()
+            .tile(2, 2, 0.0, 0.0)
+            .keys
+            .is_empty());
+    }
+
+    #[test]
+    fn test_keyboard_normalize_origin() {
+        let mut keyboard = keyboard();
+        let size = keyboard.bounding_box().unwrap();
+        keyboard.normalize_origin();
+        let bbox = keyboard.bounding_box().unwrap();
+
+        assert_is_close!(bbox.x, 0.0);
+        assert_is_close!(bbox.y, 0.0);
+        assert_is_close!(bbox.width, size.width);
+        assert_is_close!(bbox.height, size.height);
+        assert_is_close!(keyboard.keys[0].x, 0.25);
+
+        let keyboard = Keyboard::<f64>::default().normalize_origin_into();
+        assert!(keyboard.bounding_box().is_none());
+    }
+}
@@ -0,0 +1,168 @@
+use alloc::{format, string::String};
+use core::{fmt, str::Utf8Error};
+
+use num_traits::real::Real;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Keyboard, ParseError};
+
+// The same set of characters escaped by JavaScript's encodeURIComponent
+const FRAGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'!')
+    .remove(b'~')
+    .remove(b'*')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')');
+
+/// An error returned when decoding a layout using [`KleUrlCodec::decode`].
+#[derive(Debug)]
+pub enum UrlDecodeError {
+    /// The percent-decoded fragment is not valid UTF-8.
+    Utf8(Utf8Error),
+    /// The decoded fragment is not a valid KLE layout.
+    Parse(ParseError),
+}
+
+impl fmt::Display for UrlDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utf8(error) => write!(f, "invalid URL fragment: {error}"),
+            Self::Parse(error) => write!(f, "error parsing URL fragment: {error}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UrlDecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Utf8(error) => Some(error),
+            Self::Parse(error) => Some(error),
+        }
+    }
+}
+
+impl From<Utf8Error> for UrlDecodeError {
+    fn from(error: Utf8Error) -> Self {
+        Self::Utf8(error)
+    }
+}
+
+impl From<ParseError> for UrlDecodeError {
+    fn from(error: ParseError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+/// Encodes and decodes layouts embedded in URL fragments for sharing.
+///
+/// The fragment contains the layout's KLE JSON, percent-encoded the same way as JavaScript's
+/// `encodeURIComponent`.
+///
+/// ```
+/// use kle_serial::{KleUrlCodec, Keyboard};
+///
+/// let keyboard: Keyboard = serde_json::from_str(r#"[["A", "B"]]"#).unwrap();
+/// let fragment = KleUrlCodec::encode(&keyboard).unwrap();
+///
+/// assert_eq!(fragment, "%5B%5B%22A%22%2C%22B%22%5D%5D");
+/// assert_eq!(KleUrlCodec::decode::<f64>(&fragment).unwrap(), keyboard);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct KleUrlCodec;
+
+impl KleUrlCodec {
+    /// Serialises the layout into compact KLE JSON and percent-encodes it for use in a URL
+    /// fragment. The result does not include the leading `#`. See [`Keyboard::to_compact_json`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`serde_json::Error`] if the layout cannot be serialised.
+    pub fn encode<T>(keyboard: &Keyboard<T>) -> Result<String, serde_json::Error>
+    where
+        T: Real + Serialize,
+    {
+        let json = keyboard.to_compact_json()?;
+        Ok(format!("{}", utf8_percent_encode(&json, FRAGMENT)))
+    }
+
+    /// Decodes a layout from a URL fragment created by [`KleUrlCodec::encode`].
+    ///
+    /// The fragment may be given with or without its leading `#`, or as part of a full URL in
+    /// which case everything up to the first `#` is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`UrlDecodeError`] if the fragment does not decode to valid UTF-8, or if the
+    /// decoded data is not a valid KLE layout.
+    pub fn decode<T>(url_fragment: &str) -> Result<Keyboard<T>, UrlDecodeError>
+    where
+        T: Real + DeserializeOwned,
+    {
+        let fragment = url_fragment
+            .split_once('#')
+            .map_or(url_fragment, |(_, fragment)| fragment)
+            .trim_start_matches('#');
+
+        Ok(percent_decode_str(fragment).decode_utf8()?.parse()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    #[test]
+    fn test_kle_url_codec_round_trip() {
+        let keyboard: Keyboard = serde_json::from_str(
+            r##"[
+                {"name": "Test & \"quotes\"", "backcolor": "#222222"},
+                [{"c": "#ff0000", "t": "#ffffff"}, "Esc", {"x": 1}, "F1", "F2"],
+                [{"y": 0.5, "w": 1.5}, "Tab", "Q\nq", {"r": 15, "rx": 4}, "W", "Ünïcödé"]
+            ]"##,
+        )
+        .unwrap();
+
+        let fragment = KleUrlCodec::encode(&keyboard).unwrap();
+        assert!(fragment
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-_.!~*'()%".contains(&b)));
+
+        assert_eq!(KleUrlCodec::decode::<f64>(&fragment).unwrap(), keyboard);
+        assert_eq!(
+            KleUrlCodec::decode::<f64>(&format!("#{fragment}")).unwrap(),
+            keyboard
+        );
+        assert_eq!(
+            KleUrlCodec::decode::<f64>(&format!(
+                "http://www.keyboard-layout-editor.com/##{fragment}"
+            ))
+            .unwrap(),
+            keyboard
+        );
+    }
+
+    #[test]
+    fn test_kle_url_codec_decode_unencoded() {
+        let keyboard = KleUrlCodec::decode::<f32>(r#"#[["A","B"]]"#).unwrap();
+        assert_eq!(keyboard.keys.len(), 2);
+    }
+
+    #[test]
+    fn test_kle_url_codec_decode_error() {
+        let error = KleUrlCodec::decode::<f64>("%FF%FE").unwrap_err();
+        assert_matches!(error, UrlDecodeError::Utf8(_));
+        assert!(format!("{error}").starts_with("invalid URL fragment: "));
+
+        let error = KleUrlCodec::decode::<f64>("%5B%5B%7B%22w%22%3A%22x%22%7D%5D%5D").unwrap_err();
+        assert_matches!(error, UrlDecodeError::Parse(_));
+        assert!(format!("{error}").starts_with("error parsing URL fragment: "));
+    }
+}
@@ -1,16 +1,36 @@
-use std::{fmt::Debug, iter};
+use alloc::{format, vec::Vec};
+use core::fmt::{self, Debug, Display};
+use core::iter;
 
 use serde::{
     de::{Error, Unexpected},
-    Deserialize,
+    Deserialize, Serialize,
 };
 
 use crate::{Legend, NUM_LEGENDS};
 
-#[derive(Debug, Clone, Copy)]
-pub(crate) struct BoundsError;
+/// An error returned when a value is outside of the valid range for a [`FontSize`] or
+/// [`Alignment`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundsError {
+    value: usize,
+    max: usize,
+}
+
+impl Display for BoundsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "value {} is out of range, expected 0 <= value <= {}",
+            self.value, self.max
+        )
+    }
+}
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg(feature = "std")]
+impl std::error::Error for BoundsError {}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub(crate) struct BoundedUsize<const MAX: usize, const DEF: usize>(usize);
 
 impl<const MAX: usize, const DEF: usize> BoundedUsize<MAX, DEF> {
@@ -18,14 +38,18 @@ impl<const MAX: usize, const DEF: usize> BoundedUsize<MAX, DEF> {
         if value <= MAX {
             Ok(Self(value))
         } else {
-            Err(BoundsError)
+            Err(BoundsError { value, max: MAX })
         }
     }
+
+    pub fn saturating_new(value: usize) -> Self {
+        Self(value.min(MAX))
+    }
 }
 
 impl<const MAX: usize, const DEF: usize> Debug for BoundedUsize<MAX, DEF> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.0.fmt(f)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
     }
 }
 
@@ -57,16 +81,195 @@ impl<'de, const MAX: usize, const DEF: usize> Deserialize<'de> for BoundedUsize<
     }
 }
 
-// KLE uses default font size of 3 and max of 9
-pub(crate) type FontSize = BoundedUsize<9, 3>;
+impl<const MAX: usize, const DEF: usize> Serialize for BoundedUsize<MAX, DEF> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+// The approximate point size of KLE's default font size 3
+const DEFAULT_FONT_SIZE_PT: f64 = 10.0;
+
+/// A legend font size in KLE's font size unit.
+///
+/// Valid font sizes are in the range `0..=9`, with a default of 3. KLE clamps font sizes to
+/// `1..=9` when rendering, but a size of 0 is still allowed in layout files.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct FontSize(BoundedUsize<9, 3>);
+
+impl FontSize {
+    /// Creates a new [`FontSize`], returning [`None`] if `value` is out of range.
+    #[inline]
+    #[must_use]
+    pub fn new(value: usize) -> Option<Self> {
+        BoundedUsize::new(value).ok().map(Self)
+    }
+
+    /// Returns the smallest valid [`FontSize`].
+    #[inline]
+    #[must_use]
+    pub const fn min() -> Self {
+        Self(BoundedUsize(0))
+    }
+
+    /// Returns the largest valid [`FontSize`].
+    #[inline]
+    #[must_use]
+    pub const fn max() -> Self {
+        Self(BoundedUsize(9))
+    }
+
+    /// Returns the pixel size of this font size, given the pixel size `base_px` of the default font
+    /// size 3. Sizes scale linearly, so size 6 is twice the size of size 3.
+    ///
+    /// ```
+    /// use kle_serial::FontSize;
+    ///
+    /// assert_eq!(FontSize::default().scale_to_px(12.0), 12.0);
+    /// assert_eq!(FontSize::new(6).unwrap().scale_to_px(12.0), 24.0);
+    /// ```
+    #[must_use]
+    pub fn scale_to_px(self, base_px: f64) -> f64 {
+        let value = u8::try_from(usize::from(self)).map_or(f64::NAN, f64::from);
+        let default = u8::try_from(usize::from(Self::default())).map_or(f64::NAN, f64::from);
+        base_px * value / default
+    }
+
+    /// Returns the point size of this font size, assuming KLE's default styling where size 3
+    /// renders at approximately 10pt.
+    #[must_use]
+    pub fn to_pt(self) -> f64 {
+        self.scale_to_px(DEFAULT_FONT_SIZE_PT)
+    }
+
+    pub(crate) fn saturating_new(value: usize) -> Self {
+        Self(BoundedUsize::saturating_new(value))
+    }
+}
+
+impl Debug for FontSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FontSize").field(&self.0).finish()
+    }
+}
+
+impl Display for FontSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&usize::from(*self), f)
+    }
+}
+
+impl From<FontSize> for usize {
+    fn from(value: FontSize) -> Self {
+        value.0.into()
+    }
+}
+
+impl TryFrom<usize> for FontSize {
+    type Error = BoundsError;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        BoundedUsize::new(value).map(Self)
+    }
+}
 
-// KLE uses default alignment of 4
 const MAX_ALIGNMENT: usize = LEGEND_MAPPING.len() - 1;
-pub(crate) type Alignment = BoundedUsize<MAX_ALIGNMENT, 4>;
 
-// This map is the same as that of kle-serial. Note the blanks are also filled
-// in, so we're slightly more permissive with not-strictly-valid KLE input.
-const LEGEND_MAPPING: [[usize; NUM_LEGENDS]; 8] = [
+/// A legend alignment as used by KLE.
+///
+/// The alignment determines how KLE positions legends on a key and is used internally when
+/// deserialising and serialising layouts. Valid alignments are in the range `0..=7`, with a default
+/// of 4. Bit 0 centres legends horizontally, bit 1 centres legends vertically, and bit 2 centres
+/// front legends horizontally.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Alignment(BoundedUsize<MAX_ALIGNMENT, 4>);
+
+impl Alignment {
+    /// Creates a new [`Alignment`], returning [`None`] if `value` is out of range.
+    #[inline]
+    #[must_use]
+    pub fn new(value: usize) -> Option<Self> {
+        BoundedUsize::new(value).ok().map(Self)
+    }
+
+    /// Returns the smallest valid [`Alignment`].
+    #[inline]
+    #[must_use]
+    pub const fn min() -> Self {
+        Self(BoundedUsize(0))
+    }
+
+    /// Returns the largest valid [`Alignment`].
+    #[inline]
+    #[must_use]
+    pub const fn max() -> Self {
+        Self(BoundedUsize(MAX_ALIGNMENT))
+    }
+}
+
+impl Debug for Alignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Alignment").field(&self.0).finish()
+    }
+}
+
+impl Display for Alignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&usize::from(*self), f)
+    }
+}
+
+impl From<Alignment> for usize {
+    fn from(value: Alignment) -> Self {
+        value.0.into()
+    }
+}
+
+impl TryFrom<usize> for Alignment {
+    type Error = BoundsError;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        BoundedUsize::new(value).map(Self)
+    }
+}
+
+/// Maps KLE's legend indices to the legend positions used by this crate for each [`Alignment`].
+///
+/// `LEGEND_MAPPING[alignment][kle_index]` is the index into [`Key::legends`](crate::Key) of the
+/// legend at `kle_index` in a KLE layout. Positions in [`Key::legends`](crate::Key) are in row
+/// major order: `0..=2` are the top row, `3..=5` the middle row, `6..=8` the bottom row, and
+/// `9..=11` are the front legends.
+///
+/// The diagrams below show the KLE index of the legend displayed at each position for every
+/// alignment. Positions marked `.` are not displayed by KLE, but are still filled in here so this
+/// crate is slightly more permissive with not-strictly-valid KLE input.
+///
+/// ```text
+///  0 = no centering    1 = centre x        2 = centre y        3 = centre x & y
+/// +----------+        +----------+        +----------+        +----------+
+/// |  0  8  2 |        |  .  0  . |        |  .  .  . |        |  .  .  . |
+/// |  6  9  7 |        |  .  6  . |        |  0  8  2 |        |  .  0  . |
+/// |  1 10  3 |        |  .  1  . |        |  .  .  . |        |  .  .  . |
+/// +----------+        +----------+        +----------+        +----------+
+/// |  4 11  5 |        |  4 11  5 |        |  4 11  5 |        |  4 11  5 |
+/// +----------+        +----------+        +----------+        +----------+
+///
+///  4 = centre front    5 = centre front    6 = centre front    7 = centre front
+///      (default)           & x                 & y                 & x & y
+/// +----------+        +----------+        +----------+        +----------+
+/// |  0  8  2 |        |  .  0  . |        |  .  .  . |        |  .  .  . |
+/// |  6  9  7 |        |  .  6  . |        |  0  8  2 |        |  .  0  . |
+/// |  1 10  3 |        |  .  1  . |        |  .  .  . |        |  .  .  . |
+/// +----------+        +----------+        +----------+        +----------+
+/// |  .  4  . |        |  .  4  . |        |  .  4  . |        |  .  4  . |
+/// +----------+        +----------+        +----------+        +----------+
+/// ```
+pub const LEGEND_MAPPING: [[usize; NUM_LEGENDS]; 8] = [
     [0, 6, 2, 8, 9, 11, 3, 5, 1, 4, 7, 10], // 0 = no centering
     [1, 7, 0, 2, 9, 11, 4, 3, 5, 6, 8, 10], // 1 = center x
     [3, 0, 5, 1, 9, 11, 2, 6, 4, 7, 8, 10], // 2 = center y
@@ -77,7 +280,42 @@ const LEGEND_MAPPING: [[usize; NUM_LEGENDS]; 8] = [
     [4, 0, 1, 2, 10, 3, 5, 6, 7, 8, 9, 11], // 7 = center front & x & y
 ];
 
-pub(crate) fn realign_legends<T>(values: T, alignment: Alignment) -> [Option<Legend>; NUM_LEGENDS]
+// Bitmasks of the legend positions KLE can actually display with each alignment. These are the
+// positions which are not blank in kle-serial's version of the mapping above.
+const ALIGNMENT_POSITIONS: [u16; 8] = [
+    0b1111_1111_1111, // 0 = no centering
+    0b1110_1001_0010, // 1 = center x
+    0b1110_0011_1000, // 2 = center y
+    0b1110_0001_0000, // 3 = center x & y
+    0b0101_1111_1111, // 4 = center front (default)
+    0b0100_1001_0010, // 5 = center front & x
+    0b0100_0011_1000, // 6 = center front & y
+    0b0100_0001_0000, // 7 = center front & x & y
+];
+
+// The order KLE tries alignments in when serialising, most compact first
+const ALIGNMENT_PREFERENCE: [usize; 8] = [7, 5, 6, 4, 3, 1, 2, 0];
+
+/// Reorders legends from KLE's order into this crate's order.
+///
+/// KLE stores legends in a compact order that depends on the key's [`Alignment`]. This function
+/// maps legends from that order to the positional order used by [`Key::legends`](crate::Key),
+/// which is independent of alignment. Any legends beyond the 12th are ignored, and missing legends
+/// are filled with [`None`].
+///
+/// This is the inverse of [`unalign_legends`].
+///
+/// ```
+/// use kle_serial::{realign_legends, Alignment, Legend};
+///
+/// let legend = |text: &str| Some(Legend { text: text.into(), ..Legend::default() });
+/// let legends = realign_legends([legend("A"), legend("B")], Alignment::default());
+///
+/// // With the default alignment, KLE's second legend is the bottom left legend
+/// assert_eq!(legends[0], legend("A"));
+/// assert_eq!(legends[6], legend("B"));
+/// ```
+pub fn realign_legends<T>(values: T, alignment: Alignment) -> [Option<Legend>; NUM_LEGENDS]
 where
     T: IntoIterator<Item = Option<Legend>>,
 {
@@ -89,13 +327,86 @@ where
     sorted.sort_by_key(|el| el.0);
 
     let mut values = sorted.into_iter().map(|el| el.1);
-    std::array::from_fn(|_| values.next().unwrap_or(None))
+    core::array::from_fn(|_| values.next().unwrap_or(None))
+}
+
+/// Reorders legends from this crate's order into KLE's order.
+///
+/// This maps legends from the positional order used by [`Key::legends`](crate::Key) to the
+/// alignment-dependent order KLE uses when serialising layouts. Legends in positions that cannot be
+/// displayed by KLE with the given [`Alignment`] are still included in the result, so no legends
+/// are lost.
+///
+/// This is the inverse of [`realign_legends`].
+///
+/// ```
+/// use kle_serial::{unalign_legends, Alignment, Legend};
+///
+/// let legend = |text: &str| Some(Legend { text: text.into(), ..Legend::default() });
+/// let mut legends: [Option<Legend>; 12] = Default::default();
+/// legends[0] = legend("A");
+/// legends[6] = legend("B");
+///
+/// let legends = unalign_legends(legends, Alignment::default());
+///
+/// assert_eq!(legends[0], legend("A"));
+/// assert_eq!(legends[1], legend("B"));
+/// ```
+pub fn unalign_legends(
+    values: [Option<Legend>; NUM_LEGENDS],
+    alignment: Alignment,
+) -> [Option<Legend>; NUM_LEGENDS] {
+    // Guaranteed to be in range because of newtype
+    let mapping = LEGEND_MAPPING[usize::from(alignment)];
+
+    let mut values = values.map(Some);
+    mapping.map(|i| values[i].take().flatten())
+}
+
+pub(crate) fn legend_alignment(values: &[Option<Legend>; NUM_LEGENDS]) -> Alignment {
+    let positions = legend_positions(values);
+
+    // Alignment 0 supports all positions, so this will always find something
+    let alignment = ALIGNMENT_PREFERENCE
+        .into_iter()
+        .find(|&a| ALIGNMENT_POSITIONS[a] & positions == positions)
+        .unwrap_or(0);
+
+    Alignment(BoundedUsize(alignment))
+}
+
+// Returns the alignment to use when serialising the legends given the current alignment. Changing
+// alignment requires emitting an extra property, so the current alignment is kept if it can
+// display all of the legends
+pub(crate) fn encoded_legend_alignment(
+    values: &[Option<Legend>; NUM_LEGENDS],
+    current: Alignment,
+) -> Alignment {
+    let positions = legend_positions(values);
+
+    if ALIGNMENT_POSITIONS[usize::from(current)] & positions == positions {
+        current
+    } else {
+        legend_alignment(values)
+    }
+}
+
+// Returns true if KLE can display a legend at index in Key::legends with the given alignment
+pub(crate) fn alignment_displays(alignment: Alignment, index: usize) -> bool {
+    index < NUM_LEGENDS && ALIGNMENT_POSITIONS[usize::from(alignment)] & (1 << index) != 0
+}
+
+fn legend_positions(values: &[Option<Legend>; NUM_LEGENDS]) -> u16 {
+    (values.iter().enumerate())
+        .filter(|(_, v)| v.is_some())
+        .fold(0_u16, |mask, (i, _)| mask | (1 << i))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use isclose::assert_is_close;
     use serde::de::{
         value::{Error as ValueError, UsizeDeserializer},
         IntoDeserializer,
@@ -144,6 +455,64 @@ mod tests {
         assert!(value.is_err());
     }
 
+    #[test]
+    fn test_bounds_error_display() {
+        let error = BoundedUsize::<10, 5>::new(17).unwrap_err();
+
+        assert_eq!(
+            format!("{error}"),
+            "value 17 is out of range, expected 0 <= value <= 10"
+        );
+    }
+
+    #[test]
+    fn test_font_size() {
+        assert_eq!(FontSize::new(4).map(usize::from), Some(4));
+        assert_eq!(FontSize::new(10), None);
+        assert_eq!(usize::from(FontSize::min()), 0);
+        assert_eq!(usize::from(FontSize::max()), 9);
+        assert_eq!(usize::from(FontSize::default()), 3);
+        assert_eq!(FontSize::try_from(9), Ok(FontSize::max()));
+        assert_eq!(
+            FontSize::try_from(10),
+            Err(BoundsError { value: 10, max: 9 })
+        );
+        assert_eq!(format!("{}", FontSize::default()), "3");
+        assert_eq!(format!("{:?}", FontSize::default()), "FontSize(3)");
+    }
+
+    #[test]
+    fn test_font_size_scale_to_px() {
+        assert_is_close!(FontSize::default().scale_to_px(10.0), 10.0);
+        assert_is_close!(FontSize::new(6).unwrap().scale_to_px(10.0), 20.0);
+        assert_is_close!(FontSize::new(1).unwrap().scale_to_px(12.0), 4.0);
+        assert_is_close!(FontSize::min().scale_to_px(10.0), 0.0);
+    }
+
+    #[test]
+    fn test_font_size_to_pt() {
+        let size3 = FontSize::new(3).unwrap().to_pt();
+        assert_is_close!(size3, 10.0);
+        assert_is_close!(FontSize::new(6).unwrap().to_pt(), 2.0 * size3);
+        assert_is_close!(FontSize::max().to_pt(), 30.0);
+    }
+
+    #[test]
+    fn test_alignment() {
+        assert_eq!(Alignment::new(5).map(usize::from), Some(5));
+        assert_eq!(Alignment::new(8), None);
+        assert_eq!(usize::from(Alignment::min()), 0);
+        assert_eq!(usize::from(Alignment::max()), 7);
+        assert_eq!(usize::from(Alignment::default()), 4);
+        assert_eq!(Alignment::try_from(0), Ok(Alignment::min()));
+        assert_eq!(
+            Alignment::try_from(8),
+            Err(BoundsError { value: 8, max: 7 })
+        );
+        assert_eq!(format!("{}", Alignment::default()), "4");
+        assert_eq!(format!("{:?}", Alignment::default()), "Alignment(4)");
+    }
+
     #[test]
     fn test_realign_legends() {
         let legends = ["A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L"].map(|text| {
@@ -172,4 +541,63 @@ mod tests {
 
         assert_eq!(result_text, expected);
     }
+
+    #[test]
+    fn test_unalign_legends() {
+        let legends = ["A", "I", "C", "G", "J", "H", "B", "K", "D", "F", "E", "L"].map(|text| {
+            Some(Legend {
+                text: text.into(),
+                ..Legend::default()
+            })
+        });
+        let expected = ["A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L"];
+
+        let result = unalign_legends(legends.clone(), Alignment::new(4).unwrap());
+        let result_text = result.map(|l| l.unwrap().text);
+
+        assert_eq!(result_text, expected);
+
+        for alignment in 0..=MAX_ALIGNMENT {
+            let alignment = Alignment::new(alignment).unwrap();
+            let result = realign_legends(unalign_legends(legends.clone(), alignment), alignment);
+
+            assert_eq!(result, legends);
+        }
+    }
+
+    #[test]
+    fn test_legend_alignment() {
+        let legends = |positions: &[usize]| {
+            std::array::from_fn(|i| positions.contains(&i).then(Legend::default))
+        };
+
+        assert_eq!(usize::from(legend_alignment(&legends(&[]))), 7);
+        assert_eq!(usize::from(legend_alignment(&legends(&[4]))), 7);
+        assert_eq!(usize::from(legend_alignment(&legends(&[1, 10]))), 5);
+        assert_eq!(usize::from(legend_alignment(&legends(&[0, 8]))), 4);
+        assert_eq!(usize::from(legend_alignment(&legends(&[9]))), 3);
+        assert_eq!(usize::from(legend_alignment(&legends(&[0, 11]))), 0);
+    }
+
+    #[test]
+    fn test_encoded_legend_alignment() {
+        let legends = |positions: &[usize]| {
+            std::array::from_fn(|i| positions.contains(&i).then(Legend::default))
+        };
+        let alignment = |a| Alignment::new(a).unwrap();
+
+        // The current alignment is kept if it can display the legends
+        let result = encoded_legend_alignment(&legends(&[]), alignment(4));
+        assert_eq!(usize::from(result), 4);
+        let result = encoded_legend_alignment(&legends(&[4]), alignment(4));
+        assert_eq!(usize::from(result), 4);
+        let result = encoded_legend_alignment(&legends(&[4]), alignment(0));
+        assert_eq!(usize::from(result), 0);
+
+        // Otherwise the most compact alignment is used
+        let result = encoded_legend_alignment(&legends(&[9]), alignment(4));
+        assert_eq!(usize::from(result), 3);
+        let result = encoded_legend_alignment(&legends(&[0, 8]), alignment(7));
+        assert_eq!(usize::from(result), 4);
+    }
 }
@@ -0,0 +1,281 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use num_traits::real::Real;
+
+use crate::Keyboard;
+
+/// A non-fatal issue found in a layout by [`Keyboard::validate`].
+///
+/// These do not prevent a layout from being deserialised, but usually indicate a mistake in the
+/// layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationWarning {
+    /// A key has a zero or negative width or height.
+    NegativeKeyDimension {
+        /// The index of the key.
+        key_index: usize,
+    },
+    /// A key is positioned at a negative X or Y coordinate.
+    NegativePosition {
+        /// The index of the key.
+        key_index: usize,
+    },
+    /// A key is rotated, but its centre of rotation is at the origin.
+    RotationWithoutCenter {
+        /// The index of the key.
+        key_index: usize,
+    },
+    /// A legend has a font size of 0.
+    ZeroFontSize {
+        /// The index of the key.
+        key_index: usize,
+        /// The index of the legend in [`Key::legends`](crate::Key::legends).
+        legend_index: usize,
+    },
+    /// A homing key has no profile set.
+    HomingWithoutProfile {
+        /// The index of the key.
+        key_index: usize,
+    },
+    /// Two keys overlap.
+    OverlappingKeys {
+        /// The index of the first key.
+        key_a: usize,
+        /// The index of the second key.
+        key_b: usize,
+    },
+}
+
+impl fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NegativeKeyDimension { key_index } => {
+                write!(f, "key {key_index} has a zero or negative size")
+            }
+            Self::NegativePosition { key_index } => {
+                write!(f, "key {key_index} has a negative position")
+            }
+            Self::RotationWithoutCenter { key_index } => {
+                write!(
+                    f,
+                    "key {key_index} is rotated but has no centre of rotation"
+                )
+            }
+            Self::ZeroFontSize {
+                key_index,
+                legend_index,
+            } => write!(
+                f,
+                "legend {legend_index} of key {key_index} has a font size of 0"
+            ),
+            Self::HomingWithoutProfile { key_index } => {
+                write!(f, "homing key {key_index} has no profile")
+            }
+            Self::OverlappingKeys { key_a, key_b } => {
+                write!(f, "keys {key_a} and {key_b} overlap")
+            }
+        }
+    }
+}
+
+impl<T> Keyboard<T>
+where
+    T: Real,
+{
+    /// Checks the layout for non-fatal issues, returning a [`ValidationWarning`] for each issue
+    /// found.
+    ///
+    /// Warnings for individual keys are returned in key order, followed by any overlapping keys.
+    /// This never panics, even for layouts containing non-finite values.
+    ///
+    /// ```
+    /// use kle_serial::{Keyboard, ValidationWarning};
+    ///
+    /// let keyboard: Keyboard = serde_json::from_str(r#"[["A", {"x": -1.5}, "B"]]"#).unwrap();
+    ///
+    /// assert_eq!(
+    ///     keyboard.validate(),
+    ///     [
+    ///         ValidationWarning::NegativePosition { key_index: 1 },
+    ///         ValidationWarning::OverlappingKeys { key_a: 0, key_b: 1 },
+    ///     ]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn validate(&self) -> Vec<ValidationWarning> {
+        let mut warnings = Vec::new();
+
+        for (key_index, key) in self.keys.iter().enumerate() {
+            if key.width <= T::zero() || key.height <= T::zero() {
+                warnings.push(ValidationWarning::NegativeKeyDimension { key_index });
+            }
+            if key.x < T::zero() || key.y < T::zero() {
+                warnings.push(ValidationWarning::NegativePosition { key_index });
+            }
+            if !key.rotation.is_zero() && key.rx.is_zero() && key.ry.is_zero() {
+                warnings.push(ValidationWarning::RotationWithoutCenter { key_index });
+            }
+            for (legend_index, legend) in key.legends_iter() {
+                if legend.size == 0 {
+                    warnings.push(ValidationWarning::ZeroFontSize {
+                        key_index,
+                        legend_index,
+                    });
+                }
+            }
+            if key.homing && key.profile.is_empty() {
+                warnings.push(ValidationWarning::HomingWithoutProfile { key_index });
+            }
+        }
+
+        warnings.extend(
+            (self.find_overlapping_keys().into_iter())
+                .map(|(key_a, key_b)| ValidationWarning::OverlappingKeys { key_a, key_b }),
+        );
+
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+
+    use crate::{Key, Legend};
+
+    use super::*;
+
+    #[test]
+    fn test_validation_warning_display() {
+        let warnings = [
+            (
+                ValidationWarning::NegativeKeyDimension { key_index: 1 },
+                "key 1 has a zero or negative size",
+            ),
+            (
+                ValidationWarning::NegativePosition { key_index: 2 },
+                "key 2 has a negative position",
+            ),
+            (
+                ValidationWarning::RotationWithoutCenter { key_index: 3 },
+                "key 3 is rotated but has no centre of rotation",
+            ),
+            (
+                ValidationWarning::ZeroFontSize {
+                    key_index: 4,
+                    legend_index: 5,
+                },
+                "legend 5 of key 4 has a font size of 0",
+            ),
+            (
+                ValidationWarning::HomingWithoutProfile { key_index: 6 },
+                "homing key 6 has no profile",
+            ),
+            (
+                ValidationWarning::OverlappingKeys { key_a: 7, key_b: 8 },
+                "keys 7 and 8 overlap",
+            ),
+        ];
+
+        for (warning, expected) in warnings {
+            assert_eq!(warning.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_keyboard_validate() {
+        let keyboard = Keyboard::<f64> {
+            keys: vec![
+                Key {
+                    width: 0.0,
+                    ..Key::default()
+                },
+                Key {
+                    x: 2.0,
+                    y: -1.0,
+                    ..Key::default()
+                },
+                Key {
+                    x: 4.0,
+                    rotation: 15.0,
+                    ..Key::default()
+                },
+                Key {
+                    x: 6.0,
+                    legends: core::array::from_fn(|i| {
+                        (i == 3).then(|| Legend {
+                            size: 0,
+                            ..Legend::default()
+                        })
+                    }),
+                    ..Key::default()
+                },
+                Key {
+                    x: 8.0,
+                    homing: true,
+                    ..Key::default()
+                },
+                Key {
+                    x: 8.5,
+                    profile: "DSA".into(),
+                    homing: true,
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        };
+
+        assert_eq!(
+            keyboard.validate(),
+            [
+                ValidationWarning::NegativeKeyDimension { key_index: 0 },
+                ValidationWarning::NegativePosition { key_index: 1 },
+                ValidationWarning::RotationWithoutCenter { key_index: 2 },
+                ValidationWarning::ZeroFontSize {
+                    key_index: 3,
+                    legend_index: 3,
+                },
+                ValidationWarning::HomingWithoutProfile { key_index: 4 },
+                ValidationWarning::OverlappingKeys { key_a: 4, key_b: 5 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keyboard_validate_valid() {
+        let keyboard = Keyboard::<f64> {
+            keys: vec![
+                Key::default(),
+                Key {
+                    x: 2.0,
+                    rotation: 15.0,
+                    rx: 2.0,
+                    ..Key::default()
+                },
+            ],
+            ..Keyboard::default()
+        };
+
+        assert!(keyboard.validate().is_empty());
+        assert!(Keyboard::<f64>::default().validate().is_empty());
+    }
+
+    #[test]
+    fn test_keyboard_validate_non_finite() {
+        let keyboard = Keyboard::<f64> {
+            keys: vec![
+                Key {
+                    x: f64::NAN,
+                    width: f64::INFINITY,
+                    ..Key::default()
+                },
+                Key::default(),
+            ],
+            ..Keyboard::default()
+        };
+
+        // Shouldn't panic
+        let _ = keyboard.validate();
+    }
+}
@@ -0,0 +1,408 @@
+//! JavaScript bindings using [`wasm-bindgen`]. Requires the `wasm` feature.
+//!
+//! These wrap the crate's types using [`f64`] coordinates, exposing their fields as read-only
+//! properties.
+//!
+//! [`wasm-bindgen`]: https://crates.io/crates/wasm-bindgen
+
+use std::str::FromStr;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{Color, Key, Keyboard, Legend, Metadata};
+
+/// Parses a KLE JSON layout, returning a [`JsKeyboard`].
+///
+/// # Errors
+///
+/// Throws a JavaScript `Error` if the input is not a valid KLE layout.
+#[wasm_bindgen(js_name = parseKeyboard)]
+pub fn parse_keyboard(json: &str) -> Result<JsKeyboard, JsValue> {
+    Keyboard::from_str(json)
+        .map(JsKeyboard)
+        .map_err(|error| JsError::new(&error.to_string()).into())
+}
+
+/// A JavaScript wrapper around [`Color`].
+#[wasm_bindgen(js_name = Color)]
+#[derive(Debug, Clone, Copy)]
+pub struct JsColor(Color);
+
+#[wasm_bindgen(js_class = Color)]
+impl JsColor {
+    /// The red channel.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn r(&self) -> u8 {
+        self.0.r
+    }
+
+    /// The green channel.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn g(&self) -> u8 {
+        self.0.g
+    }
+
+    /// The blue channel.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn b(&self) -> u8 {
+        self.0.b
+    }
+
+    /// The alpha channel.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn a(&self) -> u8 {
+        self.0.a
+    }
+}
+
+/// A JavaScript wrapper around [`Legend`].
+#[wasm_bindgen(js_name = Legend)]
+#[derive(Debug, Clone)]
+pub struct JsLegend(Legend);
+
+#[wasm_bindgen(js_class = Legend)]
+impl JsLegend {
+    /// The legend's text.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.0.text.clone()
+    }
+
+    /// The legend size in KLE's font size unit.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.0.size
+    }
+
+    /// The legend colour.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn color(&self) -> JsColor {
+        JsColor(self.0.color)
+    }
+}
+
+/// A JavaScript wrapper around [`Key`].
+#[wasm_bindgen(js_name = Key)]
+#[derive(Debug, Clone)]
+pub struct JsKey(Key<f64>);
+
+#[wasm_bindgen(js_class = Key)]
+impl JsKey {
+    /// Returns the legend at the given index, or `undefined` if there is no legend at that index.
+    /// Legends use the same order as [`Key::legends`].
+    #[must_use]
+    pub fn legend(&self, index: usize) -> Option<JsLegend> {
+        self.0.legends.get(index)?.clone().map(JsLegend)
+    }
+
+    /// The colour of the key.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn color(&self) -> JsColor {
+        JsColor(self.0.color)
+    }
+
+    /// The X position of the key.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn x(&self) -> f64 {
+        self.0.x
+    }
+
+    /// The Y position of the key.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn y(&self) -> f64 {
+        self.0.y
+    }
+
+    /// The width of the key.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn width(&self) -> f64 {
+        self.0.width
+    }
+
+    /// The height of the key.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn height(&self) -> f64 {
+        self.0.height
+    }
+
+    /// The relative X position of a stepped or L-shaped part of the key.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn x2(&self) -> f64 {
+        self.0.x2
+    }
+
+    /// The relative Y position of a stepped or L-shaped part of the key.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn y2(&self) -> f64 {
+        self.0.y2
+    }
+
+    /// The width of a stepped or L-shaped part of the key.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn width2(&self) -> f64 {
+        self.0.width2
+    }
+
+    /// The height of a stepped or L-shaped part of the key.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn height2(&self) -> f64 {
+        self.0.height2
+    }
+
+    /// The rotation of the key in degrees.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn rotation(&self) -> f64 {
+        self.0.rotation
+    }
+
+    /// The X coordinate for the centre of rotation of the key.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn rx(&self) -> f64 {
+        self.0.rx
+    }
+
+    /// The Y coordinate for the centre of rotation of the key.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn ry(&self) -> f64 {
+        self.0.ry
+    }
+
+    /// The keycap profile and row number of the key.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn profile(&self) -> String {
+        self.0.profile.clone()
+    }
+
+    /// The key switch mount.
+    #[wasm_bindgen(getter, js_name = switchMount)]
+    #[must_use]
+    pub fn switch_mount(&self) -> String {
+        self.0.switch.mount.clone()
+    }
+
+    /// The key switch brand.
+    #[wasm_bindgen(getter, js_name = switchBrand)]
+    #[must_use]
+    pub fn switch_brand(&self) -> String {
+        self.0.switch.brand.clone()
+    }
+
+    /// The key switch type.
+    #[wasm_bindgen(getter, js_name = switchType)]
+    #[must_use]
+    pub fn switch_type(&self) -> String {
+        self.0.switch.typ.clone()
+    }
+
+    /// Whether the key is ghosted.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn ghosted(&self) -> bool {
+        self.0.ghosted
+    }
+
+    /// Whether the key is stepped.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn stepped(&self) -> bool {
+        self.0.stepped
+    }
+
+    /// Whether this is a homing key.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn homing(&self) -> bool {
+        self.0.homing
+    }
+
+    /// Whether this is a decal.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn decal(&self) -> bool {
+        self.0.decal
+    }
+}
+
+/// A JavaScript wrapper around [`Metadata`].
+#[wasm_bindgen(js_name = Metadata)]
+#[derive(Debug, Clone)]
+pub struct JsMetadata(Metadata);
+
+#[wasm_bindgen(js_class = Metadata)]
+impl JsMetadata {
+    /// Background colour for the layout.
+    #[wasm_bindgen(getter, js_name = backgroundColor)]
+    #[must_use]
+    pub fn background_color(&self) -> JsColor {
+        JsColor(self.0.background_color)
+    }
+
+    /// The name of the background style.
+    #[wasm_bindgen(getter, js_name = backgroundName)]
+    #[must_use]
+    pub fn background_name(&self) -> String {
+        self.0.background.name.clone()
+    }
+
+    /// The CSS style of the background.
+    #[wasm_bindgen(getter, js_name = backgroundStyle)]
+    #[must_use]
+    pub fn background_style(&self) -> String {
+        self.0.background.style.clone()
+    }
+
+    /// Corner radii for the background using CSS `border-radius` syntax.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn radii(&self) -> String {
+        self.0.radii.clone()
+    }
+
+    /// The name of the layout.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn name(&self) -> String {
+        self.0.name.clone()
+    }
+
+    /// The author of the layout.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn author(&self) -> String {
+        self.0.author.clone()
+    }
+
+    /// The default switch mount.
+    #[wasm_bindgen(getter, js_name = switchMount)]
+    #[must_use]
+    pub fn switch_mount(&self) -> String {
+        self.0.switch.mount.clone()
+    }
+
+    /// The default switch brand.
+    #[wasm_bindgen(getter, js_name = switchBrand)]
+    #[must_use]
+    pub fn switch_brand(&self) -> String {
+        self.0.switch.brand.clone()
+    }
+
+    /// The default switch type.
+    #[wasm_bindgen(getter, js_name = switchType)]
+    #[must_use]
+    pub fn switch_type(&self) -> String {
+        self.0.switch.typ.clone()
+    }
+
+    /// Whether the switch is plate mounted.
+    #[wasm_bindgen(getter, js_name = plateMount)]
+    #[must_use]
+    pub fn plate_mount(&self) -> bool {
+        self.0.plate_mount
+    }
+
+    /// Whether the switch is PCB mounted.
+    #[wasm_bindgen(getter, js_name = pcbMount)]
+    #[must_use]
+    pub fn pcb_mount(&self) -> bool {
+        self.0.pcb_mount
+    }
+
+    /// Notes for the layout.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn notes(&self) -> String {
+        self.0.notes.clone()
+    }
+
+    /// Custom CSS for the layout.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn css(&self) -> String {
+        self.0.css.clone()
+    }
+}
+
+/// A JavaScript wrapper around [`Keyboard`].
+#[wasm_bindgen(js_name = Keyboard)]
+#[derive(Debug, Clone)]
+pub struct JsKeyboard(Keyboard<f64>);
+
+#[wasm_bindgen(js_class = Keyboard)]
+impl JsKeyboard {
+    /// Returns the layout's metadata.
+    #[must_use]
+    pub fn metadata(&self) -> JsMetadata {
+        JsMetadata(self.0.metadata.clone())
+    }
+
+    /// Returns an array of the layout's keys.
+    #[must_use]
+    pub fn keys(&self) -> Vec<JsKey> {
+        self.0.keys.iter().cloned().map(JsKey).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_js_keyboard() {
+        let keyboard = JsKeyboard(
+            Keyboard::from_str(
+                r##"[
+                    {"name": "test", "author": "somebody", "plate": true},
+                    [{"c": "#cc2222", "t": "#ffffff", "w": 2}, "A\nB"]
+                ]"##,
+            )
+            .unwrap(),
+        );
+
+        let metadata = keyboard.metadata();
+        assert_eq!(metadata.name(), "test");
+        assert_eq!(metadata.author(), "somebody");
+        assert!(metadata.plate_mount());
+        assert!(!metadata.pcb_mount());
+        assert_eq!(metadata.background_color().r(), 0xEE);
+
+        let keys = keyboard.keys();
+        assert_eq!(keys.len(), 1);
+
+        let key = &keys[0];
+        assert_eq!(
+            (key.color().r(), key.color().g(), key.color().b()),
+            (0xCC, 0x22, 0x22)
+        );
+        assert_eq!(key.color().a(), 0xFF);
+        assert!((key.width() - 2.0).abs() < f64::EPSILON);
+
+        let legend = key.legend(0).unwrap();
+        assert_eq!(legend.text(), "A");
+        assert_eq!(legend.size(), 3);
+        assert_eq!(legend.color().g(), 0xFF);
+
+        assert_eq!(key.legend(6).unwrap().text(), "B");
+        assert!(key.legend(1).is_none());
+        assert!(key.legend(12).is_none());
+    }
+}
@@ -0,0 +1,25 @@
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use kle_serial::wasm::parse_keyboard;
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn test_parse_keyboard() {
+    let keyboard = parse_keyboard(r#"[{"name": "test"}, [{"w": 2}, "A", "B"]]"#).unwrap();
+
+    assert_eq!(keyboard.metadata().name(), "test");
+
+    let keys = keyboard.keys();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys[0].legend(0).unwrap().text(), "A");
+    assert!((keys[0].width() - 2.0).abs() < f64::EPSILON);
+    assert!((keys[1].x() - 2.0).abs() < f64::EPSILON);
+    assert_eq!(keys[1].color().r(), 0xCC);
+}
+
+#[wasm_bindgen_test]
+fn test_parse_keyboard_error() {
+    assert!(parse_keyboard("{").is_err());
+}